@@ -1,14 +1,27 @@
 //! rust-tree 工具的配置结构。
 
-use crate::core::walker::{SortField, WalkConfig};
-use clap::{Parser, ValueEnum};
+use crate::core::collector::{EmptyDuplicates, StatsOptions};
+use crate::core::fit::CollapseOrder;
+use crate::core::streaming::device_id;
+use crate::core::walker::{MetadataFields, SortField, WalkConfig};
+use crate::formatters::ext_labels::ExtensionLabels;
+use crate::formatters::size::{SizeOptions, SizeUnit};
+use crate::formatters::table::TableOptions;
+use crate::formatters::tree::{TreeCharset, TreeOptions};
+use clap::{Parser, Subcommand, ValueEnum};
+use serde::Serialize;
 use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::{Duration, SystemTime};
 
 pub mod color;
-pub use color::{ColorMode, ColorScheme};
+pub mod file;
+pub mod ls_colors;
+pub use color::{ColorBy, ColorMode, ColorScheme};
 
 /// 输出格式选项。
-#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum OutputFormat {
     /// 使用 Unicode 字符的树形输出
     Tree,
@@ -16,10 +29,80 @@ pub enum OutputFormat {
     Json,
     /// 显示统计信息的表格格式
     Table,
+    /// Prometheus 文本暴露格式（仅统计信息）
+    Prometheus,
+    /// 带深度标记的纯文本缩进大纲（`2 . . main.rs`）
+    Outline,
+    /// 按深度缩进（每层两个空格）的纯名称列表，不含制表符与路径
+    Names,
+    /// 每个文件一行的归档清单：累计字节偏移、大小与相对路径（形似 tar 目录）
+    Manifest,
+    /// 每个节点一行的 CSV（列由 `--fields` 控制）
+    Csv,
+    /// 自包含的 HTML 页面：统计表 + 可折叠的目录树
+    Html,
+    /// GitHub 风格 Markdown 嵌套列表（目录加粗，`-s` 时文件附带大小）
+    Markdown,
+    /// 每个节点一行的扁平 JSON 对象（JSON Lines；键由 `--fields` 控制，总是流式输出）
+    #[value(alias = "jsonl")]
+    Ndjson,
+}
+
+/// CSV 输出可选的列。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CsvField {
+    /// 完整路径
+    Path,
+    /// 文件或目录名
+    Name,
+    /// 节点类型（file/directory/symlink）
+    Type,
+    /// 字节大小（目录为 0）
+    Size,
+    /// 最后修改时间（Unix 秒）
+    Mtime,
+    /// 在树中的深度（根为 0）
+    Depth,
+    /// 权限位（八进制，如 `0644`；仅 Unix）
+    Mode,
+}
+
+impl CsvField {
+    /// 全部列，按默认输出顺序。
+    pub const ALL: [CsvField; 7] = [
+        CsvField::Path,
+        CsvField::Name,
+        CsvField::Type,
+        CsvField::Size,
+        CsvField::Mtime,
+        CsvField::Depth,
+        CsvField::Mode,
+    ];
+
+    /// 列名（同时用作 CSV 表头与 `--fields` 中的取值）。
+    pub fn as_str(self) -> &'static str {
+        match self {
+            CsvField::Path => "path",
+            CsvField::Name => "name",
+            CsvField::Type => "type",
+            CsvField::Size => "size",
+            CsvField::Mtime => "mtime",
+            CsvField::Depth => "depth",
+            CsvField::Mode => "mode",
+        }
+    }
+}
+
+impl std::fmt::Display for CsvField {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
 }
 
 /// 排序字段选项。
-#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum SortBy {
     /// 按文件/目录名称排序
     Name,
@@ -27,6 +110,11 @@ pub enum SortBy {
     Size,
     /// 按文件类型/扩展名排序
     Type,
+    /// 按修改时间排序（最近修改的在前）
+    #[value(name = "mtime", alias = "modified")]
+    MTime,
+    /// 按名称排序，名称中的数字按数值比较（img2 在 img10 之前）
+    Natural,
 }
 
 impl From<SortBy> for SortField {
@@ -35,108 +123,631 @@ impl From<SortBy> for SortField {
             SortBy::Name => SortField::Name,
             SortBy::Size => SortField::Size,
             SortBy::Type => SortField::Type,
+            SortBy::MTime => SortField::MTime,
+            SortBy::Natural => SortField::Natural,
         }
     }
 }
 
+/// `--fit-lines` 的折叠顺序。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FitStrategy {
+    /// 最深的目录先折叠，尽量保留上层结构（默认）
+    #[default]
+    Deepest,
+    /// 占用行数最多的目录先折叠
+    Biggest,
+}
+
+impl From<FitStrategy> for CollapseOrder {
+    fn from(strategy: FitStrategy) -> Self {
+        match strategy {
+            FitStrategy::Deepest => CollapseOrder::Deepest,
+            FitStrategy::Biggest => CollapseOrder::Biggest,
+        }
+    }
+}
+
+/// `--duplicates-empty`：零字节文件在重复文件报告中的处理方式。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DuplicatesEmpty {
+    /// 不参与重复文件分组（默认）
+    #[default]
+    Skip,
+    /// 单独列为一组 `empty files`
+    Group,
+}
+
+impl From<DuplicatesEmpty> for EmptyDuplicates {
+    fn from(mode: DuplicatesEmpty) -> Self {
+        match mode {
+            DuplicatesEmpty::Skip => EmptyDuplicates::Skip,
+            DuplicatesEmpty::Group => EmptyDuplicates::Group,
+        }
+    }
+}
+
+/// 子命令。省略时等同于 `scan`，因此 `rust-tree <dir>` 仍然可用。
+///
+/// 所有选项都是全局的，既可写在子命令之前也可写在其后。与子命令同名的目录
+/// 需写作 `scan <dir>` 或放在 `--` 之后，否则会被解析为子命令。
+#[derive(Subcommand, Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Command {
+    /// 扫描目录并按 --format 输出（默认行为）
+    Scan {
+        /// 目标目录路径（覆盖顶层的 DIRECTORY）
+        #[arg(value_name = "DIRECTORY")]
+        path: Option<PathBuf>,
+    },
+    /// 仅输出统计信息（表格；配合 -f json 输出 JSON）
+    Stats {
+        /// 目标目录路径（覆盖顶层的 DIRECTORY）
+        #[arg(value_name = "DIRECTORY")]
+        path: Option<PathBuf>,
+    },
+    /// 比较两棵树；每一侧可以是目录或 snapshot 生成的 JSON 文件
+    Diff {
+        /// 旧的一侧（目录或快照文件）
+        #[arg(value_name = "A")]
+        old: PathBuf,
+        /// 新的一侧（目录或快照文件）
+        #[arg(value_name = "B")]
+        new: PathBuf,
+    },
+    /// 扫描目录并将树与统计信息保存为 JSON 快照
+    Snapshot {
+        /// 快照输出文件
+        #[arg(value_name = "OUT")]
+        out: PathBuf,
+        /// 目标目录路径（覆盖顶层的 DIRECTORY）
+        #[arg(value_name = "DIRECTORY")]
+        path: Option<PathBuf>,
+    },
+    /// 从 JSON 快照还原并按树形渲染，不访问文件系统
+    Restore {
+        /// 快照输入文件
+        #[arg(value_name = "IN")]
+        input: PathBuf,
+    },
+}
+
 /// `--exclude-common` 受支持的语言集合。`validate` 用它做输入校验，
 /// `to_walk_config` 的 match 负责把语言映射到具体排除模式。
 pub const EXCLUDE_COMMON_LANGS: &[&str] =
     &["rust", "node", "nodejs", "javascript", "python", "common"];
 
 /// rust-tree 的命令行参数。
-#[derive(Parser, Debug)]
+#[derive(Parser, Debug, Serialize)]
 #[command(name = "rust-tree")]
 #[command(author = "rust-tree contributors")]
 #[command(version = "0.1.0")]
 #[command(about = "A fast directory tree visualization tool", long_about = None)]
 #[command(
-    after_help = "Examples:\n  rust-tree                    # Show current directory\n  rust-tree -d 2 /path/to/dir  # Limit depth to 2\n  rust-tree -f json -S         # JSON output with stats\n  rust-tree -s -o size -r      # Show sizes, sort by size (descending)"
+    after_help = "Examples:\n  rust-tree                    # Show current directory\n  rust-tree -d 2 /path/to/dir  # Limit depth to 2\n  rust-tree -f json -S         # JSON output with stats\n  rust-tree -s -o size -r      # Show sizes, sort by size (descending)\n  rust-tree stats /path        # Statistics only\n  rust-tree snapshot s.json .  # Save a snapshot\n  rust-tree diff s.json .      # Compare a snapshot with the current tree\n  rust-tree -- stats           # A directory named like a subcommand"
 )]
 pub struct Config {
+    /// 子命令（省略时为 scan）
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
     /// 目标目录路径（默认为当前目录）
     #[arg(value_name = "DIRECTORY", default_value = ".")]
     pub path: PathBuf,
 
     /// 最大递归深度（0 表示无限制）
-    #[arg(short = 'd', long = "depth", default_value = "0", value_name = "N")]
+    #[arg(
+        short = 'd',
+        long = "depth",
+        default_value = "0",
+        value_name = "N",
+        global = true
+    )]
     pub max_depth: usize,
 
+    /// 最小显示深度：更浅的层级不输出（仍会进入其中），深度为 N 的条目以相对路径列在根下
+    #[arg(
+        long = "min-depth",
+        default_value = "0",
+        value_name = "N",
+        global = true
+    )]
+    pub min_depth: usize,
+
     /// 输出格式
     #[arg(
         short = 'f',
         long = "format",
         default_value = "tree",
-        value_name = "FORMAT"
+        value_name = "FORMAT",
+        global = true
     )]
     pub format: OutputFormat,
 
-    /// 显示文件大小
-    #[arg(short = 's', long = "size")]
+    /// 显示文件大小与目录文件数（等同于 --file-size --dir-count）
+    #[arg(short = 's', long = "size", global = true)]
     pub show_size: bool,
 
+    /// 在文件后显示其大小
+    #[arg(long = "file-size", global = true)]
+    pub file_size: bool,
+
+    /// du 式输出：文件与目录（含根）都显示大小，目录为其子树的总大小
+    #[arg(long = "du", global = true)]
+    pub du: bool,
+
+    /// 在每个条目前显示 `ls -l` 式的权限位，如 `[drwxr-xr-x]`（仅 Unix，其他平台不显示）
+    #[arg(long = "perms", global = true)]
+    pub show_perms: bool,
+
+    /// 只输出树的形状：名称换成 □（文件）与 ▣/（目录），不显示大小等任何元数据
+    #[arg(
+        long = "shape-only",
+        conflicts_with_all = [
+            "duplicates",
+            "list_empty_dirs",
+            "largest_path",
+            "report_excluded_ext",
+            "compare_stats",
+        ],
+        global = true
+    )]
+    pub shape_only: bool,
+
+    /// 输出与 GNU `tree` 相同的格式：目录名不带 `/`，目录与文件混合排序，末尾附 `N directories, M files`
+    #[arg(
+        long = "compat-tree",
+        conflicts_with_all = ["shape_only", "size_column"],
+        global = true
+    )]
+    pub compat_tree: bool,
+
+    /// 用 ASCII 字符（`|--`、`` `-- ``、`|   `）代替 Unicode 制表符绘制树
+    #[arg(long = "ascii", global = true)]
+    pub ascii: bool,
+
+    /// 所有大小都换算到该单位显示（B、kB、MB、GB、TB、KiB、MiB、GiB、TiB，不区分大小写）
+    #[arg(
+        long = "size-unit",
+        visible_alias = "round-sizes-to",
+        value_name = "UNIT",
+        value_parser = SizeUnit::from_str,
+        global = true
+    )]
+    pub size_unit: Option<SizeUnit>,
+
+    /// 每个目录只显示开头与结尾的若干条目，中间以 `… (N more)` 代替（见 --head / --tail）
+    #[arg(long = "compact-levels", global = true)]
+    pub compact_levels: bool,
+
+    /// 配合 --compact-levels：每个目录开头显示的条目数
+    #[arg(
+        long = "head",
+        value_name = "N",
+        default_value_t = 3,
+        requires = "compact_levels",
+        global = true
+    )]
+    pub head: usize,
+
+    /// 配合 --compact-levels：每个目录结尾显示的条目数
+    #[arg(
+        long = "tail",
+        value_name = "M",
+        default_value_t = 3,
+        requires = "compact_levels",
+        global = true
+    )]
+    pub tail: usize,
+
+    /// 自动折叠子树，使树形输出不超过 N 行；被折叠的目录显示为 `… (K entries collapsed)`
+    #[arg(long = "fit-lines", value_name = "N", global = true)]
+    pub fit_lines: Option<usize>,
+
+    /// 完整显示到深度 N，更深的目录折叠为一行 `name/ [collapsed: 42 files, 10 MB]`（仍统计其子树）
+    #[arg(
+        long = "collapse-depth",
+        value_name = "N",
+        conflicts_with = "max_depth",
+        global = true
+    )]
+    pub collapse_depth: Option<usize>,
+
+    /// `--fit-lines` 折叠目录的顺序（deepest、biggest）
+    #[arg(
+        long = "fit-strategy",
+        default_value = "deepest",
+        value_name = "ORDER",
+        requires = "fit_lines",
+        global = true
+    )]
+    pub fit_strategy: FitStrategy,
+
+    /// 每个目录最多列出 N 个条目（排序之后），其余以 `... (M more)` 代替且不再遍历
+    #[arg(
+        long = "filelimit",
+        visible_alias = "max-entries",
+        value_name = "N",
+        global = true
+    )]
+    pub filelimit: Option<usize>,
+
+    /// ncdu 式布局：大小右对齐在左侧成一列，目录显示其子树的总大小
+    #[arg(long = "size-column", global = true)]
+    pub size_column: bool,
+
+    /// 在目录后显示其子树中的文件数（N files）
+    #[arg(long = "dir-count", global = true)]
+    pub dir_count: bool,
+
+    /// 在目录后显示其子树中文件的总大小
+    #[arg(long = "dir-size", global = true)]
+    pub dir_size: bool,
+
     /// 显示隐藏文件（以 . 开头的文件）
-    #[arg(short = 'a', long = "all")]
+    #[arg(short = 'a', long = "all", global = true)]
     pub show_hidden: bool,
 
-    /// 按字段排序（name、size、type）
-    #[arg(short = 'o', long = "sort", default_value = "name", value_name = "BY")]
+    /// 按字段排序（name、size、type、mtime、natural）
+    #[arg(
+        short = 'o',
+        long = "sort",
+        default_value = "name",
+        value_name = "BY",
+        global = true
+    )]
     pub sort_by: SortBy,
 
     /// 反向排序
-    #[arg(short = 'r', long = "reverse")]
+    #[arg(short = 'r', long = "reverse", global = true)]
     pub reverse: bool,
 
     /// 显示统计摘要（用于 tree 格式），或在 json/table 格式下始终包含统计信息
-    #[arg(short = 'S', long = "stats")]
+    #[arg(short = 'S', long = "stats", global = true)]
     pub show_stats: bool,
 
+    /// 将统计信息以 JSON 写入该文件；主输出不再包含统计块（包括 -f json）
+    #[arg(long = "stats-file", value_name = "PATH", global = true)]
+    pub stats_file: Option<PathBuf>,
+
+    /// 将输出写入该文件而非 stdout（写入文件时 --color 默认为 never）
+    #[arg(short = 'O', long = "output", value_name = "PATH", global = true)]
+    pub output: Option<PathBuf>,
+
     /// 跟随符号链接
-    #[arg(short = 'L', long = "follow")]
+    #[arg(short = 'L', long = "follow", global = true)]
     pub follow_symlinks: bool,
 
+    /// 配合 -L：仍展开被跟随的符号链接，但将其显示为 `name/ -> target/`
+    #[arg(long = "mark-followed", requires = "follow_symlinks", global = true)]
+    pub mark_followed: bool,
+
+    /// 不进入位于其他文件系统上的目录（挂载点），例如扫描 `/` 时跳过 `/proc`
+    #[arg(short = 'x', long = "one-filesystem", global = true)]
+    pub one_filesystem: bool,
+
+    /// 从 stdin 读取 tar 流并显示其中条目构成的树（`rust-tree --tar < archive.tar`），不读取磁盘
+    #[arg(long = "tar", conflicts_with = "one_filesystem", global = true)]
+    pub tar: bool,
+
     /// 统计信息中显示的最大文件数量
-    #[arg(long = "top-files", default_value = "10", value_name = "N")]
+    #[arg(
+        long = "top-files",
+        default_value = "10",
+        value_name = "N",
+        global = true
+    )]
     pub top_files: usize,
 
+    /// 表格输出中每隔 N 行重复一次表头（便于滚动查看长表）
+    #[arg(long = "repeat-header", value_name = "N", global = true)]
+    pub repeat_header: Option<usize>,
+
+    /// 统计表中扩展名的友好名称（`EXT=LABEL`，可重复，如 `--ext-label ipynb=Jupyter`），覆盖内置映射
+    #[arg(
+        long = "ext-label",
+        value_name = "EXT=LABEL",
+        value_parser = parse_ext_label,
+        global = true
+    )]
+    pub ext_label: Vec<(String, String)>,
+
+    /// 统计表中不使用内置的扩展名友好名称（`--ext-label` 的条目仍然生效）
+    #[arg(long = "no-ext-labels", global = true)]
+    pub no_ext_labels: bool,
+
+    /// 查找内容相同的文件（先按大小分组，再对同大小的文件计算哈希），在树后或统计表中列出
+    #[arg(long = "duplicates", global = true)]
+    pub duplicates: bool,
+
+    /// 配合 --duplicates：零字节文件跳过（skip）或单独列为一组 empty files（group）
+    #[arg(
+        long = "duplicates-empty",
+        value_enum,
+        default_value = "skip",
+        value_name = "MODE",
+        requires = "duplicates",
+        global = true
+    )]
+    pub duplicates_empty: DuplicatesEmpty,
+
+    /// 在 git 仓库中报告已跟踪、未跟踪与被忽略的文件数、大小与占比（仓库外不输出）
+    #[arg(long = "git-ratio", global = true)]
+    pub git_ratio: bool,
+
+    /// 在每个已跟踪文件后（变暗）显示最后一次提交它的作者，用于查看代码归属
+    #[arg(long = "git-authors", global = true)]
+    pub git_authors: bool,
+
+    /// 与之前保存的统计 JSON（`stats -f json` 或 `--stats-file` 的输出）比较，报告各项的变化量
+    #[arg(long = "compare-stats", value_name = "BASELINE", global = true)]
+    pub compare_stats: Option<PathBuf>,
+
+    /// 在每个文本文件后显示行数（如 `[123 lines]`），并在统计中汇总总行数
+    #[arg(long = "count-lines", global = true)]
+    pub count_lines: bool,
+
+    /// 在输出末尾列出空目录（不含任何文件的目录）；只因过滤而空的目录会加注
+    #[arg(long = "list-empty-dirs", global = true)]
+    pub list_empty_dirs: bool,
+
+    /// 计算平均/中位文件大小时忽略零字节文件（不影响树的显示）
+    #[arg(long = "stats-ignore-empty", global = true)]
+    pub stats_ignore_empty: bool,
+
+    /// 表格与摘要中的计数使用千位分隔符（默认 `,`，可用 `--thousands-sep=.` 指定；JSON 不受影响）
+    #[arg(
+        long = "thousands-sep",
+        value_name = "CHAR",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = ",",
+        global = true
+    )]
+    pub thousands_sep: Option<char>,
+
+    /// CSV 的列 / NDJSON 的键及其顺序（逗号分隔，如 path,size,mtime,type）
+    #[arg(
+        long = "fields",
+        value_name = "FIELDS",
+        value_delimiter = ',',
+        default_values_t = CsvField::ALL,
+        global = true
+    )]
+    pub fields: Vec<CsvField>,
+
+    /// 将大计数缩写为 12.3k / 4.5M（树中目录的文件数及统计表）
+    #[arg(
+        long = "compact-numbers",
+        conflicts_with = "thousands_sep",
+        global = true
+    )]
+    pub compact_numbers: bool,
+
     /// 颜色模式（always、never、auto）
-    #[arg(long = "color", default_value = "auto", value_name = "WHEN")]
+    #[arg(
+        long = "color",
+        default_value = "auto",
+        value_name = "WHEN",
+        global = true
+    )]
     pub color_mode: ColorMode,
 
-    /// 颜色方案（none、basic、extended）
-    #[arg(long = "color-scheme", default_value = "basic", value_name = "SCHEME")]
+    /// 颜色方案（none、basic、extended、ls-colors）；按所有者着色请用 `--color-by owner`
+    #[arg(
+        long = "color-scheme",
+        default_value = "basic",
+        value_name = "SCHEME",
+        global = true
+    )]
     pub color_scheme: ColorScheme,
 
+    /// 着色依据（type、owner）；owner 为每个所有者分配固定颜色
+    #[arg(
+        long = "color-by",
+        default_value = "type",
+        value_name = "WHAT",
+        global = true
+    )]
+    pub color_by: ColorBy,
+
     /// 扫描时显示进度条
     #[arg(
         long = "progress",
         short = 'p',
-        help = "Show progress bar during scanning"
+        help = "Show progress bar during scanning",
+        global = true
     )]
     pub show_progress: bool,
 
     /// 排除匹配模式的文件（可多次使用）
-    #[arg(short = 'e', long = "exclude", value_name = "PATTERN")]
+    #[arg(short = 'e', long = "exclude", value_name = "PATTERN", global = true)]
     pub exclude: Vec<String>,
 
     /// 仅包含匹配模式的文件
-    #[arg(long = "include-only", value_name = "PATTERN")]
+    #[arg(long = "include-only", value_name = "PATTERN", global = true)]
     pub include_only: Option<String>,
 
+    /// 预扫描目录，不进入子树中没有任何 --include-only / --ext 可包含文件的目录
+    #[arg(long = "include-prune", global = true)]
+    pub include_prune: bool,
+
+    /// 仅显示这些扩展名的文件（逗号分隔或多次使用，如 `--ext rs,toml`；不区分大小写）
+    #[arg(long = "ext", value_name = "EXT", value_delimiter = ',', global = true)]
+    pub ext: Vec<String>,
+
+    /// 在树下方汇总被 `--ext` 隐藏的文件，按扩展名计数
+    #[arg(long = "report-excluded-ext", requires = "ext", global = true)]
+    pub report_excluded_ext: bool,
+
     /// 使用某种语言常用的排除模式
-    #[arg(long = "exclude-common", value_name = "LANGUAGE")]
+    #[arg(long = "exclude-common", value_name = "LANGUAGE", global = true)]
     pub exclude_common: Option<String>,
 
+    /// 排除版本控制元数据目录（.git、.svn、.hg、.bzr、_darcs、CVS）
+    #[arg(long = "exclude-vcs", global = true)]
+    pub exclude_vcs: bool,
+
+    /// 排除小于该大小的文件（如 10M、500K；目录不受影响）
+    #[arg(long = "min-size", value_name = "SIZE", value_parser = parse_size, global = true)]
+    pub min_size: Option<u64>,
+
+    /// 排除大于该大小的文件（如 1G；目录不受影响）
+    #[arg(long = "max-size", value_name = "SIZE", value_parser = parse_size, global = true)]
+    pub max_size: Option<u64>,
+
+    /// 只保留在这段时间内修改过的文件（如 7d、12h、30m；目录不受影响）
+    #[arg(long = "newer-than", value_name = "AGE", value_parser = parse_age, global = true)]
+    pub newer_than: Option<Duration>,
+
+    /// 只保留在这段时间之前修改的文件（如 30d；目录不受影响）
+    #[arg(long = "older-than", value_name = "AGE", value_parser = parse_age, global = true)]
+    pub older_than: Option<Duration>,
+
+    /// 在 git 仓库内扫描时自动排除 `.git` 目录（默认开启）
+    #[arg(
+        long = "auto-git-exclude",
+        overrides_with = "no_auto_git_exclude",
+        global = true
+    )]
+    pub auto_git_exclude: bool,
+
+    /// 关闭 --auto-git-exclude，在仓库内扫描时保留 `.git`（仍受隐藏文件规则约束）
+    #[arg(
+        long = "no-auto-git-exclude",
+        overrides_with = "auto_git_exclude",
+        global = true
+    )]
+    pub no_auto_git_exclude: bool,
+
+    /// 跳过被 .gitignore 忽略的文件与目录（支持嵌套的 .gitignore 与 ! 否定模式）
+    #[arg(long = "gitignore", global = true)]
+    pub gitignore: bool,
+
     /// 使用流式模式以降低内存占用
-    #[arg(long = "streaming", help = "Use streaming mode for low memory usage")]
+    #[arg(
+        long = "streaming",
+        help = "Use streaming mode for low memory usage",
+        global = true
+    )]
     pub streaming: bool,
+
+    /// 构建内存树的并行线程数（1 = 顺序，0 = 自动）；流式模式始终顺序
+    #[arg(long = "threads", value_name = "N", default_value = "1", global = true)]
+    pub threads: usize,
+
+    /// 流式模式下每输出 N 个节点向 stderr 写一行累计文件数与字节数
+    #[arg(
+        long = "running-totals",
+        value_name = "N",
+        requires = "streaming",
+        global = true
+    )]
+    pub running_totals: Option<usize>,
+
+    /// 扩展名表中占总大小低于 P% 的扩展名合并为一行 (others)
+    #[arg(long = "min-percentage", value_name = "P", global = true)]
+    pub min_percentage: Option<f64>,
+
+    /// 扩展名表最多显示 N 个扩展名（按文件数），其余汇总为一行 `(M more extensions, …)`
+    #[arg(long = "ext-limit", value_name = "N", global = true)]
+    pub ext_limit: Option<usize>,
+
+    /// 要求路径必须是目录（否则报错，而非输出单文件树）
+    #[arg(long = "require-dir", global = true)]
+    pub require_dir: bool,
+
+    /// 只显示其下（任意深度）含有匹配该 glob 的文件的目录及其祖先
+    #[arg(long = "dirs-containing", value_name = "GLOB", global = true)]
+    pub dirs_containing: Option<String>,
+
+    /// 只显示自给定提交（任意 revspec，如 HEAD~5、分支名）以来有变化的文件
+    #[arg(long = "since-commit", value_name = "REV", global = true)]
+    pub since_commit: Option<String>,
+
+    /// 树形输出中每个节点显示相对当前工作目录的路径（不在其下时显示绝对路径）
+    #[arg(long = "path-from-cwd", global = true)]
+    pub path_from_cwd: bool,
+
+    /// 输出中的路径（树、JSON、CSV、最大文件、重复文件、空目录等）都相对此目录给出（可为祖先或兄弟目录，必要时含 `..`；无共同祖先时为绝对路径）
+    #[arg(
+        long = "relative-to",
+        value_name = "BASE",
+        conflicts_with = "path_from_cwd",
+        global = true
+    )]
+    pub relative_to: Option<PathBuf>,
+
+    /// 树形输出的最大行宽（列）；超出部分被裁掉
+    #[arg(long = "width", value_name = "N", global = true)]
+    pub width: Option<usize>,
+
+    /// 在树形输出后附加图例，说明各颜色与符号的含义
+    #[arg(long = "legend", global = true)]
+    pub legend: bool,
+
+    /// 超宽行以 → 结尾，表明右侧还有内容（未给出 --width 时取 $COLUMNS，默认 80）
+    #[arg(long = "scroll-indicator", global = true)]
+    pub scroll_indicator: bool,
+
+    /// 在树形输出后报告被过滤器排除的文件与目录数
+    #[arg(long = "show-filtered", global = true)]
+    pub show_filtered: bool,
+
+    /// 过滤后没有任何文件时以退出码 3 失败（用于 CI 断言文件存在）
+    #[arg(long = "fail-if-empty", global = true)]
+    pub fail_if_empty: bool,
+
+    /// 只输出一个数字：文件总数（配合 --dirs 为目录总数），不输出其他内容
+    #[arg(long = "count-only", global = true)]
+    pub count_only: bool,
+
+    /// 只输出最大文件相对扫描根的路径；没有文件时不输出并以退出码 3 失败
+    #[arg(long = "largest-path", conflicts_with = "count_only", global = true)]
+    pub largest_path: bool,
+
+    /// 最大文件表显示完整路径而非文件名；过长的路径从左侧截断（`…/deep/file.bin`）以适应终端宽度
+    #[arg(long = "full-paths", global = true)]
+    pub full_paths: bool,
+
+    /// 配合 --count-only：统计目录数而非文件数
+    #[arg(long = "dirs", requires = "count_only", global = true)]
+    pub count_dirs: bool,
+
+    /// 从指定文件读取默认选项（代替查找 .rust-tree.toml）
+    #[arg(
+        long = "config",
+        value_name = "FILE",
+        conflicts_with = "no_config",
+        global = true
+    )]
+    #[serde(skip)]
+    pub config_file: Option<PathBuf>,
+
+    /// 忽略 .rust-tree.toml 配置文件
+    #[arg(long = "no-config", global = true)]
+    #[serde(skip)]
+    pub no_config: bool,
+
+    /// 以 JSON 打印最终生效的配置并退出（不扫描）
+    #[arg(long = "print-config", global = true)]
+    #[serde(skip)]
+    pub print_config: bool,
+
+    /// 打印 `-f json` 输出的 JSON Schema 并退出（不扫描）
+    #[arg(long = "print-schema", global = true)]
+    #[serde(skip)]
+    pub print_schema: bool,
 }
 
 impl Config {
     /// 校验命令行参数的合法性。
     ///
-    /// 目前校验 `--exclude-common` 是否为受支持的语言；未知语言在此报错，
-    /// 而非像 `to_walk_config` 那样静默跳过。
+    /// 校验 `--exclude-common` 是否为受支持的语言（未知语言在此报错，
+    /// 而非像 `to_walk_config` 那样静默跳过）、`--dirs-containing` 是否为合法 glob，
+    /// `--newer-than` 与 `--older-than` 是否有交集，以及 `--min-percentage` 是否位于 0–100。
     pub fn validate(&self) -> Result<(), crate::core::models::TreeError> {
         if let Some(ref lang) = self.exclude_common {
             if !EXCLUDE_COMMON_LANGS.contains(&lang.as_str()) {
@@ -147,9 +758,106 @@ impl Config {
                 )));
             }
         }
+        // 过滤模式在 to_walk_config 中构建，这里提前报告无法解析的模式，
+        // 避免其被静默忽略。
+        for pattern in &self.exclude {
+            glob::Pattern::new(pattern).map_err(|e| {
+                crate::core::models::TreeError::Other(format!(
+                    "invalid --exclude pattern '{}': {}",
+                    pattern, e
+                ))
+            })?;
+        }
+        if let Some(ref pattern) = self.include_only {
+            glob::Pattern::new(pattern).map_err(|e| {
+                crate::core::models::TreeError::Other(format!(
+                    "invalid --include-only pattern '{}': {}",
+                    pattern, e
+                ))
+            })?;
+        }
+        if let Some(ref pattern) = self.dirs_containing {
+            glob::Pattern::new(pattern).map_err(|e| {
+                crate::core::models::TreeError::Other(format!(
+                    "invalid --dirs-containing pattern '{}': {}",
+                    pattern, e
+                ))
+            })?;
+        }
+        if self.max_depth > 0 && self.min_depth > self.max_depth {
+            return Err(crate::core::models::TreeError::Other(format!(
+                "--min-depth {} is greater than --depth {}; nothing would be shown",
+                self.min_depth, self.max_depth
+            )));
+        }
+        // 其余格式都会输出名称或路径。
+        if self.shape_only && self.format != OutputFormat::Tree {
+            return Err(crate::core::models::TreeError::Other(
+                "--shape-only only applies to tree output (-f tree)".to_string(),
+            ));
+        }
+        // names、outline、manifest、markdown 只输出相对扫描根的名称，prometheus 只有
+        // 扫描根这一个路径标签：这些格式中没有可改写的路径。
+        if self.relative_to.is_some()
+            && !matches!(
+                self.format,
+                OutputFormat::Tree
+                    | OutputFormat::Json
+                    | OutputFormat::Csv
+                    | OutputFormat::Table
+                    | OutputFormat::Html
+            )
+        {
+            return Err(crate::core::models::TreeError::Other(
+                "--relative-to only applies to tree, json, csv, table and html output".to_string(),
+            ));
+        }
+        if self.compat_tree && self.format != OutputFormat::Tree {
+            return Err(crate::core::models::TreeError::Other(
+                "--compat-tree only applies to tree output (-f tree)".to_string(),
+            ));
+        }
+        if self.include_prune && self.include_only.is_none() && self.ext.is_empty() {
+            return Err(crate::core::models::TreeError::Other(
+                "--include-prune requires --include-only or --ext".to_string(),
+            ));
+        }
+        if self.collapse_depth == Some(0) {
+            return Err(crate::core::models::TreeError::Other(
+                "--collapse-depth must be greater than 0".to_string(),
+            ));
+        }
+        if self.running_totals == Some(0) {
+            return Err(crate::core::models::TreeError::Other(
+                "--running-totals must be greater than 0".to_string(),
+            ));
+        }
+        if let (Some(newer), Some(older)) = (self.newer_than, self.older_than) {
+            if newer <= older {
+                return Err(crate::core::models::TreeError::Other(format!(
+                    "no file can be newer than {} and older than {}; \
+                     the --newer-than age must be larger than the --older-than age",
+                    format_age(newer),
+                    format_age(older)
+                )));
+            }
+        }
+        if let Some(p) = self.min_percentage {
+            if !(0.0..=100.0).contains(&p) {
+                return Err(crate::core::models::TreeError::Other(format!(
+                    "--min-percentage must be between 0 and 100, got {}",
+                    p
+                )));
+            }
+        }
         Ok(())
     }
 
+    /// 将最终生效的配置序列化为美化的 JSON（用于 `--print-config`）。
+    pub fn to_json(&self) -> Result<String, crate::core::models::TreeError> {
+        serde_json::to_string_pretty(self).map_err(crate::core::models::TreeError::from)
+    }
+
     /// 转换为 WalkConfig，供 walker 模块使用。
     pub fn to_walk_config(&self) -> WalkConfig {
         use crate::core::filter::common_excludes;
@@ -168,6 +876,39 @@ impl Config {
             let _ = filter.set_include(pattern);
         }
 
+        // 只保留指定扩展名的文件
+        for ext in &self.ext {
+            filter.add_extension(ext);
+        }
+        filter.include_prune = self.include_prune;
+
+        // 文件大小范围
+        filter.min_size = self.min_size;
+        filter.max_size = self.max_size;
+
+        // 修改时间范围：相对当前时间换算为绝对时间点
+        let now = SystemTime::now();
+        filter.modified_after = self.newer_than.and_then(|age| now.checked_sub(age));
+        filter.modified_before = self.older_than.and_then(|age| now.checked_sub(age));
+
+        // 跳过被 .gitignore 忽略的路径（各目录的文件在遍历到时才读取）
+        if self.gitignore {
+            filter.respect_gitignore = true;
+            filter.load_gitignore(&self.path);
+        }
+
+        // 在 git 仓库中扫描时自动剪除 `.git`（含嵌套仓库与子模块的 `.git`）。
+        if self.auto_git_exclude_enabled() && crate::core::git::find_git_dir(&self.path).is_some() {
+            filter.add_prune_dir(crate::core::git::GIT_DIR);
+        }
+
+        // 版本控制目录：走按名剪除的快速路径，其内部不会被遍历或 stat。
+        if self.exclude_vcs {
+            for name in common_excludes::vcs_dirs() {
+                filter.add_prune_dir(name);
+            }
+        }
+
         // 添加常用排除项
         if let Some(ref lang) = self.exclude_common {
             match lang.as_str() {
@@ -195,35 +936,242 @@ impl Config {
             }
         }
 
-        // 是否真正需要文件的字节大小：
-        // - 显示 size（-s）时需要；
-        // - 统计信息（-S / -f json / -f table）会用到 total_size / largest_files；
+        // 需要获取哪些元数据由各输出方声明，这里只取并集：
+        // - 树形输出（内存/流式）仅在显示 size（-s）时需要 SIZE；
+        // - 统计信息（-S / -f json / -f table / -f prometheus）会用到
+        //   total_size / largest_files；
         // - 按 size 排序的需求由 walk_children 内部 OR `sort_by == Size` 兜底，
         //   无需在此置位。
         //
         // 关键优化：默认 `rust-tree`（无 -s / -S）既不显示 size 也不打印统计，
-        // 此时 collect_stats 的结果会被丢弃——历史上内存路径无条件 need_size
-        // 会让每个文件白做一次 stat（大目录下 ~19s 全是内核态 syscall）。
-        // 改为按需后，默认调用与 streaming 默认路径持平。
+        // 此时 collect_stats 的结果会被丢弃——历史上内存路径无条件 stat
+        // 会让每个文件白做一次 syscall（大目录下 ~19s 全是内核态 syscall）。
+        // 改为按需后，默认调用完全跳过 metadata()，与 streaming 默认路径持平。
         //
-        // streaming 分支 should_show_stats() 恒为 false（该组合在 run() 中已被
-        // 拒绝），故本公式对两种路径统一成立。
-        let need_size = self.show_size || self.should_show_stats();
+        // streaming 分支 should_show_stats() 只在流式树的 -S 下为真（其余组合在
+        // run() 中已被拒绝），此时汇总行同样需要文件大小，故本公式对两种路径统一成立。
+        let metadata = self.required_metadata();
 
         WalkConfig {
-            max_depth: self.max_depth,
+            max_depth: self.collapse_depth.unwrap_or(self.max_depth),
+            min_depth: self.min_depth,
             show_hidden: self.show_hidden,
             follow_symlinks: self.follow_symlinks,
             sort_by: self.sort_by.into(),
             reverse: self.reverse,
+            dirs_first: !self.compat_tree,
             filter,
-            need_size,
+            metadata,
+            allow_file_root: !self.require_dir,
+            threads: self.threads,
+            mark_followed_symlinks: self.mark_followed,
+            max_entries_per_dir: self.filelimit.unwrap_or(0),
+            summarize_truncated: self.collapse_depth.is_some() || self.aggregates_sizes(),
+            same_device: if self.one_filesystem {
+                std::fs::metadata(&self.path)
+                    .ok()
+                    .and_then(|meta| device_id(&meta))
+            } else {
+                None
+            },
         }
     }
 
+    /// 汇总当前输出格式及统计信息所声明需要的元数据字段。
+    pub fn required_metadata(&self) -> MetadataFields {
+        use crate::formatters::{csv, html, json, manifest, ndjson, prometheus, table, tree};
+
+        let mut fields = match self.format {
+            OutputFormat::Tree => tree::required_metadata(
+                self.show_file_size() || self.dir_size || self.aggregates_sizes(),
+            ),
+            OutputFormat::Json => json::REQUIRED_METADATA,
+            OutputFormat::Html => html::REQUIRED_METADATA,
+            OutputFormat::Table => table::REQUIRED_METADATA,
+            OutputFormat::Prometheus => prometheus::REQUIRED_METADATA,
+            OutputFormat::Outline | OutputFormat::Names => MetadataFields::empty(),
+            OutputFormat::Manifest => manifest::REQUIRED_METADATA,
+            OutputFormat::Markdown => tree::required_metadata(self.show_file_size()),
+            OutputFormat::Csv => csv::required_metadata(&self.fields),
+            OutputFormat::Ndjson => ndjson::required_metadata(&self.fields),
+        };
+        if self.collects_stats() {
+            fields |= crate::core::collector::REQUIRED_METADATA;
+        }
+        // 按所有者着色需要 uid（只有树形输出会着色）。
+        if self.format == OutputFormat::Tree && self.effective_color_scheme() == ColorScheme::Owner
+        {
+            fields |= MetadataFields::OWNER;
+        }
+        // 累计字节数、--largest-path 与 --duplicates（按大小分组）需要文件大小。
+        if self.running_totals.is_some() || self.largest_path || self.duplicates {
+            fields |= MetadataFields::SIZE;
+        }
+        if self.count_lines {
+            fields |= MetadataFields::LINES;
+        }
+        // 权限只在树形输出中显示。
+        if self.show_perms && self.format == OutputFormat::Tree {
+            fields |= MetadataFields::MODE;
+        }
+        fields
+    }
+
+    /// 转换为 StatsOptions，供统计收集使用。
+    pub fn to_stats_options(&self) -> StatsOptions {
+        StatsOptions {
+            largest_limit: self.top_files_count(),
+            ignore_empty: self.stats_ignore_empty,
+            find_duplicates: self.duplicates,
+            empty_duplicates: self.duplicates_empty.into(),
+            count_lines: self.count_lines,
+            ..Default::default()
+        }
+    }
+
+    /// 转换为 TableOptions，供表格格式化器使用。
+    pub fn to_table_options(&self) -> TableOptions {
+        TableOptions {
+            min_percentage: self.min_percentage,
+            ext_limit: self.ext_limit,
+            thousands_sep: self.thousands_sep,
+            compact_counts: self.compact_numbers,
+            repeat_header: self.repeat_header,
+            path_width: self.full_paths.then(|| self.terminal_width()),
+            extension_labels: self.extension_labels(),
+            size: self.size_options(),
+        }
+    }
+
+    /// 树形输出（内存与流式）的连接线字符集。
+    pub fn tree_charset(&self) -> TreeCharset {
+        if self.ascii {
+            TreeCharset::Ascii
+        } else {
+            TreeCharset::Unicode
+        }
+    }
+
+    /// 各格式化器共用的大小格式化选项。
+    pub fn size_options(&self) -> SizeOptions {
+        SizeOptions {
+            fixed_unit: self.size_unit,
+            ..Default::default()
+        }
+    }
+
+    /// 统计表使用的扩展名友好名称：`--ext-label` 的条目覆盖内置映射。
+    pub fn extension_labels(&self) -> ExtensionLabels {
+        let mut labels = ExtensionLabels::new(!self.no_ext_labels);
+        for (extension, label) in &self.ext_label {
+            labels.insert(extension, label.as_str());
+        }
+        labels
+    }
+
+    /// 是否在文件后显示大小（`--size` 或 `--file-size`）。
+    pub fn show_file_size(&self) -> bool {
+        self.show_size || self.file_size
+    }
+
+    /// 树形输出的行宽限制。
+    ///
+    /// 优先使用 `--width`；仅给出 `--scroll-indicator` 时取 `$COLUMNS`，
+    /// 无法解析则为 80。两者都未给出时不限制。
+    pub fn line_width(&self) -> Option<usize> {
+        if self.width.is_some() || !self.scroll_indicator {
+            return self.width;
+        }
+        Some(self.terminal_width())
+    }
+
+    /// 终端宽度：`--width`，否则 `$COLUMNS`，都无法取得时为 80。
+    pub fn terminal_width(&self) -> usize {
+        self.width.unwrap_or_else(|| {
+            std::env::var("COLUMNS")
+                .ok()
+                .and_then(|c| c.trim().parse().ok())
+                .filter(|&c: &usize| c > 0)
+                .unwrap_or(80)
+        })
+    }
+
+    /// 实际使用的配色方案：`--color-by owner` 优先于 `--color-scheme`。
+    pub fn effective_color_scheme(&self) -> ColorScheme {
+        match self.color_by {
+            ColorBy::Type => self.color_scheme,
+            ColorBy::Owner => ColorScheme::Owner,
+        }
+    }
+
+    /// 按当前配色方案与颜色模式生成 `--legend` 图例。
+    pub fn format_legend(&self) -> String {
+        color::format_legend(
+            self.effective_color_scheme(),
+            color::should_use_colors(self.color_mode),
+        )
+    }
+
+    /// 转换为 TreeOptions，供树形格式化器使用。
+    ///
+    /// `--path-from-cwd` 在此解析当前工作目录；无法获取时退回显示名称。
+    pub fn to_tree_options(&self) -> TreeOptions {
+        TreeOptions {
+            file_size: self.show_file_size() || self.du,
+            dir_count: (self.show_size || self.dir_count) && !self.du,
+            dir_size: self.dir_size,
+            du: self.du,
+            size_column: self.size_column,
+            compact_levels: self.compact_levels.then_some((self.head, self.tail)),
+            color_mode: self.color_mode,
+            color_scheme: self.effective_color_scheme(),
+            compact_counts: self.compact_numbers,
+            path_from_cwd: if self.path_from_cwd {
+                std::env::current_dir().ok()
+            } else {
+                None
+            },
+            relative_to: self.relative_base(),
+            size: self.size_options(),
+            show_perms: self.show_perms,
+            shape_only: self.shape_only,
+            compat_tree: self.compat_tree,
+            collapse_summaries: self.collapse_depth.is_some(),
+            charset: self.tree_charset(),
+        }
+    }
+
+    /// `--relative-to` 的 `(base, cwd)`：路径相对 `base` 给出，相对路径按 `cwd` 补全。
+    pub fn relative_base(&self) -> Option<(PathBuf, PathBuf)> {
+        let base = self.relative_to.as_ref()?;
+        Some((base.clone(), std::env::current_dir().ok()?))
+    }
+
+    /// 目录是否显示其子树的总大小（`--du` 或 `--size-column`）。
+    pub fn aggregates_sizes(&self) -> bool {
+        self.du || self.size_column
+    }
+
+    /// 是否自动排除 git 仓库的 `.git` 目录（未给出 `--no-auto-git-exclude` 时开启）。
+    pub fn auto_git_exclude_enabled(&self) -> bool {
+        !self.no_auto_git_exclude
+    }
+
     /// 检查是否应显示统计信息。
     pub fn should_show_stats(&self) -> bool {
-        self.show_stats || matches!(self.format, OutputFormat::Json | OutputFormat::Table)
+        self.show_stats
+            || matches!(
+                self.format,
+                OutputFormat::Json
+                    | OutputFormat::Html
+                    | OutputFormat::Table
+                    | OutputFormat::Prometheus
+            )
+    }
+
+    /// 是否需要收集统计信息：显示在主输出中、写入 `--stats-file`，或与 `--compare-stats` 的基线比较。
+    pub fn collects_stats(&self) -> bool {
+        self.should_show_stats() || self.stats_file.is_some() || self.compare_stats.is_some()
     }
 
     /// 获取生效的最大文件显示数量。
@@ -231,3 +1179,86 @@ impl Config {
         self.top_files.max(1)
     }
 }
+
+/// 解析 `--ext-label` 的 `EXT=LABEL`（如 `ipynb=Jupyter Notebook`）。
+pub fn parse_ext_label(s: &str) -> Result<(String, String), String> {
+    let (extension, label) = s
+        .split_once('=')
+        .ok_or_else(|| format!("invalid extension label '{}' (expected EXT=LABEL)", s))?;
+    let extension = extension.trim().trim_start_matches('.');
+    let label = label.trim();
+    if extension.is_empty() || label.is_empty() {
+        return Err(format!(
+            "invalid extension label '{}' (expected EXT=LABEL)",
+            s
+        ));
+    }
+    Ok((extension.to_string(), label.to_string()))
+}
+
+/// 将 `7d`、`12h`、`30m` 之类的时长解析为 `Duration`（天、小时、分钟）。
+///
+/// 后缀不区分大小写且必须给出；数值须为非负整数。
+pub fn parse_age(s: &str) -> Result<Duration, String> {
+    let trimmed = s.trim();
+    let invalid = || format!("invalid age '{}' (expected e.g. 7d, 12h, 30m)", trimmed);
+    let lower = trimmed.to_ascii_lowercase();
+    let (number, unit_secs) = match lower.chars().last() {
+        Some('d') => (&lower[..lower.len() - 1], 24 * 60 * 60),
+        Some('h') => (&lower[..lower.len() - 1], 60 * 60),
+        Some('m') => (&lower[..lower.len() - 1], 60),
+        _ => return Err(invalid()),
+    };
+    let value: u64 = number.trim().parse().map_err(|_| invalid())?;
+    value
+        .checked_mul(unit_secs)
+        .map(Duration::from_secs)
+        .ok_or_else(invalid)
+}
+
+/// 将时长以 [`parse_age`] 接受的最大整数单位写出（用于错误信息）。
+fn format_age(age: Duration) -> String {
+    let secs = age.as_secs();
+    match secs {
+        s if s % (24 * 60 * 60) == 0 => format!("{}d", s / (24 * 60 * 60)),
+        s if s % (60 * 60) == 0 => format!("{}h", s / (60 * 60)),
+        s => format!("{}m", s / 60),
+    }
+}
+
+/// 将 `1024`、`1K`、`2.5M`、`1GiB` 之类的大小解析为字节数。
+///
+/// 后缀不区分大小写，与 [`SizeUnit`](crate::formatters::size::SizeUnit) 的含义一致：
+/// `KB`/`MB`/`GB`/`TB` 为十进制（K = 1000），`KiB`/`MiB`/`GiB`/`TiB` 为二进制（K = 1024）；
+/// 不带 `B` 的 `K`/`M`/`G`/`T` 沿用 `du -h` / `ls -h` 的习惯按二进制换算。小数结果向下取整。
+pub fn parse_size(s: &str) -> Result<u64, String> {
+    let trimmed = s.trim();
+    let lower = trimmed.to_ascii_lowercase();
+    let (unit_part, base) = if let Some(rest) = lower.strip_suffix("ib") {
+        (rest, 1024u64)
+    } else if let Some(rest) = lower.strip_suffix('b') {
+        (rest, 1000)
+    } else {
+        (lower.as_str(), 1024)
+    };
+
+    let (number, exponent) = match unit_part.chars().last() {
+        Some('k') => (&unit_part[..unit_part.len() - 1], 1),
+        Some('m') => (&unit_part[..unit_part.len() - 1], 2),
+        Some('g') => (&unit_part[..unit_part.len() - 1], 3),
+        Some('t') => (&unit_part[..unit_part.len() - 1], 4),
+        _ => (unit_part, 0),
+    };
+    let multiplier = base.pow(exponent);
+
+    let value: f64 = number.trim().parse().map_err(|_| {
+        format!(
+            "invalid size '{}' (expected e.g. 1024, 500K, 2.5M)",
+            trimmed
+        )
+    })?;
+    if !value.is_finite() || value < 0.0 {
+        return Err(format!("invalid size '{}': must be non-negative", trimmed));
+    }
+    Ok((value * multiplier as f64) as u64)
+}
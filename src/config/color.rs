@@ -1,12 +1,15 @@
 //! 树形输出的颜色配置。
 
-use crate::core::models::FsNode;
+use crate::config::ls_colors::ls_colors;
+use crate::core::models::{FsNode, FsNodeType};
 use clap::ValueEnum;
-use colored::Colorize;
+use colored::{Color, Colorize};
+use serde::Serialize;
 use std::io::IsTerminal;
 
 /// 颜色方案选项。
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum ColorScheme {
     /// 无颜色
     #[default]
@@ -15,10 +18,31 @@ pub enum ColorScheme {
     Basic,
     /// 扩展颜色方案（支持更多文件类型）
     Extended,
+    /// 按所有者着色：同一用户（uid）的条目颜色相同（仅 Unix）。不能用
+    /// `--color-scheme` 选择，只由 `--color-by owner` 经
+    /// [`Config::effective_color_scheme`](crate::Config::effective_color_scheme) 选中
+    #[value(skip)]
+    Owner,
+    /// 按 `$LS_COLORS` 着色，与 `ls` 一致
+    #[value(name = "ls-colors")]
+    #[serde(rename = "ls-colors")]
+    LsColors,
+}
+
+/// 着色依据（`--color-by`）。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorBy {
+    /// 按类型与扩展名着色，使用 `--color-scheme` 指定的方案（默认）
+    #[default]
+    Type,
+    /// 按所有者着色，每个 uid 一种固定颜色（忽略 `--color-scheme`）
+    Owner,
 }
 
 /// 何时使用颜色。
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum ColorMode {
     /// 始终使用颜色
     Always,
@@ -29,68 +53,294 @@ pub enum ColorMode {
     Auto,
 }
 
+/// 一类文件的颜色：类别名、归入该类的扩展名及其颜色。
+///
+/// 着色与图例（`--legend`）共用这些表，二者不会不同步。
+#[derive(Debug, Clone, Copy)]
+pub struct FileColor {
+    /// 类别名（如 "source"），用于图例
+    pub category: &'static str,
+    /// 归入该类别的扩展名（不含点号）
+    pub extensions: &'static [&'static str],
+    /// 该类别的颜色
+    pub color: Color,
+}
+
+/// 基础的文件颜色方案。
+const BASIC_FILE_COLORS: &[FileColor] = &[
+    FileColor {
+        category: "source",
+        extensions: &[
+            "rs", "py", "js", "ts", "java", "c", "cpp", "go", "rb", "php",
+        ],
+        color: Color::Green,
+    },
+    FileColor {
+        category: "config",
+        extensions: &["toml", "yaml", "yml", "json", "xml"],
+        color: Color::Yellow,
+    },
+    FileColor {
+        category: "docs",
+        extensions: &["md", "txt", "rst"],
+        color: Color::White,
+    },
+    FileColor {
+        category: "lock",
+        extensions: &["lock"],
+        color: Color::BrightBlack,
+    },
+];
+
+/// 扩展的文件颜色方案，支持更多文件类型。
+const EXTENDED_FILE_COLORS: &[FileColor] = &[
+    // 源代码文件
+    FileColor {
+        category: "rust",
+        extensions: &["rs"],
+        color: Color::BrightGreen,
+    },
+    FileColor {
+        category: "python",
+        extensions: &["py"],
+        color: Color::Green,
+    },
+    FileColor {
+        category: "javascript",
+        extensions: &["js", "ts", "tsx", "jsx"],
+        color: Color::Yellow,
+    },
+    FileColor {
+        category: "c/java",
+        extensions: &["java", "c", "cpp", "h", "hpp"],
+        color: Color::Blue,
+    },
+    FileColor {
+        category: "go",
+        extensions: &["go"],
+        color: Color::Cyan,
+    },
+    FileColor {
+        category: "ruby/php",
+        extensions: &["rb", "php"],
+        color: Color::Magenta,
+    },
+    // 配置文件
+    FileColor {
+        category: "config",
+        extensions: &["toml", "yaml", "yml"],
+        color: Color::BrightYellow,
+    },
+    FileColor {
+        category: "data",
+        extensions: &["json", "xml"],
+        color: Color::Yellow,
+    },
+    FileColor {
+        category: "ini",
+        extensions: &["ini", "cfg", "conf"],
+        color: Color::BrightBlack,
+    },
+    // 文档
+    FileColor {
+        category: "docs",
+        extensions: &["md", "rst", "adoc"],
+        color: Color::White,
+    },
+    FileColor {
+        category: "text",
+        extensions: &["txt"],
+        color: Color::BrightWhite,
+    },
+    // 构建/锁文件
+    FileColor {
+        category: "lock",
+        extensions: &["lock"],
+        color: Color::BrightBlack,
+    },
+    // 图片
+    FileColor {
+        category: "image",
+        extensions: &["png", "jpg", "jpeg", "gif", "svg", "ico"],
+        color: Color::BrightMagenta,
+    },
+    // 归档文件
+    FileColor {
+        category: "archive",
+        extensions: &["zip", "tar", "gz", "rar", "7z"],
+        color: Color::Red,
+    },
+];
+
+/// `Owner` 方案的调色板；uid 经哈希后落在其中一种颜色上。
+const OWNER_PALETTE: &[Color] = &[
+    Color::Red,
+    Color::Green,
+    Color::Yellow,
+    Color::Blue,
+    Color::Magenta,
+    Color::Cyan,
+    Color::BrightRed,
+    Color::BrightGreen,
+    Color::BrightYellow,
+    Color::BrightBlue,
+    Color::BrightMagenta,
+    Color::BrightCyan,
+];
+
+/// 为 uid 选择颜色：同一 uid 总是得到同一颜色，且与运行环境无关。
+pub fn owner_color(uid: u32) -> Color {
+    // Knuth 乘法哈希：相邻的 uid（1000、1001…）也会分散到不同颜色。
+    let hash = uid.wrapping_mul(2_654_435_761) >> 16;
+    OWNER_PALETTE[hash as usize % OWNER_PALETTE.len()]
+}
+
+/// 返回配色方案中按扩展名着色的文件类别（`None`、`Owner`、`LsColors` 方案为空）。
+pub fn file_colors(scheme: ColorScheme) -> &'static [FileColor] {
+    match scheme {
+        ColorScheme::None | ColorScheme::Owner | ColorScheme::LsColors => &[],
+        ColorScheme::Basic => BASIC_FILE_COLORS,
+        ColorScheme::Extended => EXTENDED_FILE_COLORS,
+    }
+}
+
 /// 根据节点类型为节点名称着色。
 pub fn colorize_node(node: &FsNode, scheme: ColorScheme) -> colored::ColoredString {
-    match node.node_type {
-        crate::core::models::FsNodeType::Directory => node.name.clone().blue().bold(),
-        crate::core::models::FsNodeType::File => colorize_file(&node.name, scheme),
-        crate::core::models::FsNodeType::Symlink => node.name.clone().cyan().italic(),
-    }
+    colorize_label(node, &node.name, scheme)
 }
 
-/// 根据扩展名为文件名着色。
-fn colorize_file(name: &str, scheme: ColorScheme) -> colored::ColoredString {
-    let ext = name.rsplit('.').next().unwrap_or("");
+/// 按节点的类型与扩展名为任意显示文本（如相对路径）着色。
+pub fn colorize_label(node: &FsNode, label: &str, scheme: ColorScheme) -> colored::ColoredString {
+    colorize_by_type(label, &node.name, &node.node_type, node.uid, scheme)
+}
 
-    match scheme {
-        ColorScheme::None => name.normal(),
-        ColorScheme::Basic => basic_file_color(name, ext),
-        ColorScheme::Extended => extended_file_color(name, ext),
+/// 按类型与扩展名（取自 `file_name`）为 `label` 着色。
+///
+/// 目录为粗体蓝色、符号链接为斜体青色，与配色方案无关；文件按方案的扩展名表着色。
+/// `Owner` 方案下改按 `uid` 取色（目录仍加粗、链接仍为斜体），uid 未知时退回无颜色。
+pub fn colorize_by_type(
+    label: &str,
+    file_name: &str,
+    node_type: &FsNodeType,
+    uid: Option<u32>,
+    scheme: ColorScheme,
+) -> colored::ColoredString {
+    if scheme == ColorScheme::Owner {
+        let styled = match uid {
+            Some(uid) => label.color(owner_color(uid)),
+            None => label.normal(),
+        };
+        return match node_type {
+            FsNodeType::Directory => styled.bold(),
+            FsNodeType::File => styled,
+            FsNodeType::Symlink => styled.italic(),
+        };
+    }
+
+    // LsColors：目录与链接优先采用 `di` / `ln`，未设置时与其他方案相同。
+    let ls_type_style = |key| {
+        (scheme == ColorScheme::LsColors)
+            .then(|| ls_colors().type_style(key))
+            .flatten()
+    };
+    match node_type {
+        FsNodeType::Directory => {
+            ls_type_style("di").map_or_else(|| label.blue().bold(), |style| style.paint(label))
+        }
+        FsNodeType::File => colorize_file(label, file_name, scheme),
+        FsNodeType::Symlink => {
+            ls_type_style("ln").map_or_else(|| label.cyan().italic(), |style| style.paint(label))
+        }
     }
 }
 
-/// 基础的文件颜色方案。
-fn basic_file_color(name: &str, ext: &str) -> colored::ColoredString {
-    match ext {
-        "rs" | "py" | "js" | "ts" | "java" | "c" | "cpp" | "go" | "rb" | "php" => name.green(),
-        "toml" | "yaml" | "yml" | "json" | "xml" => name.yellow(),
-        "md" | "txt" | "rst" => name.white(),
-        "lock" => name.bright_black(),
-        _ => name.normal(),
+/// 在配色方案中查找 `file_name` 的扩展名所属的文件类别。
+pub fn file_color_for(file_name: &str, scheme: ColorScheme) -> Option<&'static FileColor> {
+    let ext = file_name.rsplit('.').next().unwrap_or("");
+    file_colors(scheme)
+        .iter()
+        .find(|entry| entry.extensions.contains(&ext))
+}
+
+/// 根据扩展名（取自 `file_name`）为 `name` 着色。
+///
+/// `LsColors` 方案先查 `*.ext` 条目，再查 `fi`，都没有时不着色。
+fn colorize_file(name: &str, file_name: &str, scheme: ColorScheme) -> colored::ColoredString {
+    if scheme == ColorScheme::LsColors {
+        let colors = ls_colors();
+        return colors
+            .extension_style(file_name)
+            .or_else(|| colors.type_style("fi"))
+            .map_or_else(|| name.normal(), |style| style.paint(name));
     }
+    file_color_for(file_name, scheme).map_or_else(|| name.normal(), |entry| name.color(entry.color))
 }
 
-/// 扩展的文件颜色方案，支持更多文件类型。
-fn extended_file_color(name: &str, ext: &str) -> colored::ColoredString {
-    match ext {
-        // 源代码文件
-        "rs" => name.bright_green(),
-        "py" => name.green(),
-        "js" | "ts" | "tsx" | "jsx" => name.yellow(),
-        "java" | "c" | "cpp" | "h" | "hpp" => name.blue(),
-        "go" => name.cyan(),
-        "rb" | "php" => name.magenta(),
-
-        // 配置文件
-        "toml" | "yaml" | "yml" => name.bright_yellow(),
-        "json" | "xml" => name.yellow(),
-        "ini" | "cfg" | "conf" => name.bright_black(),
-
-        // 文档
-        "md" | "rst" | "adoc" => name.white(),
-        "txt" => name.bright_white(),
-
-        // 构建/锁文件
-        "lock" => name.bright_black(),
-
-        // 图片
-        "png" | "jpg" | "jpeg" | "gif" | "svg" | "ico" => name.bright_magenta(),
-
-        // 归档文件
-        "zip" | "tar" | "gz" | "rar" | "7z" => name.red(),
-
-        _ => name.normal(),
+/// 生成图例：说明配色方案中各颜色与树中各符号的含义（`--legend`）。
+///
+/// `use_color` 为假时输出纯文本，示例文字不带颜色。
+pub fn format_legend(scheme: ColorScheme, use_color: bool) -> String {
+    let mut entries: Vec<(String, colored::ColoredString, String)> = if scheme == ColorScheme::Owner
+    {
+        vec![
+            ("dir/".to_string(), "dir/".bold(), "directory".to_string()),
+            (
+                "link".to_string(),
+                "link".italic(),
+                "symbolic link".to_string(),
+            ),
+            (
+                "owner".to_string(),
+                "owner".color(owner_color(0)),
+                "one color per owning user (uid)".to_string(),
+            ),
+        ]
+    } else {
+        vec![
+            (
+                "dir/".to_string(),
+                colorize_by_type("dir/", "dir", &FsNodeType::Directory, None, scheme),
+                "directory".to_string(),
+            ),
+            (
+                "link".to_string(),
+                colorize_by_type("link", "link", &FsNodeType::Symlink, None, scheme),
+                "symbolic link".to_string(),
+            ),
+        ]
+    };
+    for entry in file_colors(scheme) {
+        let extensions: Vec<String> = entry.extensions.iter().map(|e| format!(".{}", e)).collect();
+        entries.push((
+            entry.category.to_string(),
+            entry.category.color(entry.color),
+            extensions.join(" "),
+        ));
+    }
+    for (symbol, meaning) in [
+        ("-> target", "symlink target"),
+        ("[loop]", "link back to an ancestor, not expanded"),
+        ("...", "non-empty directory beyond --depth"),
+    ] {
+        entries.push((symbol.to_string(), symbol.normal(), meaning.to_string()));
+    }
+
+    let width = entries
+        .iter()
+        .map(|(plain, _, _)| plain.chars().count())
+        .max()
+        .unwrap_or(0);
+    let mut legend = String::from("Legend:\n");
+    for (plain, styled, meaning) in &entries {
+        let sample = if use_color {
+            styled.to_string()
+        } else {
+            plain.clone()
+        };
+        let padding = " ".repeat(width - plain.chars().count());
+        legend.push_str(&format!("  {}{}  {}\n", sample, padding, meaning));
     }
+    legend
 }
 
 /// 根据模式判断是否应使用颜色。
@@ -0,0 +1,127 @@
+//! `.rust-tree.toml` 配置文件的查找、加载与合并。
+//!
+//! 文件中的键是 CLI 的长选项名（不含 `--`），值是该选项的取值：
+//!
+//! ```toml
+//! depth = 3
+//! format = "table"
+//! color = "always"
+//! exclude = ["target", "*.log"]
+//! all = true
+//! ```
+//!
+//! 合并时，命令行上显式给出的选项优先；其余选项由文件中的值补足。文件值会被
+//! 转换成 `--key=value` 形式再交给 clap 解析，因此与命令行取值经过完全相同的校验。
+
+use crate::config::Config;
+use crate::core::models::TreeError;
+use clap::parser::ValueSource;
+use clap::{ArgMatches, CommandFactory, FromArgMatches};
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+/// 配置文件名。依次在当前目录与用户主目录中查找。
+pub const CONFIG_FILE_NAME: &str = ".rust-tree.toml";
+
+/// 不能出现在配置文件中的选项（它们决定是否、从哪里读取配置文件本身）。
+const FILE_ONLY_CLI_KEYS: &[&str] = &["config", "no-config", "help", "version"];
+
+/// 在 `cwd`、`home` 中依次查找配置文件，返回第一个存在的路径。
+pub fn find_config_file(cwd: Option<&Path>, home: Option<&Path>) -> Option<PathBuf> {
+    [cwd, home]
+        .into_iter()
+        .flatten()
+        .map(|dir| dir.join(CONFIG_FILE_NAME))
+        .find(|path| path.is_file())
+}
+
+/// 读取并解析配置文件。
+///
+/// # 错误
+///
+/// 文件不存在返回 `TreeError::PathNotFound`，无法读取返回 `TreeError::Io`；TOML 语法错误返回 `TreeError::Other`。
+pub fn load_config_file(path: &Path) -> Result<toml::Table, TreeError> {
+    if !path.exists() {
+        return Err(TreeError::PathNotFound(path.to_path_buf()));
+    }
+    let text = std::fs::read_to_string(path)?;
+    text.parse::<toml::Table>()
+        .map_err(|e| TreeError::Other(format!("{}: {}", path.display(), e)))
+}
+
+/// 用配置文件补足命令行未显式给出的选项，返回合并后的配置。
+///
+/// `args` 是原始命令行（含程序名），`matches` 是其解析结果。
+///
+/// # 错误
+///
+/// 文件中含未知键、不允许的键、不支持的值类型，或取值未通过 clap 校验时返回
+/// `TreeError::Other`。
+pub fn merge_config_file(
+    args: &[OsString],
+    matches: &ArgMatches,
+    table: &toml::Table,
+) -> Result<Config, TreeError> {
+    let extra = file_args(table, matches)?;
+    let matches = if extra.is_empty() {
+        matches.clone()
+    } else {
+        // 插在程序名之后、用户参数之前：追加在末尾会落到 `--` 之后而被当作位置参数。
+        // 文件值都是自带取值的 `--key=value` 或布尔开关，不会吞掉紧随其后的参数。
+        let combined: Vec<OsString> = args
+            .iter()
+            .take(1)
+            .cloned()
+            .chain(extra)
+            .chain(args.iter().skip(1).cloned())
+            .collect();
+        Config::command()
+            .try_get_matches_from(combined)
+            .map_err(|e| TreeError::Other(format!("config file: {}", e)))?
+    };
+    Config::from_arg_matches(&matches).map_err(|e| TreeError::Other(e.to_string()))
+}
+
+/// 将文件中、且未在命令行显式给出的选项转换为 `--key=value` 参数。
+fn file_args(table: &toml::Table, matches: &ArgMatches) -> Result<Vec<OsString>, TreeError> {
+    let command = Config::command();
+    let mut args = Vec::new();
+
+    for (key, value) in table {
+        let arg = command
+            .get_arguments()
+            .find(|a| a.get_long() == Some(key.as_str()))
+            .filter(|_| !FILE_ONLY_CLI_KEYS.contains(&key.as_str()))
+            .ok_or_else(|| TreeError::Other(format!("config file: unknown option '{}'", key)))?;
+
+        if matches.value_source(arg.get_id().as_str()) == Some(ValueSource::CommandLine) {
+            continue;
+        }
+
+        let values = match value {
+            toml::Value::Array(items) => items.iter().collect(),
+            other => vec![other],
+        };
+        for value in values {
+            let text = match value {
+                toml::Value::Boolean(true) => {
+                    args.push(format!("--{}", key).into());
+                    continue;
+                }
+                toml::Value::Boolean(false) => continue,
+                toml::Value::String(s) => s.clone(),
+                toml::Value::Integer(n) => n.to_string(),
+                toml::Value::Float(f) => f.to_string(),
+                _ => {
+                    return Err(TreeError::Other(format!(
+                        "config file: unsupported value for '{}'",
+                        key
+                    )))
+                }
+            };
+            args.push(format!("--{}={}", key, text).into());
+        }
+    }
+
+    Ok(args)
+}
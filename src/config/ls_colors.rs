@@ -0,0 +1,229 @@
+//! `LS_COLORS` 环境变量的解析（`--color-scheme ls-colors`）。
+//!
+//! `LS_COLORS` 由冒号分隔的 `键=SGR 代码` 组成，例如 `di=01;34:ln=01;36:*.rs=01;32`。
+//! 这里识别两类键：
+//!
+//! - `*.ext`：按扩展名着色（不区分大小写，支持 `*.tar.gz` 这样的多段扩展名）；
+//! - 双字母的类型键：`di`（目录）、`ln`（符号链接）、`fi`（普通文件）等。
+//!
+//! 其余形式的 glob（如 `*README`）会被忽略。SGR 代码被转换为 `colored` 的样式，
+//! 支持粗体等属性、8/16 色、256 色（`38;5;n`）与真彩色（`38;2;r;g;b`）。
+
+use colored::{Color, ColoredString, Colorize};
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// 读取配色的环境变量名。
+pub const LS_COLORS_VAR: &str = "LS_COLORS";
+
+/// 由一组 SGR 代码解析出的文字样式。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LsStyle {
+    /// 前景色
+    pub fg: Option<Color>,
+    /// 背景色
+    pub bg: Option<Color>,
+    /// 粗体（`1`）
+    pub bold: bool,
+    /// 暗淡（`2`）
+    pub dimmed: bool,
+    /// 斜体（`3`）
+    pub italic: bool,
+    /// 下划线（`4`）
+    pub underline: bool,
+    /// 闪烁（`5`）
+    pub blink: bool,
+    /// 反显（`7`）
+    pub reversed: bool,
+    /// 删除线（`9`）
+    pub strikethrough: bool,
+}
+
+impl LsStyle {
+    /// 解析 `01;32` 这样的 SGR 代码序列；无法识别的代码被跳过。
+    pub fn parse(codes: &str) -> Self {
+        let mut style = Self::default();
+        let mut codes = codes
+            .split(';')
+            .map(|code| code.trim().parse::<u8>().unwrap_or(0));
+
+        while let Some(code) = codes.next() {
+            match code {
+                0 => style = Self::default(),
+                1 => style.bold = true,
+                2 => style.dimmed = true,
+                3 => style.italic = true,
+                4 => style.underline = true,
+                5 | 6 => style.blink = true,
+                7 => style.reversed = true,
+                9 => style.strikethrough = true,
+                30..=37 => style.fg = Some(basic_color(code - 30)),
+                90..=97 => style.fg = Some(basic_color(code - 90 + 8)),
+                40..=47 => style.bg = Some(basic_color(code - 40)),
+                100..=107 => style.bg = Some(basic_color(code - 100 + 8)),
+                38 => style.fg = extended_color(&mut codes),
+                48 => style.bg = extended_color(&mut codes),
+                _ => {}
+            }
+        }
+        style
+    }
+
+    /// 以该样式渲染 `text`。
+    pub fn paint(&self, text: &str) -> ColoredString {
+        let mut styled = text.normal();
+        if let Some(fg) = self.fg {
+            styled = styled.color(fg);
+        }
+        if let Some(bg) = self.bg {
+            styled = styled.on_color(bg);
+        }
+        if self.bold {
+            styled = styled.bold();
+        }
+        if self.dimmed {
+            styled = styled.dimmed();
+        }
+        if self.italic {
+            styled = styled.italic();
+        }
+        if self.underline {
+            styled = styled.underline();
+        }
+        if self.blink {
+            styled = styled.blink();
+        }
+        if self.reversed {
+            styled = styled.reversed();
+        }
+        if self.strikethrough {
+            styled = styled.strikethrough();
+        }
+        styled
+    }
+}
+
+/// 解析后的 `LS_COLORS`：类型键与扩展名各一张表。
+#[derive(Debug, Clone, Default)]
+pub struct LsColors {
+    /// 双字母类型键（`di`、`ln`、`fi`…）到样式
+    types: HashMap<String, LsStyle>,
+    /// 小写、不含前导点号的扩展名（如 `rs`、`tar.gz`）到样式
+    extensions: HashMap<String, LsStyle>,
+}
+
+impl LsColors {
+    /// 解析 `LS_COLORS` 格式的字符串；格式不对的条目被忽略。
+    pub fn parse(spec: &str) -> Self {
+        let mut colors = Self::default();
+        for entry in spec.split(':') {
+            let Some((key, codes)) = entry.split_once('=') else {
+                continue;
+            };
+            if let Some(ext) = key.strip_prefix("*.") {
+                if !ext.is_empty() {
+                    colors
+                        .extensions
+                        .insert(ext.to_lowercase(), LsStyle::parse(codes));
+                }
+            } else if key.len() == 2 && key.chars().all(|c| c.is_ascii_alphabetic()) {
+                // `ln=target` 表示“按链接目标着色”，这里无从得知目标类型，视为未设置。
+                if codes != "target" {
+                    colors.types.insert(key.to_string(), LsStyle::parse(codes));
+                }
+            }
+        }
+        colors
+    }
+
+    /// 读取并解析 `$LS_COLORS`；未设置时为空表。
+    pub fn from_env() -> Self {
+        std::env::var(LS_COLORS_VAR)
+            .map(|spec| Self::parse(&spec))
+            .unwrap_or_default()
+    }
+
+    /// 类型键（如 `di`）对应的样式。
+    pub fn type_style(&self, key: &str) -> Option<&LsStyle> {
+        self.types.get(key)
+    }
+
+    /// 文件名的扩展名对应的样式：先试最长的多段扩展名（`a.tar.gz` 先 `tar.gz` 再 `gz`）。
+    pub fn extension_style(&self, file_name: &str) -> Option<&LsStyle> {
+        if self.extensions.is_empty() {
+            return None;
+        }
+        let lower = file_name.to_lowercase();
+        // 跳过首字符，使 `.bashrc` 这样的点文件不被当作扩展名 `bashrc`。
+        lower
+            .char_indices()
+            .skip(1)
+            .filter(|&(_, c)| c == '.')
+            .find_map(|(i, _)| self.extensions.get(&lower[i + 1..]))
+    }
+}
+
+/// 进程内共享的 `$LS_COLORS` 解析结果，首次使用时读取一次。
+pub fn ls_colors() -> &'static LsColors {
+    static LS_COLORS: OnceLock<LsColors> = OnceLock::new();
+    LS_COLORS.get_or_init(LsColors::from_env)
+}
+
+/// 0–15 号颜色：8 种标准色及其高亮版本。
+fn basic_color(index: u8) -> Color {
+    match index {
+        0 => Color::Black,
+        1 => Color::Red,
+        2 => Color::Green,
+        3 => Color::Yellow,
+        4 => Color::Blue,
+        5 => Color::Magenta,
+        6 => Color::Cyan,
+        7 => Color::White,
+        8 => Color::BrightBlack,
+        9 => Color::BrightRed,
+        10 => Color::BrightGreen,
+        11 => Color::BrightYellow,
+        12 => Color::BrightBlue,
+        13 => Color::BrightMagenta,
+        14 => Color::BrightCyan,
+        _ => Color::BrightWhite,
+    }
+}
+
+/// `38;5;n` / `38;2;r;g;b`（及对应的 `48;…`）之后的参数。
+fn extended_color(codes: &mut impl Iterator<Item = u8>) -> Option<Color> {
+    match codes.next()? {
+        5 => Some(palette_color(codes.next()?)),
+        2 => Some(Color::TrueColor {
+            r: codes.next()?,
+            g: codes.next()?,
+            b: codes.next()?,
+        }),
+        _ => None,
+    }
+}
+
+/// xterm 256 色调色板中的颜色：0–15 为标准色，16–231 为 6×6×6 色立方，其余为灰阶。
+fn palette_color(index: u8) -> Color {
+    match index {
+        0..=15 => basic_color(index),
+        16..=231 => {
+            let level = |v: u8| if v == 0 { 0 } else { 55 + v * 40 };
+            let i = index - 16;
+            Color::TrueColor {
+                r: level(i / 36),
+                g: level(i / 6 % 6),
+                b: level(i % 6),
+            }
+        }
+        _ => {
+            let gray = 8 + (index - 232) * 10;
+            Color::TrueColor {
+                r: gray,
+                g: gray,
+                b: gray,
+            }
+        }
+    }
+}
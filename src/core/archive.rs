@@ -0,0 +1,245 @@
+//! 从 tar 流构建文件树（`--tar`，读自 stdin），不接触磁盘。
+//!
+//! 归档条目按路径组装成树：缺少显式目录条目的中间目录会被补上，`./` 前缀与
+//! `..` 组件被忽略，同一路径出现多次时以后出现的为准（与解包结果一致）。组装后
+//! 按 `WalkConfig` 的深度、过滤器、排序与 `max_entries_per_dir` 处理，使输出与
+//! 扫描解包后的目录一致。过滤器只按路径判断（`.gitignore` 等需要读取磁盘的规则
+//! 不适用）。
+
+use crate::core::models::{FilteredCounts, FsNode, FsNodeType, FsTree, SubtreeSummary, TreeError};
+use crate::core::streaming::sort_entries;
+use crate::core::walker::{apply_min_depth, WalkConfig};
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// `--tar` 时根节点显示的名称。
+pub const TAR_ROOT_NAME: &str = "<stdin>";
+
+/// 组装阶段的一个条目。
+#[derive(Default)]
+struct TarEntry {
+    /// 条目类型；只由子条目路径隐含出的目录为 `None`
+    node_type: Option<FsNodeType>,
+    /// 文件字节大小
+    size: u64,
+    /// 归档中记录的修改时间
+    modified: Option<SystemTime>,
+    /// 归档中记录的权限位
+    mode: Option<u32>,
+    /// 符号链接的目标
+    link_target: Option<PathBuf>,
+    /// 子条目（按名称）
+    children: BTreeMap<String, TarEntry>,
+}
+
+impl TarEntry {
+    fn is_dir(&self) -> bool {
+        matches!(self.node_type, None | Some(FsNodeType::Directory))
+    }
+}
+
+/// 读取 `reader` 中的 tar 流并构建文件树，根节点名为 `root_name`。
+///
+/// 节点的 `path` 是条目在归档中的相对路径（根为 `.`）。符号链接的目标取自归档，
+/// 因此格式化时不会去读取磁盘上的同名路径。
+///
+/// # 错误
+///
+/// 流不是合法的 tar 归档或读取失败时返回 `TreeError::Other`。
+pub fn read_tar<R: Read>(
+    reader: R,
+    root_name: &str,
+    config: &WalkConfig,
+) -> Result<FsTree, TreeError> {
+    let mut archive = tar::Archive::new(reader);
+    let mut root = TarEntry {
+        node_type: Some(FsNodeType::Directory),
+        ..Default::default()
+    };
+
+    for entry in archive.entries().map_err(tar_error)? {
+        let entry = entry.map_err(tar_error)?;
+        let entry_type = entry.header().entry_type();
+        // 扩展头只携带元数据，不对应文件系统中的条目。
+        if entry_type.is_pax_global_extensions()
+            || entry_type.is_pax_local_extensions()
+            || entry_type.is_gnu_longname()
+            || entry_type.is_gnu_longlink()
+        {
+            continue;
+        }
+
+        let path = entry.path().map_err(tar_error)?.into_owned();
+        let names: Vec<String> = path
+            .components()
+            .filter_map(|c| match c {
+                Component::Normal(name) => Some(name.to_string_lossy().into_owned()),
+                _ => None,
+            })
+            .collect();
+        let Some((last, parents)) = names.split_last() else {
+            continue;
+        };
+
+        let mut dir = &mut root;
+        for name in parents {
+            dir = dir.children.entry(name.clone()).or_default();
+            if !dir.is_dir() {
+                // 文件之后又出现了以它为前缀的条目：按目录处理。
+                dir.node_type = Some(FsNodeType::Directory);
+            }
+        }
+
+        let node = dir.children.entry(last.clone()).or_default();
+        if entry_type.is_dir() {
+            node.node_type = Some(FsNodeType::Directory);
+        } else if entry_type.is_symlink() {
+            node.node_type = Some(FsNodeType::Symlink);
+            node.link_target = Some(
+                entry
+                    .link_name()
+                    .map_err(tar_error)?
+                    .map(|target| target.into_owned())
+                    .unwrap_or_default(),
+            );
+            node.children.clear();
+        } else {
+            node.node_type = Some(FsNodeType::File);
+            node.size = entry.size();
+            node.children.clear();
+        }
+        node.modified = entry
+            .header()
+            .mtime()
+            .ok()
+            .map(|secs| SystemTime::UNIX_EPOCH + Duration::from_secs(secs));
+        node.mode = entry.header().mode().ok();
+    }
+
+    let mut filtered = FilteredCounts::default();
+    let mut max_depth = 0;
+    let mut node = build_node(
+        root_name.to_string(),
+        PathBuf::from("."),
+        &root,
+        0,
+        config,
+        &mut filtered,
+        &mut max_depth,
+    );
+    node.name = root_name.to_string();
+
+    let mut tree = FsTree::new(node, max_depth);
+    tree.filtered = filtered;
+    Ok(apply_min_depth(tree, config.min_depth))
+}
+
+/// 将组装好的条目转换为 `FsNode`，并在此应用过滤、深度限制与排序。
+fn build_node(
+    name: String,
+    path: PathBuf,
+    entry: &TarEntry,
+    depth: usize,
+    config: &WalkConfig,
+    filtered: &mut FilteredCounts,
+    max_depth: &mut usize,
+) -> FsNode {
+    *max_depth = (*max_depth).max(depth);
+    let node_type = entry.node_type.clone().unwrap_or(FsNodeType::Directory);
+    let mut node = FsNode::new(name, path.clone(), node_type, entry.size, depth);
+    node.modified = entry.modified;
+    node.mode = entry.mode;
+    node.link_target = entry.link_target.clone();
+    if !entry.is_dir() {
+        return node;
+    }
+
+    let visible: Vec<(&String, &TarEntry)> = entry
+        .children
+        .iter()
+        .filter(|(name, child)| {
+            let child_path = child_path(&path, name);
+            let is_file = child.node_type == Some(FsNodeType::File);
+            let excluded = config.filter.should_exclude(&child_path, child.is_dir())
+                || (is_file && config.filter.excludes_size(child.size))
+                || (is_file && config.filter.excludes_modified(child.modified));
+            if excluded {
+                if child.is_dir() {
+                    filtered.directories += 1;
+                } else {
+                    filtered.files += 1;
+                }
+            }
+            !excluded
+        })
+        .collect();
+
+    // 深度限制停在这里：不展开，按需记录子树的汇总（--collapse-depth、--du）。
+    if config.max_depth > 0 && depth >= config.max_depth {
+        node.truncated = !visible.is_empty();
+        if config.summarize_truncated && node.truncated {
+            node.collapsed_summary = Some(SubtreeSummary {
+                files: visible.iter().map(|(_, child)| subtree_files(child)).sum(),
+                size: visible.iter().map(|(_, child)| subtree_size(child)).sum(),
+            });
+        }
+        return node;
+    }
+
+    let mut children: Vec<FsNode> = visible
+        .into_iter()
+        .map(|(name, child)| {
+            build_node(
+                name.clone(),
+                child_path(&path, name),
+                child,
+                depth + 1,
+                config,
+                filtered,
+                max_depth,
+            )
+        })
+        .collect();
+    sort_entries(&mut children, config);
+
+    let limit = config.max_entries_per_dir;
+    if limit > 0 && children.len() > limit {
+        node.omitted = children.len() - limit;
+        children.truncate(limit);
+    }
+    node.children = Some(children);
+    node
+}
+
+/// 归档中 `parent` 之下名为 `name` 的条目的相对路径（根为 `.`，其子条目不带 `./`）。
+fn child_path(parent: &Path, name: &str) -> PathBuf {
+    if parent == Path::new(".") {
+        PathBuf::from(name)
+    } else {
+        parent.join(name)
+    }
+}
+
+/// 条目子树中所有文件的总大小。
+fn subtree_size(entry: &TarEntry) -> u64 {
+    match entry.node_type {
+        Some(FsNodeType::File) => entry.size,
+        Some(FsNodeType::Symlink) => 0,
+        _ => entry.children.values().map(subtree_size).sum(),
+    }
+}
+
+/// 条目子树中的文件数。
+fn subtree_files(entry: &TarEntry) -> usize {
+    match entry.node_type {
+        Some(FsNodeType::File) => 1,
+        Some(FsNodeType::Symlink) => 0,
+        _ => entry.children.values().map(subtree_files).sum(),
+    }
+}
+
+fn tar_error(error: std::io::Error) -> TreeError {
+    TreeError::Other(format!("invalid tar stream: {}", error))
+}
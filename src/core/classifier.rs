@@ -0,0 +1,105 @@
+//! 基于扩展名的文件分类器。
+//!
+//! `FileClassifier` 将扩展名映射到类别标签（如 "source"、"config"），
+//! 供统计信息按类别聚合使用。库的使用者可以构造自定义映射，
+//! 例如把 `.glsl` 归为 "shaders"。
+
+use std::collections::HashMap;
+
+/// 未命中任何映射的文件所归入的类别。
+pub const DEFAULT_CATEGORY: &str = "other";
+
+/// 扩展名 → 类别标签的分类器。
+///
+/// 扩展名以不含点号的小写形式存储，因此 `".RS"`、`"rs"` 视为同一键。
+#[derive(Debug, Clone)]
+pub struct FileClassifier {
+    /// 扩展名（不含点号、小写）到类别的映射
+    categories: HashMap<String, String>,
+    /// 未命中时使用的类别
+    fallback: String,
+}
+
+impl FileClassifier {
+    /// 创建一个空的分类器：所有文件都归入 `fallback` 类别。
+    pub fn empty() -> Self {
+        Self {
+            categories: HashMap::new(),
+            fallback: DEFAULT_CATEGORY.to_string(),
+        }
+    }
+
+    /// 从扩展名 → 类别的映射构造分类器。
+    pub fn from_map<I, E, C>(map: I) -> Self
+    where
+        I: IntoIterator<Item = (E, C)>,
+        E: AsRef<str>,
+        C: Into<String>,
+    {
+        let mut classifier = Self::empty();
+        for (ext, category) in map {
+            classifier.insert(ext.as_ref(), category);
+        }
+        classifier
+    }
+
+    /// 添加（或覆盖）一条扩展名到类别的映射，返回自身以便链式调用。
+    pub fn with(mut self, ext: &str, category: impl Into<String>) -> Self {
+        self.insert(ext, category);
+        self
+    }
+
+    /// 添加（或覆盖）一条扩展名到类别的映射。
+    pub fn insert(&mut self, ext: &str, category: impl Into<String>) {
+        self.categories.insert(normalize_ext(ext), category.into());
+    }
+
+    /// 设置未命中时使用的类别。
+    pub fn with_fallback(mut self, fallback: impl Into<String>) -> Self {
+        self.fallback = fallback.into();
+        self
+    }
+
+    /// 返回给定扩展名（可带或不带点号）的类别。
+    pub fn classify_ext(&self, ext: Option<&str>) -> &str {
+        ext.and_then(|e| self.categories.get(&normalize_ext(e)))
+            .map(String::as_str)
+            .unwrap_or(&self.fallback)
+    }
+}
+
+impl Default for FileClassifier {
+    /// 与内置配色方案一致的默认分类。
+    fn default() -> Self {
+        let groups: &[(&str, &[&str])] = &[
+            (
+                "source",
+                &[
+                    "rs", "py", "js", "ts", "tsx", "jsx", "java", "c", "cpp", "h", "hpp", "go",
+                    "rb", "php",
+                ],
+            ),
+            (
+                "config",
+                &["toml", "yaml", "yml", "json", "xml", "ini", "cfg", "conf"],
+            ),
+            ("docs", &["md", "rst", "adoc", "txt"]),
+            ("build", &["lock"]),
+            ("image", &["png", "jpg", "jpeg", "gif", "svg", "ico"]),
+            ("archive", &["zip", "tar", "gz", "rar", "7z"]),
+        ];
+
+        let mut classifier = Self::empty();
+        for (category, exts) in groups {
+            for ext in *exts {
+                classifier.insert(ext, *category);
+            }
+        }
+        classifier
+    }
+}
+
+/// 去掉前导点号并转为小写。
+fn normalize_ext(ext: &str) -> String {
+    ext.trim_start_matches('.').to_lowercase()
+}
@@ -1,9 +1,57 @@
 //! 从文件系统树中收集统计信息。
 
-use crate::core::models::{FileEntry, FileTypeInfo, FsNode, FsTree, TreeStats};
+use crate::core::classifier::FileClassifier;
+use crate::core::models::{CategoryInfo, FileEntry, FileTypeInfo, FsNode, FsTree, TreeStats};
+use crate::core::walker::MetadataFields;
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::time::Instant;
 
+/// 统计收集所需的元数据（total_size / 最大文件依赖文件大小）。
+pub const REQUIRED_METADATA: MetadataFields = MetadataFields::SIZE;
+
+/// 统计收集的可选配置。
+#[derive(Debug, Clone)]
+pub struct StatsOptions {
+    /// 保留多少个最大文件（来自 `--top-files`）
+    pub largest_limit: usize,
+    /// 按类别聚合时使用的分类器
+    pub classifier: FileClassifier,
+    /// 计算分布类统计（平均值、中位数）时忽略零字节文件
+    pub ignore_empty: bool,
+    /// 查找内容相同的文件（`--duplicates`），结果写入 `TreeStats::duplicates`
+    pub find_duplicates: bool,
+    /// 查找重复文件时如何处理零字节文件
+    pub empty_duplicates: EmptyDuplicates,
+    /// 汇总文件的行数（`--count-lines`），结果写入 `TreeStats::total_lines`
+    pub count_lines: bool,
+}
+
+/// 查找重复文件时零字节文件的处理方式。
+///
+/// 所有零字节文件的内容都“相同”，全部当作重复会让报告被无意义的条目淹没。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EmptyDuplicates {
+    /// 不参与重复文件分组（默认）
+    #[default]
+    Skip,
+    /// 至少两个时单独成一组，排在所有组之后（组内文件大小均为 0）
+    Group,
+}
+
+impl Default for StatsOptions {
+    fn default() -> Self {
+        Self {
+            largest_limit: 10,
+            classifier: FileClassifier::default(),
+            ignore_empty: false,
+            find_duplicates: false,
+            empty_duplicates: EmptyDuplicates::default(),
+            count_lines: false,
+        }
+    }
+}
+
 /// 从文件系统树中收集统计信息。
 ///
 /// # 参数
@@ -16,17 +64,56 @@ use std::time::Instant;
 ///
 /// 一个包含所有已收集统计信息的 `TreeStats` 对象。
 pub fn collect_stats(tree: &FsTree, start_time: Instant, largest_limit: usize) -> TreeStats {
+    let options = StatsOptions {
+        largest_limit,
+        ..Default::default()
+    };
+    collect_stats_with(tree, start_time, &options)
+}
+
+/// 按给定选项收集统计信息。
+///
+/// 与 [`collect_stats`] 相同，但 `files_by_category` 按 `options.classifier` 的映射
+/// 聚合，且可在分布类统计中忽略零字节文件。
+pub fn collect_stats_with(tree: &FsTree, start_time: Instant, options: &StatsOptions) -> TreeStats {
     let mut stats = TreeStats::new();
 
     // 收集所有文件和目录
     let mut all_files: Vec<&FsNode> = Vec::new();
     count_nodes(&tree.root, &mut stats, &mut all_files);
+    stats.empty_directories = find_empty_directories(tree);
+    if options.count_lines {
+        stats.total_lines = Some(all_files.iter().filter_map(|f| f.line_count).sum());
+    }
 
     // 按扩展名分组
     stats.files_by_extension = analyze_by_extension(&all_files, stats.total_size);
 
+    // 按类别分组
+    stats.files_by_category =
+        analyze_by_category(&all_files, stats.total_size, &options.classifier);
+
     // 查找最大的文件
-    stats.largest_files = find_largest_files(&all_files, largest_limit);
+    stats.largest_files = find_largest_files(&all_files, options.largest_limit);
+
+    // 分布类统计：零字节文件单独计数，可选地不参与平均值/中位数
+    stats.empty_files = all_files.iter().filter(|f| f.size == 0).count();
+    let mut sizes: Vec<u64> = all_files
+        .iter()
+        .map(|f| f.size)
+        .filter(|&size| !options.ignore_empty || size > 0)
+        .collect();
+    stats.average_file_size = average(&sizes);
+    stats.median_file_size = median(&mut sizes);
+
+    // 深度平衡：叶子深度的均值与标准差
+    let mut leaf_depths = Vec::new();
+    collect_leaf_depths(&tree.root, &mut leaf_depths);
+    (stats.leaf_depth_mean, stats.leaf_depth_stddev) = mean_and_stddev(&leaf_depths);
+
+    if options.find_duplicates {
+        stats.duplicates = Some(find_duplicates_with(tree, options.empty_duplicates));
+    }
 
     // 计算扫描耗时
     stats.scan_duration = start_time.elapsed();
@@ -34,21 +121,95 @@ pub fn collect_stats(tree: &FsTree, start_time: Instant, largest_limit: usize) -
     stats
 }
 
+/// 计算平均值；空切片返回 0。
+fn average(sizes: &[u64]) -> f64 {
+    if sizes.is_empty() {
+        0.0
+    } else {
+        sizes.iter().sum::<u64>() as f64 / sizes.len() as f64
+    }
+}
+
+/// 计算中位数（偶数个时取两个中间值的下取整平均）；空切片返回 0。
+fn median(sizes: &mut [u64]) -> u64 {
+    if sizes.is_empty() {
+        return 0;
+    }
+    sizes.sort_unstable();
+    let mid = sizes.len() / 2;
+    if sizes.len().is_multiple_of(2) {
+        // 已排序，差值非负；这样写可避免两数相加溢出。
+        sizes[mid - 1] + (sizes[mid] - sizes[mid - 1]) / 2
+    } else {
+        sizes[mid]
+    }
+}
+
+/// 计算均值与总体标准差；空切片返回 (0, 0)。
+fn mean_and_stddev(values: &[usize]) -> (f64, f64) {
+    if values.is_empty() {
+        return (0.0, 0.0);
+    }
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<usize>() as f64 / n;
+    let variance = values
+        .iter()
+        .map(|&v| {
+            let d = v as f64 - mean;
+            d * d
+        })
+        .sum::<f64>()
+        / n;
+    (mean, variance.sqrt())
+}
+
+/// 递归地收集叶子节点（没有子节点的节点，包括空目录）的深度。
+fn collect_leaf_depths(node: &FsNode, depths: &mut Vec<usize>) {
+    match &node.children {
+        Some(children) if !children.is_empty() => {
+            for child in children {
+                collect_leaf_depths(child, depths);
+            }
+        }
+        _ => depths.push(node.depth),
+    }
+}
+
 /// 递归地统计树中节点的数量。
+///
+/// 因深度限制未展开、带有 `collapsed_summary` 的目录（`--collapse-depth`、`--du`），
+/// 其子树的文件数与大小计入总计；这些文件不在树中，不参与按扩展名、最大文件等
+/// 逐文件的统计。
 fn count_nodes<'a>(node: &'a FsNode, stats: &mut TreeStats, all_files: &mut Vec<&'a FsNode>) {
     match node.node_type {
         crate::core::models::FsNodeType::Directory => {
             stats.total_directories += 1;
+            if let Some(summary) = node.collapsed_summary {
+                stats.total_files += summary.files;
+                stats.total_size += summary.size;
+            }
         }
         crate::core::models::FsNodeType::File => {
             stats.total_files += 1;
             stats.total_size += node.size;
             all_files.push(node);
+            if stats
+                .deepest_path
+                .as_ref()
+                .is_none_or(|&(_, depth)| node.depth > depth)
+            {
+                let path = node
+                    .path
+                    .clone()
+                    .unwrap_or_else(|| PathBuf::from(&node.name));
+                stats.deepest_path = Some((path, node.depth));
+            }
         }
         crate::core::models::FsNodeType::Symlink => {
             stats.total_symlinks += 1;
         }
     }
+    stats.omitted_entries += node.omitted;
 
     if let Some(children) = &node.children {
         for child in children {
@@ -57,6 +218,144 @@ fn count_nodes<'a>(node: &'a FsNode, stats: &mut TreeStats, all_files: &mut Vec<
     }
 }
 
+/// 按先序返回树中的递归空目录（[`FsNode::is_recursively_empty`]）。
+///
+/// 只看树本身：被过滤器排除了全部内容的目录同样算空，可用
+/// [`classify_empty_directories`] 区分。
+pub fn find_empty_directories(tree: &FsTree) -> Vec<PathBuf> {
+    let mut empty_directories = Vec::new();
+    collect_empty_directories(&tree.root, &mut empty_directories);
+    empty_directories
+}
+
+/// 先序收集递归空目录：返回该子树是否含有文件、符号链接或未知内容，
+/// 使每个目录只需检查一次其直接子节点的结果。
+fn collect_empty_directories(node: &FsNode, empty_directories: &mut Vec<PathBuf>) -> bool {
+    if !node.is_directory() {
+        return true;
+    }
+
+    // 先占位，使父目录排在其空子目录之前；子树非空时再移除。
+    let slot = empty_directories.len();
+    empty_directories.push(
+        node.path
+            .clone()
+            .unwrap_or_else(|| PathBuf::from(&node.name)),
+    );
+    let mut has_content = node.truncated || node.omitted > 0 || node.collapsed > 0;
+    for child in node.children.iter().flatten() {
+        has_content |= collect_empty_directories(child, empty_directories);
+    }
+    if has_content {
+        empty_directories.remove(slot);
+    }
+    has_content
+}
+
+/// `--list-empty-dirs` 报告中的一个空目录。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmptyDirectory {
+    /// 目录路径
+    pub path: PathBuf,
+    /// 磁盘上其实含有文件，只是全部被过滤器排除（“过滤后为空”）
+    pub filtered: bool,
+}
+
+/// 区分真正的空目录与过滤后才为空的目录：后者在磁盘上的子树中仍有
+/// 非目录条目。无法读取的目录按真正的空目录处理。
+pub fn classify_empty_directories(dirs: &[PathBuf]) -> Vec<EmptyDirectory> {
+    dirs.iter()
+        .map(|path| EmptyDirectory {
+            path: path.clone(),
+            filtered: walkdir::WalkDir::new(path)
+                .min_depth(1)
+                .into_iter()
+                .filter_map(Result::ok)
+                .any(|entry| !entry.file_type().is_dir()),
+        })
+        .collect()
+}
+
+/// 查找树中内容完全相同的文件，返回各组重复文件；零字节文件不参与分组。
+///
+/// 先按大小分组，只有大小相同的文件（至少两个）才会被读取并计算 BLAKE3 哈希，
+/// 再按哈希确认内容相同，因此大小唯一的文件不会被读取。没有路径或无法读取的
+/// 文件被跳过。每组按路径排序；组之间按浪费的空间（大小 ×（个数 − 1））
+/// 降序排列，相同时按首个路径。
+pub fn find_duplicates(tree: &FsTree) -> Vec<Vec<FileEntry>> {
+    find_duplicates_with(tree, EmptyDuplicates::Skip)
+}
+
+/// 与 [`find_duplicates`] 相同，但由 `empty` 决定零字节文件是跳过还是单独成组。
+///
+/// 零字节文件无需读取即可确定内容相同，因此 `Group` 时不会为它们计算哈希。
+pub fn find_duplicates_with(tree: &FsTree, empty: EmptyDuplicates) -> Vec<Vec<FileEntry>> {
+    let mut files: Vec<&FsNode> = Vec::new();
+    collect_files(&tree.root, &mut files);
+
+    let mut by_size: HashMap<u64, Vec<&FsNode>> = HashMap::new();
+    for file in files {
+        if file.path.is_some() {
+            by_size.entry(file.size).or_default().push(file);
+        }
+    }
+    let empty_files = by_size.remove(&0).unwrap_or_default();
+
+    let mut groups: Vec<Vec<FileEntry>> = Vec::new();
+    for candidates in by_size.into_values().filter(|c| c.len() > 1) {
+        let mut by_hash: HashMap<blake3::Hash, Vec<FileEntry>> = HashMap::new();
+        for file in candidates {
+            let path = file.path.clone().unwrap_or_default();
+            let Ok(hash) = hash_file(&path) else {
+                continue;
+            };
+            by_hash.entry(hash).or_default().push(FileEntry::new(
+                file.name.clone(),
+                path,
+                file.size,
+            ));
+        }
+        groups.extend(by_hash.into_values().filter(|g| g.len() > 1));
+    }
+
+    for group in &mut groups {
+        group.sort_by(|a, b| a.path.cmp(&b.path));
+    }
+    let wasted = |group: &[FileEntry]| group[0].size * (group.len() as u64 - 1);
+    groups.sort_by(|a, b| {
+        wasted(b)
+            .cmp(&wasted(a))
+            .then_with(|| a[0].path.cmp(&b[0].path))
+    });
+
+    if empty == EmptyDuplicates::Group && empty_files.len() > 1 {
+        let mut group: Vec<FileEntry> = empty_files
+            .iter()
+            .map(|file| FileEntry::new(file.name.clone(), file.path.clone().unwrap_or_default(), 0))
+            .collect();
+        group.sort_by(|a, b| a.path.cmp(&b.path));
+        groups.push(group);
+    }
+    groups
+}
+
+/// 先序收集子树中的所有文件。
+fn collect_files<'a>(node: &'a FsNode, files: &mut Vec<&'a FsNode>) {
+    if node.is_file() {
+        files.push(node);
+    }
+    for child in node.children.iter().flatten() {
+        collect_files(child, files);
+    }
+}
+
+/// 流式计算文件内容的 BLAKE3 哈希。
+fn hash_file(path: &Path) -> std::io::Result<blake3::Hash> {
+    let mut hasher = blake3::Hasher::new();
+    std::io::copy(&mut std::fs::File::open(path)?, &mut hasher)?;
+    Ok(hasher.finalize())
+}
+
 /// 按扩展名分析文件。
 ///
 /// 返回一个将扩展名映射到文件类型信息的 HashMap。
@@ -96,6 +395,47 @@ pub fn analyze_by_extension(files: &[&FsNode], total_size: u64) -> HashMap<Strin
         .collect()
 }
 
+/// 按 `classifier` 给出的类别分析文件。
+///
+/// 返回一个将类别标签映射到类别信息的 HashMap。
+#[doc(hidden)]
+pub fn analyze_by_category(
+    files: &[&FsNode],
+    total_size: u64,
+    classifier: &FileClassifier,
+) -> HashMap<String, CategoryInfo> {
+    let mut by_cat: HashMap<String, (usize, u64)> = HashMap::new();
+
+    for file in files {
+        let ext = file.extension();
+        let category = classifier.classify_ext(ext.as_deref());
+
+        let entry = by_cat.entry(category.to_string()).or_insert((0, 0));
+        entry.0 += 1;
+        entry.1 += file.size;
+    }
+
+    by_cat
+        .into_iter()
+        .map(|(category, (count, size))| {
+            let percentage = if total_size > 0 {
+                (size as f64 / total_size as f64) * 100.0
+            } else {
+                0.0
+            };
+
+            let info = CategoryInfo {
+                category: category.clone(),
+                count,
+                total_size: size,
+                percentage,
+            };
+
+            (category, info)
+        })
+        .collect()
+}
+
 /// 查找 N 个最大的文件。
 ///
 /// # 参数
@@ -105,7 +445,7 @@ pub fn analyze_by_extension(files: &[&FsNode], total_size: u64) -> HashMap<Strin
 ///
 /// # 返回
 ///
-/// 一个由 `FileEntry` 对象组成的向量，按大小排序（最大者在前）。
+/// 一个由 `FileEntry` 对象组成的向量，按大小排序（最大者在前），大小相同者按路径升序。
 #[doc(hidden)]
 pub fn find_largest_files(files: &[&FsNode], limit: usize) -> Vec<FileEntry> {
     if files.is_empty() || limit == 0 {
@@ -127,7 +467,8 @@ pub fn find_largest_files(files: &[&FsNode], limit: usize) -> Vec<FileEntry> {
     // 仅选出最大的前 `limit` 个，再对这前缀排序——避免对全量做 O(n log n)。
     // select_nth_unstable_by 按 `cmp` 排列：第 k 位恰好是排序后该位置的元素，
     // 其左侧均 ≤ 右侧，但前缀内部本身无序，因此还需要单独排序。
-    let cmp = |a: &FileEntry, b: &FileEntry| b.size.cmp(&a.size); // 降序：最大在前
+    // 降序：最大在前；大小相同时按路径升序，使表格与 JSON 在多次运行间保持一致。
+    let cmp = |a: &FileEntry, b: &FileEntry| b.size.cmp(&a.size).then_with(|| a.path.cmp(&b.path));
     let k = limit.min(entries.len());
     let (front, _, _) = entries.select_nth_unstable_by(k - 1, cmp);
     front.sort_by(cmp);
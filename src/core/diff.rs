@@ -0,0 +1,163 @@
+//! 两棵文件树之间的差异计算。
+
+use crate::core::models::{ExtensionDelta, FsNode, FsNodeType, FsTree, StatsDelta, TreeStats};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+/// 差异类型。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffKind {
+    /// 仅存在于新树
+    Added,
+    /// 仅存在于旧树
+    Removed,
+    /// 两侧都存在，但类型或大小不同
+    Changed,
+}
+
+/// 一条差异记录。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiffEntry {
+    /// 相对于各自根目录的路径
+    pub path: PathBuf,
+    /// 差异类型
+    pub kind: DiffKind,
+    /// 旧树中的大小（新增时为 `None`）
+    pub old_size: Option<u64>,
+    /// 新树中的大小（删除时为 `None`）
+    pub new_size: Option<u64>,
+}
+
+/// 比较两棵树，按相对路径排序返回所有差异。
+///
+/// 节点以相对于各自根节点的路径配对，因此两侧的根目录可以位于不同位置
+/// （例如比较一个快照与当前目录）。根节点本身不参与比较。
+pub fn diff_trees(old: &FsTree, new: &FsTree) -> Vec<DiffEntry> {
+    let old_index = index_tree(&old.root);
+    let mut new_index = index_tree(&new.root);
+
+    let mut entries = Vec::new();
+    for (path, (old_type, old_size)) in old_index {
+        match new_index.remove(&path) {
+            None => entries.push(DiffEntry {
+                path,
+                kind: DiffKind::Removed,
+                old_size: Some(old_size),
+                new_size: None,
+            }),
+            Some((new_type, new_size)) => {
+                if old_type != new_type || old_size != new_size {
+                    entries.push(DiffEntry {
+                        path,
+                        kind: DiffKind::Changed,
+                        old_size: Some(old_size),
+                        new_size: Some(new_size),
+                    });
+                }
+            }
+        }
+    }
+    for (path, (_, new_size)) in new_index {
+        entries.push(DiffEntry {
+            path,
+            kind: DiffKind::Added,
+            old_size: None,
+            new_size: Some(new_size),
+        });
+    }
+
+    entries.sort_by(|a, b| a.path.cmp(&b.path));
+    entries
+}
+
+/// 将树展开为“相对路径 → (类型, 大小)”的有序映射（不含根节点）。
+fn index_tree(root: &FsNode) -> BTreeMap<PathBuf, (FsNodeType, u64)> {
+    let mut index = BTreeMap::new();
+    let base = root.path.clone().unwrap_or_default();
+    if let Some(children) = &root.children {
+        for child in children {
+            index_recursive(child, &base, Path::new(""), &mut index);
+        }
+    }
+    index
+}
+
+/// 递归地登记节点；优先用真实路径求相对路径，缺失时退回按名称拼接。
+fn index_recursive(
+    node: &FsNode,
+    base: &Path,
+    parent_rel: &Path,
+    index: &mut BTreeMap<PathBuf, (FsNodeType, u64)>,
+) {
+    let rel = node
+        .path
+        .as_deref()
+        .and_then(|p| p.strip_prefix(base).ok())
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| parent_rel.join(&node.name));
+
+    index.insert(rel.clone(), (node.node_type.clone(), node.size));
+
+    if let Some(children) = &node.children {
+        for child in children {
+            index_recursive(child, base, &rel, index);
+        }
+    }
+}
+
+/// 计算 `current` 相对 `baseline` 的统计变化（`--compare-stats`）。
+///
+/// 扩展名按两侧的并集比较，只保留文件数或大小有变化的扩展名；大小变化的绝对值
+/// 大的在前，相同时按扩展名排序。
+pub fn diff_stats(baseline: &TreeStats, current: &TreeStats) -> StatsDelta {
+    let delta = |old: u64, new: u64| new as i64 - old as i64;
+
+    let extensions: BTreeSet<&String> = baseline
+        .files_by_extension
+        .keys()
+        .chain(current.files_by_extension.keys())
+        .collect();
+    let mut extension_deltas: Vec<ExtensionDelta> = extensions
+        .into_iter()
+        .filter_map(|ext| {
+            let old = baseline.files_by_extension.get(ext);
+            let new = current.files_by_extension.get(ext);
+            let count = delta(
+                old.map_or(0, |i| i.count as u64),
+                new.map_or(0, |i| i.count as u64),
+            );
+            let size = delta(
+                old.map_or(0, |i| i.total_size),
+                new.map_or(0, |i| i.total_size),
+            );
+            let extension = new
+                .or(old)
+                .map_or_else(|| ext.clone(), |i| i.extension.clone());
+            (count != 0 || size != 0).then_some(ExtensionDelta {
+                extension,
+                count,
+                size,
+            })
+        })
+        .collect();
+    extension_deltas.sort_by(|a, b| {
+        b.size
+            .unsigned_abs()
+            .cmp(&a.size.unsigned_abs())
+            .then_with(|| a.extension.cmp(&b.extension))
+    });
+
+    StatsDelta {
+        total_files: delta(baseline.total_files as u64, current.total_files as u64),
+        total_directories: delta(
+            baseline.total_directories as u64,
+            current.total_directories as u64,
+        ),
+        total_symlinks: delta(
+            baseline.total_symlinks as u64,
+            current.total_symlinks as u64,
+        ),
+        total_size: delta(baseline.total_size, current.total_size),
+        extensions: extension_deltas,
+    }
+}
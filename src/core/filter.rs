@@ -1,7 +1,16 @@
 //! 目录遍历的模式过滤。
 
-use glob::Pattern;
-use std::path::Path;
+use glob::{MatchOptions, Pattern};
+use std::collections::{HashMap, HashSet};
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::SystemTime;
+
+/// `.gitignore` 文件名。
+pub const GITIGNORE_FILE: &str = ".gitignore";
+
+/// 扫描根下本工具专用的忽略文件名。
+pub const TREEIGNORE_FILE: &str = ".treeignore";
 
 /// 过滤器配置。
 #[derive(Debug, Clone, Default)]
@@ -12,6 +21,101 @@ pub struct FilterConfig {
     pub include_pattern: Option<Pattern>,
     /// 排除隐藏文件
     pub exclude_hidden: bool,
+    /// 按名称精确剪除的目录（快速路径：不做 glob 匹配，命中后不再下降）
+    pub prune_dirs: HashSet<String>,
+    /// 跳过被 `.gitignore` 忽略的路径（规则来源见 [`load_gitignore`](Self::load_gitignore)）
+    pub respect_gitignore: bool,
+    /// 预先给定的 `.gitignore` 规则，优先级低于按目录载入的文件（文件内按行序）
+    pub gitignore_rules: Vec<GitignoreRule>,
+    /// 按需载入 `.gitignore` 的起点目录；其下各目录的文件在首次判断该目录中的条目时读取
+    pub gitignore_root: Option<PathBuf>,
+    /// 已读取的各目录 `.gitignore` 规则（没有该文件的目录记为空），遍历线程之间共享
+    pub gitignore_files: Arc<Mutex<HashMap<PathBuf, Arc<[GitignoreRule]>>>>,
+    /// 排除小于该字节数的文件（目录不受影响）
+    pub min_size: Option<u64>,
+    /// 排除大于该字节数的文件（目录不受影响）
+    pub max_size: Option<u64>,
+    /// 排除在此时间之前修改的文件（目录不受影响）
+    pub modified_after: Option<SystemTime>,
+    /// 排除在此时间之后修改的文件（目录不受影响）
+    pub modified_before: Option<SystemTime>,
+    /// 只保留这些扩展名的文件（不含点号，不区分大小写；为空表示不限制）
+    pub include_extensions: Vec<String>,
+    /// 设置了包含条件时，不进入子树中没有任何可包含文件的目录（`--include-prune`，
+    /// 见 [`contains_included_file`](Self::contains_included_file)）
+    pub include_prune: bool,
+    /// `--include-prune` 的预扫描结果：扫描根及其下含可包含文件的目录。首次调用
+    /// [`prunes_dir`](Self::prunes_dir) 时从根扫描一次填入，之后每个目录只需一次查找
+    pub included_dirs: OnceLock<(PathBuf, HashSet<PathBuf>)>,
+}
+
+/// `.gitignore` 中的一条规则。
+#[derive(Debug, Clone)]
+pub struct GitignoreRule {
+    /// 规则所在 ignore 文件的目录；模式相对它匹配
+    base: PathBuf,
+    /// 去掉 `!`、首尾 `/` 后的模式
+    pattern: Pattern,
+    /// `!` 开头：重新包含此前被忽略的路径
+    negated: bool,
+    /// 以 `/` 结尾：只匹配目录
+    dir_only: bool,
+    /// 含非末尾的 `/`：相对 `base` 匹配完整路径，而非任意层级的名称
+    anchored: bool,
+}
+
+impl GitignoreRule {
+    /// 解析 ignore 文件中的一行；空行、注释与非法模式返回 `None`。
+    pub fn parse(line: &str, base: &Path) -> Option<Self> {
+        let line = line.trim_end();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+
+        let (negated, line) = match line.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, line.strip_prefix('\\').unwrap_or(line)),
+        };
+        let (dir_only, line) = match line.strip_suffix('/') {
+            Some(rest) => (true, rest),
+            None => (false, line),
+        };
+        let anchored = line.contains('/');
+        let line = line.strip_prefix('/').unwrap_or(line);
+        if line.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            base: base.to_path_buf(),
+            pattern: Pattern::new(line).ok()?,
+            negated,
+            dir_only,
+            anchored,
+        })
+    }
+
+    /// 该规则是否匹配 `path`（须位于 `base` 之下）。
+    fn matches(&self, path: &Path, is_dir: bool) -> bool {
+        if self.dir_only && !is_dir {
+            return false;
+        }
+        let Ok(relative) = path.strip_prefix(&self.base) else {
+            return false;
+        };
+        if self.anchored {
+            let options = MatchOptions {
+                require_literal_separator: true,
+                ..MatchOptions::new()
+            };
+            self.pattern.matches_path_with(relative, options)
+        } else {
+            relative
+                .file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|name| self.pattern.matches(name))
+        }
+    }
 }
 
 impl FilterConfig {
@@ -27,6 +131,11 @@ impl FilterConfig {
             .map_err(|e| e.to_string())
     }
 
+    /// 添加一个按名称精确剪除的目录。
+    pub fn add_prune_dir(&mut self, name: &str) {
+        self.prune_dirs.insert(name.to_string());
+    }
+
     /// 设置包含模式。
     pub fn set_include(&mut self, pattern: &str) -> Result<(), String> {
         Pattern::new(pattern)
@@ -34,12 +143,281 @@ impl FilterConfig {
             .map_err(|e| e.to_string())
     }
 
+    /// 让 `root` 及其下各目录的 `.gitignore` 生效。
+    ///
+    /// 文件不在此处读取：遍历进入某个目录、判断其中的条目时才读取该目录的
+    /// `.gitignore`，因此被排除或超出深度限制的子树不会为此多走一遍。与 git 一致：
+    /// 每个文件中的模式相对其所在目录匹配，内层文件的规则优先于外层，`!` 开头的
+    /// 模式可以重新包含被更宽泛的模式忽略的路径（但父目录已被忽略时遍历不会进入，
+    /// 其中的文件无法再包含）。`.git` 之中的 `.gitignore` 不生效。
+    pub fn load_gitignore(&mut self, root: &Path) {
+        self.gitignore_root = Some(root.to_path_buf());
+        self.gitignore_files = Arc::default();
+    }
+
+    /// 目录 `dir` 中 `.gitignore` 的规则；首次请求时读取并缓存。
+    fn gitignore_file_rules(&self, dir: &Path) -> Arc<[GitignoreRule]> {
+        if let Some(rules) = self.lock_gitignore_files().get(dir) {
+            return Arc::clone(rules);
+        }
+        let rules: Arc<[GitignoreRule]> = std::fs::read_to_string(dir.join(GITIGNORE_FILE))
+            .map(|text| {
+                text.lines()
+                    .filter_map(|line| GitignoreRule::parse(line, dir))
+                    .collect()
+            })
+            .unwrap_or_default();
+        Arc::clone(
+            self.lock_gitignore_files()
+                .entry(dir.to_path_buf())
+                .or_insert(rules),
+        )
+    }
+
+    /// 锁住 `.gitignore` 缓存。缓存只存放读到的规则，持锁线程 panic 后内容仍然有效。
+    fn lock_gitignore_files(
+        &self,
+    ) -> std::sync::MutexGuard<'_, HashMap<PathBuf, Arc<[GitignoreRule]>>> {
+        self.gitignore_files
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// 读取 `root` 下的 `.treeignore`，将每一行作为排除模式（同 `add_exclude`）加入。
+    ///
+    /// 每行一个 glob，空行与 `#` 开头的注释被跳过。与 `.gitignore` 不同，暂不支持
+    /// `!` 开头的取反模式，这样的行会被忽略。文件不存在时什么也不做。
+    ///
+    /// # 错误
+    ///
+    /// 文件无法读取或某行不是合法的 glob 时返回带文件名（及行号）的错误信息。
+    pub fn load_treeignore(&mut self, root: &Path) -> Result<(), String> {
+        let path = root.join(TREEIGNORE_FILE);
+        let text = match std::fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(format!("{}: {}", path.display(), e)),
+        };
+
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with('!') {
+                continue;
+            }
+            self.add_exclude(line)
+                .map_err(|e| format!("{}:{}: {}", path.display(), index + 1, e))?;
+        }
+        Ok(())
+    }
+
+    /// 按 `.gitignore` 规则判断 `path` 是否被忽略（最后一条匹配的规则生效）。
+    ///
+    /// 从 `path` 所在目录向外直到 [`gitignore_root`](Self::gitignore_root) 依次查看
+    /// 各目录的文件，最后才是 [`gitignore_rules`](Self::gitignore_rules)。
+    pub fn gitignored(&self, path: &Path, is_dir: bool) -> bool {
+        let matching = |rules: &[GitignoreRule]| {
+            rules
+                .iter()
+                .rev()
+                .find(|rule| rule.matches(path, is_dir))
+                .map(|rule| !rule.negated)
+        };
+
+        if let Some(root) = &self.gitignore_root {
+            let dirs = path
+                .ancestors()
+                .skip(1)
+                .take_while(|dir| dir.starts_with(root));
+            for dir in dirs {
+                let inside_git = dir.strip_prefix(root).is_ok_and(|rel| {
+                    rel.components()
+                        .any(|c| c == Component::Normal(".git".as_ref()))
+                });
+                if inside_git {
+                    continue;
+                }
+                if let Some(ignored) = matching(&self.gitignore_file_rules(dir)) {
+                    return ignored;
+                }
+            }
+        }
+        matching(&self.gitignore_rules).unwrap_or(false)
+    }
+
+    /// 是否设置了文件大小范围（`min_size` / `max_size`）；设置时遍历需要读取文件大小。
+    pub fn has_size_range(&self) -> bool {
+        self.min_size.is_some() || self.max_size.is_some()
+    }
+
+    /// 大小为 `size` 的文件是否落在 `[min_size, max_size]` 之外而应被排除。
+    ///
+    /// 只用于文件：目录从不按大小排除，以便保留通往匹配文件的树结构。
+    pub fn excludes_size(&self, size: u64) -> bool {
+        self.min_size.is_some_and(|min| size < min) || self.max_size.is_some_and(|max| size > max)
+    }
+
+    /// 是否设置了修改时间范围（`modified_after` / `modified_before`）；设置时遍历需要读取文件的修改时间。
+    pub fn has_modified_range(&self) -> bool {
+        self.modified_after.is_some() || self.modified_before.is_some()
+    }
+
+    /// 修改时间为 `modified` 的文件是否落在 `[modified_after, modified_before]` 之外而应被排除。
+    ///
+    /// 与 [`excludes_size`](Self::excludes_size) 一样只用于文件。设置了范围而修改时间
+    /// 未知（平台不支持或读取失败）的文件无法判断是否匹配，按排除处理。
+    pub fn excludes_modified(&self, modified: Option<SystemTime>) -> bool {
+        if !self.has_modified_range() {
+            return false;
+        }
+        let Some(modified) = modified else {
+            return true;
+        };
+        self.modified_after.is_some_and(|after| modified < after)
+            || self.modified_before.is_some_and(|before| modified > before)
+    }
+
+    /// 添加一个要保留的扩展名（`rs` 或 `.rs` 均可）。
+    pub fn add_extension(&mut self, ext: &str) {
+        let ext = ext.trim().trim_start_matches('.');
+        if !ext.is_empty() {
+            self.include_extensions.push(ext.to_lowercase());
+        }
+    }
+
+    /// 是否设置了包含条件（`include_pattern` 或 `include_extensions`）。
+    pub fn has_include(&self) -> bool {
+        self.include_pattern.is_some() || !self.include_extensions.is_empty()
+    }
+
+    /// `--include-prune` 下是否应剪除目录 `dir`：开启了该模式、设置了包含条件，且
+    /// `dir` 的子树中没有可包含的文件。
+    ///
+    /// 逐层调用 [`contains_included_file`](Self::contains_included_file) 会让深层
+    /// 子树被每个祖先各扫描一遍，因此首次调用时从 `root` 预扫描一次（见
+    /// [`included_dirs`](Self::included_dirs)）；换了扫描根才退回逐个扫描。
+    pub fn prunes_dir(&self, root: &Path, dir: &Path, follow_symlinks: bool) -> bool {
+        if !self.include_prune || !self.has_include() {
+            return false;
+        }
+        let (scanned_root, dirs) = self.included_dirs.get_or_init(|| {
+            (
+                root.to_path_buf(),
+                self.dirs_with_included_files(root, follow_symlinks),
+            )
+        });
+        if scanned_root == root {
+            !dirs.contains(dir)
+        } else {
+            !self.contains_included_file(root, dir, follow_symlinks)
+        }
+    }
+
+    /// 一次遍历 `root` 的子树，收集含有可包含文件的目录（连同其全部祖先，直到 `root`）。
+    ///
+    /// 过滤规则与 [`contains_included_file`](Self::contains_included_file) 相同。
+    fn dirs_with_included_files(&self, root: &Path, follow_symlinks: bool) -> HashSet<PathBuf> {
+        let mut dirs = HashSet::new();
+        let files = walkdir::WalkDir::new(root)
+            .min_depth(1)
+            .follow_links(follow_symlinks)
+            .into_iter()
+            .filter_entry(|e| {
+                !e.file_type().is_dir() || !self.should_exclude_under(root, e.path(), true)
+            })
+            .filter_map(Result::ok)
+            .filter(|e| {
+                !e.file_type().is_dir() && !self.should_exclude_under(root, e.path(), false)
+            });
+        for file in files {
+            // 祖先已记录时，更上层的目录必然也已记录。
+            for dir in file.path().ancestors().skip(1) {
+                if !dir.starts_with(root) || !dirs.insert(dir.to_path_buf()) {
+                    break;
+                }
+            }
+        }
+        dirs
+    }
+
+    /// 预扫描 `dir` 的子树，判断其中是否有不会被过滤器排除的文件。
+    ///
+    /// 只用 readdir 给出的名称与类型，不读取元数据；被排除的目录不进入，找到第一个
+    /// 文件即停止。大小、修改时间等需要元数据的过滤器不参与判断，因此结果偏向
+    /// “有”，不会误剪。
+    pub fn contains_included_file(&self, root: &Path, dir: &Path, follow_symlinks: bool) -> bool {
+        walkdir::WalkDir::new(dir)
+            .min_depth(1)
+            .follow_links(follow_symlinks)
+            .into_iter()
+            .filter_entry(|e| {
+                !e.file_type().is_dir() || !self.should_exclude_under(root, e.path(), true)
+            })
+            .filter_map(Result::ok)
+            .any(|e| !e.file_type().is_dir() && !self.should_exclude_under(root, e.path(), false))
+    }
+
+    /// 名为 `name` 的文件是否因扩展名不在 `include_extensions` 中而应被排除。
+    ///
+    /// 只用于文件与符号链接：目录总是会下降，与 `include_pattern` 相同。
+    /// [`should_exclude`](Self::should_exclude) 已包含这项检查；单独调用用于
+    /// 区分被 `--ext` 隐藏的文件（`--report-excluded-ext`）。
+    pub fn excludes_extension(&self, name: &str) -> bool {
+        if self.include_extensions.is_empty() {
+            return false;
+        }
+        !extension_of(name).is_some_and(|ext| {
+            self.include_extensions
+                .iter()
+                .any(|wanted| wanted.eq_ignore_ascii_case(ext))
+        })
+    }
+
     /// 检查某个路径是否应被排除。
     ///
-    /// `is_dir` 指示该路径是否为目录。`include_pattern` 只过滤文件：
-    /// 目录总是会下降（除非命中排除模式或隐藏规则），否则一个
-    /// `--include-only "*.rs"` 会剪除每个子目录，从而什么都得不到。
+    /// `is_dir` 指示该路径是否为目录。`include_pattern` 与 `include_extensions`
+    /// 只过滤文件（两者都设置时文件须同时满足）：目录总是会下降（除非命中排除
+    /// 模式或隐藏规则），否则一个 `--include-only "*.rs"` 会剪除每个子目录，
+    /// 从而什么都得不到。
     pub fn should_exclude(&self, path: &Path, is_dir: bool) -> bool {
+        self.should_exclude_impl(path, None, is_dir, true)
+    }
+
+    /// 与 [`should_exclude`](Self::should_exclude) 相同，但已知扫描根 `root`。
+    ///
+    /// 含 `**` 的排除模式只与相对 `root` 的路径匹配，因此 `**/tests/**`
+    /// 的结果不再取决于根目录所在的绝对位置（例如根本身位于某个 `tests/` 之下）。
+    pub fn should_exclude_under(&self, root: &Path, path: &Path, is_dir: bool) -> bool {
+        self.should_exclude_impl(path, path.strip_prefix(root).ok(), is_dir, true)
+    }
+
+    /// 与 [`should_exclude_under`](Self::should_exclude_under) 相同，但不检查
+    /// `--ext`。遍历先用它应用其余过滤器，再对剩下的文件单独判断扩展名，
+    /// 这样 `--report-excluded-ext` 只统计真正因 `--ext` 而隐藏的文件。
+    pub fn should_exclude_under_except_extension(
+        &self,
+        root: &Path,
+        path: &Path,
+        is_dir: bool,
+    ) -> bool {
+        self.should_exclude_impl(path, path.strip_prefix(root).ok(), is_dir, false)
+    }
+
+    fn should_exclude_impl(
+        &self,
+        path: &Path,
+        relative: Option<&Path>,
+        is_dir: bool,
+        check_extension: bool,
+    ) -> bool {
+        // 剪除目录的快速路径：一次哈希查找，先于所有 glob 匹配。
+        if is_dir && !self.prune_dirs.is_empty() {
+            if let Some(name_str) = path.file_name().and_then(|n| n.to_str()) {
+                if self.prune_dirs.contains(name_str) {
+                    return true;
+                }
+            }
+        }
+
         // 检查隐藏文件
         if self.exclude_hidden {
             if let Some(file_name) = path.file_name() {
@@ -51,8 +429,21 @@ impl FilterConfig {
             }
         }
 
+        // 检查 .gitignore 规则
+        if self.respect_gitignore && self.gitignored(path, is_dir) {
+            return true;
+        }
+
         // 检查排除模式（同时作用于文件和目录）
         for pattern in &self.exclude_patterns {
+            if let Some(rel) = relative {
+                if pattern.as_str().contains("**") {
+                    if pattern.matches_path(rel) {
+                        return true;
+                    }
+                    continue;
+                }
+            }
             if pattern.matches_path(path) {
                 return true;
             }
@@ -80,12 +471,27 @@ impl FilterConfig {
                     return true;
                 }
             }
+            if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
+                if check_extension && self.excludes_extension(name) {
+                    return true;
+                }
+            }
         }
 
         false
     }
 }
 
+/// 文件名的扩展名（不含点号），规则与 `FsNode::extension` 相同：
+/// 点文件（`.gitignore`）和以点号结尾的名字没有扩展名。
+pub fn extension_of(name: &str) -> Option<&str> {
+    let pos = name.rfind('.')?;
+    if pos == 0 || pos == name.len() - 1 {
+        return None;
+    }
+    Some(&name[pos + 1..])
+}
+
 /// 预定义的常用排除模式。
 pub mod common_excludes {
     /// Rust 项目的常用排除模式。
@@ -117,6 +523,11 @@ pub mod common_excludes {
         ]
     }
 
+    /// 版本控制系统的元数据目录（类似 `tar --exclude-vcs`）。
+    pub fn vcs_dirs() -> Vec<&'static str> {
+        vec![".git", ".svn", ".hg", ".bzr", "_darcs", "CVS"]
+    }
+
     /// 通用开发的常用排除模式。
     pub fn common_patterns() -> Vec<&'static str> {
         vec![
@@ -0,0 +1,177 @@
+//! `--fit-lines`：把树折叠到给定的行数预算之内。
+//!
+//! 先一次遍历算出每个目录子树在树形输出中占用的行数，再按所选顺序贪心地折叠
+//! 目录（移除其子节点，改为一行 `… (N entries collapsed)` 摘要），直到总行数不超过
+//! 预算。根目录只在折叠完其他所有目录仍超出预算时才被折叠。
+
+use crate::core::models::FsNode;
+
+/// 选择下一个被折叠目录的顺序。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CollapseOrder {
+    /// 最深的目录先折叠（同深度时行数多的先折叠），尽量保留上层结构
+    #[default]
+    Deepest,
+    /// 占用行数最多的目录先折叠，折叠次数最少
+    Biggest,
+}
+
+/// 扁平化后的一个目录：在树中的位置与当前占用的行数。
+struct DirLines {
+    /// 父目录在扁平数组中的下标（根为 `None`）
+    parent: Option<usize>,
+    /// 深度（根为 0）
+    depth: usize,
+    /// 该目录子树当前占用的行数（含自身一行）
+    lines: usize,
+    /// 子树中（不含自身）的条目数，用于摘要
+    descendants: usize,
+    /// 是否已被折叠
+    collapsed: bool,
+}
+
+/// 树形输出中 `node` 子树占用的行数：每个节点一行，截断占位、略去条目与折叠摘要各一行。
+pub fn rendered_lines(node: &FsNode) -> usize {
+    1 + usize::from(node.truncated)
+        + usize::from(node.omitted > 0)
+        + usize::from(node.collapsed > 0)
+        + node
+            .children
+            .iter()
+            .flatten()
+            .map(rendered_lines)
+            .sum::<usize>()
+}
+
+/// 按 `order` 折叠目录，使 `root` 的树形输出不超过 `max_lines` 行。
+///
+/// 被折叠的目录 `children` 置为 `None`，`collapsed` 记录被隐藏的后代条目数。
+/// 预算小于 2 时无法容纳根目录及其摘要，结果可能仍超出预算。返回折叠后的行数。
+pub fn fit_lines(root: &mut FsNode, max_lines: usize, order: CollapseOrder) -> usize {
+    let mut dirs = Vec::new();
+    flatten_dirs(root, None, &mut dirs);
+
+    let mut total = dirs
+        .first()
+        .map_or_else(|| rendered_lines(root), |d| d.lines);
+    if total <= max_lines {
+        return total;
+    }
+
+    // 根目录（下标 0）不参与排序，留作最后手段。
+    let mut candidates: Vec<usize> = (1..dirs.len()).collect();
+    match order {
+        CollapseOrder::Deepest => candidates.sort_by(|&a, &b| {
+            dirs[b]
+                .depth
+                .cmp(&dirs[a].depth)
+                .then_with(|| dirs[b].lines.cmp(&dirs[a].lines))
+                .then_with(|| a.cmp(&b))
+        }),
+        CollapseOrder::Biggest => {
+            candidates.sort_by(|&a, &b| dirs[b].lines.cmp(&dirs[a].lines).then_with(|| a.cmp(&b)))
+        }
+    }
+    candidates.push(0);
+
+    for index in candidates {
+        if total <= max_lines {
+            break;
+        }
+        if has_collapsed_ancestor(&dirs, index) {
+            continue;
+        }
+        // 折叠后只剩目录行与摘要行。
+        let saved = dirs[index].lines.saturating_sub(2);
+        if saved == 0 {
+            continue;
+        }
+        dirs[index].collapsed = true;
+        dirs[index].lines -= saved;
+        let mut ancestor = dirs[index].parent;
+        while let Some(a) = ancestor {
+            dirs[a].lines -= saved;
+            ancestor = dirs[a].parent;
+        }
+        total -= saved;
+    }
+
+    let mut next = 0;
+    apply_collapse(root, &dirs, &mut next);
+    total
+}
+
+/// 先序收集所有目录，返回子树的（行数，后代条目数）。
+fn flatten_dirs(node: &FsNode, parent: Option<usize>, dirs: &mut Vec<DirLines>) -> (usize, usize) {
+    if !node.is_directory() {
+        return (1, 0);
+    }
+
+    let slot = dirs.len();
+    dirs.push(DirLines {
+        parent,
+        depth: node.depth,
+        lines: 0,
+        descendants: 0,
+        collapsed: false,
+    });
+
+    let mut lines = 1
+        + usize::from(node.truncated)
+        + usize::from(node.omitted > 0)
+        + usize::from(node.collapsed > 0);
+    let mut descendants = node.omitted + node.collapsed;
+    for child in node.children.iter().flatten() {
+        let (child_lines, child_descendants) = flatten_dirs(child, Some(slot), dirs);
+        lines += child_lines;
+        descendants += 1 + child_descendants;
+    }
+    dirs[slot].lines = lines;
+    dirs[slot].descendants = descendants;
+    (lines, descendants)
+}
+
+/// 是否有祖先目录已被折叠（其子树已不再显示）。
+fn has_collapsed_ancestor(dirs: &[DirLines], index: usize) -> bool {
+    let mut ancestor = dirs[index].parent;
+    while let Some(a) = ancestor {
+        if dirs[a].collapsed {
+            return true;
+        }
+        ancestor = dirs[a].parent;
+    }
+    false
+}
+
+/// 按与 [`flatten_dirs`] 相同的先序把折叠结果写回树中。
+fn apply_collapse(node: &mut FsNode, dirs: &[DirLines], next: &mut usize) {
+    if !node.is_directory() {
+        return;
+    }
+    let entry = &dirs[*next];
+    *next += 1;
+
+    if entry.collapsed {
+        // 跳过整棵子树在扁平数组中占用的位置。
+        *next += count_subdirs(node);
+        node.children = None;
+        node.truncated = false;
+        node.omitted = 0;
+        node.collapsed = entry.descendants;
+        return;
+    }
+
+    for child in node.children.iter_mut().flatten() {
+        apply_collapse(child, dirs, next);
+    }
+}
+
+/// 子树中（不含自身）的目录数。
+fn count_subdirs(node: &FsNode) -> usize {
+    node.children
+        .iter()
+        .flatten()
+        .filter(|child| child.is_directory())
+        .map(|child| 1 + count_subdirs(child))
+        .sum()
+}
@@ -0,0 +1,266 @@
+//! Git 集成：定位仓库的 `.git` 目录，找出自某个提交以来发生变化的路径，
+//! 统计已跟踪、未跟踪与被忽略的文件，以及每个文件最后一次提交的作者。
+
+use crate::core::models::{FsNode, GitRatio, TreeError};
+use git2::{DiffOptions, ErrorCode, Repository, Sort, Status, StatusOptions};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// 返回自 `rev` 以来发生变化的文件，路径相对于 `root`。
+///
+/// 比较 `rev` 指向的树与工作区（含暂存区改动与未跟踪文件），相当于
+/// `git diff <rev>` 再加上未跟踪文件。`rev` 可以是任意 revspec（`HEAD~5`、
+/// 分支名、提交哈希）。位于 `root` 之外的变化会被忽略。
+///
+/// # 错误
+///
+/// `root` 不在 git 仓库中、仓库没有工作区或 `rev` 无法解析时返回
+/// `TreeError::Other`。
+pub fn changed_paths_since(root: &Path, rev: &str) -> Result<HashSet<PathBuf>, TreeError> {
+    let repo = Repository::discover(root).map_err(git_error)?;
+    let workdir = repo
+        .workdir()
+        .ok_or_else(|| TreeError::Other("git: repository has no working tree".to_string()))?
+        .canonicalize()?;
+    let tree = repo
+        .revparse_single(rev)
+        .and_then(|object| object.peel_to_tree())
+        .map_err(git_error)?;
+
+    let mut options = DiffOptions::new();
+    options.include_untracked(true).recurse_untracked_dirs(true);
+    let diff = repo
+        .diff_tree_to_workdir_with_index(Some(&tree), Some(&mut options))
+        .map_err(git_error)?;
+
+    let root = root.canonicalize()?;
+    let mut changed = HashSet::new();
+    for delta in diff.deltas() {
+        // 已删除的文件不在工作区中，也就不会出现在树里；只需新侧路径。
+        if let Some(path) = delta.new_file().path() {
+            if let Ok(rel) = workdir.join(path).strip_prefix(&root) {
+                changed.insert(rel.to_path_buf());
+            }
+        }
+    }
+    Ok(changed)
+}
+
+/// 统计 `root` 之下已跟踪、未跟踪与被忽略的文件数及其大小（`--git-ratio`）。
+///
+/// 按 git 的状态而非树中的节点计数，因此不受 `--exclude` 等过滤器影响；被忽略
+/// 的目录会展开为其中的文件。已删除（不在工作区中）的文件不计入。`root` 不在
+/// git 仓库中或仓库没有工作区时返回 `Ok(None)`。
+///
+/// # 错误
+///
+/// 仓库存在但无法读取其状态时返回 `TreeError::Other`。
+pub fn git_ratio(root: &Path) -> Result<Option<GitRatio>, TreeError> {
+    let repo = match Repository::discover(root) {
+        Ok(repo) => repo,
+        Err(err) if err.code() == ErrorCode::NotFound => return Ok(None),
+        Err(err) => return Err(git_error(err)),
+    };
+    let Some(workdir) = repo.workdir() else {
+        return Ok(None);
+    };
+    let workdir = workdir.canonicalize()?;
+    let root = root.canonicalize()?;
+
+    let mut options = StatusOptions::new();
+    options
+        .include_unmodified(true)
+        .include_untracked(true)
+        .recurse_untracked_dirs(true)
+        .include_ignored(true)
+        .recurse_ignored_dirs(true);
+    let statuses = repo.statuses(Some(&mut options)).map_err(git_error)?;
+
+    let mut ratio = GitRatio::default();
+    for entry in statuses.iter() {
+        // 非 UTF-8 路径无法从 git2 取回为 &str，跳过。
+        let Ok(path) = entry.path() else {
+            continue;
+        };
+        let path = workdir.join(path);
+        if !path.starts_with(&root) {
+            continue;
+        }
+        let Ok(meta) = std::fs::symlink_metadata(&path) else {
+            continue;
+        };
+        if meta.is_dir() {
+            continue;
+        }
+
+        let status = entry.status();
+        let bucket = if status.contains(Status::IGNORED) {
+            &mut ratio.ignored
+        } else if status.contains(Status::WT_NEW) {
+            &mut ratio.untracked
+        } else {
+            &mut ratio.tracked
+        };
+        bucket.files += 1;
+        bucket.size += meta.len();
+    }
+    Ok(Some(ratio))
+}
+
+/// 返回 `root` 之下每个已跟踪文件最后一次提交的作者名（`--git-authors`），键为相对
+/// `root` 的路径。
+///
+/// 只遍历一次提交历史：从 `HEAD` 起按时间倒序，把每个提交与其父提交比较，
+/// 文件第一次出现在改动中时记下该提交的作者；所有已跟踪文件都找到作者后提前结束。
+/// 与 `git log -1 -- <file>` 一致，合并提交只认领与每个父提交都不同的路径，
+/// 因此在被合并分支上改动的文件归于该分支上的提交者而不是合并者。
+/// 未跟踪以及已暂存但尚未提交的文件没有条目。`root` 不在 git 仓库中、仓库没有工作区或还没有提交时
+/// 返回空表。
+///
+/// # 错误
+///
+/// 仓库存在但无法读取其索引或历史时返回 `TreeError::Other`。
+pub fn last_authors(root: &Path) -> Result<HashMap<PathBuf, String>, TreeError> {
+    let mut authors = HashMap::new();
+    let repo = match Repository::discover(root) {
+        Ok(repo) => repo,
+        Err(err) if err.code() == ErrorCode::NotFound => return Ok(authors),
+        Err(err) => return Err(git_error(err)),
+    };
+    let Some(workdir) = repo.workdir() else {
+        return Ok(authors);
+    };
+    let workdir = workdir.canonicalize()?;
+    let root = root.canonicalize()?;
+    let Ok(prefix) = root.strip_prefix(&workdir) else {
+        return Ok(authors);
+    };
+
+    let Ok(head) = repo.head().and_then(|head| head.peel_to_tree()) else {
+        // 还没有任何提交。
+        return Ok(authors);
+    };
+
+    // 尚未找到作者的已跟踪文件（相对仓库根的路径）。只在索引中、从未提交过的
+    // 文件在历史里找不到作者，留在表中会使下面的遍历走完整个历史。
+    let index = repo.index().map_err(git_error)?;
+    let mut pending: HashSet<PathBuf> = index
+        .iter()
+        .filter_map(|entry| String::from_utf8(entry.path).ok())
+        .map(PathBuf::from)
+        .filter(|path| path.starts_with(prefix) && head.get_path(path).is_ok())
+        .collect();
+    if pending.is_empty() {
+        return Ok(authors);
+    }
+
+    let mut revwalk = repo.revwalk().map_err(git_error)?;
+    revwalk.push_head().map_err(git_error)?;
+    revwalk
+        .set_sorting(Sort::TOPOLOGICAL | Sort::TIME)
+        .map_err(git_error)?;
+
+    let mut options = DiffOptions::new();
+    if !prefix.as_os_str().is_empty() {
+        options.pathspec(prefix);
+    }
+    for oid in revwalk {
+        let commit = repo
+            .find_commit(oid.map_err(git_error)?)
+            .map_err(git_error)?;
+        let tree = commit.tree().map_err(git_error)?;
+
+        // 本提交改动的待定路径：根提交与空树比较，其余提交取与每个父提交
+        // 比较结果的交集。
+        let mut changed: Option<HashSet<PathBuf>> = None;
+        if commit.parent_count() == 0 {
+            changed = Some(changed_pending_paths(
+                &repo,
+                None,
+                &tree,
+                &mut options,
+                &pending,
+            )?);
+        }
+        for parent in commit.parents() {
+            let parent_tree = parent.tree().map_err(git_error)?;
+            let paths =
+                changed_pending_paths(&repo, Some(&parent_tree), &tree, &mut options, &pending)?;
+            changed = Some(match changed {
+                Some(previous) => previous.intersection(&paths).cloned().collect(),
+                None => paths,
+            });
+        }
+
+        let author = commit.author();
+        for path in changed.unwrap_or_default() {
+            pending.remove(&path);
+            let rel = path.strip_prefix(prefix).unwrap_or(&path).to_path_buf();
+            authors.insert(rel, author.name().unwrap_or_default().to_string());
+        }
+        if pending.is_empty() {
+            break;
+        }
+    }
+    Ok(authors)
+}
+
+/// `old` 到 `new` 之间改动、且仍在 `pending` 中的路径（相对仓库根）。
+fn changed_pending_paths(
+    repo: &Repository,
+    old: Option<&git2::Tree>,
+    new: &git2::Tree,
+    options: &mut DiffOptions,
+    pending: &HashSet<PathBuf>,
+) -> Result<HashSet<PathBuf>, TreeError> {
+    let diff = repo
+        .diff_tree_to_tree(old, Some(new), Some(options))
+        .map_err(git_error)?;
+    Ok(diff
+        .deltas()
+        .filter_map(|delta| delta.new_file().path())
+        .filter(|path| pending.contains(*path))
+        .map(Path::to_path_buf)
+        .collect())
+}
+
+/// 把 [`last_authors`] 的结果写入树中各文件节点的 `git_author`。
+///
+/// 节点路径相对根节点的路径与 `authors` 的键比较；不在表中的文件保持 `None`。
+pub fn apply_git_authors(root: &mut FsNode, authors: &HashMap<PathBuf, String>) {
+    let base = root.path.clone().unwrap_or_default();
+    apply_authors_recursive(root, &base, authors);
+}
+
+fn apply_authors_recursive(node: &mut FsNode, base: &Path, authors: &HashMap<PathBuf, String>) {
+    if node.is_file() {
+        node.git_author = node
+            .path
+            .as_deref()
+            .and_then(|path| path.strip_prefix(base).ok())
+            .and_then(|rel| authors.get(rel))
+            .cloned();
+    }
+    for child in node.children.iter_mut().flatten() {
+        apply_authors_recursive(child, base, authors);
+    }
+}
+
+/// git 仓库元数据目录的名称。
+pub const GIT_DIR: &str = ".git";
+
+/// 从 `start` 起向上查找最近的 `.git`（目录，或 worktree / 子模块使用的 `.git` 文件）。
+///
+/// 只检查文件系统，不打开仓库；`start` 不在任何仓库中时返回 `None`。
+pub fn find_git_dir(start: &Path) -> Option<PathBuf> {
+    let start = start.canonicalize().ok()?;
+    start
+        .ancestors()
+        .map(|dir| dir.join(GIT_DIR))
+        .find(|candidate| candidate.exists())
+}
+
+/// 将 git2 错误包装为 `TreeError`，只保留对用户有意义的消息部分。
+fn git_error(err: git2::Error) -> TreeError {
+    TreeError::Other(format!("git: {}", err.message()))
+}
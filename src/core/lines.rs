@@ -0,0 +1,52 @@
+//! 文本文件的行数统计（`--count-lines`）。
+//!
+//! 行数即文件中 `\n` 字节的个数（不以换行结尾的最后一行不计）。开头 8 KiB 中含
+//! NUL 字节的文件视为二进制文件，超过 [`MAX_LINE_COUNT_SIZE`] 的文件不读取，
+//! 二者都没有行数。
+
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
+/// 统计行数的文件大小上限（字节），更大的文件不读取。
+pub const MAX_LINE_COUNT_SIZE: u64 = 16 * 1024 * 1024;
+
+/// 判断二进制文件时检查的开头字节数。
+const BINARY_SNIFF_LEN: usize = 8 * 1024;
+
+/// 统计 `path` 的行数；文件无法读取、超过大小上限或为二进制文件时返回 `None`。
+pub fn count_lines(path: &Path) -> Option<usize> {
+    let file = File::open(path).ok()?;
+    if file.metadata().ok()?.len() > MAX_LINE_COUNT_SIZE {
+        return None;
+    }
+    count_lines_in(file).ok().flatten()
+}
+
+/// 统计 `reader` 中的行数；开头 8 KiB 中出现 NUL 字节时视为二进制，返回 `Ok(None)`。
+///
+/// # 错误
+///
+/// 返回 `reader` 产生的 IO 错误。
+pub fn count_lines_in<R: Read>(mut reader: R) -> io::Result<Option<usize>> {
+    let mut buffer = vec![0u8; BINARY_SNIFF_LEN];
+    let mut lines = 0;
+    let mut offset = 0u64;
+    loop {
+        let n = match reader.read(&mut buffer) {
+            Ok(0) => return Ok(Some(lines)),
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        let chunk = &buffer[..n];
+        if offset < BINARY_SNIFF_LEN as u64 {
+            let sniff = (BINARY_SNIFF_LEN as u64 - offset).min(n as u64) as usize;
+            if chunk[..sniff].contains(&0) {
+                return Ok(None);
+            }
+        }
+        lines += chunk.iter().filter(|&&b| b == b'\n').count();
+        offset += n as u64;
+    }
+}
@@ -1,10 +1,21 @@
 //! 目录遍历与统计信息收集的核心功能。
 
+pub mod archive;
+pub mod classifier;
 pub mod collector;
+pub mod diff;
 pub mod filter;
+pub mod fit;
+pub mod git;
+pub mod lines;
 pub mod models;
+#[cfg(unix)]
+pub mod mounts;
 pub mod progress;
+pub mod snapshot;
 pub mod streaming;
 pub mod walker;
 
-pub use models::{FileEntry, FileTypeInfo, FsNode, FsNodeType, FsTree, TreeError, TreeStats};
+pub use models::{
+    CategoryInfo, FileEntry, FileTypeInfo, FsNode, FsNodeType, FsTree, TreeError, TreeStats,
+};
@@ -1,12 +1,13 @@
 //! 表示文件系统树和统计信息的核心数据结构。
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::path::PathBuf;
-use std::time::Duration;
+use std::time::{Duration, SystemTime};
 
 /// 文件系统节点类型分类。
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub enum FsNodeType {
     /// 常规文件
     #[serde(rename = "file")]
@@ -20,7 +21,7 @@ pub enum FsNodeType {
 }
 
 /// 文件系统树中的一个节点。
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct FsNode {
     /// 文件或目录的名称
     pub name: String,
@@ -33,15 +34,74 @@ pub struct FsNode {
     #[serde(rename = "type")]
     pub node_type: FsNodeType,
 
-    /// 字节大小（目录为 0）
+    /// 字节大小（目录为 0，子树总大小见 [`FsNode::aggregate_size`]）
     pub size: u64,
 
     /// 在树中的深度（根节点为 0）
     pub depth: usize,
 
+    /// 最后修改时间（仅当遍历时请求了 `MetadataFields::MODIFIED`）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modified: Option<SystemTime>,
+
+    /// 所有者的用户 ID（仅 Unix，且遍历时请求了 `MetadataFields::OWNER`）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uid: Option<u32>,
+
+    /// 权限位（`st_mode`，仅 Unix，且遍历时请求了 `MetadataFields::MODE`）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<u32>,
+
+    /// 文本文件的行数（仅 `--count-lines`；二进制文件、过大的文件与目录为 `None`）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line_count: Option<usize>,
+
+    /// 最后一次提交该文件的作者（仅 `--git-authors`；未跟踪的文件与目录为 `None`）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git_author: Option<String>,
+
     /// 子节点（仅用于目录）
     #[serde(skip_serializing_if = "Option::is_none")]
     pub children: Option<Vec<FsNode>>,
+
+    /// 经由被跟随的符号链接到达时，链接指向的目标（仅在标记跟随链接时记录）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub link_target: Option<PathBuf>,
+
+    /// 该目录经由符号链接回到了自身祖先（循环），因此未被展开
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub is_loop: bool,
+
+    /// 该目录非空，但因深度限制未被展开（子节点为空不代表目录为空）
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub truncated: bool,
+
+    /// 因每目录条目上限（`--filelimit`）未列出的子节点数
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub omitted: usize,
+
+    /// 被 `--fit-lines` 折叠而不再显示的后代条目数（非 0 时 `children` 为 `None`）
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub collapsed: usize,
+
+    /// 因深度限制未展开目录的完整子树汇总（此时 `truncated` 为 true）；
+    /// `--collapse-depth`、`--du` 与 `--size-column` 需要时才统计
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub collapsed_summary: Option<SubtreeSummary>,
+}
+
+/// 一个目录完整子树中的文件数与文件总大小。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct SubtreeSummary {
+    /// 文件数量（不含目录与符号链接）
+    pub files: usize,
+
+    /// 文件总字节大小
+    pub size: u64,
+}
+
+fn is_zero(n: &usize) -> bool {
+    *n == 0
 }
 
 impl FsNode {
@@ -59,7 +119,18 @@ impl FsNode {
             node_type,
             size,
             depth,
+            modified: None,
+            uid: None,
+            mode: None,
+            line_count: None,
+            git_author: None,
             children: None,
+            link_target: None,
+            is_loop: false,
+            truncated: false,
+            omitted: 0,
+            collapsed: 0,
+            collapsed_summary: None,
         }
     }
 
@@ -71,7 +142,18 @@ impl FsNode {
             node_type: FsNodeType::Directory,
             size: 0,
             depth,
+            modified: None,
+            uid: None,
+            mode: None,
+            line_count: None,
+            git_author: None,
             children: Some(children),
+            link_target: None,
+            is_loop: false,
+            truncated: false,
+            omitted: 0,
+            collapsed: 0,
+            collapsed_summary: None,
         }
     }
 
@@ -90,6 +172,76 @@ impl FsNode {
         self.node_type == FsNodeType::Symlink
     }
 
+    /// 是否为没有任何子节点的目录（“叶子空目录”）。
+    ///
+    /// 被深度限制截断、被 `--fit-lines` 折叠或有条目被 `--filelimit` 略去的目录
+    /// 内容未知，不算空。
+    pub fn is_leaf_empty(&self) -> bool {
+        self.is_directory()
+            && !self.truncated
+            && self.omitted == 0
+            && self.collapsed == 0
+            && self.children.as_ref().is_none_or(Vec::is_empty)
+    }
+
+    /// 是否为整棵子树中不含任何文件或符号链接的目录（“递归空目录”）。
+    ///
+    /// 只含空子目录的目录也算空；叶子空目录总是递归空目录。内容未知的目录
+    /// （见 [`FsNode::is_leaf_empty`]）不算空。
+    pub fn is_recursively_empty(&self) -> bool {
+        self.is_directory()
+            && !self.truncated
+            && self.omitted == 0
+            && self.collapsed == 0
+            && self
+                .children
+                .iter()
+                .flatten()
+                .all(FsNode::is_recursively_empty)
+    }
+
+    /// 计算该节点子树中所有文件的总字节大小。
+    ///
+    /// 文件返回自身大小；目录递归累加其后代文件；符号链接计为 0。
+    pub fn aggregate_size(&self) -> u64 {
+        self.summarize(None).size
+    }
+
+    /// 一次后序遍历算出该节点子树的汇总，并把其中每个目录（含自身）的汇总按先序
+    /// 追加到 `dirs`。
+    ///
+    /// 按先序访问目录的调用者只需依次取用，而不必为每个目录重新遍历其子树。
+    /// 带有 `collapsed_summary` 的未展开目录直接取该汇总。
+    pub fn subtree_summaries(&self, dirs: &mut Vec<SubtreeSummary>) -> SubtreeSummary {
+        self.summarize(Some(dirs))
+    }
+
+    fn summarize(&self, mut dirs: Option<&mut Vec<SubtreeSummary>>) -> SubtreeSummary {
+        match self.node_type {
+            FsNodeType::File => SubtreeSummary {
+                files: 1,
+                size: self.size,
+            },
+            FsNodeType::Symlink => SubtreeSummary::default(),
+            FsNodeType::Directory => {
+                let slot = dirs.as_deref_mut().map(|dirs| {
+                    dirs.push(SubtreeSummary::default());
+                    dirs.len() - 1
+                });
+                let mut total = self.collapsed_summary.unwrap_or_default();
+                for child in self.children.iter().flatten() {
+                    let child_total = child.summarize(dirs.as_deref_mut());
+                    total.files += child_total.files;
+                    total.size += child_total.size;
+                }
+                if let (Some(dirs), Some(slot)) = (dirs, slot) {
+                    dirs[slot] = total;
+                }
+                total
+            }
+        }
+    }
+
     /// 获取文件扩展名（如果有）。
     ///
     /// 点文件（如 `.gitignore`）和以点号结尾的名字（如 `file.`）视为无扩展名。
@@ -110,24 +262,88 @@ impl FsNode {
 }
 
 /// 文件系统树的表示。
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct FsTree {
     /// 树的根节点
     pub root: FsNode,
 
     /// 树的最大深度
     pub max_depth: usize,
+
+    /// 遍历时被过滤器排除的条目数
+    #[serde(default, skip_serializing_if = "FilteredCounts::is_empty")]
+    pub filtered: FilteredCounts,
 }
 
 impl FsTree {
     /// 创建一棵新的文件系统树。
     pub fn new(root: FsNode, max_depth: usize) -> Self {
-        Self { root, max_depth }
+        Self {
+            root,
+            max_depth,
+            filtered: FilteredCounts::default(),
+        }
+    }
+}
+
+/// 遍历时被过滤器（排除模式、隐藏文件、`--include-only`、`.gitignore` 等）排除的条目数。
+///
+/// 被排除的目录只计一次，其内部不会被遍历，也不计入。
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct FilteredCounts {
+    /// 被排除的文件（含符号链接）数
+    pub files: usize,
+    /// 被排除的目录数
+    pub directories: usize,
+    /// 其中因扩展名过滤（`--ext`）被排除的文件，按扩展名（含点号，如 `.md`）计数
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub by_extension: BTreeMap<String, usize>,
+}
+
+impl FilteredCounts {
+    /// 是否没有任何条目被排除。
+    pub fn is_empty(&self) -> bool {
+        self.files == 0 && self.directories == 0
+    }
+
+    /// 记录一个因扩展名过滤被排除的文件；`ext` 不含点号，`None` 计入 `(no extension)`。
+    pub fn add_excluded_extension(&mut self, ext: Option<&str>) {
+        self.files += 1;
+        let key = ext.map_or_else(|| "(no extension)".to_string(), |e| format!(".{}", e));
+        *self.by_extension.entry(key).or_insert(0) += 1;
+    }
+}
+
+impl std::ops::AddAssign for FilteredCounts {
+    fn add_assign(&mut self, other: Self) {
+        self.files += other.files;
+        self.directories += other.directories;
+        for (ext, count) in other.by_extension {
+            *self.by_extension.entry(ext).or_insert(0) += count;
+        }
+    }
+}
+
+impl std::ops::Add for FilteredCounts {
+    type Output = Self;
+
+    fn add(mut self, other: Self) -> Self {
+        self += other;
+        self
+    }
+}
+
+impl std::iter::Sum for FilteredCounts {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::default(), |mut acc, counts| {
+            acc += counts;
+            acc
+        })
     }
 }
 
 /// 关于特定文件类型的信息。
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct FileTypeInfo {
     /// 文件扩展名（例如 ".rs"、".txt"）
     pub extension: String,
@@ -142,8 +358,24 @@ pub struct FileTypeInfo {
     pub percentage: f64,
 }
 
+/// 关于某个文件类别（由 `FileClassifier` 决定）的信息。
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CategoryInfo {
+    /// 类别标签（例如 "source"、"config"）
+    pub category: String,
+
+    /// 属于该类别的文件数量
+    pub count: usize,
+
+    /// 属于该类别的所有文件的总大小
+    pub total_size: u64,
+
+    /// 占总大小的百分比
+    pub percentage: f64,
+}
+
 /// 用于排序清单的文件条目。
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq, PartialOrd, Ord)]
 pub struct FileEntry {
     /// 文件名
     pub name: String,
@@ -162,8 +394,70 @@ impl FileEntry {
     }
 }
 
+/// 一类 git 状态下的文件数与总大小。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct GitFileCount {
+    /// 文件数量
+    pub files: usize,
+
+    /// 文件总字节大小
+    pub size: u64,
+}
+
+/// 扫描根下被 git 跟踪、未跟踪与被忽略的文件（`--git-ratio`）。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct GitRatio {
+    /// 已跟踪（含已暂存的新文件）
+    pub tracked: GitFileCount,
+
+    /// 未跟踪且未被忽略
+    pub untracked: GitFileCount,
+
+    /// 被 `.gitignore` 等规则忽略
+    pub ignored: GitFileCount,
+}
+
+impl GitRatio {
+    /// 三类文件的总数。
+    pub fn total_files(&self) -> usize {
+        self.tracked.files + self.untracked.files + self.ignored.files
+    }
+}
+
+/// 当前统计相对基线统计（`--compare-stats`）的变化量，正数表示增长。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct StatsDelta {
+    /// 文件数的变化
+    pub total_files: i64,
+
+    /// 目录数的变化
+    pub total_directories: i64,
+
+    /// 符号链接数的变化
+    pub total_symlinks: i64,
+
+    /// 总字节大小的变化
+    pub total_size: i64,
+
+    /// 有变化的扩展名，按大小变化的绝对值降序排列
+    pub extensions: Vec<ExtensionDelta>,
+}
+
+/// 某个扩展名的文件数与总大小的变化。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ExtensionDelta {
+    /// 文件扩展名（与 `FileTypeInfo::extension` 相同）
+    pub extension: String,
+
+    /// 文件数的变化
+    pub count: i64,
+
+    /// 总字节大小的变化
+    pub size: i64,
+}
+
 /// 扫描目录树所收集的统计信息。
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct TreeStats {
     /// 文件总数
     pub total_files: usize,
@@ -180,10 +474,57 @@ pub struct TreeStats {
     /// 按扩展名分组的文件
     pub files_by_extension: HashMap<String, FileTypeInfo>,
 
+    /// 按类别分组的文件
+    pub files_by_category: HashMap<String, CategoryInfo>,
+
     /// 最大的文件（前 N 个）
     pub largest_files: Vec<FileEntry>,
 
-    /// 扫描目录所花费的时间
+    /// 零字节文件数量
+    pub empty_files: usize,
+
+    /// 内容相同的文件组（仅在请求了 `--duplicates` 时为 `Some`）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duplicates: Option<Vec<Vec<FileEntry>>>,
+
+    /// 已跟踪、未跟踪与被忽略的文件（仅在请求了 `--git-ratio` 且位于 git 仓库中时为 `Some`）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git_ratio: Option<GitRatio>,
+
+    /// 相对基线统计的变化（仅在请求了 `--compare-stats` 时为 `Some`）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stats_delta: Option<StatsDelta>,
+
+    /// 子树中不含任何文件或符号链接的目录（见 [`FsNode::is_recursively_empty`]），先序排列
+    #[serde(default)]
+    pub empty_directories: Vec<PathBuf>,
+
+    /// 因每目录条目上限（`--filelimit`）未列出、也未遍历的条目数
+    #[serde(default)]
+    pub omitted_entries: usize,
+
+    /// 深度最大的文件及其深度（根为 0）；深度相同时取先序遍历中的第一个，没有文件时为 `None`
+    #[serde(default)]
+    pub deepest_path: Option<(PathBuf, usize)>,
+
+    /// 文本文件的总行数（仅 `--count-lines`；二进制与过大的文件不计）
+    #[serde(default)]
+    pub total_lines: Option<usize>,
+
+    /// 平均文件大小（字节；`--stats-ignore-empty` 时不计零字节文件）
+    pub average_file_size: f64,
+
+    /// 文件大小中位数（字节；`--stats-ignore-empty` 时不计零字节文件）
+    pub median_file_size: u64,
+
+    /// 叶子节点（无子节点的文件、链接与目录）深度的平均值
+    pub leaf_depth_mean: f64,
+
+    /// 叶子节点深度的总体标准差；越大说明树越不平衡
+    pub leaf_depth_stddev: f64,
+
+    /// 扫描目录所花费的时间（`-f json` 输出中为 `scan_duration_ms`，载入时缺省为 0）
+    #[serde(default)]
     pub scan_duration: Duration,
 }
 
@@ -196,7 +537,20 @@ impl TreeStats {
             total_symlinks: 0,
             total_size: 0,
             files_by_extension: HashMap::new(),
+            files_by_category: HashMap::new(),
             largest_files: Vec::new(),
+            empty_files: 0,
+            duplicates: None,
+            git_ratio: None,
+            stats_delta: None,
+            empty_directories: Vec::new(),
+            omitted_entries: 0,
+            deepest_path: None,
+            total_lines: None,
+            average_file_size: 0.0,
+            median_file_size: 0,
+            leaf_depth_mean: 0.0,
+            leaf_depth_stddev: 0.0,
             scan_duration: Duration::default(),
         }
     }
@@ -231,11 +585,28 @@ pub enum TreeError {
     #[error("JSON error: {0}")]
     Json(String),
 
+    /// 过滤后树中没有任何文件（`--fail-if-empty`）
+    #[error("No files found under {0}")]
+    EmptyTree(PathBuf),
+
     /// 通用错误消息
     #[error("{0}")]
     Other(String),
 }
 
+impl TreeError {
+    /// `--fail-if-empty` 命中时的进程退出码，区别于一般错误的 1 与参数错误的 2。
+    pub const EMPTY_TREE_EXIT_CODE: i32 = 3;
+
+    /// 该错误对应的进程退出码。
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            TreeError::EmptyTree(_) => Self::EMPTY_TREE_EXIT_CODE,
+            _ => 1,
+        }
+    }
+}
+
 // 将 serde_json 错误转换为我们自己的 TreeError
 impl From<serde_json::Error> for TreeError {
     fn from(err: serde_json::Error) -> Self {
@@ -0,0 +1,161 @@
+//! 伪文件系统（`/proc`、`/sys`、`/dev` 等）检测。
+//!
+//! 扫描这些文件系统得到的大小没有意义，读取某些条目还可能阻塞。扫描前按
+//! `/proc/self/mounts` 判断扫描根所在及其下挂载的文件系统类型；无法读取挂载表
+//! 的平台上退回到按已知路径判断。仅在 Unix 上编译。
+
+use std::path::{Path, PathBuf};
+
+/// 挂载表所在位置（Linux）。
+const MOUNTS_FILE: &str = "/proc/self/mounts";
+
+/// 视为伪文件系统的类型（内核导出的虚拟文件系统，大小与内容都不是真实数据）。
+const PSEUDO_FS_TYPES: &[&str] = &[
+    "proc",
+    "sysfs",
+    "devtmpfs",
+    "devpts",
+    "cgroup",
+    "cgroup2",
+    "debugfs",
+    "tracefs",
+    "securityfs",
+    "pstore",
+    "bpf",
+    "configfs",
+    "fusectl",
+    "mqueue",
+    "hugetlbfs",
+    "binfmt_misc",
+    "efivarfs",
+    "autofs",
+];
+
+/// 读不到挂载表时按路径识别的伪文件系统挂载点及其通常的类型。
+const KNOWN_PSEUDO_MOUNTS: &[(&str, &str)] =
+    &[("/proc", "proc"), ("/sys", "sysfs"), ("/dev", "devtmpfs")];
+
+/// 挂载表中的一项。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mount {
+    /// 挂载点
+    pub mount_point: PathBuf,
+    /// 文件系统类型（如 `ext4`、`proc`）
+    pub fs_type: String,
+}
+
+impl Mount {
+    /// 该挂载是否为伪文件系统。
+    pub fn is_pseudo(&self) -> bool {
+        PSEUDO_FS_TYPES.contains(&self.fs_type.as_str())
+    }
+}
+
+/// 解析 `/proc/self/mounts` 格式的文本；挂载点中的 `\040` 等八进制转义会被还原。
+pub fn parse_mounts(text: &str) -> Vec<Mount> {
+    text.lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let _device = fields.next()?;
+            let mount_point = unescape_octal(fields.next()?);
+            let fs_type = fields.next()?.to_string();
+            Some(Mount {
+                mount_point: PathBuf::from(mount_point),
+                fs_type,
+            })
+        })
+        .collect()
+}
+
+/// 若扫描 `path` 会碰到伪文件系统，返回给用户的警告；否则返回 `None`。
+///
+/// - 扫描根本身位于伪文件系统上：结果没有意义；
+/// - 扫描根之下挂载了伪文件系统（例如扫描 `/`）：建议加 `--one-filesystem`。
+///   `one_filesystem` 已开启时不再警告这种情况。
+pub fn pseudo_filesystem_warning(path: &Path, one_filesystem: bool) -> Option<String> {
+    let root = std::fs::canonicalize(path).ok()?;
+    let mounts = match std::fs::read_to_string(MOUNTS_FILE) {
+        Ok(text) => parse_mounts(&text),
+        Err(_) => KNOWN_PSEUDO_MOUNTS
+            .iter()
+            .map(|&(mount_point, fs_type)| Mount {
+                mount_point: PathBuf::from(mount_point),
+                fs_type: fs_type.to_string(),
+            })
+            .collect(),
+    };
+    pseudo_filesystem_warning_in(&root, &mounts, one_filesystem)
+}
+
+/// [`pseudo_filesystem_warning`] 的判断部分：`root` 须为规范路径。
+pub fn pseudo_filesystem_warning_in(
+    root: &Path,
+    mounts: &[Mount],
+    one_filesystem: bool,
+) -> Option<String> {
+    // 扫描根所在的文件系统：最长的、作为其前缀的挂载点（后挂载的同名挂载点覆盖先前的）。
+    let containing = mounts
+        .iter()
+        .filter(|m| root.starts_with(&m.mount_point))
+        .max_by_key(|m| m.mount_point.components().count());
+    if let Some(mount) = containing.filter(|m| m.is_pseudo()) {
+        return Some(format!(
+            "{} is on a {} pseudo-filesystem; sizes are not meaningful and some entries may block when read",
+            root.display(),
+            mount.fs_type
+        ));
+    }
+
+    if one_filesystem {
+        return None;
+    }
+    let mut nested: Vec<&Path> = mounts
+        .iter()
+        .filter(|m| m.is_pseudo() && m.mount_point != root && m.mount_point.starts_with(root))
+        .map(|m| m.mount_point.as_path())
+        .collect();
+    if nested.is_empty() {
+        return None;
+    }
+    nested.sort();
+    nested.dedup();
+    let shown: Vec<String> = nested
+        .iter()
+        .take(3)
+        .map(|p| p.display().to_string())
+        .collect();
+    let more = if nested.len() > shown.len() {
+        format!(" and {} more", nested.len() - shown.len())
+    } else {
+        String::new()
+    };
+    Some(format!(
+        "{} contains pseudo-filesystem mounts ({}{}); consider --one-filesystem to skip them",
+        root.display(),
+        shown.join(", "),
+        more
+    ))
+}
+
+/// 还原挂载表中 `\ooo` 形式的八进制转义（空格写作 `\040`）。
+fn unescape_octal(field: &str) -> String {
+    let bytes = field.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escape = bytes
+            .get(i + 1..i + 4)
+            .filter(|digits| bytes[i] == b'\\' && digits.iter().all(|b| (b'0'..=b'7').contains(b)));
+        if let Some(digits) = escape {
+            let value = digits
+                .iter()
+                .fold(0u32, |acc, b| acc * 8 + u32::from(b - b'0'));
+            out.push(value as u8);
+            i += 4;
+            continue;
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
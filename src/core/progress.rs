@@ -1,6 +1,8 @@
 //! 目录遍历的进度报告。
 
 use indicatif::{ProgressBar, ProgressStyle};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 use std::time::Duration;
 
 /// 进度报告器配置。
@@ -68,3 +70,62 @@ pub fn abandon_progress(pb: &Option<ProgressBar>) {
         pb.abandon();
     }
 }
+
+/// 可在线程间共享的进度报告器，供并行遍历的工作线程使用。
+///
+/// 计数保存在 `AtomicUsize` 中，进度条（若启用）经 `Arc` 共享；克隆得到的报告器
+/// 指向同一计数与进度条。未启用进度条时仍照常计数。
+#[derive(Debug, Clone, Default)]
+pub struct ProgressReporter {
+    /// 已报告的条目数
+    count: Arc<AtomicUsize>,
+    /// 共享的进度条
+    bar: Option<Arc<ProgressBar>>,
+}
+
+impl ProgressReporter {
+    /// 创建报告器；`bar` 为 `None` 时只计数。
+    pub fn new(bar: Option<ProgressBar>) -> Self {
+        Self {
+            count: Arc::new(AtomicUsize::new(0)),
+            bar: bar.map(Arc::new),
+        }
+    }
+
+    /// 按配置创建进度条（见 [`create_progress_bar`]）并包装为报告器。
+    pub fn from_config(config: &ProgressConfig) -> Self {
+        Self::new(create_progress_bar(config))
+    }
+
+    /// 计数加一并推进进度条。
+    pub fn increment(&self) {
+        self.count.fetch_add(1, Ordering::Relaxed);
+        if let Some(bar) = &self.bar {
+            bar.inc(1);
+        }
+    }
+
+    /// 更新进度条上的消息（通常为当前路径）。
+    pub fn set_message(&self, msg: &str) {
+        if let Some(bar) = &self.bar {
+            bar.set_message(msg.to_string());
+        }
+    }
+
+    /// 目前为止报告的条目总数。
+    pub fn count(&self) -> usize {
+        self.count.load(Ordering::Relaxed)
+    }
+
+    /// 共享的进度条（未启用时为 `None`）。
+    pub fn bar(&self) -> Option<&ProgressBar> {
+        self.bar.as_deref()
+    }
+
+    /// 以消息完成进度条。
+    pub fn finish(&self, msg: &str) {
+        if let Some(bar) = &self.bar {
+            bar.finish_with_message(msg.to_string());
+        }
+    }
+}
@@ -0,0 +1,60 @@
+//! 树快照的保存与加载。
+//!
+//! 快照即 `-f json` 的输出：`{"tree": {"root": ..., "max_depth": ...}, "stats": ...}`。
+//! 加载时只还原 `tree` 部分；统计信息可由还原出的树重新计算。
+//!
+//! `--compare-stats` 的基线则只需要统计信息：`stats -f json`、`--stats-file` 的输出，
+//! 或快照中的 `stats` 部分均可。
+
+use crate::core::models::{FsNode, FsTree, TreeError, TreeStats};
+use crate::formatters::json::format_json;
+use std::path::Path;
+
+/// 将树及其统计信息以 JSON 快照写入 `out`。
+pub fn save_snapshot(tree: &FsTree, stats: &TreeStats, out: &Path) -> Result<(), TreeError> {
+    let json = format_json(tree, stats, true)?;
+    std::fs::write(out, json)?;
+    Ok(())
+}
+
+/// 从 JSON 快照文件加载树。
+///
+/// # 错误
+///
+/// 文件无法读取时返回 `TreeError::Io`；内容不是合法快照时返回 `TreeError::Json`。
+pub fn load_snapshot(input: &Path) -> Result<FsTree, TreeError> {
+    let text = std::fs::read_to_string(input)?;
+    parse_snapshot(&text)
+}
+
+/// 从 JSON 文本解析快照中的树。
+pub fn parse_snapshot(text: &str) -> Result<FsTree, TreeError> {
+    let mut value: serde_json::Value = serde_json::from_str(text)?;
+    let tree = value
+        .get_mut("tree")
+        .ok_or_else(|| TreeError::Json("snapshot is missing the \"tree\" field".to_string()))?;
+
+    let root: FsNode = serde_json::from_value(tree["root"].take())?;
+    let max_depth = tree["max_depth"].as_u64().unwrap_or(0) as usize;
+
+    Ok(FsTree::new(root, max_depth))
+}
+
+/// 从 JSON 文件加载基线统计（`--compare-stats`）。
+///
+/// # 错误
+///
+/// 文件无法读取时返回 `TreeError::Io`；内容不是统计 JSON 时返回 `TreeError::Json`。
+pub fn load_stats(input: &Path) -> Result<TreeStats, TreeError> {
+    let text = std::fs::read_to_string(input)?;
+    parse_stats(&text)
+}
+
+/// 从 JSON 文本解析统计信息；带 `stats` 字段的快照取其中的统计部分。
+pub fn parse_stats(text: &str) -> Result<TreeStats, TreeError> {
+    let mut value: serde_json::Value = serde_json::from_str(text)?;
+    if let Some(stats) = value.get_mut("stats") {
+        value = stats.take();
+    }
+    Ok(serde_json::from_value(value)?)
+}
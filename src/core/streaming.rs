@@ -8,9 +8,12 @@
 //! 峰值内存为 O(最宽目录)：每次只为排序而缓冲单个目录的条目——而非
 //! 整棵树。
 
-use crate::core::models::{FsNodeType, TreeError};
-use crate::core::walker::{SortField, WalkConfig};
+use crate::core::filter::extension_of;
+use crate::core::lines::count_lines;
+use crate::core::models::{FilteredCounts, FsNode, FsNodeType, TreeError};
+use crate::core::walker::{natural_cmp, MetadataFields, SortField, WalkConfig};
 use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 use walkdir::WalkDir;
 
 /// 遍历核心输出的节点。
@@ -21,8 +24,22 @@ pub struct StreamNode {
     pub node_type: FsNodeType,
     pub size: u64,
     pub depth: usize,
+    /// 最后修改时间（仅当请求了 `MetadataFields::MODIFIED`）。
+    pub modified: Option<SystemTime>,
+    /// 所有者用户 ID（仅 Unix，且请求了 `MetadataFields::OWNER`）。
+    pub uid: Option<u32>,
+    /// 权限位（仅 Unix，且请求了 `MetadataFields::MODE`）。
+    pub mode: Option<u32>,
+    /// 文本文件的行数（仅当请求了 `MetadataFields::LINES`，二进制或过大的文件为 `None`）。
+    pub line_count: Option<usize>,
     /// 若该节点是其父节点的最后一个子节点则为真（用于绘制树）。
     pub is_last: bool,
+    /// 经由被跟随的符号链接到达时的链接目标（仅当 `mark_followed_symlinks` 开启）。
+    pub link_target: Option<PathBuf>,
+    /// 该目录经由符号链接回到了自身祖先，遍历不会进入其中。
+    pub is_loop: bool,
+    /// 该目录非空，但因 `max_depth` 限制未被展开。
+    pub truncated: bool,
 }
 
 /// 经过一次 stat 调用后的目录条目，在排序和输出时被复用。
@@ -31,13 +48,24 @@ struct Scanned {
     path: PathBuf,
     node_type: FsNodeType,
     size: u64,
+    modified: Option<SystemTime>,
+    uid: Option<u32>,
+    mode: Option<u32>,
+    link_target: Option<PathBuf>,
 }
 
 /// 遍历目录树，每个后代节点只输出一次。
 ///
 /// 回调按深度优先的先序顺序接收节点。根节点的直接子节点位于深度 1；
-/// 根节点本身不会被输出（由调用者自行渲染或构建）。
-pub fn walk_core<F>(root: &Path, config: &WalkConfig, mut callback: F) -> Result<(), TreeError>
+/// 根节点本身不会被输出（由调用者自行渲染或构建）。若根是文件且
+/// `config.allow_file_root` 为真，则不输出任何节点。
+///
+/// 返回遍历过程中被过滤器排除的条目数。
+pub fn walk_core<F>(
+    root: &Path,
+    config: &WalkConfig,
+    mut callback: F,
+) -> Result<FilteredCounts, TreeError>
 where
     F: FnMut(&StreamNode),
 {
@@ -47,25 +75,115 @@ where
 
     let meta = std::fs::metadata(root)?;
     if !meta.is_dir() {
+        if config.allow_file_root {
+            return Ok(FilteredCounts::default());
+        }
         return Err(TreeError::NotADirectory(root.to_path_buf()));
     }
 
-    walk_children(root, 1, config, &mut callback);
-    Ok(())
+    // 跟随符号链接时记录当前路径上各祖先目录的规范路径，用于检测循环。
+    let mut ancestors = Vec::new();
+    if config.follow_symlinks {
+        ancestors.push(std::fs::canonicalize(root)?);
+    }
+
+    let mut filtered = FilteredCounts::default();
+    walk_children(
+        root,
+        root,
+        1,
+        config,
+        &mut ancestors,
+        &mut filtered,
+        &mut callback,
+    );
+    Ok(filtered)
 }
 
 /// 递归地输出 `dir` 在指定 `depth` 处的子节点。
-fn walk_children<F>(dir: &Path, depth: usize, config: &WalkConfig, callback: &mut F)
-where
+///
+/// `root` 是扫描根，供过滤器计算相对路径。`ancestors` 仅在跟随符号链接时
+/// 使用：保存从根到 `dir` 的规范路径栈。被过滤器排除的条目累加到 `filtered`。
+fn walk_children<F>(
+    root: &Path,
+    dir: &Path,
+    depth: usize,
+    config: &WalkConfig,
+    ancestors: &mut Vec<PathBuf>,
+    filtered: &mut FilteredCounts,
+    callback: &mut F,
+) where
     F: FnMut(&StreamNode),
 {
+    // 流式输出不显示 `max_entries_per_dir` 省略的条目数（见 `WalkConfig`）。
+    let (children, excluded, _omitted) = read_children(root, dir, depth, config, ancestors);
+    *filtered += excluded;
+
+    for child in children {
+        callback(&child.node);
+
+        if child.should_descend() {
+            if let Some(c) = child.canonical {
+                ancestors.push(c);
+                walk_children(
+                    root,
+                    &child.node.path,
+                    depth + 1,
+                    config,
+                    ancestors,
+                    filtered,
+                    callback,
+                );
+                ancestors.pop();
+            } else {
+                walk_children(
+                    root,
+                    &child.node.path,
+                    depth + 1,
+                    config,
+                    ancestors,
+                    filtered,
+                    callback,
+                );
+            }
+        }
+    }
+}
+
+/// `read_children` 返回的一个子节点。
+pub(crate) struct ChildEntry {
+    /// 待输出的节点
+    pub node: StreamNode,
+    /// 跟随符号链接时该目录的规范路径（进入该目录前压入祖先栈）
+    pub canonical: Option<PathBuf>,
+}
+
+impl ChildEntry {
+    /// 是否应继续进入该节点（非循环的目录）。
+    pub fn should_descend(&self) -> bool {
+        self.node.node_type == FsNodeType::Directory && !self.node.is_loop
+    }
+}
+
+/// 读取、过滤并排序 `dir` 在 `depth` 处的直接子节点，不做递归。
+///
+/// 这是顺序遍历与并行树构建（`walker`）共用的单目录步骤：两者因此得到完全
+/// 相同的节点与顺序。超出 `max_depth` 时返回空。同时返回该目录中被过滤器
+/// 排除的条目数，以及排序后因 `max_entries_per_dir` 被略去的条目数。
+pub(crate) fn read_children(
+    root: &Path,
+    dir: &Path,
+    depth: usize,
+    config: &WalkConfig,
+    ancestors: &[PathBuf],
+) -> (Vec<ChildEntry>, FilteredCounts, usize) {
     // 深度限制：深度 D 处的子节点当且仅当 D <= max_depth 时才会被输出。这与
     // 父节点侧的 `depth >= max_depth => 无子节点` 相对应。
     if config.max_depth > 0 && depth > config.max_depth {
-        return;
+        return (Vec::new(), FilteredCounts::default(), 0);
     }
-
     let mut scanned: Vec<Scanned> = Vec::new();
+    let mut filtered = FilteredCounts::default();
 
     let walker = WalkDir::new(dir)
         .min_depth(1)
@@ -83,10 +201,47 @@ where
         let file_type = entry.file_type();
         let is_dir = file_type.is_dir();
 
-        if config.filter.should_exclude(entry.path(), is_dir) {
+        if config
+            .filter
+            .should_exclude_under_except_extension(root, entry.path(), is_dir)
+        {
+            if is_dir {
+                filtered.directories += 1;
+            } else {
+                filtered.files += 1;
+            }
+            continue;
+        }
+
+        // --ext：在其余过滤器之后单独判断，以便只把仍然留下、
+        // 仅因扩展名被隐藏的文件按扩展名记录下来。
+        if !is_dir {
+            let name = entry.file_name().to_string_lossy();
+            if config.filter.excludes_extension(&name) {
+                filtered.add_excluded_extension(extension_of(&name));
+                continue;
+            }
+        }
+
+        // --include-prune：子树中没有可包含文件的目录不再进入。
+        if is_dir
+            && config
+                .filter
+                .prunes_dir(root, entry.path(), config.follow_symlinks)
+        {
+            filtered.directories += 1;
             continue;
         }
 
+        // --one-filesystem：挂载点（设备号不同的目录）整个跳过。
+        if is_dir && config.same_device.is_some() {
+            let device = entry.metadata().ok().and_then(|m| device_id(&m));
+            if device.is_some() && device != config.same_device {
+                filtered.directories += 1;
+                continue;
+            }
+        }
+
         let node_type = if file_type.is_symlink() {
             FsNodeType::Symlink
         } else if is_dir {
@@ -95,13 +250,59 @@ where
             FsNodeType::File
         };
 
-        // 只有当调用者需要 size（显示 size 或内存路径的统计）或按 size 排序时，
-        // 才对文件付出一次 stat 调用的代价；否则跳过，size 置 0。
-        let need = config.need_size || config.sort_by == SortField::Size;
-        let size = if need && node_type == FsNodeType::File {
-            entry.metadata().map(|m| m.len()).unwrap_or(0)
+        // 只有当调用者请求了某个元数据字段（或按 size 排序）时，才付出一次 stat
+        // 调用的代价；size 只对文件获取。未请求的字段保持零值/None。
+        let need_size = (config.metadata.contains(MetadataFields::SIZE)
+            || config.sort_by == SortField::Size
+            || config.filter.has_size_range())
+            && node_type == FsNodeType::File;
+        let need_modified = config.metadata.contains(MetadataFields::MODIFIED)
+            || config.sort_by == SortField::MTime;
+        let need_owner = config.metadata.contains(MetadataFields::OWNER);
+        let need_mode = config.metadata.contains(MetadataFields::MODE);
+        let need_age = config.filter.has_modified_range() && node_type == FsNodeType::File;
+        let meta = if need_size || need_modified || need_owner || need_mode || need_age {
+            entry.metadata().ok()
+        } else {
+            None
+        };
+        let size = match &meta {
+            Some(m) if need_size => m.len(),
+            _ => 0,
+        };
+
+        // --min-size / --max-size：只作用于文件，需要先拿到大小才能判断。
+        if node_type == FsNodeType::File && config.filter.excludes_size(size) {
+            filtered.files += 1;
+            continue;
+        }
+        // --newer-than / --older-than：同样只作用于文件。
+        if need_age
+            && config
+                .filter
+                .excludes_modified(meta.as_ref().and_then(|m| m.modified().ok()))
+        {
+            filtered.files += 1;
+            continue;
+        }
+        let modified = match &meta {
+            Some(m) if need_modified => m.modified().ok(),
+            _ => None,
+        };
+        let uid = match &meta {
+            Some(m) if need_owner => owner_uid(m),
+            _ => None,
+        };
+        let mode = match &meta {
+            Some(m) if need_mode => file_mode(m),
+            _ => None,
+        };
+
+        // 跟随链接时 file_type() 已解析到目标，只能靠 path_is_symlink 识别来源。
+        let link_target = if config.mark_followed_symlinks && entry.path_is_symlink() {
+            std::fs::read_link(entry.path()).ok()
         } else {
-            0
+            None
         };
 
         scanned.push(Scanned {
@@ -109,32 +310,141 @@ where
             path: entry.path().to_path_buf(),
             node_type,
             size,
+            modified,
+            uid,
+            mode,
+            link_target,
         });
     }
 
-    sort_scanned(&mut scanned, config);
+    sort_entries(&mut scanned, config);
+
+    // --filelimit：排序之后只保留前 N 个，略去的条目不会被进入或 stat 子树。
+    let limit = config.max_entries_per_dir;
+    let omitted = if limit > 0 && scanned.len() > limit {
+        let omitted = scanned.len() - limit;
+        scanned.truncate(limit);
+        omitted
+    } else {
+        0
+    };
 
     let total = scanned.len();
-    for (i, item) in scanned.into_iter().enumerate() {
-        let is_last = i + 1 == total;
-        let is_dir = item.node_type == FsNodeType::Directory;
-        let path = item.path.clone();
-
-        callback(&StreamNode {
-            name: item.name,
-            path: item.path,
-            node_type: item.node_type,
-            size: item.size,
-            depth,
-            is_last,
-        });
+    let children = scanned
+        .into_iter()
+        .enumerate()
+        .map(|(i, item)| {
+            let is_dir = item.node_type == FsNodeType::Directory;
 
-        if is_dir {
-            walk_children(&path, depth + 1, config, callback);
-        }
+            // 跟随符号链接时，若目录的规范路径已在祖先栈上，则是循环：标记并不再深入。
+            let canonical = if is_dir && config.follow_symlinks {
+                std::fs::canonicalize(&item.path).ok()
+            } else {
+                None
+            };
+            let is_loop = canonical
+                .as_ref()
+                .is_some_and(|c| ancestors.iter().any(|a| a == c));
+
+            // 深度限制恰好停在该目录：只有确实存在可见条目时才算截断，
+            // 以便格式化器区分“被截断”与“本来就空”。
+            let truncated = is_dir
+                && !is_loop
+                && config.max_depth > 0
+                && depth >= config.max_depth
+                && has_visible_entries(root, &item.path, config);
+
+            // 行数在 --filelimit 截断之后才统计，略去的文件不会被读取。
+            let line_count = if item.node_type == FsNodeType::File
+                && config.metadata.contains(MetadataFields::LINES)
+            {
+                count_lines(&item.path)
+            } else {
+                None
+            };
+
+            ChildEntry {
+                node: StreamNode {
+                    name: item.name,
+                    path: item.path,
+                    node_type: item.node_type,
+                    size: item.size,
+                    depth,
+                    modified: item.modified,
+                    uid: item.uid,
+                    mode: item.mode,
+                    line_count,
+                    is_last: i + 1 == total,
+                    link_target: item.link_target,
+                    is_loop,
+                    truncated,
+                },
+                canonical,
+            }
+        })
+        .collect();
+
+    (children, filtered, omitted)
+}
+
+/// 条目所有者的用户 ID；非 Unix 平台没有 uid，返回 `None`。
+pub(crate) fn owner_uid(meta: &std::fs::Metadata) -> Option<u32> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        Some(meta.uid())
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = meta;
+        None
+    }
+}
+
+/// 条目的权限位（`st_mode`，含文件类型位）；非 Unix 平台返回 `None`。
+pub(crate) fn file_mode(meta: &std::fs::Metadata) -> Option<u32> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        Some(meta.mode())
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = meta;
+        None
+    }
+}
+
+/// 条目所在文件系统的设备号；非 Unix 平台返回 `None`。
+pub(crate) fn device_id(meta: &std::fs::Metadata) -> Option<u64> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        Some(meta.dev())
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = meta;
+        None
     }
 }
 
+/// 目录下是否至少有一个未被过滤器排除的条目（找到第一个即返回）。
+fn has_visible_entries(root: &Path, dir: &Path, config: &WalkConfig) -> bool {
+    WalkDir::new(dir)
+        .min_depth(1)
+        .max_depth(1)
+        .follow_links(config.follow_symlinks)
+        .into_iter()
+        .filter_map(Result::ok)
+        .any(|entry| {
+            let is_dir = entry.file_type().is_dir();
+            !config
+                .filter
+                .should_exclude_under(root, entry.path(), is_dir)
+        })
+}
+
 /// 用于按类型排序的文件扩展名（不含点号）。
 fn ext_of(name: &str) -> &str {
     match name.rfind('.') {
@@ -143,30 +453,79 @@ fn ext_of(name: &str) -> &str {
     }
 }
 
-/// 对扫描到的条目排序：目录在前，然后按配置的字段排序。
-fn sort_scanned(entries: &mut [Scanned], config: &WalkConfig) {
-    let dir_first = |a: &Scanned, b: &Scanned| {
-        let a_dir = a.node_type == FsNodeType::Directory;
-        let b_dir = b.node_type == FsNodeType::Directory;
-        match (a_dir, b_dir) {
-            (true, false) => Some(std::cmp::Ordering::Less),
-            (false, true) => Some(std::cmp::Ordering::Greater),
-            _ => None,
-        }
+/// 排序所需的条目属性，使扫描到的条目与不来自磁盘的节点（如 tar 归档中的
+/// 条目）共用同一套排序规则。
+pub(crate) trait SortKey {
+    /// 条目名称
+    fn sort_name(&self) -> &str;
+    /// 字节大小（目录为 0）
+    fn sort_size(&self) -> u64;
+    /// 是否为目录
+    fn sort_is_dir(&self) -> bool;
+    /// 最后修改时间
+    fn sort_modified(&self) -> Option<SystemTime>;
+}
+
+impl SortKey for Scanned {
+    fn sort_name(&self) -> &str {
+        &self.name
+    }
+    fn sort_size(&self) -> u64 {
+        self.size
+    }
+    fn sort_is_dir(&self) -> bool {
+        self.node_type == FsNodeType::Directory
+    }
+    fn sort_modified(&self) -> Option<SystemTime> {
+        self.modified
+    }
+}
+
+impl SortKey for FsNode {
+    fn sort_name(&self) -> &str {
+        &self.name
+    }
+    fn sort_size(&self) -> u64 {
+        self.size
+    }
+    fn sort_is_dir(&self) -> bool {
+        self.is_directory()
+    }
+    fn sort_modified(&self) -> Option<SystemTime> {
+        self.modified
+    }
+}
+
+/// 对同一目录下的条目排序：目录在前，然后按配置的字段排序。
+pub(crate) fn sort_entries<T: SortKey>(entries: &mut [T], config: &WalkConfig) {
+    let dir_first = |a: &T, b: &T| match (a.sort_is_dir(), b.sort_is_dir()) {
+        _ if !config.dirs_first => None,
+        (true, false) => Some(std::cmp::Ordering::Less),
+        (false, true) => Some(std::cmp::Ordering::Greater),
+        _ => None,
     };
 
     match config.sort_by {
-        SortField::Name => {
-            entries.sort_by(|a, b| dir_first(a, b).unwrap_or_else(|| a.name.cmp(&b.name)))
-        }
-        SortField::Size => {
-            entries.sort_by(|a, b| dir_first(a, b).unwrap_or_else(|| b.size.cmp(&a.size)))
-        }
+        SortField::Name => entries
+            .sort_by(|a, b| dir_first(a, b).unwrap_or_else(|| a.sort_name().cmp(b.sort_name()))),
+        SortField::Natural => entries.sort_by(|a, b| {
+            dir_first(a, b).unwrap_or_else(|| natural_cmp(a.sort_name(), b.sort_name()))
+        }),
+        SortField::Size => entries
+            .sort_by(|a, b| dir_first(a, b).unwrap_or_else(|| b.sort_size().cmp(&a.sort_size()))),
         SortField::Type => entries.sort_by(|a, b| {
             dir_first(a, b).unwrap_or_else(|| {
-                ext_of(&a.name)
-                    .cmp(ext_of(&b.name))
-                    .then_with(|| a.name.cmp(&b.name))
+                ext_of(a.sort_name())
+                    .cmp(ext_of(b.sort_name()))
+                    .then_with(|| a.sort_name().cmp(b.sort_name()))
+            })
+        }),
+        // 最近修改的在前；取不到时间的条目排在最后，同一时间按名称排序。
+        SortField::MTime => entries.sort_by(|a, b| {
+            dir_first(a, b).unwrap_or_else(|| {
+                b.sort_modified()
+                    .cmp(&a.sort_modified())
+                    .then_with(|| a.sort_name().cmp(b.sort_name()))
             })
         }),
     }
@@ -5,16 +5,27 @@
 //! `walk_directory` 只是该流的一个轻量消费者，为需要整棵树常驻内存的调用者
 //! （JSON、统计信息、最大文件）物化出一棵 `FsTree`。
 
-use crate::core::filter::FilterConfig;
-use crate::core::models::{FsNode, FsNodeType, FsTree, TreeError};
-use crate::core::streaming::walk_core;
-use std::path::Path;
+use crate::core::filter::{FilterConfig, TREEIGNORE_FILE};
+use crate::core::lines::count_lines;
+use crate::core::models::{FilteredCounts, FsNode, FsNodeType, FsTree, SubtreeSummary, TreeError};
+use crate::core::progress::ProgressReporter;
+use crate::core::streaming::{file_mode, owner_uid, read_children, walk_core, StreamNode};
+use rayon::prelude::*;
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
 
 /// 目录遍历的配置。由内存树构建器和流式格式化器共享。
 #[derive(Debug, Clone)]
 pub struct WalkConfig {
     /// 遍历的最大深度（0 表示不限）
+    ///
+    /// 深度按显示层级计算：扫描根总是深度 0，即使它本身是指向目录的符号链接；
+    /// 跟随符号链接不会额外占用一层。
     pub max_depth: usize,
+    /// 最小深度（0 或 1 表示不限）：更浅的节点不输出，但仍会进入其中遍历。深度达到
+    /// `min_depth` 的节点连同子树成为根的直接子节点，见 [`apply_min_depth`]
+    pub min_depth: usize,
     /// 显示隐藏文件（以 . 开头）
     pub show_hidden: bool,
     /// 跟随符号链接
@@ -23,14 +34,58 @@ pub struct WalkConfig {
     pub sort_by: SortField,
     /// 反转排序顺序
     pub reverse: bool,
+    /// 目录排在文件之前（默认）；为 false 时目录与文件按同一字段混合排序，
+    /// 与 GNU `tree` 相同（`--compat-tree`）
+    pub dirs_first: bool,
     /// 过滤器配置
     pub filter: FilterConfig,
-    /// 是否需要文件的字节大小。
+    /// 需要为每个节点获取的元数据字段。
+    ///
+    /// 为空时遍历核心完全跳过 `metadata()` 调用，仅依赖 readdir 缓存的
+    /// `DirEntry::file_type`（size 置 0、modified 为 `None`），适用于只需名称的
+    /// 树形输出。`sort_by == Size` 总是隐式需要 `SIZE`，由遍历核心内部兜底，
+    /// 无需调用者在此置位。
+    pub metadata: MetadataFields,
+    /// 根路径是文件（而非目录）时，是否将其作为单节点树输出。
+    ///
+    /// 为 false 时保持严格行为，返回 `TreeError::NotADirectory`。
+    pub allow_file_root: bool,
+    /// 跟随符号链接时，仍记录经由链接到达的节点及其目标，以便区分显示。
+    pub mark_followed_symlinks: bool,
+    /// 构建内存树时的并行线程数：1 为顺序遍历（不创建线程池），
+    /// 0 为自动（rayon 默认线程数）。流式遍历始终是顺序的。
+    pub threads: usize,
+    /// 每个目录最多保留的子节点数（排序之后取前 N 个，0 表示不限）。
     ///
-    /// 为 false 时遍历核心会跳过对文件的 `stat` 调用（size 置 0），
-    /// 适用于流式输出且不显示 size 的场景。`sort_by == Size` 总是隐式需要 size，
-    /// 由遍历核心内部兜底，无需调用者在此置位。
-    pub need_size: bool,
+    /// 内存树把略去的数目记在父目录的 `FsNode::omitted` 上；流式遍历只是不输出
+    /// 这些条目，不报告数目。
+    pub max_entries_per_dir: usize,
+    /// 为因 `max_depth` 未展开的目录统计其完整子树的文件数与大小，记在
+    /// `FsNode::collapsed_summary` 上（`--collapse-depth`、`--du`，见 [`summarize_truncated_dirs`]）
+    pub summarize_truncated: bool,
+    /// 只进入该设备号上的目录（`--one-filesystem`）；位于其他文件系统上的目录
+    /// 按被过滤处理。`None` 表示不限，非 Unix 平台上始终为 `None`。
+    pub same_device: Option<u64>,
+}
+
+bitflags::bitflags! {
+    /// 遍历时为每个节点获取的元数据字段。
+    ///
+    /// 格式化器和统计收集各自声明所需字段（见各模块的 `REQUIRED_METADATA` /
+    /// `required_metadata`），由 `Config::to_walk_config` 取并集后写入 `WalkConfig`。
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct MetadataFields: u8 {
+        /// 文件字节大小
+        const SIZE = 1 << 0;
+        /// 最后修改时间
+        const MODIFIED = 1 << 1;
+        /// 所有者用户 ID（仅 Unix）
+        const OWNER = 1 << 2;
+        /// 文本文件的行数（`--count-lines`，只对文件读取内容）
+        const LINES = 1 << 3;
+        /// 权限位（仅 Unix，`--perms`）
+        const MODE = 1 << 4;
+    }
 }
 
 /// 目录条目的排序字段。
@@ -42,27 +97,82 @@ pub enum SortField {
     Size,
     /// 按文件类型/扩展名排序
     Type,
+    /// 按修改时间排序（最近修改的在前）
+    MTime,
+    /// 按名称排序，名称中的数字按数值比较（`img2` 在 `img10` 之前），见 [`natural_cmp`]
+    Natural,
+}
+
+/// 自然顺序比较两个名称：按数字与非数字切成若干段逐段比较，两边都是数字的段
+/// 按数值比较（不限位数），其余按字符比较。
+///
+/// 数值相等而前导零不同（`a01` 与 `a1`）或完全相等时，退回到普通的字符串比较，
+/// 保证结果是全序。
+pub fn natural_cmp(a: &str, b: &str) -> std::cmp::Ordering {
+    use std::cmp::Ordering;
+
+    let (mut rest_a, mut rest_b) = (a, b);
+    loop {
+        let (chunk_a, tail_a) = split_chunk(rest_a);
+        let (chunk_b, tail_b) = split_chunk(rest_b);
+        let is_digits = |s: &str| s.starts_with(|c: char| c.is_ascii_digit());
+        let order = match (chunk_a.is_empty(), chunk_b.is_empty()) {
+            (true, true) => return a.cmp(b),
+            (true, false) => return Ordering::Less,
+            (false, true) => return Ordering::Greater,
+            _ if is_digits(chunk_a) && is_digits(chunk_b) => {
+                let num_a = chunk_a.trim_start_matches('0');
+                let num_b = chunk_b.trim_start_matches('0');
+                num_a.len().cmp(&num_b.len()).then_with(|| num_a.cmp(num_b))
+            }
+            _ => chunk_a.cmp(chunk_b),
+        };
+        if order != Ordering::Equal {
+            return order;
+        }
+        rest_a = tail_a;
+        rest_b = tail_b;
+    }
+}
+
+/// 取出 `s` 开头的一段连续数字或连续非数字，返回该段与剩余部分。
+fn split_chunk(s: &str) -> (&str, &str) {
+    let digits = s.starts_with(|c: char| c.is_ascii_digit());
+    let end = s
+        .find(|c: char| c.is_ascii_digit() != digits)
+        .unwrap_or(s.len());
+    s.split_at(end)
 }
 
 impl Default for WalkConfig {
     fn default() -> Self {
         Self {
             max_depth: 0, // 不限
+            min_depth: 0,
             show_hidden: false,
             follow_symlinks: false,
             sort_by: SortField::Name,
             reverse: false,
+            dirs_first: true,
             filter: FilterConfig::default(),
-            need_size: true,
+            metadata: MetadataFields::SIZE,
+            allow_file_root: true,
+            mark_followed_symlinks: false,
+            threads: 1,
+            max_entries_per_dir: 0,
+            summarize_truncated: false,
+            same_device: None,
         }
     }
 }
 
 /// 遍历一个目录并构建完整的内存文件树。
 ///
+/// 若 `path` 是文件且 `config.allow_file_root` 为真，则返回只含该文件的单节点树。
+///
 /// # 错误
 ///
-/// 如果路径不存在、不是目录，或在根节点上权限被拒绝，则返回 `TreeError`。
+/// 如果路径不存在、不是目录（且不允许文件根），或在根节点上权限被拒绝，则返回 `TreeError`。
 pub fn walk_directory(
     path: &Path,
     config: &WalkConfig,
@@ -73,28 +183,65 @@ pub fn walk_directory(
     }
 
     let meta = std::fs::metadata(path)?;
-    if !meta.is_dir() {
-        return Err(TreeError::NotADirectory(path.to_path_buf()));
-    }
-
     let root_name = path
         .file_name()
         .and_then(|n| n.to_str())
         .unwrap_or(".")
         .to_string();
 
+    if !meta.is_dir() {
+        if !config.allow_file_root {
+            return Err(TreeError::NotADirectory(path.to_path_buf()));
+        }
+        let size = if config.metadata.contains(MetadataFields::SIZE) {
+            meta.len()
+        } else {
+            0
+        };
+        let mut root = FsNode::new(root_name, path.to_path_buf(), FsNodeType::File, size, 0);
+        if config.metadata.contains(MetadataFields::MODIFIED) {
+            root.modified = meta.modified().ok();
+        }
+        if config.metadata.contains(MetadataFields::OWNER) {
+            root.uid = owner_uid(&meta);
+        }
+        if config.metadata.contains(MetadataFields::MODE) {
+            root.mode = file_mode(&meta);
+        }
+        if config.metadata.contains(MetadataFields::LINES) {
+            root.line_count = count_lines(path);
+        }
+        return Ok(FsTree::new(root, 0));
+    }
+
+    // 扫描根下的 .treeignore 在遍历开始前并入排除模式。
+    let config = &*with_treeignore(path, config)?;
+
+    let mut root = FsNode::new_directory(root_name, path.to_path_buf(), 0, Vec::new());
+    if config.metadata.contains(MetadataFields::MODIFIED) {
+        root.modified = meta.modified().ok();
+    }
+    if config.metadata.contains(MetadataFields::OWNER) {
+        root.uid = owner_uid(&meta);
+    }
+    if config.metadata.contains(MetadataFields::MODE) {
+        root.mode = file_mode(&meta);
+    }
+
+    // 每目录条目上限需要把略去的数目记到父目录上，只有逐目录构建的并行路径
+    // 能做到；threads == 1 时它在单线程池中运行，结果相同。
+    if config.threads != 1 || config.max_entries_per_dir > 0 {
+        return walk_parallel(path, root, config, progress)
+            .and_then(|tree| finish_walk(tree, config));
+    }
+
     // 打开目录的栈帧栈；stack[0] 始终是根节点。一个栈帧在被弹出时会挂接到
     // 其父节点上，而弹出恰好发生在下一个兄弟节点（或叔伯节点）到达时——
     // 从而保持流（已排序）的顺序。
-    let mut stack: Vec<FsNode> = vec![FsNode::new_directory(
-        root_name,
-        path.to_path_buf(),
-        0,
-        Vec::new(),
-    )];
+    let mut stack: Vec<FsNode> = vec![root];
     let mut max_depth = 0usize;
 
-    walk_core(path, config, |node| {
+    let filtered = walk_core(path, config, |node| {
         if node.depth > max_depth {
             max_depth = node.depth;
         }
@@ -105,27 +252,11 @@ pub fn walk_directory(
             attach(&mut stack, finished);
         }
 
-        match node.node_type {
-            FsNodeType::Directory => {
-                stack.push(FsNode::new_directory(
-                    node.name.clone(),
-                    node.path.clone(),
-                    node.depth,
-                    Vec::new(),
-                ));
-            }
-            _ => {
-                let leaf = FsNode::new(
-                    node.name.clone(),
-                    node.path.clone(),
-                    node.node_type.clone(),
-                    node.size,
-                    node.depth,
-                );
-                if let Some(parent) = stack.last_mut() {
-                    parent.children.get_or_insert_with(Vec::new).push(leaf);
-                }
-            }
+        let built = node_from_stream(node);
+        if built.is_directory() {
+            stack.push(built);
+        } else if let Some(parent) = stack.last_mut() {
+            parent.children.get_or_insert_with(Vec::new).push(built);
         }
 
         // 真实进度：每个节点计数加一，目录节点更新当前路径消息。
@@ -146,7 +277,204 @@ pub fn walk_directory(
     let mut root = stack.pop().unwrap();
     normalize_empty_children(&mut root);
 
-    Ok(FsTree::new(root, max_depth))
+    let mut tree = FsTree::new(root, max_depth);
+    tree.filtered = filtered;
+    finish_walk(tree, config)
+}
+
+/// 两条遍历路径共用的收尾：汇总未展开的目录（按需），再应用 `--min-depth`。
+fn finish_walk(mut tree: FsTree, config: &WalkConfig) -> Result<FsTree, TreeError> {
+    if config.summarize_truncated {
+        summarize_truncated_dirs(&mut tree.root, config)?;
+    }
+    Ok(apply_min_depth(tree, config.min_depth))
+}
+
+/// `--min-depth`：丢弃深度小于 `min_depth` 的节点，把深度恰为 `min_depth` 的节点
+/// （连同子树）按先序提升为根的直接子节点。
+///
+/// 被提升的节点以相对扫描根的路径（如 `src/core`）为名，以免不同父目录下的同名
+/// 条目无法区分；其子树的深度整体上移，使第一层从深度 1 开始。`min_depth` 不大于 1
+/// 时原样返回。
+pub fn apply_min_depth(mut tree: FsTree, min_depth: usize) -> FsTree {
+    if min_depth <= 1 {
+        return tree;
+    }
+
+    let mut hoisted = Vec::new();
+    for child in tree.root.children.take().into_iter().flatten() {
+        collect_at_depth(child, "", min_depth, &mut hoisted);
+    }
+    for node in &mut hoisted {
+        shift_depth(node, min_depth - 1);
+    }
+    tree.root.omitted = 0;
+    tree.root.children = Some(hoisted);
+    normalize_empty_children(&mut tree.root);
+    tree.max_depth = deepest(&tree.root);
+    tree
+}
+
+/// 先序收集 `node` 子树中深度为 `min_depth` 的节点；`prefix` 为父节点的相对路径。
+fn collect_at_depth(mut node: FsNode, prefix: &str, min_depth: usize, out: &mut Vec<FsNode>) {
+    let name = if prefix.is_empty() {
+        node.name.clone()
+    } else {
+        format!("{}/{}", prefix, node.name)
+    };
+    if node.depth >= min_depth {
+        node.name = name;
+        out.push(node);
+        return;
+    }
+    for child in node.children.take().into_iter().flatten() {
+        collect_at_depth(child, &name, min_depth, out);
+    }
+}
+
+/// 将子树中每个节点的深度减去 `by`。
+fn shift_depth(node: &mut FsNode, by: usize) {
+    node.depth -= by;
+    for child in node.children.iter_mut().flatten() {
+        shift_depth(child, by);
+    }
+}
+
+/// 扫描根下有 `.treeignore` 时，返回载入了其中模式的配置副本；否则原样借用。
+pub(crate) fn with_treeignore<'a>(
+    root: &Path,
+    config: &'a WalkConfig,
+) -> Result<Cow<'a, WalkConfig>, TreeError> {
+    if !root.join(TREEIGNORE_FILE).is_file() {
+        return Ok(Cow::Borrowed(config));
+    }
+    let mut config = config.clone();
+    config
+        .filter
+        .load_treeignore(root)
+        .map_err(TreeError::Other)?;
+    Ok(Cow::Owned(config))
+}
+
+/// 由遍历核心输出的节点构建 `FsNode`（目录的子节点列表为空，稍后填充）。
+///
+/// 顺序与并行两条构建路径都经由这里，保证得到的节点逐字段一致。
+fn node_from_stream(node: &StreamNode) -> FsNode {
+    let mut built = if node.node_type == FsNodeType::Directory {
+        let mut dir =
+            FsNode::new_directory(node.name.clone(), node.path.clone(), node.depth, Vec::new());
+        dir.is_loop = node.is_loop;
+        dir.truncated = node.truncated;
+        dir
+    } else {
+        FsNode::new(
+            node.name.clone(),
+            node.path.clone(),
+            node.node_type.clone(),
+            node.size,
+            node.depth,
+        )
+    };
+    built.link_target = node.link_target.clone();
+    built.modified = node.modified;
+    built.uid = node.uid;
+    built.mode = node.mode;
+    built.line_count = node.line_count;
+    built
+}
+
+/// 并行构建整棵树：同一目录下的各子目录在 rayon 线程池中并行展开。
+///
+/// 每个目录的读取、过滤与排序与顺序路径共用 `read_children`，`par_iter` 的
+/// `collect` 保持原有顺序，因此结果与 `threads == 1` 时完全相同。
+fn walk_parallel(
+    path: &Path,
+    mut root: FsNode,
+    config: &WalkConfig,
+    progress: Option<&indicatif::ProgressBar>,
+) -> Result<FsTree, TreeError> {
+    let mut ancestors = Vec::new();
+    if config.follow_symlinks {
+        ancestors.push(std::fs::canonicalize(path)?);
+    }
+
+    // 工作线程经共享的报告器推进进度（克隆的 ProgressBar 指向同一进度条）。
+    let reporter = ProgressReporter::new(progress.cloned());
+    let build = || build_children_parallel(path, path, 1, config, &ancestors, &reporter);
+    let (children, omitted, filtered) = if config.threads == 0 {
+        build()
+    } else {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(config.threads)
+            .build()
+            .map_err(|e| TreeError::Other(e.to_string()))?
+            .install(build)
+    };
+
+    root.children = Some(children);
+    root.omitted = omitted;
+    normalize_empty_children(&mut root);
+    let max_depth = deepest(&root);
+    let mut tree = FsTree::new(root, max_depth);
+    tree.filtered = filtered;
+    Ok(tree)
+}
+
+/// 并行地构建 `dir` 的子节点（各自带完整子树），并返回 `dir` 中因条目上限被略去的
+/// 子节点数，以及整棵子树中被过滤器排除的条目数。
+fn build_children_parallel(
+    root: &Path,
+    dir: &Path,
+    depth: usize,
+    config: &WalkConfig,
+    ancestors: &[PathBuf],
+    progress: &ProgressReporter,
+) -> (Vec<FsNode>, usize, FilteredCounts) {
+    let (children, excluded, omitted) = read_children(root, dir, depth, config, ancestors);
+    let (nodes, nested): (Vec<FsNode>, Vec<FilteredCounts>) = children
+        .into_par_iter()
+        .map(|child| {
+            let mut built = node_from_stream(&child.node);
+
+            progress.increment();
+            if built.is_directory() {
+                progress.set_message(&child.node.path.display().to_string());
+            }
+
+            let mut filtered = FilteredCounts::default();
+            if child.should_descend() {
+                let mut chain = ancestors.to_vec();
+                chain.extend(child.canonical);
+                let (grandchildren, omitted, nested) = build_children_parallel(
+                    root,
+                    &child.node.path,
+                    depth + 1,
+                    config,
+                    &chain,
+                    progress,
+                );
+                built.children = Some(grandchildren);
+                built.omitted = omitted;
+                filtered = nested;
+            }
+            // 未深入的目录（循环、深度截断）同样要归一为 None，与顺序路径一致。
+            normalize_empty_children(&mut built);
+            (built, filtered)
+        })
+        .unzip();
+
+    (nodes, omitted, excluded + nested.into_iter().sum())
+}
+
+/// 子树中节点的最大深度。
+fn deepest(node: &FsNode) -> usize {
+    node.children
+        .iter()
+        .flatten()
+        .map(deepest)
+        .max()
+        .unwrap_or(node.depth)
+        .max(node.depth)
 }
 
 /// 将一个已完成的节点挂接到其父节点（当前栈顶）上。
@@ -166,3 +494,162 @@ fn normalize_empty_children(node: &mut FsNode) {
         }
     }
 }
+
+/// 仅保留含有匹配文件的目录。
+///
+/// 自底向上剪枝：一个目录当且仅当其子树中存在文件名匹配 `pattern` 的文件时保留；
+/// 其余目录以及所有非目录节点都会被移除，只留下这些目录及其祖先。根节点总是保留。
+///
+/// 返回根节点的子树中是否存在匹配文件。
+pub fn retain_dirs_containing(root: &mut FsNode, pattern: &glob::Pattern) -> bool {
+    let found = prune_dirs_recursive(root, pattern);
+    normalize_empty_children(root);
+    found
+}
+
+/// 为因深度限制未展开的目录统计其完整子树的文件数与文件总大小，写入
+/// `collapsed_summary`（`--collapse-depth`、`--du`）。
+///
+/// 这样 `--du` 与 `-d` 同用时，被截断的目录仍计入真实占用。统计沿用 `config`
+/// 的过滤规则但不限深度；子树只被流式遍历一次，不会存入树中。
+pub fn summarize_truncated_dirs(node: &mut FsNode, config: &WalkConfig) -> Result<(), TreeError> {
+    if node.truncated {
+        if let Some(path) = node.path.clone() {
+            let mut unlimited = config.clone();
+            unlimited.max_depth = 0;
+            unlimited.metadata |= MetadataFields::SIZE;
+
+            let mut summary = SubtreeSummary::default();
+            walk_core(&path, &unlimited, |child| {
+                if child.node_type == FsNodeType::File {
+                    summary.files += 1;
+                    summary.size += child.size;
+                }
+            })?;
+            node.collapsed_summary = Some(summary);
+        }
+        return Ok(());
+    }
+
+    for child in node.children.iter_mut().flatten() {
+        if child.is_directory() {
+            summarize_truncated_dirs(child, config)?;
+        }
+    }
+    Ok(())
+}
+
+/// 仅保留给定的路径（相对根节点）及其祖先目录。
+///
+/// 与 [`retain_dirs_containing`] 相同的自底向上剪枝：不在 `keep` 中的文件与链接被移除，
+/// 剪枝后为空的目录也被移除。根节点总是保留。
+///
+/// 返回是否有任何节点被保留。
+pub fn retain_paths(root: &mut FsNode, keep: &HashSet<PathBuf>) -> bool {
+    let base = root.path.clone().unwrap_or_default();
+    let found = retain_paths_recursive(root, &base, keep);
+    normalize_empty_children(root);
+    found
+}
+
+/// 递归剪枝；返回 `node` 的子树中是否有被保留的节点。
+fn retain_paths_recursive(node: &mut FsNode, base: &Path, keep: &HashSet<PathBuf>) -> bool {
+    let Some(children) = node.children.take() else {
+        return false;
+    };
+
+    let mut kept = Vec::new();
+    for mut child in children {
+        let keep_child = match child.node_type {
+            FsNodeType::Directory => {
+                let found = retain_paths_recursive(&mut child, base, keep);
+                normalize_empty_children(&mut child);
+                found
+            }
+            FsNodeType::File | FsNodeType::Symlink => child
+                .path
+                .as_deref()
+                .and_then(|p| p.strip_prefix(base).ok())
+                .is_some_and(|rel| keep.contains(rel)),
+        };
+        if keep_child {
+            kept.push(child);
+        }
+    }
+
+    let found = !kept.is_empty();
+    node.children = Some(kept);
+    found
+}
+
+/// 仅保留匹配包含模式的文件及通向它们的目录（遍历后的剪枝）。
+///
+/// 与遍历时的 `--include-only` 相同，文件名或路径匹配 `pattern` 的文件与符号链接
+/// 保留，其余的移除；与之不同的是，子树中没有任何匹配的目录也会被移除，而不是作为
+/// 空目录留在树中。因深度限制未展开的目录与根节点总是保留。
+///
+/// 返回是否有任何匹配的节点被保留。
+pub fn prune_to_matches(tree: &mut FsTree, pattern: &glob::Pattern) -> bool {
+    let found = prune_to_matches_recursive(&mut tree.root, pattern);
+    normalize_empty_children(&mut tree.root);
+    found
+}
+
+/// 递归剪枝；返回 `node` 的子树中是否有匹配的文件。
+fn prune_to_matches_recursive(node: &mut FsNode, pattern: &glob::Pattern) -> bool {
+    let Some(children) = node.children.take() else {
+        return false;
+    };
+
+    let mut kept = Vec::new();
+    for mut child in children {
+        let keep_child = match child.node_type {
+            FsNodeType::Directory => {
+                // 因深度限制未展开的目录内容未知，保留。
+                let found = prune_to_matches_recursive(&mut child, pattern);
+                normalize_empty_children(&mut child);
+                found || child.truncated
+            }
+            FsNodeType::File | FsNodeType::Symlink => {
+                pattern.matches(&child.name)
+                    || child
+                        .path
+                        .as_deref()
+                        .is_some_and(|p| pattern.matches_path(p))
+            }
+        };
+        if keep_child {
+            kept.push(child);
+        }
+    }
+
+    let found = !kept.is_empty();
+    node.children = Some(kept);
+    found
+}
+
+/// 递归剪枝；返回 `node` 的子树中是否存在匹配文件。
+fn prune_dirs_recursive(node: &mut FsNode, pattern: &glob::Pattern) -> bool {
+    let Some(children) = node.children.take() else {
+        return false;
+    };
+
+    let mut found = false;
+    let mut kept = Vec::new();
+    for mut child in children {
+        match child.node_type {
+            FsNodeType::File => found |= pattern.matches(&child.name),
+            FsNodeType::Directory => {
+                if prune_dirs_recursive(&mut child, pattern) {
+                    found = true;
+                    normalize_empty_children(&mut child);
+                    kept.push(child);
+                }
+            }
+            FsNodeType::Symlink => {}
+        }
+    }
+
+    node.children = Some(kept);
+    found
+}
@@ -0,0 +1,101 @@
+//! 树差异的输出格式化器。
+
+use crate::core::diff::{DiffEntry, DiffKind};
+use crate::core::models::StatsDelta;
+use crate::formatters::size::{format_size, SizeOptions};
+
+/// 将差异列表格式化为逐行文本，并在末尾附上汇总行。
+///
+/// 每行以 `+`（新增）、`-`（删除）或 `~`（变更）开头，后跟相对路径与大小：
+///
+/// ```text
+/// + src/new.rs (120 B)
+/// - old.txt (3 B)
+/// ~ lib.rs (1 kB -> 2 kB)
+///
+/// 1 added, 1 removed, 1 changed
+/// ```
+///
+/// 大小按 `size` 格式化（`--size-unit` 等）。
+pub fn format_diff(entries: &[DiffEntry], size: &SizeOptions) -> String {
+    if entries.is_empty() {
+        return "No differences\n".to_string();
+    }
+
+    let size = |bytes: Option<u64>| format_size(bytes.unwrap_or(0), size);
+    let mut output = String::new();
+    let (mut added, mut removed, mut changed) = (0, 0, 0);
+
+    for entry in entries {
+        let path = entry.path.display();
+        let line = match entry.kind {
+            DiffKind::Added => {
+                added += 1;
+                format!("+ {} ({})", path, size(entry.new_size))
+            }
+            DiffKind::Removed => {
+                removed += 1;
+                format!("- {} ({})", path, size(entry.old_size))
+            }
+            DiffKind::Changed => {
+                changed += 1;
+                format!(
+                    "~ {} ({} -> {})",
+                    path,
+                    size(entry.old_size),
+                    size(entry.new_size)
+                )
+            }
+        };
+        output.push_str(&line);
+        output.push('\n');
+    }
+
+    output.push_str(&format!(
+        "\n{} added, {} removed, {} changed\n",
+        added, removed, changed
+    ));
+    output
+}
+
+/// `--compare-stats` 的报告：每项一行，带符号的变化量，例如
+///
+/// ```text
+/// changes since baseline:
+///   total_files +12
+///   total_directories +1
+///   total_symlinks +0
+///   total_size +4.2 MB
+///   .rs +3 files, +12 kB
+/// ```
+///
+/// 扩展名只列出有变化的，顺序与 [`StatsDelta::extensions`] 相同。
+pub fn format_stats_delta(delta: &StatsDelta, size: &SizeOptions) -> String {
+    let signed_size = |bytes: i64| {
+        let sign = if bytes < 0 { '-' } else { '+' };
+        format!("{}{}", sign, format_size(bytes.unsigned_abs(), size))
+    };
+
+    let mut output = String::from("changes since baseline:");
+    output.push_str(&format!("\n  total_files {:+}", delta.total_files));
+    output.push_str(&format!(
+        "\n  total_directories {:+}",
+        delta.total_directories
+    ));
+    output.push_str(&format!("\n  total_symlinks {:+}", delta.total_symlinks));
+    output.push_str(&format!("\n  total_size {}", signed_size(delta.total_size)));
+    for ext in &delta.extensions {
+        output.push_str(&format!(
+            "\n  {} {:+} {}, {}",
+            ext.extension,
+            ext.count,
+            if ext.count.abs() == 1 {
+                "file"
+            } else {
+                "files"
+            },
+            signed_size(ext.size)
+        ));
+    }
+    output
+}
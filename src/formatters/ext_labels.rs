@@ -0,0 +1,115 @@
+//! 扩展名的友好名称（统计表中显示为 `Rust (.rs)`）。
+//!
+//! 内置一份常见类型的映射；`--ext-label EXT=LABEL`（配置文件中写作
+//! `ext-label = ["ipynb=Jupyter Notebook"]`）可以补充或覆盖其中的条目，
+//! `--no-ext-labels` 关闭内置映射。原始扩展名始终保留在括号中。
+
+use std::collections::HashMap;
+
+/// 内置的扩展名（小写、不含点号）到友好名称的映射。
+const BUILTIN_LABELS: &[(&str, &str)] = &[
+    ("rs", "Rust"),
+    ("py", "Python"),
+    ("ipynb", "Jupyter Notebook"),
+    ("js", "JavaScript"),
+    ("mjs", "JavaScript"),
+    ("ts", "TypeScript"),
+    ("tsx", "TypeScript JSX"),
+    ("jsx", "JavaScript JSX"),
+    ("go", "Go"),
+    ("java", "Java"),
+    ("kt", "Kotlin"),
+    ("swift", "Swift"),
+    ("c", "C"),
+    ("h", "C Header"),
+    ("cpp", "C++"),
+    ("cc", "C++"),
+    ("hpp", "C++ Header"),
+    ("cs", "C#"),
+    ("rb", "Ruby"),
+    ("php", "PHP"),
+    ("sh", "Shell Script"),
+    ("sql", "SQL"),
+    ("html", "HTML"),
+    ("css", "CSS"),
+    ("scss", "Sass"),
+    ("md", "Markdown"),
+    ("txt", "Plain Text"),
+    ("json", "JSON"),
+    ("toml", "TOML"),
+    ("yaml", "YAML"),
+    ("yml", "YAML"),
+    ("xml", "XML"),
+    ("csv", "CSV"),
+    ("lock", "Lockfile"),
+    ("pdf", "PDF Document"),
+    ("png", "PNG Image"),
+    ("jpg", "JPEG Image"),
+    ("jpeg", "JPEG Image"),
+    ("gif", "GIF Image"),
+    ("svg", "SVG Image"),
+    ("zip", "Zip Archive"),
+    ("gz", "Gzip Archive"),
+    ("tar", "Tar Archive"),
+];
+
+/// 扩展名到友好名称的映射：用户条目优先，其次是（可关闭的）内置映射。
+#[derive(Debug, Clone)]
+pub struct ExtensionLabels {
+    /// 用户给出的映射，键为小写、不含点号的扩展名
+    custom: HashMap<String, String>,
+    /// 是否回退到内置映射
+    builtin: bool,
+}
+
+impl Default for ExtensionLabels {
+    fn default() -> Self {
+        Self::new(true)
+    }
+}
+
+impl ExtensionLabels {
+    /// 创建只含用户条目的映射；`builtin` 为 true 时未命中的扩展名回退到内置映射。
+    pub fn new(builtin: bool) -> Self {
+        Self {
+            custom: HashMap::new(),
+            builtin,
+        }
+    }
+
+    /// 添加或覆盖一个扩展名（`rs` 或 `.rs`，不区分大小写）的名称。
+    pub fn insert(&mut self, extension: &str, label: impl Into<String>) {
+        self.custom.insert(normalize(extension), label.into());
+    }
+
+    /// `extension`（`rs` 或 `.rs`）的友好名称；没有映射时返回 `None`。
+    pub fn label(&self, extension: &str) -> Option<&str> {
+        let key = normalize(extension);
+        if let Some(label) = self.custom.get(&key) {
+            return Some(label);
+        }
+        if !self.builtin {
+            return None;
+        }
+        BUILTIN_LABELS
+            .iter()
+            .find(|&&(ext, _)| ext == key)
+            .map(|&(_, label)| label)
+    }
+
+    /// 统计表中显示的文本：有映射时为 `Rust (.rs)`，否则原样返回。
+    pub fn display(&self, extension: &str) -> String {
+        match self.label(extension) {
+            Some(label) => format!("{} ({})", label, extension),
+            None => extension.to_string(),
+        }
+    }
+}
+
+/// 去掉前导点号并转为小写。
+fn normalize(extension: &str) -> String {
+    extension
+        .strip_prefix('.')
+        .unwrap_or(extension)
+        .to_lowercase()
+}
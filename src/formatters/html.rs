@@ -0,0 +1,233 @@
+//! 自包含的 HTML 输出格式化器。
+//!
+//! 生成单个 HTML 文件：顶部是统计表，下方是以嵌套 `<details>` / `<summary>`
+//! 表示的树，目录可在浏览器中折叠。样式全部内联，不引用任何外部资源；文件名
+//! 按扩展配色方案（`ColorScheme::Extended`）的类别着色。
+
+use crate::config::color::{file_color_for, file_colors, FileColor};
+use crate::config::ColorScheme;
+use crate::core::models::{FsNode, FsTree, TreeStats};
+use crate::core::walker::MetadataFields;
+use crate::formatters::size::{format_size, SizeOptions};
+use crate::formatters::tree::TRUNCATED_MARKER;
+use colored::Color;
+use std::fmt::Write;
+
+/// HTML 输出所需的元数据（统计信息与文件大小）。
+pub const REQUIRED_METADATA: MetadataFields = MetadataFields::SIZE;
+
+/// 内联样式表中与配色无关的部分。
+const BASE_CSS: &str = "\
+body { font-family: ui-monospace, SFMono-Regular, Menlo, Consolas, monospace; margin: 2em; }
+table { border-collapse: collapse; margin-bottom: 1.5em; }
+th, td { border: 1px solid #ccc; padding: 0.2em 0.8em; text-align: left; }
+td.num { text-align: right; }
+ul { list-style: none; margin: 0; padding-left: 1.5em; border-left: 1px dotted #bbb; }
+summary { cursor: pointer; }
+.dir { color: #1f4fbf; font-weight: bold; }
+.link { color: #0a8a8a; font-style: italic; }
+.size { color: #888; }
+.more { color: #888; }
+";
+
+/// 将文件树及其统计信息格式化为一个完整的 HTML 文档。
+///
+/// # 参数
+///
+/// * `tree` - 要格式化的文件系统树
+/// * `stats` - 显示在顶部的统计信息
+///
+/// # 返回
+///
+/// 不依赖外部资源的 HTML 字符串。
+pub fn format_html(tree: &FsTree, stats: &TreeStats) -> String {
+    format_html_with(tree, stats, &SizeOptions::default())
+}
+
+/// 与 [`format_html`] 相同，但按 `size` 格式化统计表与树中的大小（`--size-unit` 等）。
+pub fn format_html_with(tree: &FsTree, stats: &TreeStats, size: &SizeOptions) -> String {
+    let title = escape(&tree.root.name);
+    let mut html = String::new();
+
+    html.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    let _ = writeln!(html, "<title>{}</title>", title);
+    let _ = writeln!(html, "<style>\n{}{}</style>", BASE_CSS, category_css());
+    html.push_str("</head>\n<body>\n");
+    let _ = writeln!(html, "<h1>{}</h1>", title);
+
+    write_stats(&mut html, stats, size);
+
+    html.push_str("<div class=\"tree\">\n");
+    write_node(&mut html, &tree.root, true, size);
+    html.push_str("</div>\n</body>\n</html>\n");
+    html
+}
+
+/// 统计表：概览、按扩展名分组、最大文件。
+fn write_stats(html: &mut String, stats: &TreeStats, size: &SizeOptions) {
+    html.push_str("<table class=\"stats\">\n<tr><th colspan=\"2\">Statistics</th></tr>\n");
+    for (label, value) in [
+        ("Total Files", stats.total_files.to_string()),
+        ("Total Directories", stats.total_directories.to_string()),
+        ("Total Symlinks", stats.total_symlinks.to_string()),
+        ("Total Size", format_size(stats.total_size, size)),
+    ] {
+        let _ = writeln!(
+            html,
+            "<tr><td>{}</td><td class=\"num\">{}</td></tr>",
+            label, value
+        );
+    }
+    html.push_str("</table>\n");
+
+    if !stats.files_by_extension.is_empty() {
+        let mut extensions: Vec<_> = stats.files_by_extension.values().collect();
+        extensions.sort_by(|a, b| {
+            b.total_size
+                .cmp(&a.total_size)
+                .then_with(|| a.extension.cmp(&b.extension))
+        });
+
+        html.push_str(
+            "<table class=\"extensions\">\n<tr><th>Extension</th><th>Count</th>\
+             <th>Size</th><th>Percentage</th></tr>\n",
+        );
+        for info in extensions {
+            let _ = writeln!(
+                html,
+                "<tr><td>{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td>\
+                 <td class=\"num\">{:.1}%</td></tr>",
+                escape(&info.extension),
+                info.count,
+                format_size(info.total_size, size),
+                info.percentage
+            );
+        }
+        html.push_str("</table>\n");
+    }
+
+    if !stats.largest_files.is_empty() {
+        html.push_str("<table class=\"largest\">\n<tr><th>File</th><th>Size</th></tr>\n");
+        for file in &stats.largest_files {
+            let _ = writeln!(
+                html,
+                "<tr><td title=\"{}\">{}</td><td class=\"num\">{}</td></tr>",
+                escape(&file.path.to_string_lossy()),
+                escape(&file.name),
+                format_size(file.size, size)
+            );
+        }
+        html.push_str("</table>\n");
+    }
+}
+
+/// 递归地写出节点：目录为 `<details>`，文件与符号链接为普通列表项的内容。
+fn write_node(html: &mut String, node: &FsNode, is_root: bool, size: &SizeOptions) {
+    let name = escape(&node.name);
+
+    if !node.is_directory() {
+        if node.is_symlink() {
+            let target = node
+                .link_target
+                .clone()
+                .or_else(|| node.path.as_ref().and_then(|p| std::fs::read_link(p).ok()))
+                .map(|t| escape(&t.to_string_lossy()))
+                .unwrap_or_default();
+            let _ = write!(html, "<span class=\"link\">{} → {}</span>", name, target);
+        } else {
+            let class = file_color_for(&node.name, ColorScheme::Extended)
+                .map(|entry| format!(" class=\"{}\"", category_class(entry)))
+                .unwrap_or_default();
+            let _ = write!(html, "<span{}>{}</span>", class, name);
+            if node.size > 0 {
+                let _ = write!(
+                    html,
+                    " <span class=\"size\">({})</span>",
+                    format_size(node.size, size)
+                );
+            }
+        }
+        return;
+    }
+
+    // 根目录默认展开，其余目录默认折叠。
+    let open = if is_root { " open" } else { "" };
+    let _ = writeln!(
+        html,
+        "<details{}><summary class=\"dir\">{}/</summary>",
+        open, name
+    );
+    html.push_str("<ul>\n");
+    for child in node.children.iter().flatten() {
+        html.push_str("<li>");
+        write_node(html, child, false, size);
+        html.push_str("</li>\n");
+    }
+    if node.truncated {
+        let _ = writeln!(html, "<li class=\"more\">{}</li>", TRUNCATED_MARKER);
+    }
+    html.push_str("</ul>\n</details>\n");
+}
+
+/// 扩展配色方案中每个文件类别的 CSS 规则。
+fn category_css() -> String {
+    file_colors(ColorScheme::Extended)
+        .iter()
+        .map(|entry| {
+            format!(
+                ".{} {{ color: {}; }}\n",
+                category_class(entry),
+                css_color(entry.color)
+            )
+        })
+        .collect()
+}
+
+/// 类别对应的 CSS 类名，如 `cat-rust`、`cat-c-java`。
+fn category_class(entry: &FileColor) -> String {
+    let slug: String = entry
+        .category
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    format!("cat-{}", slug)
+}
+
+/// 终端颜色在浅色背景网页上的近似色。
+fn css_color(color: Color) -> String {
+    match color {
+        Color::Black => "#000000".to_string(),
+        Color::Red => "#b22222".to_string(),
+        Color::Green => "#2e7d32".to_string(),
+        Color::Yellow => "#9a7d00".to_string(),
+        Color::Blue => "#1f4fbf".to_string(),
+        Color::Magenta => "#8e24aa".to_string(),
+        Color::Cyan => "#00838f".to_string(),
+        Color::White => "#555555".to_string(),
+        Color::BrightBlack => "#888888".to_string(),
+        Color::BrightRed => "#e53935".to_string(),
+        Color::BrightGreen => "#43a047".to_string(),
+        Color::BrightYellow => "#c49000".to_string(),
+        Color::BrightBlue => "#1e88e5".to_string(),
+        Color::BrightMagenta => "#d81b60".to_string(),
+        Color::BrightCyan => "#00acc1".to_string(),
+        Color::BrightWhite => "#777777".to_string(),
+        Color::TrueColor { r, g, b } => format!("#{:02x}{:02x}{:02x}", r, g, b),
+    }
+}
+
+/// 转义 HTML 特殊字符。
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -1,7 +1,18 @@
 //! JSON 输出格式化器。
 
-use crate::core::models::{FsTree, TreeError, TreeStats};
-use serde_json::json;
+use crate::core::models::{
+    CategoryInfo, FileEntry, FileTypeInfo, FsNode, FsTree, GitRatio, StatsDelta, SubtreeSummary,
+    TreeError, TreeStats,
+};
+use crate::core::walker::MetadataFields;
+use schemars::{schema_for, JsonSchema};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// JSON 输出所需的元数据（统计信息依赖文件大小）。
+pub const REQUIRED_METADATA: MetadataFields = MetadataFields::SIZE;
 
 /// 将文件树及其统计信息格式化为 JSON。
 ///
@@ -19,21 +30,36 @@ use serde_json::json;
 ///
 /// 如果序列化失败，返回 `TreeError::Json`。
 pub fn format_json(tree: &FsTree, stats: &TreeStats, pretty: bool) -> Result<String, TreeError> {
-    let output = json!({
-        "tree": {
-            "root": tree.root,
-            "max_depth": tree.max_depth
+    let document = JsonDocument {
+        tree: JsonTree {
+            root: node_to_json(&tree.root)?,
+            max_depth: tree.max_depth,
         },
-        "stats": {
-            "total_files": stats.total_files,
-            "total_directories": stats.total_directories,
-            "total_symlinks": stats.total_symlinks,
-            "total_size": stats.total_size,
-            "files_by_extension": stats.files_by_extension,
-            "largest_files": stats.largest_files,
-            "scan_duration_ms": stats.scan_duration.as_millis()
-        }
-    });
+        stats: JsonStats {
+            total_files: stats.total_files,
+            total_directories: stats.total_directories,
+            total_symlinks: stats.total_symlinks,
+            total_size: stats.total_size,
+            files_by_extension: &stats.files_by_extension,
+            files_by_category: &stats.files_by_category,
+            largest_files: &stats.largest_files,
+            empty_files: stats.empty_files,
+            duplicates: stats.duplicates.as_deref(),
+            git_ratio: stats.git_ratio.as_ref(),
+            stats_delta: stats.stats_delta.as_ref(),
+            empty_directories: &stats.empty_directories,
+            omitted_entries: stats.omitted_entries,
+            deepest_path: stats.deepest_path.as_ref(),
+            total_lines: stats.total_lines,
+            average_file_size: stats.average_file_size,
+            median_file_size: stats.median_file_size,
+            leaf_depth_mean: stats.leaf_depth_mean,
+            leaf_depth_stddev: stats.leaf_depth_stddev,
+            scan_duration_ms: stats.scan_duration.as_millis(),
+        },
+    };
+    // 先转成 Value：其 Map 按键名排序，保持输出中键的顺序稳定。
+    let output = serde_json::to_value(document)?;
 
     if pretty {
         serde_json::to_string_pretty(&output).map_err(TreeError::from)
@@ -42,6 +68,73 @@ pub fn format_json(tree: &FsTree, stats: &TreeStats, pretty: bool) -> Result<Str
     }
 }
 
+/// [`format_json`] 的输出：`{"tree": ..., "stats": ...}`。
+///
+/// 输出与 [`json_schema`] 都由这组类型生成，二者不会脱节。
+#[derive(Serialize, JsonSchema)]
+struct JsonDocument<'a> {
+    tree: JsonTree,
+    stats: JsonStats<'a>,
+}
+
+/// 树部分：根节点（目录额外带 `aggregate_size`）与最大深度。
+#[derive(Serialize, JsonSchema)]
+struct JsonTree {
+    #[schemars(with = "FsNode")]
+    root: Value,
+    max_depth: usize,
+}
+
+/// 统计部分：与 `TreeStats` 相同，但扫描耗时以毫秒整数给出。
+#[derive(Serialize, JsonSchema)]
+struct JsonStats<'a> {
+    total_files: usize,
+    total_directories: usize,
+    total_symlinks: usize,
+    total_size: u64,
+    files_by_extension: &'a HashMap<String, FileTypeInfo>,
+    files_by_category: &'a HashMap<String, CategoryInfo>,
+    largest_files: &'a [FileEntry],
+    empty_files: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    duplicates: Option<&'a [Vec<FileEntry>]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    git_ratio: Option<&'a GitRatio>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stats_delta: Option<&'a StatsDelta>,
+    empty_directories: &'a [PathBuf],
+    omitted_entries: usize,
+    deepest_path: Option<&'a (PathBuf, usize)>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    total_lines: Option<usize>,
+    average_file_size: f64,
+    median_file_size: u64,
+    leaf_depth_mean: f64,
+    leaf_depth_stddev: f64,
+    scan_duration_ms: u128,
+}
+
+/// 返回描述 [`format_json`] 输出的 JSON Schema（draft-07）。
+///
+/// Schema 由输出所用的 serde 类型（`FsNode`、`FileTypeInfo` 等）经 `schemars`
+/// 生成，而非手写；只额外补上序列化时为目录添加的 `aggregate_size`。
+pub fn json_schema() -> Value {
+    let mut schema =
+        serde_json::to_value(schema_for!(JsonDocument)).expect("a JSON Schema is valid JSON");
+
+    if let Some(properties) = schema
+        .pointer_mut("/definitions/FsNode/properties")
+        .and_then(Value::as_object_mut)
+    {
+        let mut aggregate =
+            serde_json::to_value(schema_for!(u64).schema).expect("a JSON Schema is valid JSON");
+        aggregate["description"] = Value::from("目录子树中所有文件大小之和（仅目录）");
+        properties.insert("aggregate_size".to_string(), aggregate);
+    }
+
+    schema
+}
+
 /// 仅将树结构格式化为 JSON（不含统计信息）。
 ///
 /// # 参数
@@ -57,11 +150,46 @@ pub fn format_json(tree: &FsTree, stats: &TreeStats, pretty: bool) -> Result<Str
 ///
 /// 如果序列化失败，返回 `TreeError::Json`。
 pub fn format_tree_only(tree: &FsTree, pretty: bool) -> Result<String, TreeError> {
+    let root = node_to_json(&tree.root)?;
     if pretty {
-        serde_json::to_string_pretty(&tree.root).map_err(TreeError::from)
+        serde_json::to_string_pretty(&root).map_err(TreeError::from)
     } else {
-        serde_json::to_string(&tree.root).map_err(TreeError::from)
+        serde_json::to_string(&root).map_err(TreeError::from)
+    }
+}
+
+/// 将节点序列化为 JSON，并为每个目录补充 `aggregate_size`。
+///
+/// 目录自身的 `size` 恒为 0；`aggregate_size` 是其所有后代文件大小之和（含因深度
+/// 限制未展开的子树），供构建 treemap 等场景使用。各目录的汇总由
+/// [`FsNode::subtree_summaries`] 一次算出，再按先序写入对应的 JSON 对象。
+fn node_to_json(node: &FsNode) -> Result<Value, TreeError> {
+    let mut value = serde_json::to_value(node)?;
+    let mut summaries = Vec::new();
+    node.subtree_summaries(&mut summaries);
+    insert_aggregate_sizes(node, &mut value, &mut summaries.iter());
+    Ok(value)
+}
+
+/// 按先序为 `node` 子树中每个目录对应的 JSON 对象写入 `aggregate_size`。
+fn insert_aggregate_sizes(
+    node: &FsNode,
+    value: &mut Value,
+    summaries: &mut std::slice::Iter<'_, SubtreeSummary>,
+) {
+    if !node.is_directory() {
+        return;
+    }
+    let total = summaries.next().map_or(0, |summary| summary.size);
+    let Some(obj) = value.as_object_mut() else {
+        return;
+    };
+    if let Some(children) = obj.get_mut("children").and_then(Value::as_array_mut) {
+        for (child, child_value) in node.children.iter().flatten().zip(children) {
+            insert_aggregate_sizes(child, child_value, summaries);
+        }
     }
+    obj.insert("aggregate_size".to_string(), Value::from(total));
 }
 
 /// 仅将统计信息格式化为 JSON。
@@ -0,0 +1,122 @@
+//! 归档清单输出格式化器（`-f manifest`）。
+//!
+//! 按遍历顺序（即树形输出中的顺序）每个文件一行，给出其在“把所有文件首尾相接”
+//! 时的起始字节偏移、字节大小与相对扫描根的路径，形似带偏移的 tar 目录：
+//!
+//! ```text
+//!    0  120  src/lib.rs
+//!  120   48  src/main.rs
+//!  168  900  README.md
+//! ```
+//!
+//! 偏移是此前所有文件大小的累加和；目录与符号链接不占用字节，不输出。
+
+use crate::core::models::FsNode;
+use crate::core::walker::MetadataFields;
+use std::io::{self, Write};
+
+/// 清单输出所需的元数据（偏移由文件大小累加得到）。
+pub const REQUIRED_METADATA: MetadataFields = MetadataFields::SIZE;
+
+/// 清单中的一个文件。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManifestEntry {
+    /// 相对扫描根、以 `/` 分隔的路径
+    pub path: String,
+    /// 此前所有文件大小之和
+    pub offset: u64,
+    /// 文件字节大小
+    pub size: u64,
+}
+
+/// 按遍历顺序收集文件，并为每个文件计算累计偏移。
+pub fn manifest_entries(root: &FsNode) -> Vec<ManifestEntry> {
+    let mut entries = Vec::new();
+    let mut offset = 0;
+    if root.is_file() {
+        push_entry(&mut entries, &mut offset, root.name.clone(), root.size);
+    }
+    for child in root.children.iter().flatten() {
+        collect_entries(child, "", &mut offset, &mut entries);
+    }
+    entries
+}
+
+/// 将文件树格式化为带偏移的清单。
+///
+/// # 参数
+///
+/// * `root` - 树的根节点（根目录本身不出现在路径中）
+///
+/// # 返回
+///
+/// 每个文件一行的字符串；偏移与大小两列右对齐。
+pub fn format_manifest(root: &FsNode) -> String {
+    let mut buffer = Vec::new();
+    format_manifest_to(root, &mut buffer).expect("writing to a Vec<u8> cannot fail");
+    String::from_utf8(buffer).expect("manifest output is valid UTF-8")
+}
+
+/// 将清单逐行写入 `writer`；输出与 [`format_manifest`] 一致。
+///
+/// # 错误
+///
+/// 返回 `writer` 产生的 IO 错误。
+pub fn format_manifest_to<W: Write>(root: &FsNode, writer: &mut W) -> io::Result<()> {
+    let entries = manifest_entries(root);
+    let offset_width = entries
+        .iter()
+        .map(|e| e.offset.to_string().len())
+        .max()
+        .unwrap_or(0);
+    let size_width = entries
+        .iter()
+        .map(|e| e.size.to_string().len())
+        .max()
+        .unwrap_or(0);
+
+    for entry in &entries {
+        writeln!(
+            writer,
+            "{:>ow$}  {:>sw$}  {}",
+            entry.offset,
+            entry.size,
+            entry.path,
+            ow = offset_width,
+            sw = size_width
+        )?;
+    }
+    Ok(())
+}
+
+/// 先序收集 `node` 子树中的文件；`prefix` 为父目录的相对路径（根的子节点为空）。
+fn collect_entries(
+    node: &FsNode,
+    prefix: &str,
+    offset: &mut u64,
+    entries: &mut Vec<ManifestEntry>,
+) {
+    let path = if prefix.is_empty() {
+        node.name.clone()
+    } else {
+        format!("{}/{}", prefix, node.name)
+    };
+
+    if node.is_file() {
+        push_entry(entries, offset, path, node.size);
+        return;
+    }
+    for child in node.children.iter().flatten() {
+        collect_entries(child, &path, offset, entries);
+    }
+}
+
+/// 追加一个文件并推进累计偏移。
+fn push_entry(entries: &mut Vec<ManifestEntry>, offset: &mut u64, path: String, size: u64) {
+    entries.push(ManifestEntry {
+        path,
+        offset: *offset,
+        size,
+    });
+    *offset += size;
+}
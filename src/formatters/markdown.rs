@@ -0,0 +1,109 @@
+//! GitHub 风格 Markdown 输出格式化器。
+//!
+//! 输出嵌套的无序列表，每层缩进两个空格，便于直接嵌入文档：
+//!
+//! ```text
+//! - **root/**
+//!   - **src/**
+//!     - main.rs `42 B`
+//!   - link → target
+//! ```
+
+use crate::core::models::FsNode;
+use crate::formatters::size::{format_size, SizeOptions};
+use crate::formatters::tree::TRUNCATED_MARKER;
+use std::io::{self, Write};
+
+/// 将文件树格式化为 Markdown 嵌套列表（不显示大小）。
+pub fn format_markdown(node: &FsNode) -> String {
+    format_markdown_with(node, false, &SizeOptions::default())
+}
+
+/// 将文件树格式化为 Markdown 嵌套列表；`show_size` 时文件后附带按 `size` 格式化的
+/// `` `大小` ``。
+pub fn format_markdown_with(node: &FsNode, show_size: bool, size: &SizeOptions) -> String {
+    let mut buffer = Vec::new();
+    format_markdown_to(node, show_size, size, &mut buffer)
+        .expect("writing to a Vec<u8> cannot fail");
+    String::from_utf8(buffer).expect("markdown output is valid UTF-8")
+}
+
+/// 将 Markdown 列表逐行写入 `writer`；输出与 [`format_markdown_with`] 一致。
+///
+/// # 错误
+///
+/// 返回 `writer` 产生的 IO 错误。
+pub fn format_markdown_to<W: Write>(
+    node: &FsNode,
+    show_size: bool,
+    size: &SizeOptions,
+    writer: &mut W,
+) -> io::Result<()> {
+    format_markdown_recursive(node, 0, show_size, size, writer)
+}
+
+/// 递归地输出节点及其子节点。
+fn format_markdown_recursive<W: Write>(
+    node: &FsNode,
+    depth: usize,
+    show_size: bool,
+    size: &SizeOptions,
+    writer: &mut W,
+) -> io::Result<()> {
+    let indent = "  ".repeat(depth);
+    writeln!(writer, "{}- {}", indent, item_text(node, show_size, size))?;
+
+    // 因深度限制未展开的非空目录，与树形输出一样补一个占位项。
+    if node.truncated {
+        writeln!(writer, "{}  - {}", indent, TRUNCATED_MARKER)?;
+    }
+
+    if let Some(children) = &node.children {
+        for child in children {
+            format_markdown_recursive(child, depth + 1, show_size, size, writer)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// 列表项文本：目录为 `**name/**`，符号链接为 `name → target`，文件为名称加可选大小。
+fn item_text(node: &FsNode, show_size: bool, size: &SizeOptions) -> String {
+    let name = escape(&node.name);
+
+    if node.is_directory() {
+        return format!("**{}/**", name);
+    }
+
+    let target = node.link_target.clone().or_else(|| {
+        if node.is_symlink() {
+            node.path.as_ref().and_then(|p| std::fs::read_link(p).ok())
+        } else {
+            None
+        }
+    });
+    if let Some(target) = target {
+        return format!("{} → {}", name, escape(&target.to_string_lossy()));
+    }
+
+    if show_size && node.size > 0 {
+        format!("{} `{}`", name, format_size(node.size, size))
+    } else {
+        name
+    }
+}
+
+/// 转义会被 Markdown 解释为格式的字符，使名称按原样显示。
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(
+            c,
+            '\\' | '*' | '_' | '`' | '[' | ']' | '<' | '>' | '#' | '|'
+        ) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
@@ -1,10 +1,31 @@
 //! 不同显示格式的输出格式化器。
 
+pub mod csv;
+pub mod diff;
+pub mod ext_labels;
+pub mod html;
 pub mod json;
+pub mod manifest;
+pub mod markdown;
+pub mod names;
+pub mod ndjson;
+pub mod number;
+pub mod outline;
+pub mod prometheus;
+pub mod size;
 pub mod streaming_tree;
 pub mod table;
 pub mod tree;
+pub mod width;
 
+pub use csv::format_csv;
+pub use diff::format_diff;
+pub use html::format_html;
 pub use json::format_json;
+pub use manifest::format_manifest;
+pub use markdown::format_markdown;
+pub use names::format_names;
+pub use outline::format_outline;
+pub use prometheus::format_prometheus;
 pub use table::format_table;
-pub use tree::format_tree;
+pub use tree::{format_tree, format_tree_to, TreeCharset, TreeOptions};
@@ -0,0 +1,53 @@
+//! 缩进名称列表输出格式化器（`-f names`）。
+//!
+//! 每行只有节点名称，按深度缩进（每层两个空格），不含制表符或路径，
+//! 介于树形输出与完整路径之间，形似 YAML：
+//!
+//! ```text
+//! root
+//!   src
+//!     main.rs
+//! ```
+
+use crate::core::models::FsNode;
+use std::io::{self, Write};
+
+/// 每层深度的缩进。
+const INDENT: &str = "  ";
+
+/// 将文件树格式化为按深度缩进的名称列表。
+///
+/// # 参数
+///
+/// * `node` - 树的根节点（深度 0，不缩进）
+///
+/// # 返回
+///
+/// 每个节点一行的字符串。
+pub fn format_names(node: &FsNode) -> String {
+    let mut buffer = Vec::new();
+    format_names_to(node, &mut buffer).expect("writing to a Vec<u8> cannot fail");
+    String::from_utf8(buffer).expect("names output is valid UTF-8")
+}
+
+/// 将名称列表逐行写入 `writer`；输出与 [`format_names`] 一致。
+///
+/// # 错误
+///
+/// 返回 `writer` 产生的 IO 错误。
+pub fn format_names_to<W: Write>(node: &FsNode, writer: &mut W) -> io::Result<()> {
+    format_names_recursive(node, 0, writer)
+}
+
+/// 递归地输出节点及其子节点。
+fn format_names_recursive<W: Write>(node: &FsNode, depth: usize, writer: &mut W) -> io::Result<()> {
+    writeln!(writer, "{}{}", INDENT.repeat(depth), node.name)?;
+
+    if let Some(children) = &node.children {
+        for child in children {
+            format_names_recursive(child, depth + 1, writer)?;
+        }
+    }
+
+    Ok(())
+}
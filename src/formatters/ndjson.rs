@@ -0,0 +1,160 @@
+//! NDJSON（JSON Lines）输出格式化器。
+//!
+//! 每个节点（先序，含根）一个扁平 JSON 对象、一行一个，便于直接导入
+//! SQLite / DuckDB 等工具。键及其顺序与 CSV 一样由 `--fields` 决定：
+//!
+//! ```text
+//! {"path":"src","type":"directory","depth":0}
+//! {"path":"src/main.rs","type":"file","depth":1}
+//! ```
+//!
+//! 输出建立在 `walk_core` 之上，边遍历边写出，峰值内存与流式树相同。
+
+use crate::config::CsvField;
+use crate::core::models::{FilteredCounts, FsNodeType, TreeError};
+use crate::core::streaming::{file_mode, walk_core, StreamNode};
+use crate::core::walker::{with_treeignore, MetadataFields, WalkConfig};
+use crate::formatters::csv;
+use serde_json::Value;
+use std::io::Write;
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+/// 给定字段所需的元数据，与 CSV 的列一致（`size` → `SIZE`，`mtime` → `MODIFIED`，
+/// `mode` → `MODE`）。
+pub fn required_metadata(fields: &[CsvField]) -> MetadataFields {
+    csv::required_metadata(fields)
+}
+
+/// 将单个节点格式化为一行 JSON 对象（不含换行），只含 `fields` 中的键，顺序与之一致。
+///
+/// 未读取的 mtime 与 mode 为 `null`；mode 与 CSV 一样是八进制字符串，如 `"0644"`。
+pub fn format_ndjson_line(node: &StreamNode, fields: &[CsvField]) -> String {
+    // serde_json 的 Map 会按键名排序，这里手工拼接以保持 `--fields` 的顺序。
+    let members: Vec<String> = fields
+        .iter()
+        .map(|&field| {
+            format!(
+                "{}:{}",
+                Value::from(field.as_str()),
+                field_value(node, field)
+            )
+        })
+        .collect();
+    format!("{{{}}}", members.join(","))
+}
+
+/// 单个字段的 JSON 值。
+fn field_value(node: &StreamNode, field: CsvField) -> Value {
+    match field {
+        CsvField::Path => Value::from(node.path.to_string_lossy().into_owned()),
+        CsvField::Name => Value::from(node.name.clone()),
+        CsvField::Type => Value::from(match node.node_type {
+            FsNodeType::File => "file",
+            FsNodeType::Directory => "directory",
+            FsNodeType::Symlink => "symlink",
+        }),
+        CsvField::Size => Value::from(node.size),
+        CsvField::Mtime => node
+            .modified
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| Value::from(d.as_secs()))
+            .unwrap_or(Value::Null),
+        CsvField::Depth => Value::from(node.depth),
+        CsvField::Mode => node
+            .mode
+            .map(|m| Value::from(csv::mode_text(m)))
+            .unwrap_or(Value::Null),
+    }
+}
+
+/// 遍历 `root` 并将每个节点作为一行 JSON 写入 `writer`，返回被过滤器排除的计数。
+///
+/// 根节点（深度 0）先于其子节点输出；`config.metadata` 应至少包含
+/// [`required_metadata`] 给出的字段，否则对应键为零值或 `null`。
+///
+/// # 错误
+///
+/// 根路径不存在或不可读时返回相应的 `TreeError`；写入失败返回 `TreeError::Io`。
+pub fn write_ndjson<W: Write>(
+    root: &Path,
+    config: &WalkConfig,
+    fields: &[CsvField],
+    writer: &mut W,
+) -> Result<FilteredCounts, TreeError> {
+    let config = &*with_treeignore(root, config)?;
+    let root_node = root_stream_node(root, config)?;
+    write_line(&root_node, fields, writer)?;
+
+    // 回调不能返回错误：记下第一个写入错误，遍历结束后再上报。
+    let mut write_error = None;
+    let filtered = walk_core(root, config, |node| {
+        if write_error.is_none() {
+            if let Err(e) = write_line(node, fields, writer) {
+                write_error = Some(e);
+            }
+        }
+    })?;
+
+    match write_error {
+        Some(e) => Err(e),
+        None => Ok(filtered),
+    }
+}
+
+/// 写出一行 JSON。
+fn write_line<W: Write>(
+    node: &StreamNode,
+    fields: &[CsvField],
+    writer: &mut W,
+) -> Result<(), TreeError> {
+    writeln!(writer, "{}", format_ndjson_line(node, fields))?;
+    Ok(())
+}
+
+/// 为扫描根构造深度为 0 的节点（`walk_core` 只输出根之下的节点）。
+fn root_stream_node(root: &Path, config: &WalkConfig) -> Result<StreamNode, TreeError> {
+    if !root.exists() {
+        return Err(TreeError::PathNotFound(root.to_path_buf()));
+    }
+    let meta = std::fs::metadata(root)?;
+    if !meta.is_dir() && !config.allow_file_root {
+        return Err(TreeError::NotADirectory(root.to_path_buf()));
+    }
+    let node_type = if meta.is_dir() {
+        FsNodeType::Directory
+    } else {
+        FsNodeType::File
+    };
+
+    Ok(StreamNode {
+        name: root
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| root.to_string_lossy().into_owned()),
+        path: root.to_path_buf(),
+        size: if node_type == FsNodeType::File && config.metadata.contains(MetadataFields::SIZE) {
+            meta.len()
+        } else {
+            0
+        },
+        node_type,
+        depth: 0,
+        modified: if config.metadata.contains(MetadataFields::MODIFIED) {
+            meta.modified().ok()
+        } else {
+            None
+        },
+        uid: None,
+        mode: if config.metadata.contains(MetadataFields::MODE) {
+            file_mode(&meta)
+        } else {
+            None
+        },
+        line_count: None,
+        is_last: true,
+        link_target: None,
+        is_loop: false,
+        truncated: false,
+    })
+}
@@ -0,0 +1,40 @@
+//! 表格、摘要与树形注解中计数类数字的格式化。
+
+/// 将计数格式化为字符串，可选地每三位插入分组分隔符（如 `1,234,567`）。
+///
+/// `sep` 为 `None` 时输出纯数字，保证脚本解析表格输出时不受影响。
+pub fn format_count(n: u64, sep: Option<char>) -> String {
+    let digits = n.to_string();
+    let Some(sep) = sep else {
+        return digits;
+    };
+
+    let mut out = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(sep);
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// 将计数缩写为 SI 风格的短形式（`999`、`1.0k`、`12.3k`、`4.5M`）。
+///
+/// 小于 1000 的数原样输出；其余保留一位小数，四舍五入后达到 1000 的值会进位到
+/// 下一个单位（`999_950` 显示为 `1.0M` 而不是 `1000.0k`）。
+pub fn format_count_compact(n: u64) -> String {
+    const UNITS: [&str; 5] = ["k", "M", "G", "T", "P"];
+
+    if n < 1000 {
+        return n.to_string();
+    }
+
+    let mut value = n as f64 / 1000.0;
+    let mut unit = 0;
+    while unit + 1 < UNITS.len() && (value * 10.0).round() / 10.0 >= 1000.0 {
+        value /= 1000.0;
+        unit += 1;
+    }
+    format!("{:.1}{}", value, UNITS[unit])
+}
@@ -0,0 +1,59 @@
+//! 纯文本大纲输出格式化器。
+//!
+//! 每行以节点深度开头，随后是按深度重复的 `. ` 缩进和名称，例如：
+//!
+//! ```text
+//! 0 root
+//! 1 . src
+//! 2 . . main.rs
+//! ```
+//!
+//! 不使用任何制表符，按第一个空格切分即可得到深度，便于机器解析。
+
+use crate::core::models::FsNode;
+use std::io::{self, Write};
+
+/// 将文件树格式化为带深度标记的缩进大纲。
+///
+/// # 参数
+///
+/// * `node` - 树的根节点（深度 0）
+///
+/// # 返回
+///
+/// 每个节点一行的大纲字符串。
+pub fn format_outline(node: &FsNode) -> String {
+    let mut buffer = Vec::new();
+    format_outline_to(node, &mut buffer).expect("writing to a Vec<u8> cannot fail");
+    String::from_utf8(buffer).expect("outline output is valid UTF-8")
+}
+
+/// 将大纲逐行写入 `writer`；输出与 [`format_outline`] 一致。
+///
+/// # 错误
+///
+/// 返回 `writer` 产生的 IO 错误。
+pub fn format_outline_to<W: Write>(node: &FsNode, writer: &mut W) -> io::Result<()> {
+    format_outline_recursive(node, 0, writer)
+}
+
+/// 递归地输出节点及其子节点。
+fn format_outline_recursive<W: Write>(
+    node: &FsNode,
+    depth: usize,
+    writer: &mut W,
+) -> io::Result<()> {
+    write!(writer, "{} ", depth)?;
+    for _ in 0..depth {
+        writer.write_all(b". ")?;
+    }
+    writeln!(writer, "{}", node.name)?;
+
+    if let Some(children) = &node.children {
+        for child in children {
+            format_outline_recursive(child, depth + 1, writer)?;
+        }
+    }
+
+    Ok(())
+}
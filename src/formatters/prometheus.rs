@@ -0,0 +1,118 @@
+//! Prometheus 文本暴露格式（text exposition format）的统计输出。
+
+use crate::core::models::TreeStats;
+use crate::core::walker::MetadataFields;
+use std::fmt::Write;
+use std::path::Path;
+
+/// Prometheus 输出所需的元数据（统计信息依赖文件大小）。
+pub const REQUIRED_METADATA: MetadataFields = MetadataFields::SIZE;
+
+/// 将统计信息格式化为 Prometheus 文本暴露格式。
+///
+/// 每个指标都带有 `path` 标签（扫描根路径）；按扩展名的指标额外带有
+/// `extension` 标签。所有指标均为 `gauge`，每行一个样本，便于 cron 任务
+/// 通过 textfile collector 等方式导入监控后端。
+///
+/// # 参数
+///
+/// * `stats` - 要导出的统计信息
+/// * `root` - 被扫描的根路径（作为 `path` 标签值）
+///
+/// # 返回
+///
+/// 以换行结尾的 Prometheus 文本。
+pub fn format_prometheus(stats: &TreeStats, root: &Path) -> String {
+    let path = escape_label_value(&root.to_string_lossy());
+    let mut out = String::new();
+
+    let totals: [(&str, &str, String); 5] = [
+        (
+            "rust_tree_total_files",
+            "Total number of files.",
+            stats.total_files.to_string(),
+        ),
+        (
+            "rust_tree_total_directories",
+            "Total number of directories.",
+            stats.total_directories.to_string(),
+        ),
+        (
+            "rust_tree_total_symlinks",
+            "Total number of symlinks.",
+            stats.total_symlinks.to_string(),
+        ),
+        (
+            "rust_tree_total_bytes",
+            "Total size of all files in bytes.",
+            stats.total_size.to_string(),
+        ),
+        (
+            "rust_tree_scan_duration_seconds",
+            "Time spent scanning the tree in seconds.",
+            stats.scan_duration.as_secs_f64().to_string(),
+        ),
+    ];
+
+    for (name, help, value) in &totals {
+        write_header(&mut out, name, help);
+        let _ = writeln!(out, "{}{{path=\"{}\"}} {}", name, path, value);
+    }
+
+    // 按扩展名的指标：排序后输出，保证结果稳定、便于比对。
+    let mut extensions: Vec<_> = stats.files_by_extension.values().collect();
+    extensions.sort_by(|a, b| a.extension.cmp(&b.extension));
+
+    write_header(
+        &mut out,
+        "rust_tree_extension_files",
+        "Number of files per extension.",
+    );
+    for info in &extensions {
+        let _ = writeln!(
+            out,
+            "rust_tree_extension_files{{path=\"{}\",extension=\"{}\"}} {}",
+            path,
+            escape_label_value(&info.extension),
+            info.count
+        );
+    }
+
+    write_header(
+        &mut out,
+        "rust_tree_extension_bytes",
+        "Total size of files per extension in bytes.",
+    );
+    for info in &extensions {
+        let _ = writeln!(
+            out,
+            "rust_tree_extension_bytes{{path=\"{}\",extension=\"{}\"}} {}",
+            path,
+            escape_label_value(&info.extension),
+            info.total_size
+        );
+    }
+
+    out
+}
+
+/// 写入指标的 `# HELP` 与 `# TYPE` 行。
+fn write_header(out: &mut String, name: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} gauge", name);
+}
+
+/// 按暴露格式规范转义标签值：反斜杠、双引号和换行。
+#[doc(hidden)]
+pub fn escape_label_value(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,164 @@
+//! 各格式化器共用的字节大小格式化。
+
+use humansize::{FormatSizeOptions, BINARY, DECIMAL};
+use serde::{Serialize, Serializer};
+use std::str::FromStr;
+
+/// 大小单位制。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SizeUnits {
+    /// 十进制单位（1 kB = 1000 B）
+    #[default]
+    Decimal,
+    /// 二进制单位（1 KiB = 1024 B）
+    Binary,
+}
+
+/// 固定的显示单位（`--size-unit`）：所有大小都换算到该单位，不随数量级自动切换。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SizeUnit {
+    /// 字节
+    B,
+    /// 千字节（1000 B）
+    KB,
+    /// 兆字节（1000² B）
+    MB,
+    /// 吉字节（1000³ B）
+    GB,
+    /// 太字节（1000⁴ B）
+    TB,
+    /// 1024 B
+    KiB,
+    /// 1024² B
+    MiB,
+    /// 1024³ B
+    GiB,
+    /// 1024⁴ B
+    TiB,
+}
+
+impl SizeUnit {
+    /// 全部单位，按 `--size-unit` 的写法排列。
+    pub const ALL: [SizeUnit; 9] = [
+        SizeUnit::B,
+        SizeUnit::KB,
+        SizeUnit::MB,
+        SizeUnit::GB,
+        SizeUnit::TB,
+        SizeUnit::KiB,
+        SizeUnit::MiB,
+        SizeUnit::GiB,
+        SizeUnit::TiB,
+    ];
+
+    /// 一个单位等于多少字节。
+    pub fn bytes(self) -> u64 {
+        match self {
+            SizeUnit::B => 1,
+            SizeUnit::KB => 1000,
+            SizeUnit::MB => 1000u64.pow(2),
+            SizeUnit::GB => 1000u64.pow(3),
+            SizeUnit::TB => 1000u64.pow(4),
+            SizeUnit::KiB => 1 << 10,
+            SizeUnit::MiB => 1 << 20,
+            SizeUnit::GiB => 1 << 30,
+            SizeUnit::TiB => 1 << 40,
+        }
+    }
+
+    /// 输出中使用的单位符号，与 humansize 自动换算时的写法一致（`kB`、`MiB`）。
+    pub fn symbol(self) -> &'static str {
+        match self {
+            SizeUnit::B => "B",
+            SizeUnit::KB => "kB",
+            SizeUnit::MB => "MB",
+            SizeUnit::GB => "GB",
+            SizeUnit::TB => "TB",
+            SizeUnit::KiB => "KiB",
+            SizeUnit::MiB => "MiB",
+            SizeUnit::GiB => "GiB",
+            SizeUnit::TiB => "TiB",
+        }
+    }
+}
+
+impl Serialize for SizeUnit {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.symbol())
+    }
+}
+
+impl FromStr for SizeUnit {
+    type Err = String;
+
+    /// 解析单位名称，不区分大小写（`MB`、`mb`、`MiB`、`kB` 均可）。
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        SizeUnit::ALL
+            .into_iter()
+            .find(|unit| unit.symbol().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| {
+                let valid: Vec<&str> = SizeUnit::ALL.iter().map(|u| u.symbol()).collect();
+                format!(
+                    "invalid size unit '{}': expected one of {}",
+                    s,
+                    valid.join(", ")
+                )
+            })
+    }
+}
+
+/// 字节大小的格式化选项。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SizeOptions {
+    /// 单位制
+    pub units: SizeUnits,
+    /// 小数位数（`None` 表示使用 humansize 的默认值）
+    pub precision: Option<usize>,
+    /// 输出原始字节数（纯数字，不带单位），忽略单位与精度
+    pub raw_bytes: bool,
+    /// 大小为 0 时使用的替代文本（`None` 表示照常格式化为 "0 B"）
+    pub zero_label: Option<&'static str>,
+    /// 固定换算到该单位（`None` 表示按数量级自动选择），优先于 `units`；
+    /// 小数位数取 `precision`，默认一位（`B` 不带小数）
+    pub fixed_unit: Option<SizeUnit>,
+}
+
+/// 按给定选项将字节数格式化为字符串。
+pub fn format_size(bytes: u64, opts: &SizeOptions) -> String {
+    if bytes == 0 {
+        if let Some(label) = opts.zero_label {
+            return label.to_string();
+        }
+    }
+
+    if opts.raw_bytes {
+        return bytes.to_string();
+    }
+
+    if let Some(unit) = opts.fixed_unit {
+        return format_fixed(bytes, unit, opts.precision);
+    }
+
+    let mut human = match opts.units {
+        SizeUnits::Decimal => FormatSizeOptions::from(DECIMAL),
+        SizeUnits::Binary => FormatSizeOptions::from(BINARY),
+    };
+    if let Some(precision) = opts.precision {
+        human = human.decimal_places(precision);
+    }
+
+    humansize::format_size(bytes, human)
+}
+
+/// 按固定单位格式化，如 `1500.0 MB`；不经过 humansize 的自动换算。
+fn format_fixed(bytes: u64, unit: SizeUnit, precision: Option<usize>) -> String {
+    if unit == SizeUnit::B {
+        return format!("{} B", bytes);
+    }
+    format!(
+        "{:.*} {}",
+        precision.unwrap_or(1),
+        bytes as f64 / unit.bytes() as f64,
+        unit.symbol()
+    )
+}
@@ -1,11 +1,48 @@
 //! 用于内存高效输出的流式树格式化器。
 
-use crate::config::color::should_use_colors;
+use crate::config::color::{colorize_by_type, should_use_colors};
 use crate::config::{ColorMode, ColorScheme};
-use crate::core::streaming::{walk_core, StreamNode};
-use crate::core::walker::WalkConfig;
-use humansize::format_size;
+use crate::core::models::{FilteredCounts, TreeError};
+use crate::core::streaming::{file_mode, owner_uid, walk_core, StreamNode};
+use crate::core::walker::{with_treeignore, WalkConfig};
+use crate::formatters::size::{format_size, SizeOptions};
+use crate::formatters::tree::{line_annotation, perms_annotation, TreeCharset, TRUNCATED_MARKER};
 use std::io::Write;
+use std::path::Path;
+
+/// 流式树输出的显示选项。
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StreamOptions {
+    /// 显示文件大小
+    pub show_size: bool,
+    /// 何时使用颜色
+    pub color_mode: ColorMode,
+    /// 使用的配色方案
+    pub color_scheme: ColorScheme,
+    /// 每输出 N 个节点回调一次累计值（0 表示关闭）
+    pub running_totals_every: usize,
+    /// 大小的格式化选项（`--size-unit` 固定单位等）
+    pub size: SizeOptions,
+    /// 在每个条目前显示权限（`--perms`）
+    pub show_perms: bool,
+    /// 连接线字符集（`--ascii`）
+    pub charset: TreeCharset,
+    /// 在树的末尾输出一行汇总（`--stats`），见 [`format_stream_summary`]
+    pub summary: bool,
+}
+
+/// 流式输出过程中的累计值。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RunningTotals {
+    /// 已输出的节点数
+    pub nodes: u64,
+    /// 已输出的文件数
+    pub files: u64,
+    /// 已输出的目录数（不含根）
+    pub directories: u64,
+    /// 已输出文件的总字节数
+    pub bytes: u64,
+}
 
 /// 使用流式核心格式化树（峰值内存为 O(最宽目录的宽度)）。
 pub fn format_tree_streaming<W: Write>(
@@ -17,6 +54,41 @@ pub fn format_tree_streaming<W: Write>(
     config: WalkConfig,
     progress: Option<&indicatif::ProgressBar>,
 ) -> Result<(), Box<dyn std::error::Error>> {
+    let options = StreamOptions {
+        show_size,
+        color_mode,
+        color_scheme,
+        ..Default::default()
+    };
+    format_tree_streaming_with(root, writer, &options, config, progress, |_| {})
+}
+
+/// 按给定选项流式格式化树。
+///
+/// 当 `options.running_totals_every > 0` 时，每输出该数量的节点就以当前
+/// 累计值调用一次 `on_totals`，遍历结束后若有未报告的节点再补一次。
+pub fn format_tree_streaming_with<W, F>(
+    root: &std::path::Path,
+    writer: &mut W,
+    options: &StreamOptions,
+    config: WalkConfig,
+    progress: Option<&indicatif::ProgressBar>,
+    mut on_totals: F,
+) -> Result<(), Box<dyn std::error::Error>>
+where
+    W: Write,
+    F: FnMut(&RunningTotals),
+{
+    let StreamOptions {
+        show_size,
+        color_mode,
+        color_scheme,
+        running_totals_every,
+        size: size_options,
+        show_perms,
+        charset,
+        summary,
+    } = *options;
     let use_color = should_use_colors(color_mode);
 
     // 先输出根目录
@@ -26,33 +98,68 @@ pub fn format_tree_streaming<W: Write>(
         .unwrap_or(".")
         .to_string();
 
+    // 文件根（allow_file_root）渲染为单行：无尾随 `/`，按需显示大小。
+    let root_meta = std::fs::metadata(root).ok();
+    let root_is_file = root_meta.as_ref().is_some_and(|m| m.is_file());
+    let root_type = if root_is_file {
+        crate::core::models::FsNodeType::File
+    } else {
+        crate::core::models::FsNodeType::Directory
+    };
+
     let root_colored = if use_color {
         colorize_by_type_and_ext(
             &root_name,
-            &crate::core::models::FsNodeType::Directory,
+            &root_type,
+            root_meta.as_ref().and_then(owner_uid),
             color_scheme,
         )
         .to_string()
     } else {
         root_name.clone()
     };
+    let root_colored = if show_perms {
+        perms_annotation(&root_type, root_meta.as_ref().and_then(file_mode)) + &root_colored
+    } else {
+        root_colored
+    };
+
+    if root_is_file && config.allow_file_root {
+        let size = root_meta.map(|m| m.len()).unwrap_or(0);
+        if show_size && size > 0 {
+            writeln!(
+                writer,
+                "{} ({})",
+                root_colored,
+                format_size(size, &size_options)
+            )?;
+        } else {
+            writeln!(writer, "{}", root_colored)?;
+        }
+        return Ok(());
+    }
 
     writeln!(writer, "{}/", root_colored)?;
 
-    // prefix_stack[d] 保存当前路径上深度为 d 的节点的 is_last 标志
-    // 子节点从深度 1 开始。
-    let mut prefix_stack: Vec<bool> = Vec::new();
+    let mut totals = RunningTotals::default();
 
-    walk_core(root, &config, |node| {
-        while prefix_stack.len() <= node.depth {
-            prefix_stack.push(false);
+    walk_with_prefix(root, &config, charset, |prefix, node| {
+        let mut label = build_label(node, show_size, &size_options, use_color, color_scheme);
+        if show_perms {
+            label.insert_str(0, &perms_annotation(&node.node_type, node.mode));
         }
-        prefix_stack[node.depth] = node.is_last;
-
-        let prefix = build_prefix(&prefix_stack, node.depth);
-        let label = build_label(node, show_size, use_color, color_scheme);
         let _ = writeln!(writer, "{}{}", prefix, label);
 
+        // 因深度限制未展开的非空目录：补一个 `...` 占位子节点。
+        if node.truncated {
+            let _ = writeln!(
+                writer,
+                "{}{}",
+                last_child_prefix(prefix, node.is_last, charset),
+                TRUNCATED_MARKER
+            );
+        }
+
         // 真实进度：每个节点计数加一，目录节点更新当前路径消息。
         if let Some(pb) = progress {
             pb.inc(1);
@@ -60,26 +167,144 @@ pub fn format_tree_streaming<W: Write>(
                 pb.set_message(node.path.display().to_string());
             }
         }
+
+        // 累计值只是几个计数器，逐个节点累加，不需要保留任何节点。
+        totals.nodes += 1;
+        match node.node_type {
+            crate::core::models::FsNodeType::File => {
+                totals.files += 1;
+                totals.bytes += node.size;
+            }
+            crate::core::models::FsNodeType::Directory => totals.directories += 1,
+            crate::core::models::FsNodeType::Symlink => {}
+        }
+        if running_totals_every > 0 && totals.nodes % running_totals_every as u64 == 0 {
+            on_totals(&totals);
+        }
     })?;
 
+    if running_totals_every > 0 && totals.nodes % running_totals_every as u64 != 0 {
+        on_totals(&totals);
+    }
+
+    if summary {
+        writeln!(
+            writer,
+            "\n{}",
+            format_stream_summary(&totals, &size_options)
+        )?;
+    }
+
     Ok(())
 }
 
+/// 流式树末尾的汇总行：`N files, M directories, SIZE total`（单数时为 `file` / `directory`）。
+///
+/// 只计入输出的条目，根目录本身不计；符号链接既不算文件也不算目录。
+pub fn format_stream_summary(totals: &RunningTotals, size: &SizeOptions) -> String {
+    format!(
+        "{} {}, {} {}, {} total",
+        totals.files,
+        if totals.files == 1 { "file" } else { "files" },
+        totals.directories,
+        if totals.directories == 1 {
+            "directory"
+        } else {
+            "directories"
+        },
+        format_size(totals.bytes, size)
+    )
+}
+
+/// 流式遍历 `root`，把每个节点连同其树形前缀（如 `│   ├── `）交给回调。
+///
+/// 前缀与 [`format_tree_streaming`] 输出中该节点所在行的前缀完全一致，
+/// 便于库使用者自行渲染标签而复用制表符布局。根节点不会传给回调。
+///
+/// 与 [`walk_core`] 相同，返回被过滤器排除的条目数。
+///
+/// # 错误
+///
+/// 与 [`walk_core`] 相同：根不存在或无法读取时返回 `TreeError`。
+pub fn walk_streaming_with_prefix<F>(
+    root: &Path,
+    config: &WalkConfig,
+    callback: F,
+) -> Result<FilteredCounts, TreeError>
+where
+    F: FnMut(&str, &StreamNode),
+{
+    walk_with_prefix(root, config, TreeCharset::Unicode, callback)
+}
+
+/// [`walk_streaming_with_prefix`] 的实现，前缀按 `charset` 绘制。
+fn walk_with_prefix<F>(
+    root: &Path,
+    config: &WalkConfig,
+    charset: TreeCharset,
+    mut callback: F,
+) -> Result<FilteredCounts, TreeError>
+where
+    F: FnMut(&str, &StreamNode),
+{
+    // prefix_stack[d] 保存当前路径上深度为 d 的节点的 is_last 标志
+    // 子节点从深度 1 开始。
+    let mut prefix_stack: Vec<bool> = Vec::new();
+    let config = with_treeignore(root, config)?;
+
+    walk_core(root, &config, |node| {
+        while prefix_stack.len() <= node.depth {
+            prefix_stack.push(false);
+        }
+        prefix_stack[node.depth] = node.is_last;
+
+        callback(&build_prefix_with(&prefix_stack, node.depth, charset), node);
+    })
+}
+
+/// 由节点自身的前缀推出其唯一（最后一个）子节点的前缀。
+fn last_child_prefix(prefix: &str, is_last: bool, charset: TreeCharset) -> String {
+    let ancestors = prefix
+        .strip_suffix(charset.last_branch())
+        .or_else(|| prefix.strip_suffix(charset.branch()))
+        .unwrap_or(prefix);
+    let rail = if is_last {
+        charset.blank()
+    } else {
+        charset.vertical()
+    };
+    format!("{}{}{}", ancestors, rail, charset.last_branch())
+}
+
 /// 为深度为 `depth` (>= 1) 的节点构建树形前缀。
 ///
 /// 祖先层 1..depth 根据该祖先是否为其父节点的最后一个子节点，
 /// 绘制空白间隔或竖线；节点自身所在层绘制分支连接符。
 #[doc(hidden)]
 pub fn build_prefix(prefix_stack: &[bool], depth: usize) -> String {
+    build_prefix_with(prefix_stack, depth, TreeCharset::Unicode)
+}
+
+/// 与 [`build_prefix`] 相同，但按 `charset` 绘制连接线。
+#[doc(hidden)]
+pub fn build_prefix_with(prefix_stack: &[bool], depth: usize, charset: TreeCharset) -> String {
     let mut prefix = String::new();
 
     for level in 1..depth {
         let ancestor_is_last = prefix_stack.get(level).copied().unwrap_or(false);
-        prefix.push_str(if ancestor_is_last { "    " } else { "│   " });
+        prefix.push_str(if ancestor_is_last {
+            charset.blank()
+        } else {
+            charset.vertical()
+        });
     }
 
     let is_last = prefix_stack.get(depth).copied().unwrap_or(false);
-    prefix.push_str(if is_last { "└── " } else { "├── " });
+    prefix.push_str(if is_last {
+        charset.last_branch()
+    } else {
+        charset.branch()
+    });
 
     prefix
 }
@@ -88,11 +313,12 @@ pub fn build_prefix(prefix_stack: &[bool], depth: usize) -> String {
 fn build_label(
     node: &StreamNode,
     show_size: bool,
+    size_options: &SizeOptions,
     use_color: bool,
     color_scheme: ColorScheme,
 ) -> String {
     let name = if use_color {
-        colorize_by_type_and_ext(&node.name, &node.node_type, color_scheme).to_string()
+        colorize_by_type_and_ext(&node.name, &node.node_type, node.uid, color_scheme).to_string()
     } else {
         node.name.clone()
     };
@@ -102,6 +328,17 @@ fn build_label(
     // 添加目录指示符
     if node.node_type == crate::core::models::FsNodeType::Directory {
         label.push('/');
+        if let Some(target) = &node.link_target {
+            label.push_str(" -> ");
+            label.push_str(&target.to_string_lossy());
+            label.push('/');
+        }
+        if node.is_loop {
+            label.push_str(" [loop]");
+        }
+    } else if let Some(target) = &node.link_target {
+        label.push_str(" -> ");
+        label.push_str(&target.to_string_lossy());
     } else if node.node_type == crate::core::models::FsNodeType::Symlink {
         label.push_str(" -> ");
         if let Ok(target) = std::fs::read_link(&node.path) {
@@ -111,11 +348,9 @@ fn build_label(
 
     // 添加大小
     if show_size && node.node_type == crate::core::models::FsNodeType::File && node.size > 0 {
-        label.push_str(&format!(
-            " ({})",
-            format_size(node.size, humansize::DECIMAL)
-        ));
+        label.push_str(&format!(" ({})", format_size(node.size, size_options)));
     }
+    label.push_str(&line_annotation(node.line_count));
 
     label
 }
@@ -124,55 +359,8 @@ fn build_label(
 fn colorize_by_type_and_ext(
     name: &str,
     node_type: &crate::core::models::FsNodeType,
+    uid: Option<u32>,
     scheme: ColorScheme,
 ) -> colored::ColoredString {
-    use crate::core::models::FsNodeType;
-    use colored::Colorize;
-
-    match node_type {
-        FsNodeType::Directory => name.blue().bold(),
-        FsNodeType::Symlink => name.cyan().italic(),
-        FsNodeType::File => {
-            let ext = name.rsplit('.').next().unwrap_or("");
-            match scheme {
-                ColorScheme::None => name.normal(),
-                ColorScheme::Basic => basic_file_color(name, ext),
-                ColorScheme::Extended => extended_file_color(name, ext),
-            }
-        }
-    }
-}
-
-/// 基础文件配色方案。
-fn basic_file_color(name: &str, ext: &str) -> colored::ColoredString {
-    use colored::Colorize;
-    match ext {
-        "rs" | "py" | "js" | "ts" | "java" | "c" | "cpp" | "go" | "rb" | "php" => name.green(),
-        "toml" | "yaml" | "yml" | "json" | "xml" => name.yellow(),
-        "md" | "txt" | "rst" => name.white(),
-        "lock" => name.bright_black(),
-        _ => name.normal(),
-    }
-}
-
-/// 扩展文件配色方案。
-fn extended_file_color(name: &str, ext: &str) -> colored::ColoredString {
-    use colored::Colorize;
-    match ext {
-        "rs" => name.bright_green(),
-        "py" => name.green(),
-        "js" | "ts" | "tsx" | "jsx" => name.yellow(),
-        "java" | "c" | "cpp" | "h" | "hpp" => name.blue(),
-        "go" => name.cyan(),
-        "rb" | "php" => name.magenta(),
-        "toml" | "yaml" | "yml" => name.bright_yellow(),
-        "json" | "xml" => name.yellow(),
-        "ini" | "cfg" | "conf" => name.bright_black(),
-        "md" | "rst" | "adoc" => name.white(),
-        "txt" => name.bright_white(),
-        "lock" => name.bright_black(),
-        "png" | "jpg" | "jpeg" | "gif" | "svg" | "ico" => name.bright_magenta(),
-        "zip" | "tar" | "gz" | "rar" | "7z" => name.red(),
-        _ => name.normal(),
-    }
+    colorize_by_type(name, name, node_type, uid, scheme)
 }
@@ -1,10 +1,63 @@
 //! 统计信息的表格输出格式化器。
 
-use crate::core::models::TreeStats;
+use crate::core::models::{FileEntry, FileTypeInfo, GitRatio, TreeStats};
+use crate::core::walker::MetadataFields;
+use crate::formatters::diff::format_stats_delta;
+use crate::formatters::ext_labels::ExtensionLabels;
+use crate::formatters::number::{format_count, format_count_compact};
+use crate::formatters::size::{format_size, SizeOptions};
+use crate::formatters::width::truncate_path_left;
 use comfy_table::{
     modifiers::UTF8_ROUND_CORNERS, presets::UTF8_FULL, Attribute, Cell, Color, Table,
 };
-use humansize::format_size;
+
+/// 表格输出所需的元数据（统计信息依赖文件大小）。
+pub const REQUIRED_METADATA: MetadataFields = MetadataFields::SIZE;
+
+/// 重复文件报告中零字节文件组的标签。
+pub const EMPTY_FILES_GROUP: &str = "empty files";
+
+/// 扩展名表中汇总长尾条目的行标签。
+pub const OTHERS_LABEL: &str = "(others)";
+
+/// 表格输出的可选配置。
+#[derive(Debug, Clone, Default)]
+pub struct TableOptions {
+    /// 占总大小百分比低于该阈值的扩展名会被合并为一行 `(others)`（`None` 表示不合并）
+    pub min_percentage: Option<f64>,
+    /// 计数的千位分组分隔符（`None` 表示不分组）
+    pub thousands_sep: Option<char>,
+    /// 将计数缩写为 `12.3k` 之类的短形式（优先于 `thousands_sep`）
+    pub compact_counts: bool,
+    /// 长表格每隔 N 行数据重复一次表头（`None` 或 0 表示不重复）
+    pub repeat_header: Option<usize>,
+    /// 设置时最大文件表显示完整路径而非文件名，并将路径从左侧截断，
+    /// 使整张表不超过该宽度（列）
+    pub path_width: Option<usize>,
+    /// 扩展名表最多显示的扩展名行数，其余汇总为表下的一行 `(N more extensions, …)`
+    /// （`None` 表示不限）。先于 `min_percentage` 的合并生效
+    pub ext_limit: Option<usize>,
+    /// 扩展名表中扩展名的友好名称（显示为 `Rust (.rs)`）
+    pub extension_labels: ExtensionLabels,
+    /// 大小的格式化选项（`--size-unit` 固定单位等）
+    pub size: SizeOptions,
+}
+
+impl TableOptions {
+    /// 按选项格式化一个计数。
+    pub fn format_count(&self, n: u64) -> String {
+        if self.compact_counts {
+            format_count_compact(n)
+        } else {
+            format_count(n, self.thousands_sep)
+        }
+    }
+
+    /// 按选项格式化一个字节大小。
+    pub fn format_size(&self, bytes: u64) -> String {
+        format_size(bytes, &self.size)
+    }
+}
 
 /// 将统计信息格式化为表格。
 ///
@@ -16,28 +69,61 @@ use humansize::format_size;
 ///
 /// 包含一个或多个表格的格式化字符串。
 pub fn format_table(stats: &TreeStats) -> String {
+    format_table_with(stats, &TableOptions::default())
+}
+
+/// 按给定选项将统计信息格式化为表格。
+///
+/// 与 [`format_table`] 相同，但允许通过 `TableOptions` 调整表格内容。
+pub fn format_table_with(stats: &TreeStats, options: &TableOptions) -> String {
     let mut output = String::new();
 
     // 概览表
-    output.push_str(&format_overview(stats));
+    output.push_str(&format_overview(stats, options));
     output.push_str("\n\n");
 
     // 按扩展名分组的文件表
     if !stats.files_by_extension.is_empty() {
-        output.push_str(&format_extension_table(stats));
+        output.push_str(&format_extension_table(stats, options));
         output.push_str("\n\n");
     }
 
     // 最大文件表
     if !stats.largest_files.is_empty() {
-        output.push_str(&format_largest_files_table(stats));
+        output.push_str(&format_largest_files_table(stats, options));
+    }
+
+    // 重复文件表（仅 --duplicates）
+    if let Some(groups) = &stats.duplicates {
+        if !output.ends_with("\n\n") {
+            output.push_str("\n\n");
+        }
+        output.push_str(&format_duplicates_table(groups, options));
+    }
+
+    // git 文件状态表（仅 --git-ratio，且位于仓库中）
+    if let Some(ratio) = &stats.git_ratio {
+        if !output.ends_with("\n\n") {
+            output.push_str("\n\n");
+        }
+        output.push_str(&format_git_ratio_table(ratio, options));
+    }
+
+    // 相对基线的变化（仅 --compare-stats）
+    if let Some(delta) = &stats.stats_delta {
+        if !output.ends_with("\n\n") {
+            output.push_str("\n\n");
+        }
+        output.push_str(&format_stats_delta(delta, &options.size));
     }
 
     output
 }
 
 /// 格式化统计概览表。
-fn format_overview(stats: &TreeStats) -> String {
+fn format_overview(stats: &TreeStats, options: &TableOptions) -> String {
+    let count = |n: usize| options.format_count(n as u64);
+
     let mut table = Table::new();
     table
         .load_preset(UTF8_FULL)
@@ -48,22 +134,73 @@ fn format_overview(stats: &TreeStats) -> String {
 
     table.add_row(vec![
         Cell::new("Total Files"),
-        Cell::new(stats.total_files.to_string()).fg(Color::Green),
+        Cell::new(count(stats.total_files)).fg(Color::Green),
     ]);
 
     table.add_row(vec![
         Cell::new("Total Directories"),
-        Cell::new(stats.total_directories.to_string()).fg(Color::Blue),
+        Cell::new(count(stats.total_directories)).fg(Color::Blue),
     ]);
 
     table.add_row(vec![
         Cell::new("Total Symlinks"),
-        Cell::new(stats.total_symlinks.to_string()).fg(Color::Yellow),
+        Cell::new(count(stats.total_symlinks)).fg(Color::Yellow),
     ]);
 
     table.add_row(vec![
         Cell::new("Total Size"),
-        Cell::new(format_size_impl(stats.total_size)).fg(Color::Magenta),
+        Cell::new(options.format_size(stats.total_size)).fg(Color::Magenta),
+    ]);
+
+    table.add_row(vec![
+        Cell::new("Average File Size"),
+        Cell::new(options.format_size(stats.average_file_size.round() as u64)).fg(Color::Magenta),
+    ]);
+
+    table.add_row(vec![
+        Cell::new("Median File Size"),
+        Cell::new(options.format_size(stats.median_file_size)).fg(Color::Magenta),
+    ]);
+
+    table.add_row(vec![
+        Cell::new("Empty Files"),
+        Cell::new(count(stats.empty_files)).fg(Color::Green),
+    ]);
+
+    table.add_row(vec![
+        Cell::new("Empty Directories"),
+        Cell::new(count(stats.empty_directories.len())).fg(Color::Blue),
+    ]);
+
+    if let Some((path, depth)) = &stats.deepest_path {
+        table.add_row(vec![
+            Cell::new("Deepest File"),
+            Cell::new(format!("{} at depth {}", path.display(), depth)).fg(Color::Blue),
+        ]);
+    }
+
+    if let Some(lines) = stats.total_lines {
+        table.add_row(vec![
+            Cell::new("Total Lines"),
+            Cell::new(count(lines)).fg(Color::Green),
+        ]);
+    }
+
+    // 只有 --filelimit 略去了条目时才显示，提醒以上计数不含这些条目。
+    if stats.omitted_entries > 0 {
+        table.add_row(vec![
+            Cell::new("Omitted Entries"),
+            Cell::new(count(stats.omitted_entries)).fg(Color::Grey),
+        ]);
+    }
+
+    table.add_row(vec![
+        Cell::new("Leaf Depth (mean ± σ)"),
+        Cell::new(format!(
+            "{:.2} ± {:.2}",
+            stats.leaf_depth_mean, stats.leaf_depth_stddev
+        ))
+        .fg(Color::Blue),
     ]);
 
     table.add_row(vec![
@@ -75,35 +212,36 @@ fn format_overview(stats: &TreeStats) -> String {
 }
 
 /// 格式化按扩展名分组的文件表。
-fn format_extension_table(stats: &TreeStats) -> String {
+fn format_extension_table(stats: &TreeStats, options: &TableOptions) -> String {
     let mut table = Table::new();
     table
         .load_preset(UTF8_FULL)
         .apply_modifier(UTF8_ROUND_CORNERS)
-        .set_header(vec![
-            Cell::new("Extension")
-                .add_attribute(Attribute::Bold)
-                .fg(Color::Cyan),
-            Cell::new("Count")
-                .add_attribute(Attribute::Bold)
-                .fg(Color::Cyan),
-            Cell::new("Size")
-                .add_attribute(Attribute::Bold)
-                .fg(Color::Cyan),
-            Cell::new("Percentage")
-                .add_attribute(Attribute::Bold)
-                .fg(Color::Cyan),
-        ]);
+        .set_header(header_cells(EXTENSION_HEADER));
 
-    // 按数量排序（降序）
-    let mut extensions: Vec<_> = stats.files_by_extension.iter().collect();
-    extensions.sort_by_key(|e| std::cmp::Reverse(e.1.count));
+    // 按数量排序（降序），数量相同时按扩展名，使 --ext-limit 的截断结果稳定
+    let mut extensions: Vec<FileTypeInfo> = stats.files_by_extension.values().cloned().collect();
+    extensions.sort_by(|a, b| {
+        b.count
+            .cmp(&a.count)
+            .then_with(|| a.extension.cmp(&b.extension))
+    });
 
-    for (_ext, info) in extensions {
+    let hidden = match options.ext_limit {
+        Some(limit) if extensions.len() > limit => extensions.split_off(limit),
+        _ => Vec::new(),
+    };
+
+    if let Some(threshold) = options.min_percentage {
+        extensions = collapse_below_percentage(extensions, threshold, stats.total_size);
+    }
+
+    for (i, info) in extensions.iter().enumerate() {
+        repeat_header_at(&mut table, i, EXTENSION_HEADER, options);
         table.add_row(vec![
-            Cell::new(&info.extension),
-            Cell::new(info.count.to_string()).fg(Color::Green),
-            Cell::new(format_size_impl(info.total_size)).fg(Color::Magenta),
+            Cell::new(options.extension_labels.display(&info.extension)),
+            Cell::new(options.format_count(info.count as u64)).fg(Color::Green),
+            Cell::new(options.format_size(info.total_size)).fg(Color::Magenta),
             Cell::new(format!("{:.1}%", info.percentage)).fg(Color::Yellow),
         ]);
     }
@@ -112,29 +250,89 @@ fn format_extension_table(stats: &TreeStats) -> String {
     let mut output = String::new();
     output.push_str("Files by Extension\n");
     output.push_str(&table.to_string());
+    if !hidden.is_empty() {
+        let count: usize = hidden.iter().map(|info| info.count).sum();
+        let size: u64 = hidden.iter().map(|info| info.total_size).sum();
+        output.push_str(&format!(
+            "\n({} more extensions, {} files, {})",
+            options.format_count(hidden.len() as u64),
+            options.format_count(count as u64),
+            options.format_size(size)
+        ));
+    }
     output
 }
 
+/// 将占比低于 `threshold`（百分比）的扩展名合并为一行 `(others)`，追加在末尾。
+///
+/// 恰好等于阈值的条目会被保留。合并行的数量与大小为各条目之和，百分比按
+/// `total_size` 重新计算（而非直接累加，以免浮点误差）。
+#[doc(hidden)]
+pub fn collapse_below_percentage(
+    extensions: Vec<FileTypeInfo>,
+    threshold: f64,
+    total_size: u64,
+) -> Vec<FileTypeInfo> {
+    let (mut kept, others): (Vec<_>, Vec<_>) = extensions
+        .into_iter()
+        .partition(|info| info.percentage >= threshold);
+
+    if others.is_empty() {
+        return kept;
+    }
+
+    let count = others.iter().map(|info| info.count).sum();
+    let size: u64 = others.iter().map(|info| info.total_size).sum();
+    let percentage = if total_size > 0 {
+        (size as f64 / total_size as f64) * 100.0
+    } else {
+        0.0
+    };
+
+    kept.push(FileTypeInfo {
+        extension: OTHERS_LABEL.to_string(),
+        count,
+        total_size: size,
+        percentage,
+    });
+    kept
+}
+
 /// 格式化最大文件表。
-fn format_largest_files_table(stats: &TreeStats) -> String {
+fn format_largest_files_table(stats: &TreeStats, options: &TableOptions) -> String {
     let mut table = Table::new();
     table
         .load_preset(UTF8_FULL)
         .apply_modifier(UTF8_ROUND_CORNERS)
-        .set_header(vec![
-            Cell::new("File")
-                .add_attribute(Attribute::Bold)
-                .fg(Color::Cyan),
-            Cell::new("Size")
-                .add_attribute(Attribute::Bold)
-                .fg(Color::Cyan),
-        ]);
+        .set_header(header_cells(LARGEST_FILES_HEADER));
 
-    for file in &stats.largest_files {
-        table.add_row(vec![
-            Cell::new(&file.name),
-            Cell::new(format_size_impl(file.size)).fg(Color::Magenta),
-        ]);
+    let sizes: Vec<String> = stats
+        .largest_files
+        .iter()
+        .map(|file| options.format_size(file.size))
+        .collect();
+    let path_budget = options
+        .path_width
+        .map(|width| path_column_width(width, &sizes));
+
+    // 列表按大小降序排列，第一项即最大值。
+    let largest = stats.largest_files.first().map_or(0, |file| file.size);
+    let heatmap = stats.largest_files.len() > 1;
+    for (i, (file, size)) in stats.largest_files.iter().zip(sizes).enumerate() {
+        repeat_header_at(&mut table, i, LARGEST_FILES_HEADER, options);
+        let label = match path_budget {
+            Some(budget) => {
+                let path = file.path.to_string_lossy();
+                truncate_path_left(&path, budget).into_owned()
+            }
+            None => file.name.clone(),
+        };
+        let color = if heatmap {
+            size_heat_color(file.size, largest)
+        } else {
+            Color::Magenta
+        };
+        table.add_row(vec![Cell::new(label), Cell::new(size).fg(color)]);
     }
 
     // 添加标题
@@ -147,23 +345,182 @@ fn format_largest_files_table(stats: &TreeStats) -> String {
     output
 }
 
-/// 将字节数格式化为人类可读的字符串。
-fn format_size_impl(bytes: u64) -> String {
-    if bytes == 0 {
-        "0 B".to_string()
+/// 按 `size` 占 `largest` 的比例选择热力图颜色：不到三分之一为绿色，不到三分之二
+/// 为黄色，其余为红色。`largest` 为 0 时按绿色处理。
+pub fn size_heat_color(size: u64, largest: u64) -> Color {
+    if largest == 0 {
+        return Color::Green;
+    }
+    let ratio = size as f64 / largest as f64;
+    if ratio < 1.0 / 3.0 {
+        Color::Green
+    } else if ratio < 2.0 / 3.0 {
+        Color::Yellow
     } else {
-        format_size(bytes, humansize::DECIMAL)
+        Color::Red
+    }
+}
+
+/// 格式化重复文件表：每组文件连续排列，组号与大小只在组的第一行显示。
+fn format_duplicates_table(groups: &[Vec<FileEntry>], options: &TableOptions) -> String {
+    if groups.is_empty() {
+        return "Duplicate Files: none found".to_string();
+    }
+
+    let mut table = Table::new();
+    table
+        .load_preset(UTF8_FULL)
+        .apply_modifier(UTF8_ROUND_CORNERS)
+        .set_header(header_cells(DUPLICATES_HEADER));
+
+    let mut row = 0;
+    for (i, group) in groups.iter().enumerate() {
+        for (j, file) in group.iter().enumerate() {
+            repeat_header_at(&mut table, row, DUPLICATES_HEADER, options);
+            row += 1;
+            let (label, size) = if j == 0 && file.size == 0 {
+                ((i + 1).to_string(), EMPTY_FILES_GROUP.to_string())
+            } else if j == 0 {
+                ((i + 1).to_string(), options.format_size(file.size))
+            } else {
+                (String::new(), String::new())
+            };
+            table.add_row(vec![
+                Cell::new(label),
+                Cell::new(size).fg(Color::Magenta),
+                Cell::new(file.path.display()),
+            ]);
+        }
+    }
+
+    let mut output = String::new();
+    output.push_str(&format!(
+        "Duplicate Files ({} {}, {} wasted)\n",
+        options.format_count(groups.len() as u64),
+        if groups.len() == 1 { "group" } else { "groups" },
+        options.format_size(wasted_bytes(groups))
+    ));
+    output.push_str(&table.to_string());
+    output
+}
+
+/// 格式化 git 文件状态表：已跟踪、未跟踪与被忽略的文件数、大小及数量占比。
+pub fn format_git_ratio_table(ratio: &GitRatio, options: &TableOptions) -> String {
+    let total = ratio.total_files();
+    let mut table = Table::new();
+    table
+        .load_preset(UTF8_FULL)
+        .apply_modifier(UTF8_ROUND_CORNERS)
+        .set_header(header_cells(GIT_RATIO_HEADER));
+
+    let rows = [
+        ("Tracked", ratio.tracked),
+        ("Untracked", ratio.untracked),
+        ("Ignored", ratio.ignored),
+    ];
+    for (label, count) in rows {
+        let share = if total > 0 {
+            count.files as f64 / total as f64 * 100.0
+        } else {
+            0.0
+        };
+        table.add_row(vec![
+            Cell::new(label),
+            Cell::new(options.format_count(count.files as u64)),
+            Cell::new(options.format_size(count.size)).fg(Color::Magenta),
+            Cell::new(format!("{:.1}%", share)).fg(Color::Yellow),
+        ]);
+    }
+
+    let mut output = format!(
+        "Git Files ({} of {} tracked)\n",
+        options.format_count(ratio.tracked.files as u64),
+        options.format_count(total as u64)
+    );
+    output.push_str(&table.to_string());
+    output
+}
+
+/// 重复文件占用的多余空间：每组只保留一份时可释放的字节数。
+pub fn wasted_bytes(groups: &[Vec<FileEntry>]) -> u64 {
+    groups
+        .iter()
+        .map(|group| group.first().map_or(0, |f| f.size) * group.len().saturating_sub(1) as u64)
+        .sum()
+}
+
+/// 表宽为 `table_width` 时，最大文件表的路径列可用的宽度。
+///
+/// 两列表格的边框与内边距共占 7 列（`│ ` + ` │ ` + ` │`）；路径列至少保留
+/// 10 列，窄终端下宁可整表超宽也不把路径截成只剩省略号。
+fn path_column_width(table_width: usize, sizes: &[String]) -> usize {
+    const BORDERS: usize = 7;
+    const MIN_PATH_WIDTH: usize = 10;
+
+    let size_width = sizes
+        .iter()
+        .map(|s| s.len())
+        .chain(std::iter::once(LARGEST_FILES_HEADER[1].len()))
+        .max()
+        .unwrap_or(0);
+    table_width
+        .saturating_sub(size_width + BORDERS)
+        .max(MIN_PATH_WIDTH)
+}
+
+/// 扩展名表的列标题。
+const EXTENSION_HEADER: &[&str] = &["Extension", "Count", "Size", "Percentage"];
+
+/// 最大文件表的列标题。
+const LARGEST_FILES_HEADER: &[&str] = &["File", "Size"];
+
+/// 重复文件表的列标题。
+const DUPLICATES_HEADER: &[&str] = &["Group", "Size", "Path"];
+
+/// git 文件状态表的列标题。
+const GIT_RATIO_HEADER: &[&str] = &["Status", "Files", "Size", "Ratio"];
+
+/// 以表头样式（粗体青色）构建一行单元格。
+fn header_cells(titles: &[&str]) -> Vec<Cell> {
+    titles
+        .iter()
+        .map(|title| {
+            Cell::new(title)
+                .add_attribute(Attribute::Bold)
+                .fg(Color::Cyan)
+        })
+        .collect()
+}
+
+/// `--repeat-header N`：在第 `row`（从 0 计）行数据之前，若已输出 N 的整数倍行，
+/// 则插入一行表头样式的标题行，使滚动长表时仍能看到列名。
+fn repeat_header_at(table: &mut Table, row: usize, titles: &[&str], options: &TableOptions) {
+    if let Some(every) = options.repeat_header.filter(|&n| n > 0) {
+        if row > 0 && row.is_multiple_of(every) {
+            table.add_row(header_cells(titles));
+        }
     }
 }
 
-/// 将时长格式化为人类可读的字符串。
+/// 将时长格式化为人类可读的字符串，按量级选择单位：
+/// `850µs`、`500ms`、`1.5s`、`2m 13s`、`1h 5m`。
 #[doc(hidden)]
 pub fn format_duration(duration: std::time::Duration) -> String {
-    let ms = duration.as_millis();
-    if ms < 1000 {
-        format!("{}ms", ms)
+    let secs = duration.as_secs();
+    let millis = duration.as_millis();
+    // 秒数先取整到一位小数再选单位：59.95s 已是 60.0s，应显示为 1m 0s。
+    let tenths = (millis + 50) / 100;
+    if millis == 0 {
+        format!("{}µs", duration.as_micros())
+    } else if secs == 0 {
+        format!("{}ms", millis)
+    } else if tenths < 600 {
+        format!("{}.{}s", tenths / 10, tenths % 10)
+    } else if secs < 3600 {
+        let secs = secs.max(60);
+        format!("{}m {}s", secs / 60, secs % 60)
     } else {
-        format!("{:.1}s", ms as f64 / 1000.0)
+        format!("{}h {}m", secs / 3600, secs % 3600 / 60)
     }
 }
 
@@ -177,10 +534,29 @@ pub fn format_duration(duration: std::time::Duration) -> String {
 ///
 /// 汇总统计信息的精简单行字符串。
 pub fn format_compact(stats: &TreeStats) -> String {
-    format!(
+    format_compact_with(stats, &TableOptions::default())
+}
+
+/// 按给定选项以精简的单行格式格式化统计信息。
+pub fn format_compact_with(stats: &TreeStats, options: &TableOptions) -> String {
+    let mut summary = format!(
         "{} files, {} directories, {} total",
-        stats.total_files,
-        stats.total_directories,
-        format_size_impl(stats.total_size)
-    )
+        options.format_count(stats.total_files as u64),
+        options.format_count(stats.total_directories as u64),
+        options.format_size(stats.total_size)
+    );
+    // 没有空目录时保持原有的三段式摘要。
+    if !stats.empty_directories.is_empty() {
+        summary.push_str(&format!(
+            ", {} empty directories",
+            options.format_count(stats.empty_directories.len() as u64)
+        ));
+    }
+    if stats.omitted_entries > 0 {
+        summary.push_str(&format!(
+            ", {} omitted",
+            options.format_count(stats.omitted_entries as u64)
+        ));
+    }
+    summary
 }
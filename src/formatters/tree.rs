@@ -1,9 +1,242 @@
 //! 树形输出格式化器。
 
-use crate::config::color::{colorize_node, should_use_colors};
+use crate::config::color::{colorize_label, should_use_colors};
 use crate::config::{ColorMode, ColorScheme};
-use crate::core::models::FsNode;
-use humansize::format_size;
+use crate::core::collector::EmptyDirectory;
+use crate::core::models::{FileEntry, FilteredCounts, FsNode, FsNodeType, SubtreeSummary};
+use crate::core::walker::MetadataFields;
+use crate::formatters::number::format_count_compact;
+use crate::formatters::size::{format_size, SizeOptions};
+use crate::formatters::table::{wasted_bytes, EMPTY_FILES_GROUP};
+use colored::Colorize;
+use std::io::{self, Write};
+use std::path::{Component, Path, PathBuf};
+
+/// 树形输出（内存与流式）所需的元数据：仅在显示文件或目录大小时需要 `SIZE`。
+pub fn required_metadata(show_size: bool) -> MetadataFields {
+    if show_size {
+        MetadataFields::SIZE
+    } else {
+        MetadataFields::empty()
+    }
+}
+
+/// 目录因深度限制未展开时，在其下显示的占位子节点文本。
+pub const TRUNCATED_MARKER: &str = "...";
+
+/// `shape_only` 时代替文件（与符号链接）名称的占位符。
+pub const SHAPE_FILE: &str = "□";
+
+/// `shape_only` 时代替目录名称的占位符（其后照常带 `/`）。
+pub const SHAPE_DIR: &str = "▣";
+
+/// 树形连接线使用的字符集（`--ascii` 时为 [`TreeCharset::Ascii`]）。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TreeCharset {
+    /// Unicode 制表符：`├── `、`└── `、`│   `（默认）
+    #[default]
+    Unicode,
+    /// 纯 ASCII：`|-- `、`` `-- ``、`|   `，适用于不支持制表符的终端与日志查看器
+    Ascii,
+}
+
+impl TreeCharset {
+    /// 非最后一个子节点的连接符。
+    pub fn branch(self) -> &'static str {
+        match self {
+            TreeCharset::Unicode => "├── ",
+            TreeCharset::Ascii => "|-- ",
+        }
+    }
+
+    /// 最后一个子节点的连接符。
+    pub fn last_branch(self) -> &'static str {
+        match self {
+            TreeCharset::Unicode => "└── ",
+            TreeCharset::Ascii => "`-- ",
+        }
+    }
+
+    /// 非最后一个子节点之下的续行前缀。
+    pub fn vertical(self) -> &'static str {
+        match self {
+            TreeCharset::Unicode => "│   ",
+            TreeCharset::Ascii => "|   ",
+        }
+    }
+
+    /// 最后一个子节点之下的续行前缀。
+    pub fn blank(self) -> &'static str {
+        "    "
+    }
+
+    /// 省略条目（`--compact-levels`、`--fit-lines`）时的标记，见 [`GAP_MARKER`]。
+    pub fn gap_marker(self) -> &'static str {
+        match self {
+            TreeCharset::Unicode => GAP_MARKER,
+            TreeCharset::Ascii => "...",
+        }
+    }
+
+    /// `shape_only` 时代替文件名称的占位符，见 [`SHAPE_FILE`]。
+    pub fn shape_file(self) -> &'static str {
+        match self {
+            TreeCharset::Unicode => SHAPE_FILE,
+            TreeCharset::Ascii => "*",
+        }
+    }
+
+    /// `shape_only` 时代替目录名称的占位符，见 [`SHAPE_DIR`]。
+    pub fn shape_dir(self) -> &'static str {
+        match self {
+            TreeCharset::Unicode => SHAPE_DIR,
+            TreeCharset::Ascii => "#",
+        }
+    }
+}
+
+/// `compat_tree` 时非最后一个子节点之下的续行前缀：与 GNU `tree` 相同，竖线后是
+/// 两个不换行空格（U+00A0）与一个普通空格。
+pub const COMPAT_VERTICAL: &str = "│\u{a0}\u{a0} ";
+
+/// `compact_levels` 省略中间条目时显示的标记。
+pub const GAP_MARKER: &str = "…";
+
+/// 树形输出的可选配置。
+#[derive(Debug, Clone, Default)]
+pub struct TreeOptions {
+    /// 在文件后显示其大小
+    pub file_size: bool,
+    /// 在目录后显示其子树中的文件数（`(N files)`）
+    pub dir_count: bool,
+    /// 在目录后显示其子树中文件的总大小
+    pub dir_size: bool,
+    /// 何时使用颜色
+    pub color_mode: ColorMode,
+    /// 使用的配色方案
+    pub color_scheme: ColorScheme,
+    /// 目录的文件数注解缩写为 `12.3k files` 之类的短形式
+    pub compact_counts: bool,
+    /// `du` 式输出：目录显示其子树的总大小，代替文件数
+    pub du: bool,
+    /// ncdu 式布局：大小在左侧右对齐成一列，树在右侧；目录显示其子树的总大小
+    pub size_column: bool,
+    /// 每个目录只显示开头 `head` 个与结尾 `tail` 个子节点（`(head, tail)`），中间以
+    /// `… (N more)` 代替；`None` 表示全部显示
+    pub compact_levels: Option<(usize, usize)>,
+    /// 设置时，每个节点显示相对该目录（通常为当前工作目录）的路径而非名称
+    pub path_from_cwd: Option<PathBuf>,
+    /// 设置为 `(base, cwd)` 时，每个节点显示相对 `base` 的路径（可含 `..`），见
+    /// [`relative_path`]；优先于 `path_from_cwd`
+    pub relative_to: Option<(PathBuf, PathBuf)>,
+    /// 大小的格式化选项（`--size-unit` 固定单位等）
+    pub size: SizeOptions,
+    /// 在每个条目前显示 `[drwxr-xr-x]` 式的权限（`--perms`，需要节点带有 `mode`）
+    pub show_perms: bool,
+    /// 只保留树的形状（`--shape-only`）：名称换成 [`TreeCharset::shape_file`] /
+    /// [`TreeCharset::shape_dir`]，
+    /// 不显示大小、权限、行数、链接目标与颜色，只剩嵌套关系与条目数
+    pub shape_only: bool,
+    /// 与 GNU `tree` 的输出逐字节一致（`--compat-tree`）：根显示为给定的路径，目录名
+    /// 不带 `/`，竖线后用 `tree` 的不换行空格，未展开的目录不显示占位行，末尾附
+    /// [`compat_summary`] 的汇总行
+    pub compat_tree: bool,
+    /// `--collapse-depth`：未展开的目录后显示其 `collapsed_summary`，代替 `...` 占位行
+    pub collapse_summaries: bool,
+    /// 连接线字符集（`--ascii`）
+    pub charset: TreeCharset,
+}
+
+/// `--show-filtered` 的页脚：报告被过滤器排除的文件与目录数。
+pub fn format_filtered(filtered: &FilteredCounts) -> String {
+    format!(
+        "{} {} and {} {} filtered out",
+        filtered.files,
+        if filtered.files == 1 { "file" } else { "files" },
+        filtered.directories,
+        if filtered.directories == 1 {
+            "directory"
+        } else {
+            "directories"
+        }
+    )
+}
+
+/// `--report-excluded-ext` 的页脚：被 `--ext` 隐藏的文件按扩展名计数，
+/// 数量多的在前，例如 `hidden by --ext: 3 .md, 1 .txt`。
+pub fn format_excluded_extensions(filtered: &FilteredCounts) -> String {
+    if filtered.by_extension.is_empty() {
+        return "hidden by --ext: none".to_string();
+    }
+
+    let mut counts: Vec<(&String, &usize)> = filtered.by_extension.iter().collect();
+    counts.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+    let parts: Vec<String> = counts
+        .iter()
+        .map(|(ext, count)| format!("{} {}", count, ext))
+        .collect();
+    format!("hidden by --ext: {}", parts.join(", "))
+}
+
+/// `--duplicates` 的页脚：每组重复文件一行，浪费空间多的在前，例如
+///
+/// ```text
+/// duplicate files: 1 group, 120 B wasted
+///   120 B × 2: ./a.txt, ./b/a.txt
+/// ```
+///
+/// 大小按 `size` 格式化（`--size-unit` 等）。
+pub fn format_duplicates(groups: &[Vec<FileEntry>], size: &SizeOptions) -> String {
+    if groups.is_empty() {
+        return "duplicate files: none".to_string();
+    }
+
+    let size = |bytes| format_size(bytes, size);
+    let mut output = format!(
+        "duplicate files: {} {}, {} wasted",
+        groups.len(),
+        if groups.len() == 1 { "group" } else { "groups" },
+        size(wasted_bytes(groups))
+    );
+    for group in groups {
+        let paths: Vec<String> = group.iter().map(|f| f.path.display().to_string()).collect();
+        // 零字节文件组（--duplicates-empty group）明确标出，以免被当作真正的重复。
+        let label = match group.first().map_or(0, |f| f.size) {
+            0 => EMPTY_FILES_GROUP.to_string(),
+            bytes => size(bytes),
+        };
+        output.push_str(&format!(
+            "\n  {} × {}: {}",
+            label,
+            group.len(),
+            paths.join(", ")
+        ));
+    }
+    output
+}
+
+/// `--list-empty-dirs` 的页脚：每个空目录一行，先序排列；目录在磁盘上有文件、
+/// 只是全部被过滤掉时加注，例如
+///
+/// ```text
+/// empty directories: 2
+///   ./cache
+///   ./docs (empty after filtering)
+/// ```
+pub fn format_empty_directories(dirs: &[EmptyDirectory]) -> String {
+    if dirs.is_empty() {
+        return "empty directories: none".to_string();
+    }
+
+    let mut output = format!("empty directories: {}", dirs.len());
+    for dir in dirs {
+        output.push_str(&format!("\n  {}", dir.path.display()));
+        if dir.filtered {
+            output.push_str(" (empty after filtering)");
+        }
+    }
+    output
+}
 
 /// 使用 Unicode 制表符将文件树格式化为树形结构。
 ///
@@ -23,76 +256,322 @@ pub fn format_tree(
     color_mode: ColorMode,
     color_scheme: ColorScheme,
 ) -> String {
-    let mut output = String::new();
+    // `show_size` 对应 `--size` 的常用组合：文件大小 + 目录文件数。
+    let options = TreeOptions {
+        file_size: show_size,
+        dir_count: show_size,
+        color_mode,
+        color_scheme,
+        ..Default::default()
+    };
+    format_tree_with(node, &options)
+}
 
-    // 打印根目录并着色
-    let root_name = if should_use_colors(color_mode) {
-        colorize_node(node, color_scheme).to_string()
-    } else {
-        node.name.clone()
+/// 按给定选项格式化文件树。
+///
+/// 与 [`format_tree`] 相同，但可通过 `options.path_from_cwd` 让节点显示相对路径。
+pub fn format_tree_with(node: &FsNode, options: &TreeOptions) -> String {
+    let mut buffer = Vec::new();
+    format_tree_to(node, options, &mut buffer).expect("writing to a Vec<u8> cannot fail");
+    // 所有标签都来自 String 或 to_string_lossy，必然是合法 UTF-8。
+    String::from_utf8(buffer).expect("tree output is valid UTF-8")
+}
+
+/// 将文件树逐行写入 `writer`，而不是先拼成一个完整的 `String`。
+///
+/// 输出与 [`format_tree_with`] 逐字节一致。配合 `BufWriter` 使用时首行可以立即
+/// 出现，且不再需要为整棵树的文本额外分配一块与输出等大的内存。
+///
+/// # 错误
+///
+/// 返回 `writer` 产生的 IO 错误（例如管道被关闭）。
+pub fn format_tree_to<W: Write>(
+    node: &FsNode,
+    options: &TreeOptions,
+    writer: &mut W,
+) -> io::Result<()> {
+    if options.size_column && !options.shape_only {
+        return format_size_column_to(node, options, writer);
+    }
+    emit_lines(node, options, &mut |_, line| writeln!(writer, "{}", line))?;
+    if options.compat_tree {
+        writeln!(writer, "\n{}", compat_summary(node))?;
+    }
+    Ok(())
+}
+
+/// GNU `tree` 的汇总行：`N directories, M files`（单数时为 `directory` / `file`）。
+///
+/// 只计入树中显示的条目，根目录本身不计。与 `tree` 相同，指向目录的符号链接计为
+/// 目录（按链接目标判断，目标不存在时计为文件），其余符号链接计为文件。
+pub fn compat_summary(root: &FsNode) -> String {
+    let (mut dirs, mut files) = (0usize, 0usize);
+    let mut stack: Vec<&FsNode> = root.children.iter().flatten().collect();
+    while let Some(node) = stack.pop() {
+        if node.is_directory() {
+            dirs += 1;
+            stack.extend(node.children.iter().flatten());
+        } else if node.is_symlink() && links_to_directory(node) {
+            dirs += 1;
+        } else {
+            files += 1;
+        }
+    }
+    format!(
+        "{} {}, {} {}",
+        dirs,
+        if dirs == 1 {
+            "directory"
+        } else {
+            "directories"
+        },
+        files,
+        if files == 1 { "file" } else { "files" }
+    )
+}
+
+/// 符号链接节点的目标是否为目录（跟随链接读取元数据）。
+fn links_to_directory(node: &FsNode) -> bool {
+    node.path
+        .as_ref()
+        .and_then(|path| std::fs::metadata(path).ok())
+        .is_some_and(|meta| meta.is_dir())
+}
+
+/// `size_column` 布局：先收集所有行及其大小，再按最宽的大小右对齐输出。
+///
+/// 文件显示 `size`，目录显示其子树总大小（[`FsNode::aggregate_size`]），符号链接与
+/// 截断占位行的大小列留空。
+fn format_size_column_to<W: Write>(
+    node: &FsNode,
+    options: &TreeOptions,
+    writer: &mut W,
+) -> io::Result<()> {
+    let mut rows: Vec<(String, String)> = Vec::new();
+    emit_lines(node, options, &mut |source, line| {
+        let size = match source {
+            Some(n) if n.is_directory() => format_size(n.aggregate_size(), &options.size),
+            Some(n) if !n.is_symlink() => format_size(n.size, &options.size),
+            _ => String::new(),
+        };
+        rows.push((size, line.to_string()));
+        Ok(())
+    })?;
+
+    let width = rows.iter().map(|(size, _)| size.len()).max().unwrap_or(0);
+    for (size, line) in rows {
+        writeln!(writer, "{:>width$}  {}", size, line, width = width)?;
+    }
+    Ok(())
+}
+
+/// 按先序逐行生成树的文本（不含换行），连同产生该行的节点一起交给 `emit`。
+///
+/// 截断占位行与折叠摘要行没有对应节点，此时节点为 `None`。
+fn emit_lines<F>(node: &FsNode, options: &TreeOptions, emit: &mut F) -> io::Result<()>
+where
+    F: FnMut(Option<&FsNode>, &str) -> io::Result<()>,
+{
+    // 目录的文件数 / 总大小注解只在需要时预先汇总一次。
+    let mut all_totals = Vec::new();
+    if (options.dir_count || options.dir_size || options.du) && !options.size_column {
+        node.subtree_summaries(&mut all_totals);
+    }
+    let mut totals = all_totals.iter();
+
+    // 打印根目录并着色；GNU `tree` 原样显示给定的路径。
+    let root_label = match (&node.path, options.compat_tree) {
+        (Some(path), true) => path.display().to_string(),
+        _ => node_label(node, options),
     };
+    let root_name = perms_prefix(node, options) + &root_label;
+    let root_totals = next_totals(node, &mut totals);
+    let mut size_str = size_annotation(node, options, root_totals, true);
+    if !options.shape_only {
+        size_str.push_str(&line_annotation(node.line_count));
+        size_str.push_str(&author_annotation(node, options));
+    }
 
-    let size_str = if show_size && node.is_directory() {
-        format!(" ({} files)", count_files_recursive(node))
-    } else if show_size && node.size > 0 {
-        format!(" ({})", format_size_impl(node.size))
+    // 文件根（单节点树）不带尾随 `/`。
+    let suffix = if node.is_directory() && !options.compat_tree {
+        "/"
     } else {
-        String::new()
+        ""
     };
-
-    output.push_str(&format!("{}{}/\n", root_name, size_str));
+    emit(Some(node), &format!("{}{}{}", root_name, suffix, size_str))?;
+    if node.collapsed > 0 {
+        emit(None, &collapsed_line("", node.collapsed, options.charset))?;
+    }
 
     // 打印子节点并附带树形前缀
-    if let Some(children) = &node.children {
-        let last_index = children.len().saturating_sub(1);
-        for (i, child) in children.iter().enumerate() {
-            format_node_recursive(
-                child,
-                "",
-                i == last_index,
-                show_size,
-                color_mode,
-                color_scheme,
-                &mut output,
-            );
+    let children = node.children.as_deref().unwrap_or_default();
+    format_children(children, node.omitted, "", options, &mut totals, emit)?;
+
+    Ok(())
+}
+
+/// 格式化一个目录的子节点列表。
+///
+/// 设置了 `compact_levels` 且子节点多于 `head + tail` 个时，只显示开头 `head` 个与
+/// 结尾 `tail` 个，中间以一行 `… (N more)` 代替。`omitted` 为遍历时因条目上限
+/// 略去的子节点数，非 0 时在最后补一行 `... (M more)`。
+fn format_children<F>(
+    children: &[FsNode],
+    omitted: usize,
+    prefix: &str,
+    options: &TreeOptions,
+    totals: &mut std::slice::Iter<'_, SubtreeSummary>,
+    emit: &mut F,
+) -> io::Result<()>
+where
+    F: FnMut(Option<&FsNode>, &str) -> io::Result<()>,
+{
+    let (head, hidden, tail) = match options.compact_levels {
+        Some((head, tail)) if children.len() > head + tail => (
+            &children[..head],
+            &children[head..children.len() - tail],
+            &children[children.len() - tail..],
+        ),
+        _ => (children, &children[..0], &children[..0]),
+    };
+
+    let gap_is_last = tail.is_empty() && omitted == 0;
+    for (i, child) in head.iter().enumerate() {
+        let is_last = hidden.is_empty() && i + 1 == head.len() && omitted == 0;
+        format_node_recursive(child, prefix, is_last, options, totals, emit)?;
+    }
+
+    if !hidden.is_empty() {
+        let connector = if gap_is_last {
+            options.charset.last_branch()
+        } else {
+            options.charset.branch()
+        };
+        emit(
+            None,
+            &format!(
+                "{}{}{} ({} more)",
+                prefix,
+                connector,
+                options.charset.gap_marker(),
+                hidden.len()
+            ),
+        )?;
+        // 被省略的子树中的目录同样占有预先汇总的位置，需一并跳过。
+        let skipped: usize = hidden.iter().map(count_dirs).sum();
+        if skipped > 0 {
+            totals.nth(skipped - 1);
         }
     }
 
-    output
+    for (i, child) in tail.iter().enumerate() {
+        let is_last = i + 1 == tail.len() && omitted == 0;
+        format_node_recursive(child, prefix, is_last, options, totals, emit)?;
+    }
+
+    if omitted > 0 {
+        emit(
+            None,
+            &format!(
+                "{}{}{} ({} more)",
+                prefix,
+                options.charset.last_branch(),
+                TRUNCATED_MARKER,
+                omitted
+            ),
+        )?;
+    }
+
+    Ok(())
+}
+
+/// `--git-authors` 的作者注解：` (alice)`，着色时变暗显示。
+fn author_annotation(node: &FsNode, options: &TreeOptions) -> String {
+    match &node.git_author {
+        Some(author) if should_use_colors(options.color_mode) => {
+            format!(" {}", format!("({})", author).dimmed())
+        }
+        Some(author) => format!(" ({})", author),
+        None => String::new(),
+    }
+}
+
+/// `--collapse-depth` 折叠目录的汇总注解：` [collapsed: 42 files, 10 MB]`。
+pub fn collapsed_annotation(summary: SubtreeSummary, size: &SizeOptions) -> String {
+    format!(
+        " [collapsed: {} {}, {}]",
+        summary.files,
+        if summary.files == 1 { "file" } else { "files" },
+        format_size(summary.size, size)
+    )
+}
+
+/// `--fit-lines` 折叠目录的摘要行，代替其全部子节点。
+fn collapsed_line(prefix: &str, hidden: usize, charset: TreeCharset) -> String {
+    format!(
+        "{}{}{} ({} {} collapsed)",
+        prefix,
+        charset.last_branch(),
+        charset.gap_marker(),
+        hidden,
+        if hidden == 1 { "entry" } else { "entries" }
+    )
+}
+
+/// 子树中（含自身）的目录数。
+fn count_dirs(node: &FsNode) -> usize {
+    if !node.is_directory() {
+        return 0;
+    }
+    1 + node
+        .children
+        .iter()
+        .flatten()
+        .map(count_dirs)
+        .sum::<usize>()
 }
 
 /// 递归地格式化节点并附带相应的树形前缀。
-fn format_node_recursive(
+fn format_node_recursive<F>(
     node: &FsNode,
     prefix: &str,
     is_last: bool,
-    show_size: bool,
-    color_mode: ColorMode,
-    color_scheme: ColorScheme,
-    output: &mut String,
-) {
+    options: &TreeOptions,
+    totals: &mut std::slice::Iter<'_, SubtreeSummary>,
+    emit: &mut F,
+) -> io::Result<()>
+where
+    F: FnMut(Option<&FsNode>, &str) -> io::Result<()>,
+{
     // 确定连接符和下一个前缀
+    let charset = options.charset;
     let (connector, next_prefix_base) = if is_last {
-        ("└── ", "    ")
+        (charset.last_branch(), charset.blank())
+    } else if options.compat_tree && charset == TreeCharset::Unicode {
+        (charset.branch(), COMPAT_VERTICAL)
     } else {
-        ("├── ", "│   ")
+        (charset.branch(), charset.vertical())
     };
 
     let next_prefix = format!("{}{}", prefix, next_prefix_base);
 
     // 构建节点标签并着色
-    let use_color = should_use_colors(color_mode);
-    let name = if use_color {
-        colorize_node(node, color_scheme).to_string()
-    } else {
-        node.name.clone()
-    };
-
-    let mut label = name;
+    let mut label = perms_prefix(node, options);
+    label.push_str(&node_label(node, options));
 
     // 添加目录指示符
     if node.is_directory() {
-        label.push('/');
+        if !options.compat_tree {
+            label.push('/');
+        }
+        if !options.shape_only {
+            push_link_marker(node, &mut label);
+        }
+    } else if options.shape_only {
+        // 链接目标同样会泄露名称。
+    } else if let Some(target) = &node.link_target {
+        label.push_str(" -> ");
+        label.push_str(&target.to_string_lossy());
     } else if node.is_symlink() {
         label.push_str(" -> ");
         if let Some(path) = &node.path {
@@ -103,53 +582,265 @@ fn format_node_recursive(
     }
 
     // 如有需要，添加大小信息
-    if show_size && node.is_file() && node.size > 0 {
-        label.push_str(&format!(" ({})", format_size_impl(node.size)));
-    } else if show_size && node.is_directory() {
-        let file_count = count_files_recursive(node);
-        if file_count > 0 {
-            label.push_str(&format!(" ({} files)", file_count));
-        }
+    let node_totals = next_totals(node, totals);
+    label.push_str(&size_annotation(node, options, node_totals, false));
+    let summary = node
+        .collapsed_summary
+        .filter(|_| options.collapse_summaries && !options.shape_only);
+    if let Some(summary) = summary {
+        label.push_str(&collapsed_annotation(summary, &options.size));
+    }
+    if !options.shape_only {
+        label.push_str(&line_annotation(node.line_count));
+        label.push_str(&author_annotation(node, options));
     }
 
-    output.push_str(&format!("{}{}{}\n", prefix, connector, label));
+    emit(Some(node), &format!("{}{}{}", prefix, connector, label))?;
+
+    // 因深度限制未展开的非空目录：补一个占位子节点，与“空目录”区分开。
+    if node.truncated && !options.compat_tree && summary.is_none() {
+        emit(
+            None,
+            &format!(
+                "{}{}{}",
+                next_prefix,
+                charset.last_branch(),
+                TRUNCATED_MARKER
+            ),
+        )?;
+    }
+    if node.collapsed > 0 {
+        emit(
+            None,
+            &collapsed_line(&next_prefix, node.collapsed, options.charset),
+        )?;
+    }
 
     // 打印子节点
-    if let Some(children) = &node.children {
-        let last_index = children.len().saturating_sub(1);
-        for (i, child) in children.iter().enumerate() {
-            format_node_recursive(
-                child,
-                &next_prefix,
-                i == last_index,
-                show_size,
-                color_mode,
-                color_scheme,
-                output,
-            );
+    let children = node.children.as_deref().unwrap_or_default();
+    format_children(children, node.omitted, &next_prefix, options, totals, emit)?;
+
+    Ok(())
+}
+
+/// 节点的显示文本（名称，或 `--relative-to` / `--path-from-cwd` 下的相对路径），按需着色。
+fn node_label(node: &FsNode, options: &TreeOptions) -> String {
+    if options.shape_only {
+        let placeholder = if node.is_directory() {
+            options.charset.shape_dir()
+        } else {
+            options.charset.shape_file()
+        };
+        return placeholder.to_string();
+    }
+
+    let text = match (&options.relative_to, &options.path_from_cwd, &node.path) {
+        (Some((base, cwd)), _, Some(path)) => relative_path(path, base, cwd).display().to_string(),
+        (None, Some(cwd), Some(path)) => path_from_cwd(path, cwd).display().to_string(),
+        _ => node.name.clone(),
+    };
+
+    if should_use_colors(options.color_mode) {
+        colorize_label(node, &text, options.color_scheme).to_string()
+    } else {
+        text
+    }
+}
+
+/// 计算 `path` 相对 `cwd` 的显示路径。
+///
+/// 相对路径先按 `cwd` 补全为绝对路径并按字面消去 `.`/`..` 再比较。不在 `cwd`
+/// 之下的节点（相对路径需要以 `..` 开头）退回显示该绝对路径；`cwd` 自身显示为 `.`。
+pub fn path_from_cwd(path: &Path, cwd: &Path) -> PathBuf {
+    let absolute = normalize_lexically(&if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        cwd.join(path)
+    });
+
+    match pathdiff::diff_paths(&absolute, cwd) {
+        Some(rel) if rel.as_os_str().is_empty() => PathBuf::from("."),
+        Some(rel) if !matches!(rel.components().next(), Some(Component::ParentDir)) => rel,
+        _ => absolute,
+    }
+}
+
+/// 按字面消去路径中的 `.` 与 `..`，不访问文件系统（因此不解析符号链接）。
+/// 根目录之上的 `..` 被丢弃；相对路径开头无法消去的 `..` 原样保留。
+fn normalize_lexically(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => match normalized.components().next_back() {
+                Some(Component::Normal(_)) => {
+                    normalized.pop();
+                }
+                Some(Component::RootDir | Component::Prefix(_)) => {}
+                _ => normalized.push(".."),
+            },
+            other => normalized.push(other),
         }
     }
+    normalized
+}
+
+/// 计算 `path` 相对任意基准目录 `base` 的路径（`--relative-to`）。
+///
+/// 相对的 `path` 与 `base` 都先按 `cwd` 补全为绝对路径。与 [`path_from_cwd`] 不同，
+/// 结果可以以 `..` 开头（`base` 为兄弟目录时得到 `../src/main.rs`）；只有当两者除
+/// 根目录外没有共同的祖先（或根本无法相对表示）时才退回绝对路径，免得输出一串
+/// 直达根目录的 `../../..`。`base` 自身显示为 `.`。
+pub fn relative_path(path: &Path, base: &Path, cwd: &Path) -> PathBuf {
+    let absolute = cwd.join(path);
+    let base = cwd.join(base);
+
+    let related = absolute.starts_with(&base)
+        || absolute
+            .components()
+            .zip(base.components())
+            .take_while(|(a, b)| a == b)
+            .any(|(component, _)| matches!(component, Component::Normal(_)));
+    if !related {
+        return absolute;
+    }
+
+    match pathdiff::diff_paths(&absolute, &base) {
+        Some(rel) if rel.as_os_str().is_empty() => PathBuf::from("."),
+        Some(rel) => rel,
+        None => absolute,
+    }
+}
+
+/// 为经由被跟随符号链接到达的目录追加 ` -> target/`，循环时追加 `[loop]`。
+fn push_link_marker(node: &FsNode, label: &mut String) {
+    if let Some(target) = &node.link_target {
+        label.push_str(" -> ");
+        label.push_str(&target.to_string_lossy());
+        label.push('/');
+    }
+    if node.is_loop {
+        label.push_str(" [loop]");
+    }
 }
 
 /// 将字节数格式化为人类可读的字符串。
 #[doc(hidden)]
 pub fn format_size_impl(bytes: u64) -> String {
-    format_size(bytes, humansize::DECIMAL)
+    format_size(bytes, &SizeOptions::default())
+}
+
+/// 先序遍历到目录时取出其预先汇总的 [`SubtreeSummary`]；文件与符号链接不占位。
+fn next_totals<'a>(
+    node: &FsNode,
+    totals: &mut std::slice::Iter<'a, SubtreeSummary>,
+) -> Option<&'a SubtreeSummary> {
+    if node.is_directory() {
+        totals.next()
+    } else {
+        None
+    }
 }
 
-/// 统计子树中的所有文件（递归）。
-fn count_files_recursive(node: &FsNode) -> usize {
-    let mut count = 0;
+/// 节点名后的大小注解，如 ` (2 KiB)`、` (3 files, 2 KiB)`；无内容时为空串。
+///
+/// 文件按 `file_size` 显示大小（0 字节不显示）。目录按 `dir_count` 显示文件数
+/// （子目录为空时省略，根目录总是显示），按 `dir_size` 显示子树总大小；两者都取自
+/// 预先汇总的 `totals`。
+fn size_annotation(
+    node: &FsNode,
+    options: &TreeOptions,
+    totals: Option<&SubtreeSummary>,
+    is_root: bool,
+) -> String {
+    // 大小已在左侧单独成列；只显示形状时不显示大小。
+    if options.size_column || options.shape_only {
+        return String::new();
+    }
+    let mut parts = Vec::new();
 
-    if let Some(children) = &node.children {
-        for child in children {
-            if child.is_file() {
-                count += 1;
-            } else if child.is_directory() {
-                count += count_files_recursive(child);
-            }
+    if node.is_directory() && options.du {
+        let totals = totals.copied().unwrap_or_default();
+        parts.push(format_size(totals.size, &options.size));
+    } else if node.is_directory() {
+        let totals = totals.copied().unwrap_or_default();
+        if options.dir_count && (is_root || totals.files > 0) {
+            parts.push(format!(
+                "{} files",
+                format_file_count(totals.files, options)
+            ));
         }
+        if options.dir_size {
+            parts.push(format_size(totals.size, &options.size));
+        }
+    } else if options.file_size && node.size > 0 {
+        parts.push(format_size(node.size, &options.size));
+    }
+
+    if parts.is_empty() {
+        String::new()
+    } else {
+        format!(" ({})", parts.join(", "))
+    }
+}
+
+/// 将权限位渲染为 `ls -l` 式的九个字符，如 `0o755` → `rwxr-xr-x`。
+///
+/// setuid / setgid / sticky 位分别显示在属主、属组、其他人的执行位上
+/// （`s`/`S`、`s`/`S`、`t`/`T`）；文件类型位被忽略。
+pub fn format_mode(mode: u32) -> String {
+    let mut out = String::with_capacity(9);
+    let special = [(0o4000, 's', 'S'), (0o2000, 's', 'S'), (0o1000, 't', 'T')];
+    for (i, (bit, exec_set, noexec_set)) in special.into_iter().enumerate() {
+        let bits = (mode >> (6 - 3 * i)) & 0o7;
+        out.push(if bits & 0o4 != 0 { 'r' } else { '-' });
+        out.push(if bits & 0o2 != 0 { 'w' } else { '-' });
+        out.push(match (bits & 0o1 != 0, mode & bit != 0) {
+            (true, true) => exec_set,
+            (false, true) => noexec_set,
+            (true, false) => 'x',
+            (false, false) => '-',
+        });
+    }
+    out
+}
+
+/// `--perms` 时条目前的权限前缀，如 `[drwxr-xr-x]  `；首字符为类型（`d`、`l`、`-`）。
+/// 节点没有 `mode`（非 Unix 平台）时为空串。
+pub fn perms_annotation(node_type: &FsNodeType, mode: Option<u32>) -> String {
+    let Some(mode) = mode else {
+        return String::new();
+    };
+    let kind = match node_type {
+        FsNodeType::Directory => 'd',
+        FsNodeType::Symlink => 'l',
+        FsNodeType::File => '-',
+    };
+    format!("[{}{}]  ", kind, format_mode(mode))
+}
+
+fn perms_prefix(node: &FsNode, options: &TreeOptions) -> String {
+    if options.show_perms && !options.shape_only {
+        perms_annotation(&node.node_type, node.mode)
+    } else {
+        String::new()
     }
+}
+
+/// `--count-lines` 时文件名后的行数注解，如 ` [123 lines]`；没有行数时为空串。
+pub fn line_annotation(line_count: Option<usize>) -> String {
+    match line_count {
+        Some(1) => " [1 line]".to_string(),
+        Some(lines) => format!(" [{} lines]", lines),
+        None => String::new(),
+    }
+}
 
-    count
+/// 按选项格式化目录的文件数注解。
+fn format_file_count(count: usize, options: &TreeOptions) -> String {
+    if options.compact_counts {
+        format_count_compact(count as u64)
+    } else {
+        count.to_string()
+    }
 }
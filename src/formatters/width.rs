@@ -0,0 +1,182 @@
+//! 按终端宽度裁剪输出行（`--width`、`--scroll-indicator`）。
+//!
+//! 宽度按显示列计算：ANSI 颜色转义序列不占列，CJK 等宽字符占两列。被裁剪的行
+//! 在启用滚动标记时以 [`SCROLL_INDICATOR`] 结尾，表明该行右侧还有内容。
+
+use std::borrow::Cow;
+use std::io::{self, Write};
+use unicode_width::UnicodeWidthChar;
+
+/// 超宽行末尾的滚动标记。
+pub const SCROLL_INDICATOR: char = '→';
+
+/// 将一行（不含换行符）裁剪到 `width` 列。
+///
+/// 不超宽的行原样返回。超宽时保留能放下的前缀；`indicator` 为真时为
+/// [`SCROLL_INDICATOR`] 预留最后一列。被裁掉部分中的 ANSI 转义序列仍会保留，
+/// 以免颜色状态泄漏到后续行。
+pub fn clip_line(line: &str, width: usize, indicator: bool) -> Cow<'_, str> {
+    if display_width(line) <= width {
+        return Cow::Borrowed(line);
+    }
+
+    let budget = if indicator {
+        width.saturating_sub(1)
+    } else {
+        width
+    };
+    let mut clipped = String::with_capacity(line.len());
+    let mut used = 0;
+    let mut full = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            clipped.push(c);
+            copy_escape(&mut chars, &mut clipped);
+            continue;
+        }
+        let w = c.width().unwrap_or(0);
+        if !full && used + w <= budget {
+            clipped.push(c);
+            used += w;
+        } else if !full {
+            full = true;
+            if indicator && width > 0 {
+                clipped.push(SCROLL_INDICATOR);
+            }
+        }
+    }
+
+    Cow::Owned(clipped)
+}
+
+/// 左侧被截断的路径开头的省略号。
+pub const PATH_ELLIPSIS: char = '…';
+
+/// 将路径从左侧截断到 `max_width` 列，保留有意义的尾部（文件名）。
+///
+/// 不超宽的路径原样返回。超宽时输出 `…/deep/path/file.bin`：尽量从某个 `/`
+/// 处开始保留，使第一段是完整的目录名；若文件名本身就放不下，则保留其尾部。
+pub fn truncate_path_left(path: &str, max_width: usize) -> Cow<'_, str> {
+    if display_width(path) <= max_width {
+        return Cow::Borrowed(path);
+    }
+    if max_width == 0 {
+        return Cow::Borrowed("");
+    }
+
+    // 从末尾向前收集能放进 `max_width - 1` 列（给省略号留一列）的最长后缀。
+    let budget = max_width - 1;
+    let mut used = 0;
+    let mut start = path.len();
+    for (i, c) in path.char_indices().rev() {
+        let w = c.width().unwrap_or(0);
+        if used + w > budget {
+            break;
+        }
+        used += w;
+        start = i;
+    }
+    let tail = &path[start..];
+
+    // 后缀中间含 `/` 时从该处开始，避免留下半截目录名。
+    let tail = match tail.find('/') {
+        Some(pos) if pos > 0 => &tail[pos..],
+        _ => tail,
+    };
+    Cow::Owned(format!("{}{}", PATH_ELLIPSIS, tail))
+}
+
+/// 计算一行的显示列数（忽略 ANSI 转义序列）。
+pub fn display_width(line: &str) -> usize {
+    let mut width = 0;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            copy_escape(&mut chars, &mut String::new());
+        } else {
+            width += c.width().unwrap_or(0);
+        }
+    }
+    width
+}
+
+/// 复制 `ESC` 之后的 CSI 序列（`[` 参数… 终止字母）到 `out`。
+fn copy_escape<I: Iterator<Item = char>>(chars: &mut std::iter::Peekable<I>, out: &mut String) {
+    if chars.peek() != Some(&'[') {
+        return;
+    }
+    for c in chars.by_ref() {
+        out.push(c);
+        if c.is_ascii_alphabetic() {
+            break;
+        }
+    }
+}
+
+/// 逐行裁剪写入内容的 `Write` 包装器。
+///
+/// `width` 为 `None` 时原样透传。内容按行缓冲，遇到换行才裁剪并写出；
+/// 结束时须调用 [`ClipWriter::finish`] 写出末尾不带换行的残行。
+pub struct ClipWriter<W: Write> {
+    inner: W,
+    width: Option<usize>,
+    indicator: bool,
+    line: Vec<u8>,
+}
+
+impl<W: Write> ClipWriter<W> {
+    /// 创建包装器；`indicator` 为真时超宽行以 [`SCROLL_INDICATOR`] 结尾。
+    pub fn new(inner: W, width: Option<usize>, indicator: bool) -> Self {
+        Self {
+            inner,
+            width,
+            indicator,
+            line: Vec::new(),
+        }
+    }
+
+    /// 写出缓冲中的残行并返回内部 writer。
+    pub fn finish(mut self) -> io::Result<W> {
+        if !self.line.is_empty() {
+            self.write_line(false)?;
+        }
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+
+    fn write_line(&mut self, newline: bool) -> io::Result<()> {
+        let width = self.width.unwrap_or(usize::MAX);
+        let text = String::from_utf8_lossy(&self.line);
+        let clipped = clip_line(&text, width, self.indicator);
+        self.inner.write_all(clipped.as_bytes())?;
+        if newline {
+            self.inner.write_all(b"\n")?;
+        }
+        self.line.clear();
+        Ok(())
+    }
+}
+
+impl<W: Write> Write for ClipWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.width.is_none() {
+            return self.inner.write(buf);
+        }
+        for chunk in buf.split_inclusive(|&b| b == b'\n') {
+            match chunk.split_last() {
+                Some((b'\n', text)) => {
+                    self.line.extend_from_slice(text);
+                    self.write_line(true)?;
+                }
+                _ => self.line.extend_from_slice(chunk),
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
@@ -21,40 +21,196 @@ pub mod core;
 pub mod formatters;
 
 // 重新导出常用类型
-pub use config::{ColorMode, ColorScheme, Config, OutputFormat, SortBy};
+pub use config::{
+    ColorBy, ColorMode, ColorScheme, Command, Config, CsvField, DuplicatesEmpty, FitStrategy,
+    OutputFormat, SortBy,
+};
 pub use core::{
-    collector::{collect_stats, get_all_directories, get_all_files},
-    models::{FileEntry, FileTypeInfo, FsNode, FsNodeType, FsTree, TreeError, TreeStats},
-    walker::{walk_directory, SortField, WalkConfig},
+    archive::{read_tar, TAR_ROOT_NAME},
+    classifier::FileClassifier,
+    collector::{
+        classify_empty_directories, collect_stats, collect_stats_with, find_empty_directories,
+        get_all_directories, get_all_files, EmptyDirectory, StatsOptions,
+    },
+    diff::{diff_stats, diff_trees, DiffEntry, DiffKind},
+    git::{apply_git_authors, changed_paths_since, find_git_dir, git_ratio, last_authors},
+    models::{
+        CategoryInfo, FileEntry, FileTypeInfo, FilteredCounts, FsNode, FsNodeType, FsTree,
+        GitFileCount, GitRatio, StatsDelta, SubtreeSummary, TreeError, TreeStats,
+    },
+    snapshot::{load_snapshot, load_stats, save_snapshot},
+    walker::{
+        natural_cmp, prune_to_matches, retain_dirs_containing, retain_paths, walk_directory,
+        MetadataFields, SortField, WalkConfig,
+    },
+};
+pub use formatters::{
+    format_csv, format_diff, format_html, format_json, format_manifest, format_markdown,
+    format_names, format_outline, format_prometheus, format_table, format_tree, format_tree_to,
+    TreeCharset, TreeOptions,
 };
-pub use formatters::{format_json, format_table, format_tree};
 
+use crate::core::fit::fit_lines;
 use crate::core::progress::{
     create_progress_bar, finish_progress, update_progress, ProgressConfig,
 };
+use crate::formatters::csv::format_csv_to;
+use crate::formatters::diff::format_stats_delta;
+use crate::formatters::manifest::format_manifest_to;
+use crate::formatters::markdown::format_markdown_to;
+use crate::formatters::names::format_names_to;
+use crate::formatters::ndjson::write_ndjson;
+use crate::formatters::outline::format_outline_to;
+use crate::formatters::table::format_git_ratio_table;
+use crate::formatters::table::format_table_with;
+use crate::formatters::tree::{
+    format_duplicates, format_empty_directories, format_excluded_extensions, format_filtered,
+    relative_path,
+};
+use crate::formatters::width::ClipWriter;
 use std::io::{self, Write};
+use std::path::Path;
 use std::time::Instant;
 
 /// 使用给定配置运行 rust-tree 工具。
 ///
-/// 这是本库的主入口。它执行以下步骤：
+/// 这是本库的主入口。它按子命令分派到各自的处理函数；
+/// 未给出子命令时等同于 `scan`：
 /// 1. 遍历目录树
 /// 2. 收集统计信息
 /// 3. 格式化并输出结果
 ///
+/// 输出写往 stdout 或 `--output` 给出的文件；写往其他目标时使用 [`run_to`]，
+/// 需要以字符串取得输出时使用 [`render`]。
+///
 /// # 错误
 ///
 /// 如果目录遍历失败或输出格式化失败，则返回 `TreeError`。
 pub fn run(config: Config) -> Result<(), TreeError> {
+    let mut out = open_output(&config);
+    run_to(config, &mut out)
+}
+
+/// 与 [`run`] 执行相同的流程，但把输出写入 `out`（忽略 `--output`）。
+///
+/// `--stats-file` 等写往单独文件的选项照常生效；`--running-totals` 与进度条
+/// 仍写往 stderr。
+///
+/// # 错误
+///
+/// 如果目录遍历失败、输出格式化失败或写入 `out` 失败，则返回 `TreeError`。
+pub fn run_to(mut config: Config, out: &mut dyn Write) -> Result<(), TreeError> {
+    // --print-config：仅打印生效配置并退出，不扫描；放在所有校验之前，
+    // 以便排查“为什么某个 flag 没有生效”。
+    if config.print_config {
+        return print_output(out, &format!("{}\n", config.to_json()?));
+    }
+    if config.print_schema {
+        let schema = serde_json::to_string_pretty(&formatters::json::json_schema())?;
+        return print_output(out, &format!("{}\n", schema));
+    }
+
+    // -O：颜色码只对终端有意义，写入文件时除非显式 --color always，否则不着色。
+    if config.output.is_some() && config.color_mode == ColorMode::Auto {
+        config.color_mode = ColorMode::Never;
+    }
+
+    match config.command.take() {
+        None => run_scan(config, out),
+        Some(Command::Scan { path }) => {
+            if let Some(path) = path {
+                config.path = path;
+            }
+            run_scan(config, out)
+        }
+        Some(Command::Stats { path }) => {
+            if let Some(path) = path {
+                config.path = path;
+            }
+            run_stats(config, out)
+        }
+        Some(Command::Snapshot { out, path }) => {
+            if let Some(path) = path {
+                config.path = path;
+            }
+            run_snapshot(config, &out)
+        }
+        Some(Command::Restore { input }) => run_restore(config, &input, out),
+        Some(Command::Diff { old, new }) => run_diff(config, &old, &new, out),
+    }
+}
+
+/// 与 [`run`] 执行相同的流程，但把输出作为字符串返回，而不是写往 stdout 或 `--output`。
+///
+/// 适合嵌入到其他程序（如生成树形 HTML / JSON 的 Web 服务）中使用。`--color auto`
+/// 按不着色处理；`--stats-file` 等写往单独文件的选项照常生效。
+///
+/// # 错误
+///
+/// 与 [`run`] 相同。流式输出（`--streaming`、`-f ndjson`）边遍历边写出，不在此缓冲，
+/// 返回 `TreeError::Other`；这类输出请使用接收 writer 的
+/// [`format_tree_streaming_with`](formatters::streaming_tree::format_tree_streaming_with)
+/// 与 [`write_ndjson`](formatters::ndjson::write_ndjson)。
+///
+/// # 示例
+///
+/// ```
+/// use rust_tree::{render, Config, OutputFormat};
+///
+/// let dir = tempfile::tempdir().unwrap();
+/// std::fs::create_dir(dir.path().join("src")).unwrap();
+/// std::fs::write(dir.path().join("src/main.rs"), "fn main() {}").unwrap();
+///
+/// let tree = render(Config {
+///     path: dir.path().to_path_buf(),
+///     ..Default::default()
+/// })
+/// .unwrap();
+/// assert!(tree.ends_with("└── src/\n    └── main.rs\n"));
+///
+/// let json = render(Config {
+///     path: dir.path().to_path_buf(),
+///     format: OutputFormat::Json,
+///     ..Default::default()
+/// })
+/// .unwrap();
+/// let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+/// assert_eq!(value["stats"]["total_files"], 1);
+/// ```
+pub fn render(mut config: Config) -> Result<String, TreeError> {
+    if config.streaming || config.format == OutputFormat::Ndjson {
+        return Err(TreeError::Other(
+            "render() does not support streaming output (--streaming / -f ndjson); \
+             use the writer-based streaming formatters instead"
+                .to_string(),
+        ));
+    }
+    if config.color_mode == ColorMode::Auto {
+        config.color_mode = ColorMode::Never;
+    }
+    config.output = None;
+
+    let mut output = Vec::new();
+    run_to(config, &mut output)?;
+    String::from_utf8(output)
+        .map_err(|e| TreeError::Other(format!("output is not valid UTF-8: {}", e)))
+}
+
+/// `scan` 子命令：遍历目录并按 `--format` 输出（默认行为）。
+fn run_scan(config: Config, out: &mut dyn Write) -> Result<(), TreeError> {
     let start_time = Instant::now();
+    // NDJSON 输出总是边遍历边写出，与 --streaming 受相同的限制。
+    let streaming = config.streaming || config.format == OutputFormat::Ndjson;
 
     // 流式模式在访问节点时即输出，并不会将整棵树具体化，
     // 因此统计信息（需要完整树）无法计算。这里显式拒绝
-    // 该组合，而不是静默丢弃统计信息。
-    if config.streaming && config.should_show_stats() {
+    // 该组合，而不是静默丢弃统计信息。唯一的例外是流式树的 -S：
+    // 边输出边累加计数，末尾只输出一行汇总。
+    let streamed_summary = config.streaming && config.format == OutputFormat::Tree;
+    if streaming && config.should_show_stats() && !streamed_summary {
         return Err(TreeError::Other(
             "streaming mode does not support statistics; drop --stats or --streaming \
-             (and note -f json / -f table imply stats)"
+             (and note -f json / -f html / -f table / -f prometheus imply stats)"
                 .to_string(),
         ));
     }
@@ -62,9 +218,185 @@ pub fn run(config: Config) -> Result<(), TreeError> {
     // 校验参数（如 --exclude-common 的未知语言）。
     config.validate()?;
 
+    // --dirs-containing 需要整棵树做自底向上剪枝，流式模式无法支持。
+    if streaming && config.dirs_containing.is_some() {
+        return Err(TreeError::Other(
+            "streaming mode does not support --dirs-containing".to_string(),
+        ));
+    }
+
+    // --since-commit 同样是对整棵树的自底向上剪枝。
+    if streaming && config.since_commit.is_some() {
+        return Err(TreeError::Other(
+            "streaming mode does not support --since-commit".to_string(),
+        ));
+    }
+
+    // --fail-if-empty 需要在输出后知道整棵树是否含文件。
+    if streaming && config.fail_if_empty {
+        return Err(TreeError::Other(
+            "streaming mode does not support --fail-if-empty".to_string(),
+        ));
+    }
+
+    // 目录行先于其子树输出，流式模式无法在那时给出子树的文件数与总大小。
+    if streaming && config.dir_count {
+        return Err(TreeError::Other(
+            "streaming mode does not support --dir-count".to_string(),
+        ));
+    }
+
+    if streaming && config.dir_size {
+        return Err(TreeError::Other(
+            "streaming mode does not support --dir-size".to_string(),
+        ));
+    }
+
+    // --du 需要整棵树自底向上汇总目录大小。
+    if streaming && config.du {
+        return Err(TreeError::Other(
+            "streaming mode does not support --du".to_string(),
+        ));
+    }
+
+    // --size-column 需要两遍：先汇总目录大小、再按最宽的大小对齐。
+    if streaming && config.size_column {
+        return Err(TreeError::Other(
+            "streaming mode does not support --size-column".to_string(),
+        ));
+    }
+
+    // --compact-levels 要先知道每个目录的条目总数才能决定省略哪些。
+    if streaming && config.compact_levels {
+        return Err(TreeError::Other(
+            "streaming mode does not support --compact-levels".to_string(),
+        ));
+    }
+
+    // --show-filtered 的计数来自整棵树构建完成后的结果。
+    if streaming && config.show_filtered {
+        return Err(TreeError::Other(
+            "streaming mode does not support --show-filtered".to_string(),
+        ));
+    }
+
+    if streaming && config.report_excluded_ext {
+        return Err(TreeError::Other(
+            "streaming mode does not support --report-excluded-ext".to_string(),
+        ));
+    }
+
+    // --fit-lines 需要先知道整棵树的行数才能决定折叠哪些子树。
+    if streaming && config.fit_lines.is_some() {
+        return Err(TreeError::Other(
+            "streaming mode does not support --fit-lines".to_string(),
+        ));
+    }
+
+    // 流式遍历无法在父目录的子节点之后补出略去的数目。
+    if streaming && config.filelimit.is_some() {
+        return Err(TreeError::Other(
+            "streaming mode does not support --filelimit".to_string(),
+        ));
+    }
+
+    // --duplicates 要在整棵树中按大小分组后才能确定哪些文件需要哈希。
+    if streaming && config.duplicates {
+        return Err(TreeError::Other(
+            "streaming mode does not support --duplicates".to_string(),
+        ));
+    }
+
+    // --largest-path 需要比较整棵树中的文件大小。
+    if streaming && config.largest_path {
+        return Err(TreeError::Other(
+            "streaming mode does not support --largest-path".to_string(),
+        ));
+    }
+
+    // --count-only 输出的是汇总计数，流式模式边走边打印树，二者不兼容。
+    if streaming && config.count_only {
+        return Err(TreeError::Other(
+            "streaming mode does not support --count-only".to_string(),
+        ));
+    }
+
+    if streaming && config.stats_file.is_some() {
+        return Err(TreeError::Other(
+            "streaming mode does not support --stats-file".to_string(),
+        ));
+    }
+
+    // 流式输出边遍历边画树，无法把深层条目提升到根下。
+    if streaming && config.min_depth > 1 {
+        return Err(TreeError::Other(
+            "streaming mode does not support --min-depth".to_string(),
+        ));
+    }
+
+    if streaming && config.collapse_depth.is_some() {
+        return Err(TreeError::Other(
+            "streaming mode does not support --collapse-depth".to_string(),
+        ));
+    }
+
+    if streaming && config.compat_tree {
+        return Err(TreeError::Other(
+            "streaming mode does not support --compat-tree".to_string(),
+        ));
+    }
+
+    if streaming && config.shape_only {
+        return Err(TreeError::Other(
+            "streaming mode does not support --shape-only".to_string(),
+        ));
+    }
+
+    if streaming && config.list_empty_dirs {
+        return Err(TreeError::Other(
+            "streaming mode does not support --list-empty-dirs".to_string(),
+        ));
+    }
+
+    if streaming && config.git_authors {
+        return Err(TreeError::Other(
+            "streaming mode does not support --git-authors".to_string(),
+        ));
+    }
+
+    // --git-ratio 的表格在整棵树输出之后追加，流式模式没有这一步。
+    if streaming && config.git_ratio {
+        return Err(TreeError::Other(
+            "streaming mode does not support --git-ratio".to_string(),
+        ));
+    }
+
+    if streaming && config.compare_stats.is_some() {
+        return Err(TreeError::Other(
+            "streaming mode does not support --compare-stats".to_string(),
+        ));
+    }
+
+    // 流式输出边遍历边写出节点路径，不经过 --relative-to 的改写。
+    if streaming && config.relative_to.is_some() {
+        return Err(TreeError::Other(
+            "streaming mode does not support --relative-to".to_string(),
+        ));
+    }
+
+    // --tar 从 stdin 一次读完归档再构建树；流式输出与需要读取磁盘的选项均不适用。
+    if config.tar {
+        validate_tar(&config, streaming)?;
+    } else {
+        warn_pseudo_filesystem(&config);
+    }
+
     // 检查是否启用了流式模式
-    if config.streaming {
-        return run_streaming(config);
+    if config.format == OutputFormat::Ndjson {
+        return run_ndjson(config, out);
+    }
+    if streaming {
+        return run_streaming(config, out);
     }
 
     // 传统模式
@@ -77,54 +409,567 @@ pub fn run(config: Config) -> Result<(), TreeError> {
 
     // 遍历目录
     update_progress(&progress, &format!("Scanning: {}", config.path.display()));
-    let tree = walk_directory(&config.path, &config.to_walk_config(), progress.as_ref())?;
+    let walk_config = config.to_walk_config();
+    let mut tree = if config.tar {
+        read_tar(std::io::stdin().lock(), TAR_ROOT_NAME, &walk_config)?
+    } else {
+        walk_directory(&config.path, &walk_config, progress.as_ref())?
+    };
     finish_progress(&progress, "Scan complete");
 
+    prune_tree(&config, &mut tree, &config.path)?;
+
+    // --git-authors：按一次历史遍历得到的作者表标注文件；仓库外不标注。
+    if config.git_authors {
+        apply_git_authors(&mut tree.root, &last_authors(&config.path)?);
+    }
+
+    // --largest-path：只输出最大文件的路径，便于 `open "$(rust-tree --largest-path)"`。
+    if config.largest_path {
+        return print_largest_path(&config, &tree, out);
+    }
+
     // 收集统计信息：仅当统计会被使用时（-S、-f json、-f table）才收集。
     // 默认 tree 视图无 -s/-S 时统计结果会被丢弃，跳过可省去一次全树遍历；
-    // 且此时 need_size=false 已使文件 size 为 0，即便收集也是零值。
+    // 且此时未请求 MetadataFields::SIZE，文件 size 为 0，即便收集也是零值。
     // scan_duration 仅在统计块中展示，跳过时也无需计算。
-    let stats = if config.should_show_stats() {
-        collect_stats(&tree, start_time, config.top_files_count())
+    let mut stats = if config.collects_stats() || config.count_only {
+        collect_stats_with(&tree, start_time, &config.to_stats_options())
     } else {
         crate::core::models::TreeStats::new()
     };
+    // 默认树视图不收集统计，但 --duplicates 的页脚仍需要重复文件组。
+    if config.duplicates && stats.duplicates.is_none() {
+        stats.duplicates = Some(crate::core::collector::find_duplicates_with(
+            &tree,
+            config.duplicates_empty.into(),
+        ));
+    }
+    // --git-ratio 按仓库状态统计，与树中的节点无关；仓库外为 None，不输出。
+    if config.git_ratio {
+        stats.git_ratio = git_ratio(&config.path)?;
+    }
+    if let Some(baseline) = &config.compare_stats {
+        stats.stats_delta = Some(diff_stats(&load_stats(baseline)?, &stats));
+    }
+    // --list-empty-dirs 的页脚同样不依赖统计块。
+    if config.list_empty_dirs && stats.empty_directories.is_empty() {
+        stats.empty_directories = find_empty_directories(&tree);
+    }
+    // --stats-file：统计写入单独的文件，主输出不再包含统计块。
+    if let Some(path) = &config.stats_file {
+        write_stats_file(&config, path, &stats)?;
+    }
+
+    // --fail-if-empty 按完整的树判断，须在 --fit-lines 折叠之前。
+    let is_empty = config.fail_if_empty && get_all_files(&tree).is_empty();
+
+    // --fit-lines：统计已基于完整的树收集，这里只折叠用于显示的树。
+    if let Some(max_lines) = config.fit_lines {
+        fit_lines(&mut tree.root, max_lines, config.fit_strategy.into());
+    }
+
+    // --count-only：只输出一个数字，便于 `N=$(rust-tree --count-only)`。
+    if config.count_only {
+        let count = if config.count_dirs {
+            stats.total_directories
+        } else {
+            stats.total_files
+        };
+        print_output(out, &format!("{}\n", count))?;
+    } else {
+        write_scan_output(&config, &tree, &stats, out)?;
+    }
+
+    // --fail-if-empty：过滤后没有任何文件时以专用退出码失败（输出照常打印）。
+    if is_empty {
+        return Err(TreeError::EmptyTree(config.path.clone()));
+    }
+
+    Ok(())
+}
+
+/// 输出树中最大文件相对扫描根的路径；没有文件时返回 `TreeError::EmptyTree`。
+fn print_largest_path(
+    config: &Config,
+    tree: &FsTree,
+    out: &mut dyn Write,
+) -> Result<(), TreeError> {
+    let files = get_all_files(tree);
+    let refs: Vec<&FsNode> = files.iter().collect();
+    let largest = crate::core::collector::find_largest_files(&refs, 1)
+        .into_iter()
+        .next()
+        .ok_or_else(|| TreeError::EmptyTree(config.path.clone()))?;
+
+    // --relative-to 优先；否则相对扫描根，扫描根本身是文件时相对路径为空，此时输出原路径。
+    let path = match (
+        config.relative_base(),
+        largest.path.strip_prefix(&config.path),
+    ) {
+        (Some((base, cwd)), _) => relative_path(&largest.path, &base, &cwd),
+        (None, Ok(rel)) if !rel.as_os_str().is_empty() => rel.to_path_buf(),
+        _ => largest.path.clone(),
+    };
+    print_output(out, &format!("{}\n", path.display()))
+}
 
-    // 根据所选格式格式化输出
+/// 按 `--format` 输出扫描结果。
+fn write_scan_output(
+    config: &Config,
+    tree: &FsTree,
+    stats: &TreeStats,
+    out: &mut dyn Write,
+) -> Result<(), TreeError> {
+    // --relative-to：统计中的路径（最大文件、重复文件等）总在副本上改写；JSON 与 CSV
+    // 直接序列化节点路径，树也在副本上改写。树形输出在标签中处理，HTML 仍按原路径
+    // 读取符号链接目标，二者的树不改写。空目录报告需按原路径检查磁盘，使用原统计。
+    let raw_stats = stats;
+    let relative_stats;
+    let relative_tree;
+    let (tree, stats) = match config.relative_base() {
+        Some((base, cwd)) => {
+            relative_stats = relativize_stats(stats, &base, &cwd);
+            let tree = if matches!(config.format, OutputFormat::Json | OutputFormat::Csv) {
+                let mut copy = tree.clone();
+                relativize_paths(&mut copy.root, &base, &cwd);
+                relative_tree = copy;
+                &relative_tree
+            } else {
+                tree
+            };
+            (tree, &relative_stats)
+        }
+        None => (tree, stats),
+    };
+
+    // 可增量生成的格式（tree、outline、names）直接写入带缓冲的 stdout：首行立即出现，
+    // 也不必再为整份输出分配一个 String。其余格式仍先整体生成再打印。
     let output = match config.format {
         OutputFormat::Tree => {
-            let mut result = format_tree(
-                &tree.root,
-                config.show_size,
-                config.color_mode,
-                config.color_scheme,
-            );
+            return write_output(out, |out| {
+                // --width / --scroll-indicator 只作用于树本身，不裁剪统计表。
+                let mut clip =
+                    ClipWriter::new(&mut *out, config.line_width(), config.scroll_indicator);
+                format_tree_to(&tree.root, &config.to_tree_options(), &mut clip)?;
+                clip.finish()?;
 
-            // 如有需要则追加统计信息
-            if config.show_stats {
-                result.push_str("\n\n");
-                result.push_str(&crate::formatters::table::format_compact(&stats));
-                result.push('\n');
-            }
+                if config.show_filtered {
+                    writeln!(out, "\n{}", format_filtered(&tree.filtered))?;
+                }
+
+                if config.report_excluded_ext {
+                    writeln!(out, "\n{}", format_excluded_extensions(&tree.filtered))?;
+                }
+
+                if let Some(groups) = &stats.duplicates {
+                    writeln!(
+                        out,
+                        "\n{}",
+                        format_duplicates(groups, &config.size_options())
+                    )?;
+                }
+
+                if let Some(ratio) = &stats.git_ratio {
+                    writeln!(
+                        out,
+                        "\n{}",
+                        format_git_ratio_table(ratio, &config.to_table_options())
+                    )?;
+                }
+
+                if let Some(delta) = &stats.stats_delta {
+                    writeln!(
+                        out,
+                        "\n{}",
+                        format_stats_delta(delta, &config.size_options())
+                    )?;
+                }
+
+                if config.list_empty_dirs {
+                    writeln!(out, "\n{}", empty_directory_report(config, raw_stats))?;
+                }
+
+                if config.legend {
+                    write!(out, "\n{}", config.format_legend())?;
+                }
 
-            result
+                // 如有需要则追加统计信息
+                if config.show_stats && config.stats_file.is_none() {
+                    writeln!(
+                        out,
+                        "\n\n{}",
+                        crate::formatters::table::format_compact_with(
+                            stats,
+                            &config.to_table_options(),
+                        )
+                    )?;
+                }
+                Ok(())
+            });
         }
-        OutputFormat::Json => format_json(&tree, &stats, true)?,
-        OutputFormat::Table => format_table(&stats),
+        OutputFormat::Outline => {
+            return write_output(out, |out| format_outline_to(&tree.root, out))
+        }
+        OutputFormat::Names => return write_output(out, |out| format_names_to(&tree.root, out)),
+        OutputFormat::Manifest => {
+            return write_output(out, |out| format_manifest_to(&tree.root, out));
+        }
+        OutputFormat::Markdown => {
+            return write_output(out, |out| {
+                format_markdown_to(
+                    &tree.root,
+                    config.show_file_size(),
+                    &config.size_options(),
+                    out,
+                )
+            });
+        }
+        OutputFormat::Csv => {
+            return write_output(out, |out| format_csv_to(&tree.root, &config.fields, out));
+        }
+        // run_scan 已将 NDJSON 交给 run_ndjson 流式输出，这里只为穷尽匹配。
+        OutputFormat::Ndjson => unreachable!("ndjson output is always streamed"),
+        OutputFormat::Json if config.stats_file.is_some() => {
+            crate::formatters::json::format_tree_only(tree, true)?
+        }
+        OutputFormat::Json => format_json(tree, stats, true)?,
+        OutputFormat::Html => {
+            crate::formatters::html::format_html_with(tree, stats, &config.size_options())
+        }
+        OutputFormat::Table if config.list_empty_dirs => format!(
+            "{}\n{}\n",
+            format_table_with(stats, &config.to_table_options()),
+            empty_directory_report(config, raw_stats)
+        ),
+        OutputFormat::Table => format_table_with(stats, &config.to_table_options()),
+        OutputFormat::Prometheus => format_prometheus(stats, &config.path),
     };
 
-    // 打印输出
-    print!("{}", output);
-    io::stdout()
-        .flush()
-        .map_err(|e| TreeError::Other(e.to_string()))?;
+    print_output(out, &output)
+}
+
+/// `--list-empty-dirs` 的页脚。`--tar` 的条目不在磁盘上，无法判断是否因过滤而空。
+///
+/// `stats` 须带原始路径：分类时要读取磁盘，`--relative-to` 的改写在分类之后进行。
+fn empty_directory_report(config: &Config, stats: &TreeStats) -> String {
+    let mut dirs: Vec<EmptyDirectory> = if config.tar {
+        stats
+            .empty_directories
+            .iter()
+            .map(|path| EmptyDirectory {
+                path: path.clone(),
+                filtered: false,
+            })
+            .collect()
+    } else {
+        classify_empty_directories(&stats.empty_directories)
+    };
+    if let Some((base, cwd)) = config.relative_base() {
+        for dir in &mut dirs {
+            dir.path = relative_path(&dir.path, &base, &cwd);
+        }
+    }
+    format_empty_directories(&dirs)
+}
+
+/// 返回统计的副本，其中所有路径都改写为相对 `base` 的路径（见 [`relative_path`]）。
+fn relativize_stats(stats: &TreeStats, base: &Path, cwd: &Path) -> TreeStats {
+    let mut stats = stats.clone();
+    let entries = stats
+        .largest_files
+        .iter_mut()
+        .chain(stats.duplicates.iter_mut().flatten().flatten());
+    for entry in entries {
+        entry.path = relative_path(&entry.path, base, cwd);
+    }
+    for dir in &mut stats.empty_directories {
+        *dir = relative_path(dir, base, cwd);
+    }
+    if let Some((path, _)) = &mut stats.deepest_path {
+        *path = relative_path(path, base, cwd);
+    }
+    stats
+}
+
+/// 将子树中每个节点的 `path` 改写为相对 `base` 的路径。
+fn relativize_paths(node: &mut FsNode, base: &Path, cwd: &Path) {
+    if let Some(path) = &node.path {
+        node.path = Some(relative_path(path, base, cwd));
+    }
+    for child in node.children.iter_mut().flatten() {
+        relativize_paths(child, base, cwd);
+    }
+}
 
+/// 将统计信息以美化的 JSON 写入 `path`（`--stats-file`），路径按 `--relative-to` 改写。
+///
+/// 文件无法创建或写入时返回 `TreeError::Io`。
+fn write_stats_file(config: &Config, path: &Path, stats: &TreeStats) -> Result<(), TreeError> {
+    let relative = config
+        .relative_base()
+        .map(|(base, cwd)| relativize_stats(stats, &base, &cwd));
+    let stats = relative.as_ref().unwrap_or(stats);
+    let mut json = crate::formatters::json::format_stats_only(stats, true)?;
+    json.push('\n');
+    std::fs::write(path, json).map_err(|e| {
+        TreeError::Io(io::Error::new(
+            e.kind(),
+            format!("cannot write stats file {}: {}", path.display(), e),
+        ))
+    })
+}
+
+/// 通过带缓冲的输出目标增量写出结果，结束时刷新。
+///
+/// 下游提前关闭管道（如 `| head`）时返回 `TreeError::Io`。
+fn write_output<F>(out: &mut dyn Write, write: F) -> Result<(), TreeError>
+where
+    F: FnOnce(&mut OutputWriter<'_>) -> io::Result<()>,
+{
+    let mut out = io::BufWriter::new(out);
+    write(&mut out)?;
+    out.flush()?;
     Ok(())
 }
 
+/// 将结果写到输出目标并刷新。
+fn print_output(out: &mut dyn Write, output: &str) -> Result<(), TreeError> {
+    write_output(out, |out| out.write_all(output.as_bytes()))
+}
+
+/// 带缓冲的输出目标（见 [`run_to`]）。
+type OutputWriter<'a> = io::BufWriter<&'a mut dyn Write>;
+
+/// 打开 [`run`] 的输出目标：`--output` 给出的文件，或 stdout。
+fn open_output(config: &Config) -> Box<dyn Write> {
+    match &config.output {
+        Some(path) => Box::new(OutputFile {
+            path: path.clone(),
+            file: None,
+        }),
+        None => Box::new(io::stdout().lock()),
+    }
+}
+
+/// `--output` 文件：首次写入时才创建，因此校验失败时不会截断已有文件。
+struct OutputFile {
+    path: std::path::PathBuf,
+    file: Option<std::fs::File>,
+}
+
+impl OutputFile {
+    fn file(&mut self) -> io::Result<&mut std::fs::File> {
+        if self.file.is_none() {
+            let file = std::fs::File::create(&self.path).map_err(|e| {
+                io::Error::new(
+                    e.kind(),
+                    format!("cannot create output file {}: {}", self.path.display(), e),
+                )
+            })?;
+            self.file = Some(file);
+        }
+        Ok(self.file.as_mut().expect("output file was just created"))
+    }
+}
+
+impl Write for OutputFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.file()?.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file()?.flush()
+    }
+}
+
+/// 按配置遍历 `path` 并应用剪枝选项，保证文件 size 已读取。
+///
+/// 供 `stats`、`snapshot`、`diff` 这些总是需要大小信息的子命令使用。
+fn walk_with_sizes(config: &Config, path: &Path) -> Result<FsTree, TreeError> {
+    let mut walk_config = config.to_walk_config();
+    walk_config.metadata |= crate::core::collector::REQUIRED_METADATA;
+
+    let mut tree = if config.tar {
+        read_tar(std::io::stdin().lock(), TAR_ROOT_NAME, &walk_config)?
+    } else {
+        walk_directory(path, &walk_config, None)?
+    };
+    prune_tree(config, &mut tree, path)?;
+    Ok(tree)
+}
+
+/// 对已构建的树应用需要整棵树的剪枝选项（`--dirs-containing`、`--include-prune`、
+/// `--since-commit`）。
+fn prune_tree(config: &Config, tree: &mut FsTree, path: &Path) -> Result<(), TreeError> {
+    // 只保留含匹配文件的目录（validate 已保证模式合法）。
+    if let Some(ref pattern) = config.dirs_containing {
+        if let Ok(pattern) = glob::Pattern::new(pattern) {
+            retain_dirs_containing(&mut tree.root, &pattern);
+        }
+    }
+
+    // --include-prune 在遍历时已跳过没有匹配文件的目录；--tar 的树不经过那一步，
+    // 在这里按 --include-only 补上（对磁盘遍历得到的树不再有变化）。
+    if config.include_prune {
+        if let Some(pattern) = config
+            .include_only
+            .as_deref()
+            .and_then(|p| glob::Pattern::new(p).ok())
+        {
+            prune_to_matches(tree, &pattern);
+        }
+    }
+
+    // 只保留自给定提交以来有变化的文件及其祖先目录。
+    if let Some(ref rev) = config.since_commit {
+        let changed = changed_paths_since(path, rev)?;
+        retain_paths(&mut tree.root, &changed);
+    }
+
+    Ok(())
+}
+
+/// 拒绝与 `--tar` 不兼容的选项：流式输出，以及需要读取磁盘上文件的选项。
+fn validate_tar(config: &Config, streaming: bool) -> Result<(), TreeError> {
+    if streaming {
+        return Err(TreeError::Other(
+            "--tar does not support streaming output (--streaming / -f ndjson)".to_string(),
+        ));
+    }
+    if config.since_commit.is_some() {
+        return Err(TreeError::Other(
+            "--tar does not support --since-commit".to_string(),
+        ));
+    }
+    // --duplicates 要读取文件内容计算哈希，而归档条目不在磁盘上。
+    if config.duplicates {
+        return Err(TreeError::Other(
+            "--tar does not support --duplicates".to_string(),
+        ));
+    }
+    if config.git_ratio {
+        return Err(TreeError::Other(
+            "--tar does not support --git-ratio".to_string(),
+        ));
+    }
+    if config.git_authors {
+        return Err(TreeError::Other(
+            "--tar does not support --git-authors".to_string(),
+        ));
+    }
+    if config.count_lines {
+        return Err(TreeError::Other(
+            "--tar does not support --count-lines".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// 扫描根位于或包含伪文件系统（`/proc` 等）时向 stderr 打印警告，不中断扫描。
+fn warn_pseudo_filesystem(config: &Config) {
+    #[cfg(unix)]
+    if let Some(warning) =
+        crate::core::mounts::pseudo_filesystem_warning(&config.path, config.one_filesystem)
+    {
+        eprintln!("warning: {}", warning);
+    }
+    #[cfg(not(unix))]
+    let _ = config;
+}
+
+/// `stats` 子命令：仅输出统计信息（默认表格，`-f json` 时输出 JSON）。
+fn run_stats(config: Config, out: &mut dyn Write) -> Result<(), TreeError> {
+    let start_time = Instant::now();
+    config.validate()?;
+    if config.tar {
+        validate_tar(&config, false)?;
+    } else {
+        warn_pseudo_filesystem(&config);
+    }
+
+    let tree = walk_with_sizes(&config, &config.path)?;
+    let mut stats = collect_stats_with(&tree, start_time, &config.to_stats_options());
+    if config.git_ratio {
+        stats.git_ratio = git_ratio(&config.path)?;
+    }
+    if let Some(baseline) = &config.compare_stats {
+        stats.stats_delta = Some(diff_stats(&load_stats(baseline)?, &stats));
+    }
+    if let Some(path) = &config.stats_file {
+        write_stats_file(&config, path, &stats)?;
+    }
+
+    // --relative-to：输出改写后的副本；空目录报告仍按原路径检查磁盘。
+    let relative = config
+        .relative_base()
+        .map(|(base, cwd)| relativize_stats(&stats, &base, &cwd));
+    let shown = relative.as_ref().unwrap_or(&stats);
+    let output = match config.format {
+        OutputFormat::Json => {
+            let mut json = crate::formatters::json::format_stats_only(shown, true)?;
+            json.push('\n');
+            json
+        }
+        OutputFormat::Prometheus => format_prometheus(shown, &config.path),
+        _ if config.list_empty_dirs => format!(
+            "{}\n{}\n",
+            format_table_with(shown, &config.to_table_options()),
+            empty_directory_report(&config, &stats)
+        ),
+        _ => format_table_with(shown, &config.to_table_options()),
+    };
+
+    print_output(out, &output)
+}
+
+/// `snapshot` 子命令：扫描目录，将树与统计信息写入 JSON 快照文件。
+fn run_snapshot(config: Config, out: &Path) -> Result<(), TreeError> {
+    let start_time = Instant::now();
+    config.validate()?;
+
+    let tree = walk_with_sizes(&config, &config.path)?;
+    let stats = collect_stats_with(&tree, start_time, &config.to_stats_options());
+    save_snapshot(&tree, &stats, out)
+}
+
+/// `restore` 子命令：从快照还原树并以树形渲染，不访问被快照的目录。
+fn run_restore(config: Config, input: &Path, out: &mut dyn Write) -> Result<(), TreeError> {
+    let tree = load_snapshot(input)?;
+    write_output(out, |out| {
+        let mut clip = ClipWriter::new(out, config.line_width(), config.scroll_indicator);
+        format_tree_to(&tree.root, &config.to_tree_options(), &mut clip)?;
+        clip.finish().map(drop)
+    })
+}
+
+/// `diff` 子命令：比较两侧并逐行列出新增、删除与变更。
+///
+/// 每一侧若为 `.json` 文件则按快照加载，否则按目录扫描。
+fn run_diff(config: Config, old: &Path, new: &Path, out: &mut dyn Write) -> Result<(), TreeError> {
+    config.validate()?;
+
+    let load_side = |path: &Path| -> Result<FsTree, TreeError> {
+        let is_snapshot = path.is_file() && path.extension().is_some_and(|ext| ext == "json");
+        if is_snapshot {
+            load_snapshot(path)
+        } else {
+            walk_with_sizes(&config, path)
+        }
+    };
+
+    let old_tree = load_side(old)?;
+    let new_tree = load_side(new)?;
+    print_output(
+        out,
+        &format_diff(&diff_trees(&old_tree, &new_tree), &config.size_options()),
+    )
+}
+
 /// 以流式模式运行（峰值内存为 O(最宽目录的宽度)）。
-fn run_streaming(config: Config) -> Result<(), TreeError> {
-    use crate::formatters::streaming_tree::format_tree_streaming;
+fn run_streaming(config: Config, out: &mut dyn Write) -> Result<(), TreeError> {
+    use crate::formatters::size::format_size;
+    use crate::formatters::streaming_tree::{format_tree_streaming_with, StreamOptions};
 
     let walk_config = config.to_walk_config();
 
@@ -136,49 +981,156 @@ fn run_streaming(config: Config) -> Result<(), TreeError> {
     let progress = create_progress_bar(&progress_config);
     update_progress(&progress, &format!("Scanning: {}", config.path.display()));
 
-    // 流式模式直接使用 stdout
-    let mut stdout = io::stdout().lock();
+    // 流式模式直接写入输出目标（按需裁剪超宽行）
+    let mut stdout = ClipWriter::new(
+        io::BufWriter::new(out),
+        config.line_width(),
+        config.scroll_indicator,
+    );
+
+    let options = StreamOptions {
+        show_size: config.show_file_size(),
+        color_mode: config.color_mode,
+        color_scheme: config.effective_color_scheme(),
+        running_totals_every: config.running_totals.unwrap_or(0),
+        size: config.size_options(),
+        show_perms: config.show_perms,
+        charset: config.tree_charset(),
+        summary: config.show_stats,
+    };
 
-    format_tree_streaming(
+    // --running-totals：定期向 stderr 写入累计值，不干扰 stdout 上的树。
+    format_tree_streaming_with(
         &config.path,
         &mut stdout,
-        config.show_size,
-        config.color_mode,
-        config.color_scheme,
+        &options,
         walk_config,
         progress.as_ref(),
+        |totals| {
+            eprintln!(
+                "[running totals] {} nodes, {} files, {}",
+                totals.nodes,
+                totals.files,
+                format_size(totals.bytes, &options.size)
+            );
+        },
     )
     .map_err(|e| TreeError::Other(e.to_string()))?;
 
     finish_progress(&progress, "Scan complete");
-    io::stdout()
-        .flush()
+    let mut stdout = stdout
+        .finish()
         .map_err(|e| TreeError::Other(e.to_string()))?;
+    if config.legend {
+        write!(stdout, "\n{}", config.format_legend())?;
+        stdout.flush()?;
+    }
 
     Ok(())
 }
 
+/// `-f ndjson`：遍历时逐个节点写出一行 JSON，不构建内存树。
+fn run_ndjson(config: Config, out: &mut dyn Write) -> Result<(), TreeError> {
+    let walk_config = config.to_walk_config();
+    let mut out = io::BufWriter::new(out);
+    write_ndjson(&config.path, &walk_config, &config.fields, &mut out)?;
+    out.flush()?;
+    Ok(())
+}
+
 /// 用于创建基础 Config 的默认实现。
 impl Default for Config {
     fn default() -> Self {
         Config {
+            command: None,
             path: ".".into(),
             max_depth: 0,
+            min_depth: 0,
             format: OutputFormat::Tree,
             show_size: false,
+            file_size: false,
+            dir_count: false,
+            dir_size: false,
             show_hidden: false,
             sort_by: SortBy::Name,
             reverse: false,
             show_stats: false,
             follow_symlinks: false,
+            mark_followed: false,
             top_files: 10,
+            stats_ignore_empty: false,
+            thousands_sep: None,
+            fields: CsvField::ALL.to_vec(),
+            compact_numbers: false,
             color_mode: config::ColorMode::Auto,
             color_scheme: config::ColorScheme::Basic,
+            color_by: config::ColorBy::Type,
             show_progress: false,
             exclude: Vec::new(),
             include_only: None,
+            include_prune: false,
+            compat_tree: false,
+            ascii: false,
             exclude_common: None,
+            exclude_vcs: false,
+            gitignore: false,
+            auto_git_exclude: false,
+            no_auto_git_exclude: false,
+            min_size: None,
+            max_size: None,
+            newer_than: None,
+            older_than: None,
             streaming: false,
+            threads: 1,
+            running_totals: None,
+            min_percentage: None,
+            require_dir: false,
+            dirs_containing: None,
+            since_commit: None,
+            path_from_cwd: false,
+            relative_to: None,
+            width: None,
+            scroll_indicator: false,
+            legend: false,
+            fail_if_empty: false,
+            repeat_header: None,
+            show_filtered: false,
+            ext: Vec::new(),
+            report_excluded_ext: false,
+            du: false,
+            size_unit: None,
+            show_perms: false,
+            shape_only: false,
+            size_column: false,
+            compact_levels: false,
+            head: 3,
+            tail: 3,
+            fit_lines: None,
+            collapse_depth: None,
+            fit_strategy: config::FitStrategy::Deepest,
+            filelimit: None,
+            one_filesystem: false,
+            tar: false,
+            ext_label: Vec::new(),
+            no_ext_labels: false,
+            ext_limit: None,
+            duplicates: false,
+            duplicates_empty: config::DuplicatesEmpty::Skip,
+            git_ratio: false,
+            git_authors: false,
+            compare_stats: None,
+            count_lines: false,
+            list_empty_dirs: false,
+            stats_file: None,
+            output: None,
+            count_only: false,
+            largest_path: false,
+            full_paths: false,
+            count_dirs: false,
+            config_file: None,
+            no_config: false,
+            print_config: false,
+            print_schema: false,
         }
     }
 }
@@ -2,17 +2,54 @@
 //!
 //! 这是命令行界面的主入口。
 
-use clap::Parser;
-use rust_tree::Config;
+use clap::{CommandFactory, FromArgMatches};
+use rust_tree::config::file::{find_config_file, load_config_file, merge_config_file};
+use rust_tree::{Config, TreeError};
+use std::ffi::OsString;
 use std::process;
 
 fn main() {
-    // 解析命令行参数
-    let config = Config::parse();
+    // 解析命令行参数，再用配置文件补足未显式给出的选项
+    let config = match parse_config() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            process::exit(1);
+        }
+    };
 
     // 运行工具
     if let Err(e) = rust_tree::run(config) {
         eprintln!("Error: {}", e);
-        process::exit(1);
+        process::exit(e.exit_code());
     }
 }
+
+/// 解析命令行；除非给出 `--no-config`，否则合并 `--config` 指定的文件或
+/// 在当前目录、主目录中找到的 `.rust-tree.toml`。
+fn parse_config() -> Result<Config, TreeError> {
+    let args: Vec<OsString> = std::env::args_os().collect();
+    let matches = Config::command().get_matches_from(&args);
+    let config = Config::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+
+    if config.no_config {
+        return Ok(config);
+    }
+
+    let path = match config.config_file {
+        Some(path) => path,
+        None => {
+            let cwd = std::env::current_dir().ok();
+            let home = std::env::var_os("HOME")
+                .or_else(|| std::env::var_os("USERPROFILE"))
+                .map(std::path::PathBuf::from);
+            match find_config_file(cwd.as_deref(), home.as_deref()) {
+                Some(path) => path,
+                None => return Ok(config),
+            }
+        }
+    };
+
+    let table = load_config_file(&path)?;
+    merge_config_file(&args, &matches, &table)
+}
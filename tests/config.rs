@@ -1,15 +1,26 @@
-//! `config`（CLI 配置类型）及 `config::color` 的测试。
+//! `config`（CLI 配置类型）及 `config::color`、`config::file`、`config::ls_colors` 的测试。
 
 #[path = "config/color.rs"]
 mod color;
+#[path = "config/file.rs"]
+mod file;
+#[path = "config/ls_colors.rs"]
+mod ls_colors;
 
-use rust_tree::{Config, OutputFormat, SortBy, SortField};
+use clap::Parser;
+use rust_tree::{Command, Config, MetadataFields, OutputFormat, SortBy, SortField};
+use std::path::PathBuf;
 
 #[test]
 fn test_sort_by_conversion() {
     assert_eq!(SortField::from(SortBy::Name), SortField::Name);
     assert_eq!(SortField::from(SortBy::Size), SortField::Size);
     assert_eq!(SortField::from(SortBy::Type), SortField::Type);
+    assert_eq!(SortField::from(SortBy::MTime), SortField::MTime);
+
+    // `modified` 是 `mtime` 的别名
+    let config = Config::try_parse_from(["rust-tree", "--sort", "modified"]).unwrap();
+    assert_eq!(config.sort_by, SortBy::MTime);
 }
 
 #[test]
@@ -58,3 +69,248 @@ fn test_validate_exclude_common_none_is_ok() {
     };
     assert!(cfg.validate().is_ok());
 }
+
+#[test]
+fn test_validate_rejects_invalid_filter_patterns() {
+    let cfg = Config {
+        exclude: vec!["*.log".into(), "[abc".into()],
+        ..Default::default()
+    };
+    let err = cfg.validate().unwrap_err().to_string();
+    assert!(err.contains("invalid --exclude pattern '[abc'"), "{}", err);
+
+    let cfg = Config {
+        include_only: Some("**/[".into()),
+        ..Default::default()
+    };
+    let err = cfg.validate().unwrap_err().to_string();
+    assert!(err.contains("invalid --include-only pattern"), "{}", err);
+
+    let cfg = Config {
+        exclude: vec!["target".into()],
+        include_only: Some("*.rs".into()),
+        ..Default::default()
+    };
+    assert!(cfg.validate().is_ok());
+}
+
+#[test]
+fn test_validate_min_percentage_range() {
+    for p in [0.0, 2.5, 100.0] {
+        let cfg = Config {
+            min_percentage: Some(p),
+            ..Default::default()
+        };
+        assert!(cfg.validate().is_ok(), "{} should be valid", p);
+    }
+    for p in [-1.0, 100.1] {
+        let cfg = Config {
+            min_percentage: Some(p),
+            ..Default::default()
+        };
+        assert!(cfg.validate().is_err(), "{} should be rejected", p);
+    }
+}
+
+#[test]
+fn test_to_json_reflects_overridden_flags() {
+    let cfg = Config {
+        max_depth: 7,
+        format: OutputFormat::Table,
+        print_config: true,
+        ..Default::default()
+    };
+    let value: serde_json::Value = serde_json::from_str(&cfg.to_json().unwrap()).unwrap();
+    assert_eq!(value["max_depth"], 7);
+    assert_eq!(value["format"], "table");
+    assert_eq!(value["sort_by"], "name");
+    // --print-config 自身不属于“生效配置”，不会出现在输出中。
+    assert!(value.get("print_config").is_none());
+}
+
+#[test]
+fn test_required_metadata_follows_output() {
+    // 默认树形输出只需要名称。
+    let cfg = Config::default();
+    assert!(cfg.to_walk_config().metadata.is_empty());
+
+    let cfg = Config {
+        show_size: true,
+        ..Default::default()
+    };
+    assert_eq!(cfg.to_walk_config().metadata, MetadataFields::SIZE);
+
+    let cfg = Config {
+        format: OutputFormat::Json,
+        ..Default::default()
+    };
+    assert!(cfg.to_walk_config().metadata.contains(MetadataFields::SIZE));
+}
+
+#[test]
+fn test_bare_directory_is_scan_shorthand() {
+    let cfg = Config::try_parse_from(["rust-tree", "-d", "2", "src"]).unwrap();
+    assert_eq!(cfg.command, None);
+    assert_eq!(cfg.path, PathBuf::from("src"));
+    assert_eq!(cfg.max_depth, 2);
+
+    let cfg = Config::try_parse_from(["rust-tree", "-f", "json", "src"]).unwrap();
+    assert_eq!(cfg.format, OutputFormat::Json);
+}
+
+#[test]
+fn test_directory_named_like_a_subcommand() {
+    let cfg = Config::try_parse_from(["rust-tree", "--", "stats"]).unwrap();
+    assert_eq!(cfg.command, None);
+    assert_eq!(cfg.path, PathBuf::from("stats"));
+
+    let cfg = Config::try_parse_from(["rust-tree", "-f", "json", "scan", "stats"]).unwrap();
+    assert_eq!(
+        cfg.command,
+        Some(Command::Scan {
+            path: Some("stats".into())
+        })
+    );
+    assert_eq!(cfg.format, OutputFormat::Json);
+}
+
+#[test]
+fn test_subcommands_parse_with_global_flags() {
+    let cfg = Config::try_parse_from(["rust-tree", "stats", "src", "-f", "json"]).unwrap();
+    assert_eq!(
+        cfg.command,
+        Some(Command::Stats {
+            path: Some("src".into())
+        })
+    );
+    assert_eq!(cfg.format, OutputFormat::Json);
+
+    let cfg = Config::try_parse_from(["rust-tree", "diff", "a.json", "b"]).unwrap();
+    assert_eq!(
+        cfg.command,
+        Some(Command::Diff {
+            old: "a.json".into(),
+            new: "b".into()
+        })
+    );
+
+    let cfg = Config::try_parse_from(["rust-tree", "snapshot", "out.json"]).unwrap();
+    assert_eq!(
+        cfg.command,
+        Some(Command::Snapshot {
+            out: "out.json".into(),
+            path: None
+        })
+    );
+}
+
+#[test]
+fn test_size_flags_map_to_tree_options() {
+    let opts = Config::try_parse_from(["rust-tree", "-s"])
+        .unwrap()
+        .to_tree_options();
+    assert!(opts.file_size && opts.dir_count && !opts.dir_size);
+
+    let cfg = Config::try_parse_from(["rust-tree", "--file-size", "--dir-size"]).unwrap();
+    let opts = cfg.to_tree_options();
+    assert!(opts.file_size && !opts.dir_count && opts.dir_size);
+    assert!(cfg.required_metadata().contains(MetadataFields::SIZE));
+
+    let cfg = Config::try_parse_from(["rust-tree", "--dir-count"]).unwrap();
+    assert!(!cfg.required_metadata().contains(MetadataFields::SIZE));
+}
+
+#[test]
+fn test_parse_size() {
+    use rust_tree::config::parse_size;
+
+    assert_eq!(parse_size("1024"), Ok(1024));
+    assert_eq!(parse_size("1K"), Ok(1024));
+    assert_eq!(parse_size("500k"), Ok(500 * 1024));
+    assert_eq!(parse_size("2.5M"), Ok(2_621_440));
+    assert_eq!(parse_size("1GiB"), Ok(1 << 30));
+    assert_eq!(parse_size("1G"), Ok(1 << 30));
+    assert_eq!(parse_size("1.5G"), Ok(3 << 29));
+    // KB/MB 为十进制，KiB/MiB 为二进制，与 --size-unit 一致
+    assert_eq!(parse_size("2KB"), Ok(2000));
+    assert_eq!(parse_size("2KiB"), Ok(2048));
+    assert_eq!(parse_size("2kb"), Ok(2000));
+    assert_eq!(parse_size("1.5MB"), Ok(1_500_000));
+    assert_eq!(parse_size("1MiB"), Ok(1 << 20));
+    assert_eq!(parse_size("3B"), Ok(3));
+    assert!(parse_size("").is_err());
+    assert!(parse_size("abc").is_err());
+    assert!(parse_size("10X").is_err());
+    assert!(parse_size("-1K").is_err());
+
+    let config = Config::try_parse_from(["rust-tree", "--min-size", "10M"]).unwrap();
+    assert_eq!(config.min_size, Some(10 << 20));
+    assert!(Config::try_parse_from(["rust-tree", "--max-size", "lots"]).is_err());
+}
+
+#[test]
+fn test_parse_age() {
+    use rust_tree::config::parse_age;
+    use std::time::Duration;
+
+    assert_eq!(parse_age("7d"), Ok(Duration::from_secs(7 * 86_400)));
+    assert_eq!(parse_age("12h"), Ok(Duration::from_secs(12 * 3_600)));
+    assert_eq!(parse_age("30M"), Ok(Duration::from_secs(30 * 60)));
+    assert!(parse_age("7").is_err());
+    assert!(parse_age("d").is_err());
+    assert!(parse_age("1.5d").is_err());
+    assert!(parse_age("-1d").is_err());
+    assert!(parse_age("7w").is_err());
+}
+
+#[test]
+fn test_validate_rejects_crossed_age_bounds() {
+    let config =
+        Config::try_parse_from(["rust-tree", "--newer-than", "30d", "--older-than", "7d"]).unwrap();
+    assert!(config.validate().is_ok());
+    let filter = config.to_walk_config().filter;
+    assert!(filter.modified_after.unwrap() < filter.modified_before.unwrap());
+
+    let config =
+        Config::try_parse_from(["rust-tree", "--newer-than", "7d", "--older-than", "30d"]).unwrap();
+    let err = config.validate().unwrap_err().to_string();
+    assert!(
+        err.contains("no file can be newer than 7d and older than 30d"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn test_ext_label_overrides_builtin_labels() {
+    let config = Config::try_parse_from([
+        "rust-tree",
+        "--ext-label",
+        ".ipynb=Notebook",
+        "--ext-label",
+        "foo=Foo Data",
+    ])
+    .unwrap();
+    let labels = config.extension_labels();
+    assert_eq!(labels.label(".ipynb"), Some("Notebook"));
+    assert_eq!(labels.label(".foo"), Some("Foo Data"));
+    assert_eq!(labels.label(".rs"), Some("Rust"));
+
+    let config =
+        Config::try_parse_from(["rust-tree", "--no-ext-labels", "--ext-label", "foo=Foo"]).unwrap();
+    let labels = config.extension_labels();
+    assert_eq!(labels.label(".rs"), None);
+    assert_eq!(labels.label(".foo"), Some("Foo"));
+
+    assert!(Config::try_parse_from(["rust-tree", "--ext-label", "rs"]).is_err());
+    assert!(Config::try_parse_from(["rust-tree", "--ext-label", "=Rust"]).is_err());
+}
+
+#[test]
+fn test_owner_coloring_is_selected_only_by_color_by() {
+    assert!(Config::try_parse_from(["rust-tree", "--color-scheme", "owner"]).is_err());
+
+    let cfg = Config::try_parse_from(["rust-tree", "--color-by", "owner"]).unwrap();
+    assert_eq!(cfg.effective_color_scheme(), rust_tree::ColorScheme::Owner);
+    assert!(cfg.required_metadata().contains(MetadataFields::OWNER));
+}
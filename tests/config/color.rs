@@ -1,6 +1,8 @@
 //! `config::color`（节点着色）的测试。
 
-use rust_tree::config::color::colorize_node;
+use rust_tree::config::color::{
+    colorize_node, file_color_for, file_colors, format_legend, owner_color,
+};
 use rust_tree::{ColorScheme, FsNode, FsNodeType};
 
 #[test]
@@ -55,3 +57,119 @@ fn test_no_color_scheme() {
     let colored = colorize_node(&node, ColorScheme::None);
     assert!(colored.to_string().contains("test.rs"));
 }
+
+/// 图例中列出的类别名（每行第一列）。
+fn legend_categories(legend: &str) -> Vec<String> {
+    legend
+        .lines()
+        .skip(1)
+        .map(|line| line.trim_start().split("  ").next().unwrap().to_string())
+        .collect()
+}
+
+#[test]
+fn test_legend_lists_categories_for_each_scheme() {
+    let symbols = ["dir/", "link", "-> target", "[loop]", "..."];
+    for (scheme, files) in [
+        (ColorScheme::None, vec![]),
+        (ColorScheme::Owner, vec!["owner"]),
+        (ColorScheme::Basic, vec!["source", "config", "docs", "lock"]),
+        (
+            ColorScheme::Extended,
+            vec![
+                "rust",
+                "python",
+                "javascript",
+                "c/java",
+                "go",
+                "ruby/php",
+                "config",
+                "data",
+                "ini",
+                "docs",
+                "text",
+                "lock",
+                "image",
+                "archive",
+            ],
+        ),
+    ] {
+        let categories = legend_categories(&format_legend(scheme, false));
+        for expected in symbols.iter().chain(files.iter()) {
+            assert!(
+                categories.iter().any(|c| c == expected),
+                "{:?} legend lacks {}",
+                scheme,
+                expected
+            );
+        }
+        assert_eq!(categories.len(), symbols.len() + files.len());
+        if scheme != ColorScheme::Owner {
+            assert_eq!(file_colors(scheme).len(), files.len());
+        }
+    }
+}
+
+#[test]
+fn test_legend_without_color_is_plain_text() {
+    let legend = format_legend(ColorScheme::Extended, false);
+    assert!(legend.starts_with("Legend:\n"));
+    assert!(!legend.contains('\x1b'));
+    assert!(legend.contains(".rs"));
+}
+
+#[test]
+fn test_legend_matches_node_colors() {
+    // 只比较颜色值而不渲染转义序列，避免依赖（并修改）colored 的全局开关。
+    let legend = format_legend(ColorScheme::Basic, false);
+    let file = FsNode::new("x.rs".into(), "/x.rs".into(), FsNodeType::File, 0, 0);
+    let colored_source = colorize_node(&file, ColorScheme::Basic);
+    // 图例中列出 .rs 的类别与 .rs 文件使用相同的颜色
+    let entry = file_color_for("x.rs", ColorScheme::Basic).unwrap();
+    assert_eq!(colored_source.fgcolor, Some(entry.color));
+    let source_line = legend.lines().find(|l| l.contains(".rs")).unwrap();
+    assert!(source_line.contains(entry.category), "{:?}", source_line);
+}
+
+/// 以给定 uid 构造文件节点。
+#[cfg(unix)]
+fn owned_file(name: &str, uid: Option<u32>) -> FsNode {
+    let mut node = FsNode::new(name.into(), name.into(), FsNodeType::File, 0, 0);
+    node.uid = uid;
+    node
+}
+
+#[test]
+fn test_owner_color_is_stable_and_spreads_adjacent_uids() {
+    assert_eq!(owner_color(1000), owner_color(1000));
+    assert_ne!(owner_color(1000), owner_color(1001));
+    assert_ne!(owner_color(0), owner_color(1000));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_color_by_owner_same_uid_same_color() {
+    use std::os::unix::fs::MetadataExt;
+
+    // 遍历得到的 uid 与文件元数据一致
+    let temp = tempfile::TempDir::new().unwrap();
+    std::fs::write(temp.path().join("a.rs"), b"").unwrap();
+    std::fs::write(temp.path().join("b.txt"), b"").unwrap();
+    let config = rust_tree::WalkConfig {
+        metadata: rust_tree::MetadataFields::OWNER,
+        ..Default::default()
+    };
+    let tree = rust_tree::walk_directory(temp.path(), &config, None).unwrap();
+    let children = tree.root.children.unwrap();
+    let uid = std::fs::metadata(temp.path().join("a.rs")).unwrap().uid();
+    assert!(children.iter().all(|c| c.uid == Some(uid)));
+
+    // 同一所有者：扩展名不同也得到相同颜色
+    let paint = |node: &FsNode| colorize_node(node, ColorScheme::Owner).fgcolor;
+    assert_eq!(paint(&children[0]), Some(owner_color(uid)));
+    assert_eq!(paint(&children[0]), paint(&children[1]));
+
+    // 不同所有者得到不同颜色
+    let other = owned_file("c.rs", Some(uid + 1));
+    assert_ne!(paint(&children[0]), paint(&other));
+}
@@ -0,0 +1,114 @@
+//! `config::file`（.rust-tree.toml 配置文件）的测试。
+
+use clap::CommandFactory;
+use rust_tree::config::file::{
+    find_config_file, load_config_file, merge_config_file, CONFIG_FILE_NAME,
+};
+use rust_tree::{Config, OutputFormat};
+use std::ffi::OsString;
+use std::fs;
+use tempfile::TempDir;
+
+/// 以给定命令行与 TOML 文本合并出最终配置。
+fn merged(cli: &[&str], toml_text: &str) -> Result<Config, rust_tree::TreeError> {
+    let args: Vec<OsString> = std::iter::once("rust-tree")
+        .chain(cli.iter().copied())
+        .map(OsString::from)
+        .collect();
+    let matches = Config::command().get_matches_from(&args);
+    let table: toml::Table = toml_text.parse().unwrap();
+    merge_config_file(&args, &matches, &table)
+}
+
+#[test]
+fn test_file_values_apply_when_not_on_cli() {
+    let config = merged(
+        &[],
+        "depth = 2\nformat = \"json\"\nall = true\nexclude = [\"target\", \"*.log\"]\n",
+    )
+    .unwrap();
+    assert_eq!(config.max_depth, 2);
+    assert_eq!(config.format, OutputFormat::Json);
+    assert!(config.show_hidden);
+    assert_eq!(config.exclude, vec!["target", "*.log"]);
+}
+
+#[test]
+fn test_cli_overrides_file() {
+    let config = merged(
+        &["-d", "5", "-f", "table"],
+        "depth = 2\nformat = \"json\"\n",
+    )
+    .unwrap();
+    assert_eq!(config.max_depth, 5);
+    assert_eq!(config.format, OutputFormat::Table);
+}
+
+#[test]
+fn test_file_values_precede_double_dash() {
+    let config = merged(&["--", "stats"], "depth = 2\nall = true\n").unwrap();
+    assert_eq!(config.command, None);
+    assert_eq!(config.path, std::path::PathBuf::from("stats"));
+    assert_eq!(config.max_depth, 2);
+    assert!(config.show_hidden);
+}
+
+#[test]
+fn test_cli_overrides_file_for_lists() {
+    let config = merged(&["-e", "dist"], "exclude = [\"target\"]\n").unwrap();
+    assert_eq!(config.exclude, vec!["dist"]);
+}
+
+#[test]
+fn test_false_boolean_is_ignored() {
+    let config = merged(&[], "all = false\n").unwrap();
+    assert!(!config.show_hidden);
+}
+
+#[test]
+fn test_unknown_key_is_error() {
+    let err = merged(&[], "no-such-option = 1\n").unwrap_err();
+    assert!(err.to_string().contains("no-such-option"));
+}
+
+#[test]
+fn test_config_key_is_rejected() {
+    assert!(merged(&[], "no-config = true\n").is_err());
+}
+
+#[test]
+fn test_invalid_value_is_error() {
+    assert!(merged(&[], "format = \"nope\"\n").is_err());
+}
+
+#[test]
+fn test_find_prefers_cwd_over_home() {
+    let cwd = TempDir::new().unwrap();
+    let home = TempDir::new().unwrap();
+    fs::write(home.path().join(CONFIG_FILE_NAME), "depth = 1\n").unwrap();
+
+    assert_eq!(
+        find_config_file(Some(cwd.path()), Some(home.path())),
+        Some(home.path().join(CONFIG_FILE_NAME))
+    );
+
+    fs::write(cwd.path().join(CONFIG_FILE_NAME), "depth = 2\n").unwrap();
+    assert_eq!(
+        find_config_file(Some(cwd.path()), Some(home.path())),
+        Some(cwd.path().join(CONFIG_FILE_NAME))
+    );
+}
+
+#[test]
+fn test_find_none_when_absent() {
+    let dir = TempDir::new().unwrap();
+    assert_eq!(find_config_file(Some(dir.path()), None), None);
+}
+
+#[test]
+fn test_load_reports_syntax_error() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join(CONFIG_FILE_NAME);
+    fs::write(&path, "depth = = 1\n").unwrap();
+    assert!(load_config_file(&path).is_err());
+}
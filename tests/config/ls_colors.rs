@@ -0,0 +1,75 @@
+//! `config::ls_colors`（`LS_COLORS` 解析）的测试。
+
+use colored::Color;
+use rust_tree::config::ls_colors::{LsColors, LsStyle};
+
+#[test]
+fn test_parse_ls_colors_sample() {
+    let colors = LsColors::parse("*.rs=01;32:di=01;34");
+
+    let rs = colors.extension_style("main.rs").unwrap();
+    assert_eq!(rs.fg, Some(Color::Green));
+    assert!(rs.bold);
+    // 扩展名不区分大小写
+    assert_eq!(colors.extension_style("BUILD.RS"), Some(rs));
+
+    let dir = colors.type_style("di").unwrap();
+    assert_eq!(dir.fg, Some(Color::Blue));
+    assert!(dir.bold);
+
+    assert!(colors.type_style("ln").is_none());
+    assert!(colors.extension_style("README.md").is_none());
+    assert!(colors.extension_style("Makefile").is_none());
+}
+
+#[test]
+fn test_parse_ls_colors_ignores_malformed_and_unsupported_entries() {
+    let colors = LsColors::parse("::bogus:*README=01:ln=target:*.=31:fi=0:*.tar.gz=31:*.gz=33");
+
+    // `ln=target` 无法解析目标类型，视为未设置
+    assert!(colors.type_style("ln").is_none());
+    assert_eq!(colors.type_style("fi"), Some(&LsStyle::default()));
+    // 多段扩展名优先于最后一段
+    assert_eq!(
+        colors.extension_style("a.tar.gz").unwrap().fg,
+        Some(Color::Red)
+    );
+    assert_eq!(
+        colors.extension_style("a.gz").unwrap().fg,
+        Some(Color::Yellow)
+    );
+    // 点文件没有扩展名
+    assert!(LsColors::parse("*.bashrc=32")
+        .extension_style(".bashrc")
+        .is_none());
+}
+
+#[test]
+fn test_ls_style_sgr_codes() {
+    let style = LsStyle::parse("04;93;41");
+    assert!(style.underline);
+    assert_eq!(style.fg, Some(Color::BrightYellow));
+    assert_eq!(style.bg, Some(Color::Red));
+
+    assert_eq!(
+        LsStyle::parse("38;5;196").fg,
+        Some(Color::TrueColor { r: 255, g: 0, b: 0 })
+    );
+    assert_eq!(
+        LsStyle::parse("38;2;10;20;30").fg,
+        Some(Color::TrueColor {
+            r: 10,
+            g: 20,
+            b: 30
+        })
+    );
+    assert_eq!(LsStyle::parse("38;5;9").fg, Some(Color::BrightRed));
+    // `0` 重置之前的属性
+    assert_eq!(LsStyle::parse("01;0"), LsStyle::default());
+
+    // 检查 paint 得到的样式本身，而不渲染转义序列（那需要修改 colored 的全局开关）。
+    let painted = LsStyle::parse("01;32").paint("x");
+    assert_eq!(painted.fgcolor, Some(Color::Green));
+    assert!(painted.style.contains(colored::Styles::Bold));
+    assert_eq!(&*painted, "x");
+}
@@ -1,13 +1,28 @@
-//! `core`（遍历、统计、过滤、进度、流式）的测试。
+//! `core`（遍历、tar 归档、统计、过滤、行数预算、文本行数、挂载点、进度、流式、差异与快照、git）的测试。
 //!
 //! `tests/core.rs` 是 `core` 集成测试目标的 crate root，因此每个子模块都用
 //! `#[path]` 锚定到 `tests/core/` 下对应的镜像位置（crate root 的 `mod` 声明
 //! 相对 `tests/` 解析，而非进入 `core/` 子目录）。
 
+#[path = "core/archive.rs"]
+mod archive;
+#[path = "core/classifier.rs"]
+mod classifier;
 #[path = "core/collector.rs"]
 mod collector;
+#[path = "core/diff.rs"]
+mod diff;
 #[path = "core/filter.rs"]
 mod filter;
+#[path = "core/fit.rs"]
+mod fit;
+#[path = "core/git.rs"]
+mod git;
+#[path = "core/lines.rs"]
+mod lines;
+#[cfg(unix)]
+#[path = "core/mounts.rs"]
+mod mounts;
 #[path = "core/progress.rs"]
 mod progress;
 #[path = "core/streaming.rs"]
@@ -0,0 +1,112 @@
+//! `core::archive`（从 tar 流构建文件树）的测试。
+
+use rust_tree::core::archive::{read_tar, TAR_ROOT_NAME};
+use rust_tree::core::walker::WalkConfig;
+use rust_tree::{format_tree, ColorMode, ColorScheme, FsNode};
+use std::path::Path;
+
+/// 在内存中构建一个小归档：`src/` 没有显式的目录条目，`docs/` 有。
+fn sample_tar() -> Vec<u8> {
+    let mut builder = tar::Builder::new(Vec::new());
+    let mut append = |path: &str, data: &[u8]| {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(data.len() as u64);
+        header.set_mode(0o644);
+        header.set_mtime(1_700_000_000);
+        header.set_cksum();
+        builder.append_data(&mut header, path, data).unwrap();
+    };
+    append("./src/main.rs", b"fn main() {}\n");
+    append("./src/lib.rs", b"");
+    append("README.md", b"# demo\n");
+
+    let mut dir = tar::Header::new_gnu();
+    dir.set_entry_type(tar::EntryType::Directory);
+    dir.set_size(0);
+    dir.set_mode(0o755);
+    dir.set_cksum();
+    builder.append_data(&mut dir, "docs/", &[][..]).unwrap();
+
+    let mut link = tar::Header::new_gnu();
+    link.set_entry_type(tar::EntryType::Symlink);
+    link.set_size(0);
+    link.set_cksum();
+    builder
+        .append_link(&mut link, "docs/readme", "../README.md")
+        .unwrap();
+
+    builder.into_inner().unwrap()
+}
+
+fn child<'a>(node: &'a FsNode, name: &str) -> &'a FsNode {
+    node.children
+        .iter()
+        .flatten()
+        .find(|c| c.name == name)
+        .unwrap_or_else(|| panic!("no child named {}", name))
+}
+
+#[test]
+fn test_read_tar_builds_tree() {
+    let tree = read_tar(&sample_tar()[..], TAR_ROOT_NAME, &WalkConfig::default()).unwrap();
+    assert_eq!(tree.root.name, "<stdin>");
+    assert_eq!(tree.max_depth, 2);
+
+    let src = child(&tree.root, "src");
+    assert!(src.is_directory());
+    let main = child(src, "main.rs");
+    assert_eq!(main.size, 13);
+    assert_eq!(main.path.as_deref(), Some(Path::new("src/main.rs")));
+    assert!(main.modified.is_some());
+
+    let link = child(child(&tree.root, "docs"), "readme");
+    assert_eq!(link.link_target.as_deref(), Some(Path::new("../README.md")));
+}
+
+#[test]
+fn test_read_tar_renders_as_tree() {
+    let tree = read_tar(&sample_tar()[..], TAR_ROOT_NAME, &WalkConfig::default()).unwrap();
+    let output = format_tree(&tree.root, false, ColorMode::Never, ColorScheme::default());
+    assert_eq!(
+        output,
+        "<stdin>/
+├── docs/
+│   └── readme -> ../README.md
+├── src/
+│   ├── lib.rs
+│   └── main.rs
+└── README.md
+"
+    );
+}
+
+#[test]
+fn test_read_tar_applies_depth_and_filter() {
+    let mut config = WalkConfig {
+        max_depth: 1,
+        summarize_truncated: true,
+        ..Default::default()
+    };
+    config.filter.add_exclude("*.md").unwrap();
+    let tree = read_tar(&sample_tar()[..], TAR_ROOT_NAME, &config).unwrap();
+
+    assert!(tree
+        .root
+        .children
+        .iter()
+        .flatten()
+        .all(|c| c.name != "README.md"));
+    assert_eq!(tree.filtered.files, 1);
+    // 截断的目录不展开，但保留子树中文件的总大小；目录自身的 size 仍为 0
+    let src = child(&tree.root, "src");
+    assert!(src.truncated);
+    assert!(src.children.is_none());
+    assert_eq!(src.aggregate_size(), 13);
+    assert_eq!(src.size, 0);
+}
+
+#[test]
+fn test_read_tar_rejects_invalid_stream() {
+    let garbage = vec![b'x'; 700];
+    assert!(read_tar(&garbage[..], TAR_ROOT_NAME, &WalkConfig::default()).is_err());
+}
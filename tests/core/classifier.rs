@@ -0,0 +1,21 @@
+//! `core::classifier`（扩展名 → 类别映射）的测试。
+
+use rust_tree::core::classifier::DEFAULT_CATEGORY;
+use rust_tree::FileClassifier;
+
+#[test]
+fn test_classifier_from_map_normalizes_extensions() {
+    let classifier = FileClassifier::from_map([(".Frag", "shaders")]).with_fallback("misc");
+    assert_eq!(classifier.classify_ext(Some(".frag")), "shaders");
+    assert_eq!(classifier.classify_ext(Some("rs")), "misc");
+    assert_eq!(classifier.classify_ext(None), "misc");
+}
+
+#[test]
+fn test_default_classifier_matches_builtin_groups() {
+    let classifier = FileClassifier::default();
+    assert_eq!(classifier.classify_ext(Some(".rs")), "source");
+    assert_eq!(classifier.classify_ext(Some(".toml")), "config");
+    assert_eq!(classifier.classify_ext(Some(".md")), "docs");
+    assert_eq!(classifier.classify_ext(Some(".glsl")), DEFAULT_CATEGORY);
+}
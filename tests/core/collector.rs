@@ -1,7 +1,10 @@
 //! `core::collector`（统计聚合辅助函数）的测试。
 
 use rust_tree::core::collector::{analyze_by_extension, find_largest_files};
-use rust_tree::{FsNode, FsNodeType};
+use rust_tree::{
+    collect_stats_with, FileClassifier, FsNode, FsNodeType, FsTree, StatsOptions, SubtreeSummary,
+};
+use std::time::Instant;
 
 #[test]
 fn test_find_largest_files() {
@@ -22,6 +25,39 @@ fn test_find_largest_files() {
     assert_eq!(largest[2].size, 200);
 }
 
+#[test]
+fn test_find_largest_files_ties_ordered_by_path() {
+    // 故意打乱顺序：同样大小的文件应按路径升序输出，与输入顺序无关。
+    let files = [
+        FsNode::new("d.txt".into(), "/d.txt".into(), FsNodeType::File, 100, 0),
+        FsNode::new("big".into(), "/z/big".into(), FsNodeType::File, 900, 0),
+        FsNode::new("b.txt".into(), "/b.txt".into(), FsNodeType::File, 100, 0),
+        FsNode::new(
+            "a.txt".into(),
+            "/sub/a.txt".into(),
+            FsNodeType::File,
+            100,
+            0,
+        ),
+        FsNode::new("c.txt".into(), "/c.txt".into(), FsNodeType::File, 100, 0),
+    ];
+
+    let refs: Vec<&FsNode> = files.iter().collect();
+    let paths = |limit| -> Vec<String> {
+        find_largest_files(&refs, limit)
+            .into_iter()
+            .map(|f| f.path.to_string_lossy().into_owned())
+            .collect()
+    };
+
+    assert_eq!(
+        paths(5),
+        ["/z/big", "/b.txt", "/c.txt", "/d.txt", "/sub/a.txt"]
+    );
+    // 截断发生在并列组内部时，保留的也是路径最小的那些。
+    assert_eq!(paths(3), ["/z/big", "/b.txt", "/c.txt"]);
+}
+
 #[test]
 fn test_analyze_by_extension() {
     let files = [
@@ -79,6 +115,7 @@ fn test_find_largest_files_limit_exceeds_count() {
 }
 
 #[test]
+#[allow(clippy::unnecessary_get_then_check)]
 fn test_analyze_by_extension_ignores_dotfiles() {
     // 点文件应归入“(no extension)”，而非被当成扩展名 ".gitignore"
     let files = [
@@ -102,3 +139,334 @@ fn test_analyze_by_extension_ignores_dotfiles() {
     assert_eq!(by_ext.get("(no extension)").unwrap().count, 1);
     assert!(by_ext.get(".gitignore").is_none());
 }
+
+#[test]
+fn test_custom_classifier_category_stats() {
+    let options = StatsOptions {
+        classifier: FileClassifier::default().with("glsl", "shaders"),
+        ..Default::default()
+    };
+    let root = FsNode::new_directory(
+        "root".into(),
+        "/root".into(),
+        0,
+        vec![
+            FsNode::new(
+                "light.glsl".into(),
+                "/root/light.glsl".into(),
+                FsNodeType::File,
+                30,
+                1,
+            ),
+            FsNode::new(
+                "blur.GLSL".into(),
+                "/root/blur.GLSL".into(),
+                FsNodeType::File,
+                20,
+                1,
+            ),
+            FsNode::new(
+                "main.rs".into(),
+                "/root/main.rs".into(),
+                FsNodeType::File,
+                50,
+                1,
+            ),
+            FsNode::new(
+                "data.bin".into(),
+                "/root/data.bin".into(),
+                FsNodeType::File,
+                0,
+                1,
+            ),
+        ],
+    );
+    let tree = FsTree::new(root, 1);
+
+    let stats = collect_stats_with(&tree, Instant::now(), &options);
+
+    let shaders = stats.files_by_category.get("shaders").unwrap();
+    assert_eq!(shaders.count, 2);
+    assert_eq!(shaders.total_size, 50);
+    assert!((shaders.percentage - 50.0).abs() < 1e-9);
+    // 默认映射仍然生效；未命中的扩展名归入 "other"。
+    assert_eq!(stats.files_by_category.get("source").unwrap().count, 1);
+    assert_eq!(stats.files_by_category.get("other").unwrap().count, 1);
+}
+
+fn tree_with_sizes(sizes: &[u64]) -> FsTree {
+    let children = sizes
+        .iter()
+        .enumerate()
+        .map(|(i, &size)| {
+            let name = format!("f{}.bin", i);
+            let path = format!("/root/{}", name);
+            FsNode::new(name, path.into(), FsNodeType::File, size, 1)
+        })
+        .collect();
+    FsTree::new(
+        FsNode::new_directory("root".into(), "/root".into(), 0, children),
+        1,
+    )
+}
+
+#[test]
+fn test_average_and_median_include_empty_by_default() {
+    let tree = tree_with_sizes(&[0, 0, 100, 300]);
+    let stats = collect_stats_with(&tree, Instant::now(), &StatsOptions::default());
+
+    assert_eq!(stats.empty_files, 2);
+    assert!((stats.average_file_size - 100.0).abs() < 1e-9);
+    assert_eq!(stats.median_file_size, 50);
+}
+
+#[test]
+fn test_stats_ignore_empty_excludes_zero_byte_files() {
+    let tree = tree_with_sizes(&[0, 0, 100, 300]);
+    let options = StatsOptions {
+        ignore_empty: true,
+        ..Default::default()
+    };
+    let stats = collect_stats_with(&tree, Instant::now(), &options);
+
+    // 零字节文件仍单独计数，但不再拉低平均值/中位数；总数不受影响。
+    assert_eq!(stats.empty_files, 2);
+    assert_eq!(stats.total_files, 4);
+    assert!((stats.average_file_size - 200.0).abs() < 1e-9);
+    assert_eq!(stats.median_file_size, 200);
+}
+
+fn file(name: &str, depth: usize) -> FsNode {
+    FsNode::new(name.into(), name.into(), FsNodeType::File, 1, depth)
+}
+
+fn dir(name: &str, depth: usize, children: Vec<FsNode>) -> FsNode {
+    FsNode::new_directory(name.into(), name.into(), depth, children)
+}
+
+#[test]
+fn test_leaf_depth_stddev_balanced_tree_is_zero() {
+    // 两个子目录各含两个文件：所有叶子深度都是 2。
+    let root = dir(
+        "root",
+        0,
+        vec![
+            dir("a", 1, vec![file("a1", 2), file("a2", 2)]),
+            dir("b", 1, vec![file("b1", 2), file("b2", 2)]),
+        ],
+    );
+    let stats = collect_stats_with(
+        &FsTree::new(root, 2),
+        Instant::now(),
+        &StatsOptions::default(),
+    );
+
+    assert!((stats.leaf_depth_mean - 2.0).abs() < 1e-9);
+    assert!(stats.leaf_depth_stddev.abs() < 1e-9);
+}
+
+#[test]
+fn test_leaf_depth_stddev_lopsided_tree_is_larger() {
+    // 一侧是深度 1 的文件，另一侧是深度 4 的长链：叶子深度 {1, 4}。
+    let chain = dir(
+        "d1",
+        1,
+        vec![dir("d2", 2, vec![dir("d3", 3, vec![file("deep", 4)])])],
+    );
+    let root = dir("root", 0, vec![chain, file("shallow", 1)]);
+    let stats = collect_stats_with(
+        &FsTree::new(root, 4),
+        Instant::now(),
+        &StatsOptions::default(),
+    );
+
+    assert!((stats.leaf_depth_mean - 2.5).abs() < 1e-9);
+    assert!((stats.leaf_depth_stddev - 1.5).abs() < 1e-9);
+}
+
+#[test]
+fn test_empty_directories_are_recursively_empty() {
+    // build/ 只含空子目录，也算空；src/ 含文件，不算。
+    let build = dir(
+        "build",
+        1,
+        vec![
+            dir("out", 2, vec![dir("tmp", 3, vec![])]),
+            dir("cache", 2, vec![]),
+        ],
+    );
+    let src = dir("src", 1, vec![file("main.rs", 2), dir("empty", 2, vec![])]);
+    let root = dir("root", 0, vec![build, src]);
+    let stats = collect_stats_with(
+        &FsTree::new(root.clone(), 3),
+        Instant::now(),
+        &StatsOptions::default(),
+    );
+
+    let empty: Vec<_> = stats
+        .empty_directories
+        .iter()
+        .map(|p| p.to_str().unwrap())
+        .collect();
+    assert_eq!(empty, ["build", "out", "tmp", "cache", "empty"]);
+
+    let build = &root.children.as_ref().unwrap()[0];
+    assert!(build.is_recursively_empty() && !build.is_leaf_empty());
+    let tmp = &build.children.as_ref().unwrap()[0]
+        .children
+        .as_ref()
+        .unwrap()[0];
+    assert!(tmp.is_leaf_empty() && tmp.is_recursively_empty());
+    assert!(!root.is_recursively_empty());
+}
+
+#[test]
+fn test_deepest_path_is_deepest_file() {
+    // 最深的节点是空目录 x/y/z/w，但只统计文件：最深的文件是 a/b 下的两个文件（深度 3）。
+    let deep = dir(
+        "a",
+        1,
+        vec![dir("b", 2, vec![file("c.rs", 3), file("d.rs", 3)])],
+    );
+    let shallow = dir(
+        "x",
+        1,
+        vec![dir("y", 2, vec![dir("z", 3, vec![dir("w", 4, vec![])])])],
+    );
+    let root = dir("root", 0, vec![file("top.rs", 1), shallow, deep]);
+    let stats = collect_stats_with(
+        &FsTree::new(root, 4),
+        Instant::now(),
+        &StatsOptions::default(),
+    );
+    // 深度相同时取先序遍历中的第一个
+    assert_eq!(stats.deepest_path, Some(("c.rs".into(), 3)));
+
+    let empty = collect_stats_with(
+        &FsTree::new(dir("root", 0, vec![]), 0),
+        Instant::now(),
+        &StatsOptions::default(),
+    );
+    assert_eq!(empty.deepest_path, None);
+}
+
+#[test]
+fn test_truncated_directory_is_not_empty() {
+    let mut deep = dir("deep", 1, vec![]);
+    deep.children = None;
+    deep.truncated = true;
+    let root = dir("root", 0, vec![deep]);
+    let stats = collect_stats_with(
+        &FsTree::new(root, 1),
+        Instant::now(),
+        &StatsOptions::default(),
+    );
+    assert!(stats.empty_directories.is_empty());
+}
+
+#[test]
+fn test_max_entries_truncates_and_counts_omitted() {
+    let temp = tempfile::TempDir::new().unwrap();
+    for i in 0..100 {
+        std::fs::write(temp.path().join(format!("f{:03}.txt", i)), b"").unwrap();
+    }
+    let config = rust_tree::WalkConfig {
+        max_entries_per_dir: 10,
+        ..Default::default()
+    };
+    let tree = rust_tree::walk_directory(temp.path(), &config, None).unwrap();
+    let children = tree.root.children.as_ref().unwrap();
+    assert_eq!(children.len(), 10);
+    assert_eq!(children[9].name, "f009.txt");
+    assert_eq!(tree.root.omitted, 90);
+
+    let output = rust_tree::format_tree(
+        &tree.root,
+        false,
+        rust_tree::ColorMode::Never,
+        rust_tree::ColorScheme::default(),
+    );
+    assert!(output.ends_with("└── ... (90 more)\n"), "{}", output);
+
+    let stats = collect_stats_with(&tree, Instant::now(), &StatsOptions::default());
+    assert_eq!(stats.total_files, 10);
+    assert_eq!(stats.omitted_entries, 90);
+}
+
+#[test]
+fn test_find_duplicates_groups_identical_files() {
+    use rust_tree::core::collector::find_duplicates;
+    use rust_tree::{walk_directory, WalkConfig};
+
+    let temp = tempfile::TempDir::new().unwrap();
+    std::fs::create_dir(temp.path().join("sub")).unwrap();
+    std::fs::write(temp.path().join("a.txt"), b"same content").unwrap();
+    std::fs::write(temp.path().join("sub/copy.txt"), b"same content").unwrap();
+    // 大小相同但内容不同：需要哈希才能排除
+    std::fs::write(temp.path().join("other.txt"), b"diff content").unwrap();
+    std::fs::write(temp.path().join("unique.txt"), b"unique").unwrap();
+    // 零字节文件不算重复
+    std::fs::write(temp.path().join("e1"), b"").unwrap();
+    std::fs::write(temp.path().join("e2"), b"").unwrap();
+
+    let tree = walk_directory(temp.path(), &WalkConfig::default(), None).unwrap();
+    let groups = find_duplicates(&tree);
+    assert_eq!(groups.len(), 1);
+    let names: Vec<&str> = groups[0].iter().map(|f| f.name.as_str()).collect();
+    assert_eq!(names, ["a.txt", "copy.txt"]);
+    assert_eq!(groups[0][0].size, 12);
+
+    // 只有请求时才写入统计信息
+    let stats = collect_stats_with(&tree, Instant::now(), &StatsOptions::default());
+    assert!(stats.duplicates.is_none());
+    let options = StatsOptions {
+        find_duplicates: true,
+        ..Default::default()
+    };
+    let stats = collect_stats_with(&tree, Instant::now(), &options);
+    assert_eq!(stats.duplicates.as_ref().map(Vec::len), Some(1));
+}
+
+#[test]
+fn test_find_duplicates_empty_files_skipped_or_grouped() {
+    use rust_tree::core::collector::{find_duplicates_with, EmptyDuplicates};
+    use rust_tree::{walk_directory, WalkConfig};
+
+    let temp = tempfile::TempDir::new().unwrap();
+    for name in ["__init__.py", "a.keep", "b.keep", ".gitkeep"] {
+        std::fs::write(temp.path().join(name), b"").unwrap();
+    }
+    std::fs::write(temp.path().join("x.txt"), b"hello").unwrap();
+    std::fs::write(temp.path().join("y.txt"), b"hello").unwrap();
+    let tree = walk_directory(temp.path(), &WalkConfig::default(), None).unwrap();
+
+    let skipped = find_duplicates_with(&tree, EmptyDuplicates::Skip);
+    assert_eq!(skipped.len(), 1);
+    assert_eq!(skipped[0].len(), 2);
+    assert_eq!(skipped[0][0].size, 5);
+
+    // 零字节文件单独成组，排在真正的重复之后
+    let grouped = find_duplicates_with(&tree, EmptyDuplicates::Group);
+    assert_eq!(grouped.len(), 2);
+    assert_eq!(grouped[0], skipped[0]);
+    let empty: Vec<&str> = grouped[1].iter().map(|f| f.name.as_str()).collect();
+    assert_eq!(empty, [".gitkeep", "__init__.py", "a.keep", "b.keep"]);
+    assert!(grouped[1].iter().all(|f| f.size == 0));
+}
+
+#[test]
+fn test_collect_stats_counts_collapsed_subtrees() {
+    // `--collapse-depth 1`：a/ 未展开，其子树汇总仍计入总计。
+    let mut collapsed = FsNode::new_directory("a".into(), "/r/a".into(), 1, Vec::new());
+    collapsed.children = None;
+    collapsed.truncated = true;
+    collapsed.collapsed_summary = Some(SubtreeSummary { files: 2, size: 5 });
+    let file = FsNode::new("b.txt".into(), "/r/b.txt".into(), FsNodeType::File, 1, 1);
+    let root = FsNode::new_directory("r".into(), "/r".into(), 0, vec![collapsed, file]);
+    let tree = FsTree::new(root, 1);
+
+    let stats = collect_stats_with(&tree, Instant::now(), &StatsOptions::default());
+    assert_eq!(stats.total_files, 3);
+    assert_eq!(stats.total_size, 6);
+    assert_eq!(stats.total_directories, 2);
+}
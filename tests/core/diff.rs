@@ -0,0 +1,127 @@
+//! `core::diff`（两棵树的差异）与 `core::snapshot`（快照读写）的测试。
+
+use rust_tree::core::snapshot::parse_stats;
+use rust_tree::{
+    collect_stats, diff_stats, diff_trees, load_snapshot, save_snapshot, walk_directory, DiffKind,
+    TreeStats, WalkConfig,
+};
+use std::path::PathBuf;
+use tempfile::TempDir;
+
+#[test]
+fn test_diff_trees_reports_added_removed_changed() {
+    let old = TempDir::new().unwrap();
+    std::fs::create_dir(old.path().join("sub")).unwrap();
+    std::fs::write(old.path().join("sub/gone.txt"), b"x").unwrap();
+    std::fs::write(old.path().join("same.txt"), b"same").unwrap();
+    std::fs::write(old.path().join("grow.txt"), b"a").unwrap();
+
+    let new = TempDir::new().unwrap();
+    std::fs::create_dir(new.path().join("sub")).unwrap();
+    std::fs::write(new.path().join("same.txt"), b"same").unwrap();
+    std::fs::write(new.path().join("grow.txt"), b"abc").unwrap();
+    std::fs::write(new.path().join("fresh.txt"), b"new").unwrap();
+
+    let config = WalkConfig::default();
+    let old_tree = walk_directory(old.path(), &config, None).unwrap();
+    let new_tree = walk_directory(new.path(), &config, None).unwrap();
+
+    let diff = diff_trees(&old_tree, &new_tree);
+    let summary: Vec<(PathBuf, DiffKind)> = diff.iter().map(|e| (e.path.clone(), e.kind)).collect();
+    assert_eq!(
+        summary,
+        vec![
+            (PathBuf::from("fresh.txt"), DiffKind::Added),
+            (PathBuf::from("grow.txt"), DiffKind::Changed),
+            (PathBuf::from("sub/gone.txt"), DiffKind::Removed),
+        ]
+    );
+
+    let grow = &diff[1];
+    assert_eq!(grow.old_size, Some(1));
+    assert_eq!(grow.new_size, Some(3));
+}
+
+#[test]
+fn test_diff_identical_trees_is_empty() {
+    let temp = TempDir::new().unwrap();
+    std::fs::write(temp.path().join("a.txt"), b"a").unwrap();
+
+    let tree = walk_directory(temp.path(), &WalkConfig::default(), None).unwrap();
+    assert!(diff_trees(&tree, &tree).is_empty());
+}
+
+#[test]
+fn test_snapshot_round_trip_diffs_clean() {
+    let temp = TempDir::new().unwrap();
+    std::fs::create_dir(temp.path().join("src")).unwrap();
+    std::fs::write(temp.path().join("src/main.rs"), b"fn main() {}").unwrap();
+
+    let tree = walk_directory(temp.path(), &WalkConfig::default(), None).unwrap();
+    let out = TempDir::new().unwrap();
+    let snapshot = out.path().join("snap.json");
+    save_snapshot(&tree, &TreeStats::new(), &snapshot).unwrap();
+
+    let restored = load_snapshot(&snapshot).unwrap();
+    assert_eq!(restored.max_depth, tree.max_depth);
+    assert_eq!(restored.root.name, tree.root.name);
+    assert!(diff_trees(&tree, &restored).is_empty());
+}
+
+#[test]
+fn test_load_snapshot_rejects_non_snapshot_json() {
+    let temp = TempDir::new().unwrap();
+    let path = temp.path().join("bad.json");
+    std::fs::write(&path, br#"{"total_files": 1}"#).unwrap();
+
+    assert!(load_snapshot(&path).is_err());
+}
+
+#[test]
+fn test_diff_stats_reports_totals_and_extension_changes() {
+    let temp = TempDir::new().unwrap();
+    std::fs::write(temp.path().join("main.rs"), b"fn main() {}").unwrap();
+    std::fs::write(temp.path().join("notes.txt"), b"hello").unwrap();
+    let config = WalkConfig::default();
+    let tree = walk_directory(temp.path(), &config, None).unwrap();
+    let baseline = collect_stats(&tree, std::time::Instant::now(), 10);
+
+    std::fs::create_dir(temp.path().join("src")).unwrap();
+    std::fs::write(temp.path().join("src/lib.rs"), b"pub fn f() {}").unwrap();
+    std::fs::remove_file(temp.path().join("notes.txt")).unwrap();
+    let tree = walk_directory(temp.path(), &config, None).unwrap();
+    let current = collect_stats(&tree, std::time::Instant::now(), 10);
+
+    let delta = diff_stats(&baseline, &current);
+    assert_eq!(delta.total_files, 0);
+    assert_eq!(delta.total_directories, 1);
+    assert_eq!(delta.total_size, 13 - 5);
+    let extensions: Vec<(&str, i64, i64)> = delta
+        .extensions
+        .iter()
+        .map(|e| (e.extension.as_str(), e.count, e.size))
+        .collect();
+    assert_eq!(extensions, vec![(".rs", 1, 13), (".txt", -1, -5)]);
+
+    assert!(diff_stats(&current, &current).extensions.is_empty());
+}
+
+#[test]
+fn test_parse_stats_accepts_stats_json_and_snapshot() {
+    let temp = TempDir::new().unwrap();
+    std::fs::write(temp.path().join("a.rs"), b"abc").unwrap();
+    let tree = walk_directory(temp.path(), &WalkConfig::default(), None).unwrap();
+    let stats = collect_stats(&tree, std::time::Instant::now(), 10);
+
+    let json = rust_tree::formatters::json::format_stats_only(&stats, true).unwrap();
+    let parsed = parse_stats(&json).unwrap();
+    assert_eq!(parsed.total_files, 1);
+    assert_eq!(parsed.total_size, 3);
+
+    let snapshot = temp.path().join("snap.json");
+    save_snapshot(&tree, &stats, &snapshot).unwrap();
+    let text = std::fs::read_to_string(&snapshot).unwrap();
+    assert_eq!(parse_stats(&text).unwrap().total_size, 3);
+
+    assert!(parse_stats("[1, 2]").is_err());
+}
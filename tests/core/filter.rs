@@ -1,6 +1,6 @@
 //! `core::filter`（包含/排除模式过滤）的测试。
 
-use rust_tree::core::filter::common_excludes::rust_patterns;
+use rust_tree::core::filter::common_excludes::{rust_patterns, vcs_dirs};
 use rust_tree::core::filter::FilterConfig;
 use std::path::Path;
 
@@ -10,6 +10,7 @@ fn test_filter_config_default() {
     assert!(!config.exclude_hidden);
     assert!(config.exclude_patterns.is_empty());
     assert!(config.include_pattern.is_none());
+    assert!(config.prune_dirs.is_empty());
 }
 
 #[test]
@@ -78,3 +79,384 @@ fn test_rust_patterns() {
     assert!(patterns.contains(&".git"));
     assert!(patterns.contains(&"target"));
 }
+
+#[test]
+fn test_prune_dirs_only_match_directories_by_name() {
+    let mut config = FilterConfig::new();
+    for name in vcs_dirs() {
+        config.add_prune_dir(name);
+    }
+    assert!(config.should_exclude(Path::new("repo/.git"), true));
+    assert!(config.should_exclude(Path::new("CVS"), true));
+    // 同名文件不受影响（例如 git worktree 中的 `.git` 文件）。
+    assert!(!config.should_exclude(Path::new("repo/.git"), false));
+    assert!(!config.should_exclude(Path::new("src"), true));
+}
+
+#[test]
+fn test_double_star_matches_root_relative_path() {
+    let mut filter = FilterConfig::new();
+    filter.add_exclude("**/tests/**").unwrap();
+
+    // 根本身位于某个 `tests/` 之下：只看相对路径，src 下的文件不受影响。
+    let root = Path::new("/home/u/tests/project");
+    assert!(!filter.should_exclude_under(root, &root.join("src/main.rs"), false));
+    assert!(filter.should_exclude_under(root, &root.join("tests/unit/a.rs"), false));
+    assert!(filter.should_exclude_under(root, &root.join("crates/x/tests/b.rs"), false));
+
+    // 根不在 `tests/` 之下时结果相同。
+    let root = Path::new("/srv/project");
+    assert!(!filter.should_exclude_under(root, &root.join("src/main.rs"), false));
+    assert!(filter.should_exclude_under(root, &root.join("tests/unit/a.rs"), false));
+}
+
+#[test]
+fn test_double_star_exclude_during_walk_ignores_absolute_prefix() {
+    let temp = tempfile::TempDir::new().unwrap();
+    let root = temp.path().join("tests/project");
+    std::fs::create_dir_all(root.join("src")).unwrap();
+    std::fs::create_dir_all(root.join("tests/nested")).unwrap();
+    std::fs::write(root.join("src/lib.rs"), b"").unwrap();
+    std::fs::write(root.join("tests/nested/case.rs"), b"").unwrap();
+
+    let mut config = rust_tree::WalkConfig::default();
+    config.filter.add_exclude("**/tests/**").unwrap();
+    let tree = rust_tree::walk_directory(&root, &config, None).unwrap();
+
+    let names: Vec<String> = rust_tree::get_all_files(&tree)
+        .into_iter()
+        .map(|f| f.name)
+        .collect();
+    assert_eq!(names, vec!["lib.rs".to_string()]);
+}
+
+/// 以 `.gitignore` 规则遍历 `root`，返回所有文件相对 `root` 的路径（已排序）。
+fn gitignored_walk(root: &Path) -> Vec<String> {
+    let mut config = rust_tree::WalkConfig::default();
+    config.filter.exclude_hidden = true;
+    config.filter.respect_gitignore = true;
+    config.filter.load_gitignore(root);
+    let tree = rust_tree::walk_directory(root, &config, None).unwrap();
+
+    let mut files: Vec<String> = rust_tree::get_all_files(&tree)
+        .into_iter()
+        .map(|f| {
+            f.path
+                .as_ref()
+                .unwrap()
+                .strip_prefix(root)
+                .unwrap()
+                .to_string_lossy()
+                .into_owned()
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+#[test]
+fn test_gitignore_nested_files_are_relative_to_their_directory() {
+    let temp = tempfile::TempDir::new().unwrap();
+    let root = temp.path();
+    std::fs::create_dir_all(root.join("target/debug")).unwrap();
+    std::fs::create_dir_all(root.join("sub/build")).unwrap();
+    std::fs::create_dir_all(root.join("build")).unwrap();
+    std::fs::write(root.join(".gitignore"), "target/\n*.log\n").unwrap();
+    std::fs::write(root.join("sub/.gitignore"), "/build\n").unwrap();
+    std::fs::write(root.join("target/debug/app"), b"").unwrap();
+    std::fs::write(root.join("run.log"), b"").unwrap();
+    std::fs::write(root.join("main.rs"), b"").unwrap();
+    std::fs::write(root.join("sub/build/out.o"), b"").unwrap();
+    std::fs::write(root.join("sub/deep.log"), b"").unwrap();
+    std::fs::write(root.join("build/keep.txt"), b"").unwrap();
+
+    // 外层的 *.log 作用于所有层级；sub 中锚定的 /build 只作用于 sub/build。
+    assert_eq!(gitignored_walk(root), vec!["build/keep.txt", "main.rs"],);
+}
+
+#[test]
+fn test_gitignore_negation_reincludes_file() {
+    let temp = tempfile::TempDir::new().unwrap();
+    let root = temp.path();
+    std::fs::create_dir_all(root.join("logs")).unwrap();
+    std::fs::write(root.join(".gitignore"), "*.log\n!important.log\n").unwrap();
+    std::fs::write(root.join("debug.log"), b"").unwrap();
+    std::fs::write(root.join("important.log"), b"").unwrap();
+    // 内层文件可再次忽略被外层否定重新包含的名称
+    std::fs::write(root.join("logs/.gitignore"), "important.log\n").unwrap();
+    std::fs::write(root.join("logs/important.log"), b"").unwrap();
+
+    assert_eq!(gitignored_walk(root), vec!["important.log"]);
+}
+
+#[test]
+fn test_gitignore_files_are_read_only_where_the_walk_goes() {
+    let temp = tempfile::TempDir::new().unwrap();
+    let root = temp.path();
+    std::fs::create_dir_all(root.join("target/debug")).unwrap();
+    std::fs::create_dir_all(root.join("src/deep")).unwrap();
+    std::fs::write(root.join(".gitignore"), "target/\n").unwrap();
+    std::fs::write(root.join("target/.gitignore"), "*\n").unwrap();
+    std::fs::write(root.join("src/deep/.gitignore"), "*.tmp\n").unwrap();
+
+    let mut config = rust_tree::WalkConfig {
+        max_depth: 1,
+        ..Default::default()
+    };
+    config.filter.respect_gitignore = true;
+    config.filter.load_gitignore(root);
+    rust_tree::walk_directory(root, &config, None).unwrap();
+
+    // 被忽略的 target/ 与超出深度的 src/deep/ 都没有被读取
+    let files = config.filter.gitignore_files.lock().unwrap();
+    assert!(files.contains_key(root));
+    assert!(!files.contains_key(&root.join("target")));
+    assert!(!files.contains_key(&root.join("src/deep")));
+}
+
+#[test]
+fn test_gitignore_rule_parsing() {
+    use rust_tree::core::filter::GitignoreRule;
+
+    let base = Path::new("/repo");
+    assert!(GitignoreRule::parse("", base).is_none());
+    assert!(GitignoreRule::parse("# comment", base).is_none());
+    assert!(GitignoreRule::parse("/", base).is_none());
+
+    let mut filter = FilterConfig::new();
+    filter.gitignore_rules = ["docs/*.md", "cache/"]
+        .iter()
+        .filter_map(|line| GitignoreRule::parse(line, base))
+        .collect();
+
+    // 含 `/` 的模式锚定在 base，`*` 不跨越目录
+    assert!(filter.gitignored(Path::new("/repo/docs/a.md"), false));
+    assert!(!filter.gitignored(Path::new("/repo/docs/x/a.md"), false));
+    assert!(!filter.gitignored(Path::new("/repo/sub/docs/a.md"), false));
+    // 末尾 `/` 只匹配目录
+    assert!(filter.gitignored(Path::new("/repo/x/cache"), true));
+    assert!(!filter.gitignored(Path::new("/repo/x/cache"), false));
+}
+
+#[test]
+fn test_size_range_excludes_files_only() {
+    let mut filter = FilterConfig::new();
+    assert!(!filter.has_size_range());
+    assert!(!filter.excludes_size(0));
+
+    filter.min_size = Some(10);
+    filter.max_size = Some(100);
+    assert!(filter.excludes_size(9));
+    assert!(!filter.excludes_size(10));
+    assert!(!filter.excludes_size(100));
+    assert!(filter.excludes_size(101));
+
+    // 遍历时目录不按大小排除：空目录依然保留
+    let temp = tempfile::TempDir::new().unwrap();
+    std::fs::write(temp.path().join("small.txt"), b"x").unwrap();
+    std::fs::write(temp.path().join("medium.txt"), vec![b'x'; 50]).unwrap();
+    std::fs::write(temp.path().join("large.txt"), vec![b'x'; 500]).unwrap();
+    std::fs::create_dir(temp.path().join("empty")).unwrap();
+
+    let config = rust_tree::WalkConfig {
+        filter,
+        ..Default::default()
+    };
+    let tree = rust_tree::walk_directory(temp.path(), &config, None).unwrap();
+    let names: Vec<String> = tree
+        .root
+        .children
+        .as_ref()
+        .unwrap()
+        .iter()
+        .map(|c| c.name.clone())
+        .collect();
+    assert_eq!(names, vec!["empty", "medium.txt"]);
+    assert_eq!(tree.filtered.files, 2);
+}
+
+#[test]
+fn test_modified_range_excludes_files_only() {
+    use std::time::{Duration, SystemTime};
+
+    let now = SystemTime::now();
+    let days = |n: u64| Duration::from_secs(n * 86_400);
+    let mut filter = FilterConfig::new();
+    assert!(!filter.has_modified_range());
+    assert!(!filter.excludes_modified(None));
+
+    filter.modified_after = Some(now - days(7));
+    assert!(!filter.excludes_modified(Some(now - days(1))));
+    assert!(filter.excludes_modified(Some(now - days(8))));
+    // 设置了范围时，修改时间未知的文件被排除
+    assert!(filter.excludes_modified(None));
+
+    // 遍历时只保留 7 天内修改的文件；目录不受影响，即使其中的文件都被排除
+    let temp = tempfile::TempDir::new().unwrap();
+    std::fs::create_dir(temp.path().join("archive")).unwrap();
+    std::fs::write(temp.path().join("new.txt"), b"x").unwrap();
+    let old = std::fs::File::create(temp.path().join("archive/old.txt")).unwrap();
+    old.set_modified(now - days(30)).unwrap();
+
+    let config = rust_tree::WalkConfig {
+        filter,
+        ..Default::default()
+    };
+    let tree = rust_tree::walk_directory(temp.path(), &config, None).unwrap();
+    let names: Vec<_> = tree
+        .root
+        .children
+        .iter()
+        .flatten()
+        .map(|c| c.name.as_str())
+        .collect();
+    assert_eq!(names, ["archive", "new.txt"]);
+    assert_eq!(tree.filtered.files, 1);
+}
+
+#[test]
+fn test_extension_filter_records_hidden_extensions() {
+    let mut filter = FilterConfig::new();
+    assert!(!filter.excludes_extension("README.md"));
+
+    filter.add_extension(".RS");
+    assert!(!filter.excludes_extension("main.rs"));
+    assert!(filter.excludes_extension("README.md"));
+    assert!(filter.excludes_extension(".gitignore"));
+
+    let temp = tempfile::TempDir::new().unwrap();
+    std::fs::create_dir(temp.path().join("docs")).unwrap();
+    std::fs::write(temp.path().join("main.rs"), b"").unwrap();
+    std::fs::write(temp.path().join("README.md"), b"").unwrap();
+    std::fs::write(temp.path().join("docs/guide.md"), b"").unwrap();
+    std::fs::write(temp.path().join("LICENSE"), b"").unwrap();
+
+    for threads in [1, 4] {
+        let config = rust_tree::WalkConfig {
+            filter: filter.clone(),
+            threads,
+            ..Default::default()
+        };
+        let tree = rust_tree::walk_directory(temp.path(), &config, None).unwrap();
+        let names: Vec<_> = tree
+            .root
+            .children
+            .iter()
+            .flatten()
+            .map(|c| c.name.as_str())
+            .collect();
+        // 目录照常下降，只有 main.rs 留下
+        assert_eq!(names, ["docs", "main.rs"]);
+        assert_eq!(tree.filtered.files, 3);
+        assert_eq!(tree.filtered.by_extension.get(".md"), Some(&2));
+        assert_eq!(tree.filtered.by_extension.get("(no extension)"), Some(&1));
+    }
+}
+
+#[test]
+fn test_load_treeignore_adds_exclude_patterns() {
+    let temp = tempfile::TempDir::new().unwrap();
+    let mut filter = FilterConfig::new();
+
+    // 文件不存在时什么也不做
+    filter.load_treeignore(temp.path()).unwrap();
+    assert!(filter.exclude_patterns.is_empty());
+
+    std::fs::write(
+        temp.path().join(".treeignore"),
+        "# build output\n\ntarget\n  *.log  \n!keep.log\n",
+    )
+    .unwrap();
+    filter.load_treeignore(temp.path()).unwrap();
+    let patterns: Vec<_> = filter.exclude_patterns.iter().map(|p| p.as_str()).collect();
+    assert_eq!(patterns, ["target", "*.log"]);
+    assert!(filter.should_exclude(Path::new("/p/keep.log"), false));
+
+    std::fs::write(temp.path().join(".treeignore"), "ok\n[unclosed\n").unwrap();
+    let err = FilterConfig::new()
+        .load_treeignore(temp.path())
+        .unwrap_err();
+    assert!(err.contains(".treeignore:2"), "{}", err);
+}
+
+#[test]
+fn test_should_exclude_applies_extension_list_with_include_pattern() {
+    let mut filter = FilterConfig::new();
+    for ext in ["rs", ".TOML", "Md"] {
+        filter.add_extension(ext);
+    }
+    assert_eq!(filter.include_extensions, ["rs", "toml", "md"]);
+
+    // 不区分大小写；目录总是保留
+    assert!(!filter.should_exclude(Path::new("src/MAIN.RS"), false));
+    assert!(!filter.should_exclude(Path::new("Cargo.toml"), false));
+    assert!(filter.should_exclude(Path::new("build.log"), false));
+    assert!(filter.should_exclude(Path::new("LICENSE"), false));
+    assert!(!filter.should_exclude(Path::new("target.d"), true));
+
+    // 与 include_pattern 同时设置时两者都须满足
+    filter.set_include("test_*").unwrap();
+    assert!(!filter.should_exclude(Path::new("test_walk.rs"), false));
+    assert!(filter.should_exclude(Path::new("walk.rs"), false));
+    assert!(filter.should_exclude(Path::new("test_data.json"), false));
+}
+
+#[test]
+fn test_include_prune_skips_dirs_without_included_files() {
+    let temp = tempfile::TempDir::new().unwrap();
+    std::fs::create_dir_all(temp.path().join("src/nested")).unwrap();
+    std::fs::create_dir_all(temp.path().join("docs/guide/images")).unwrap();
+    std::fs::write(temp.path().join("src/nested/lib.rs"), b"").unwrap();
+    std::fs::write(temp.path().join("docs/guide/intro.md"), b"").unwrap();
+    std::fs::write(temp.path().join("docs/guide/images/logo.png"), b"").unwrap();
+
+    let mut filter = FilterConfig::new();
+    filter.add_extension("rs");
+    assert!(filter.contains_included_file(temp.path(), &temp.path().join("src"), false));
+    assert!(!filter.contains_included_file(temp.path(), &temp.path().join("docs"), false));
+    // 未开启 --include-prune 时不剪除
+    assert!(!filter.prunes_dir(temp.path(), &temp.path().join("docs"), false));
+
+    filter.include_prune = true;
+    let config = rust_tree::WalkConfig {
+        filter,
+        ..Default::default()
+    };
+    let tree = rust_tree::walk_directory(temp.path(), &config, None).unwrap();
+    let names: Vec<_> = tree
+        .root
+        .children
+        .iter()
+        .flatten()
+        .map(|c| c.name.as_str())
+        .collect();
+    // docs/ 之下没有 .rs 文件：整个目录不出现，也不会被展开
+    assert_eq!(names, ["src"]);
+    assert_eq!(tree.filtered.directories, 1);
+    assert_eq!(tree.filtered.files, 0);
+}
+
+#[test]
+fn test_include_prune_scans_the_root_once() {
+    let temp = tempfile::TempDir::new().unwrap();
+    std::fs::create_dir_all(temp.path().join("a/b/c")).unwrap();
+    std::fs::create_dir_all(temp.path().join("a/empty")).unwrap();
+    std::fs::write(temp.path().join("a/b/c/lib.rs"), b"").unwrap();
+
+    let mut filter = FilterConfig::new();
+    filter.add_extension("rs");
+    filter.include_prune = true;
+
+    assert!(!filter.prunes_dir(temp.path(), &temp.path().join("a"), false));
+    // 第一次调用已记录整棵子树的结果
+    let (root, dirs) = filter.included_dirs.get().unwrap();
+    assert_eq!(root, temp.path());
+    assert_eq!(dirs.len(), 4); // 根、a、a/b、a/b/c
+    assert!(!filter.prunes_dir(temp.path(), &temp.path().join("a/b/c"), false));
+    assert!(filter.prunes_dir(temp.path(), &temp.path().join("a/empty"), false));
+
+    // 换了扫描根时不使用缓存
+    let other = temp.path().join("a");
+    assert!(!filter.prunes_dir(&other, &other.join("b"), false));
+    assert!(filter.prunes_dir(&other, &other.join("empty"), false));
+}
@@ -0,0 +1,117 @@
+//! `core::fit`（`--fit-lines` 行数预算）的测试。
+
+use rust_tree::core::fit::{fit_lines, rendered_lines, CollapseOrder};
+use rust_tree::{format_tree, ColorMode, ColorScheme, FsNode, FsNodeType};
+
+/// 一棵三层、每个目录含若干文件的树。
+fn wide_tree() -> FsNode {
+    let file = |dir: &str, name: String, depth| {
+        FsNode::new(
+            name.clone(),
+            format!("/r/{}/{}", dir, name).into(),
+            FsNodeType::File,
+            1,
+            depth,
+        )
+    };
+    let mut top = Vec::new();
+    for a in 0..3 {
+        let mut children = Vec::new();
+        for b in 0..2 {
+            let dir = format!("d{}/s{}", a, b);
+            let files = (0..4)
+                .map(|i| file(&dir, format!("f{}.rs", i), 3))
+                .collect();
+            children.push(FsNode::new_directory(
+                format!("s{}", b),
+                format!("/r/{}", dir).into(),
+                2,
+                files,
+            ));
+        }
+        children.push(file(&format!("d{}", a), "top.rs".into(), 2));
+        top.push(FsNode::new_directory(
+            format!("d{}", a),
+            format!("/r/d{}", a).into(),
+            1,
+            children,
+        ));
+    }
+    FsNode::new_directory("r".into(), "/r".into(), 0, top)
+}
+
+fn render(root: &FsNode) -> String {
+    format_tree(root, false, ColorMode::Never, ColorScheme::None)
+}
+
+#[test]
+fn test_fit_lines_stays_within_budget() {
+    let full = wide_tree();
+    let full_lines = render(&full).lines().count();
+    assert_eq!(rendered_lines(&full), full_lines);
+
+    for order in [CollapseOrder::Deepest, CollapseOrder::Biggest] {
+        for budget in [2, 5, 10, 20, 30, full_lines] {
+            let mut root = wide_tree();
+            let reported = fit_lines(&mut root, budget, order);
+            let output = render(&root);
+            let lines = output.lines().count();
+            assert!(
+                lines <= budget,
+                "{:?} budget {}: {} lines\n{}",
+                order,
+                budget,
+                lines,
+                output
+            );
+            assert_eq!(reported, lines);
+        }
+    }
+}
+
+#[test]
+fn test_fit_lines_summarizes_collapsed_directories() {
+    // 每个 s* 目录 5 行；折叠为 2 行可省 3 行。
+    let mut root = wide_tree();
+    let full = rendered_lines(&root);
+    fit_lines(&mut root, full - 3, CollapseOrder::Deepest);
+
+    let output = render(&root);
+    // 最深的目录先折叠，只需折叠一个
+    assert_eq!(output.matches("collapsed").count(), 1);
+    assert!(
+        output.contains("s0/\n│   │   └── … (4 entries collapsed)"),
+        "{}",
+        output
+    );
+    let collapsed = &root.children.as_ref().unwrap()[0]
+        .children
+        .as_ref()
+        .unwrap()[0];
+    assert_eq!(collapsed.collapsed, 4);
+    assert!(collapsed.children.is_none());
+}
+
+#[test]
+fn test_fit_lines_biggest_first_collapses_top_level() {
+    let mut root = wide_tree();
+    let full = rendered_lines(&root);
+    fit_lines(&mut root, full - 3, CollapseOrder::Biggest);
+
+    // 最大的子树是第一个顶层目录 d0（11 个后代条目）
+    let output = render(&root);
+    assert!(
+        output.contains("d0/\n│   └── … (11 entries collapsed)"),
+        "{}",
+        output
+    );
+    assert_eq!(output.matches("collapsed").count(), 1);
+}
+
+#[test]
+fn test_fit_lines_collapses_root_as_last_resort() {
+    let mut root = wide_tree();
+    let lines = fit_lines(&mut root, 2, CollapseOrder::Deepest);
+    assert_eq!(lines, 2);
+    assert_eq!(render(&root), "r/\n└── … (36 entries collapsed)\n");
+}
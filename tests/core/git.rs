@@ -0,0 +1,229 @@
+//! `core::git`（--since-commit 的变更路径计算、--git-ratio 的文件状态统计、--auto-git-exclude）的测试。
+
+use clap::Parser;
+use git2::{Repository, Signature};
+use rust_tree::core::git::{apply_git_authors, changed_paths_since, git_ratio, last_authors};
+use rust_tree::{get_all_files, retain_paths, walk_directory, Config, GitFileCount, WalkConfig};
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+/// 暂存工作区中的全部文件并提交。
+fn commit_all(repo: &Repository, message: &str) {
+    commit_all_as(repo, message, "test");
+}
+
+/// 以指定作者暂存并提交工作区中的全部文件。
+fn commit_all_as(repo: &Repository, message: &str, author: &str) {
+    let mut index = repo.index().unwrap();
+    index
+        .add_all(["*"].iter(), git2::IndexAddOption::DEFAULT, None)
+        .unwrap();
+    index.write().unwrap();
+    let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+    let sig = Signature::now(author, "test@example.com").unwrap();
+    let parents: Vec<git2::Commit> = repo
+        .head()
+        .ok()
+        .and_then(|h| h.peel_to_commit().ok())
+        .into_iter()
+        .collect();
+    let parent_refs: Vec<&git2::Commit> = parents.iter().collect();
+    repo.commit(Some("HEAD"), &sig, &sig, message, &tree, &parent_refs)
+        .unwrap();
+}
+
+fn write(root: &Path, rel: &str, content: &str) {
+    let path = root.join(rel);
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, content).unwrap();
+}
+
+#[test]
+fn test_since_commit_keeps_only_changed_files() {
+    let temp = TempDir::new().unwrap();
+    let root = temp.path();
+    let repo = Repository::init(root).unwrap();
+
+    write(root, "src/stable.rs", "fn a() {}");
+    write(root, "src/changed.rs", "fn b() {}");
+    write(root, "docs/readme.md", "docs");
+    commit_all(&repo, "initial");
+
+    write(root, "src/changed.rs", "fn b() { /* edited */ }");
+
+    let changed = changed_paths_since(root, "HEAD").unwrap();
+    assert_eq!(
+        changed.into_iter().collect::<Vec<_>>(),
+        vec![PathBuf::from("src/changed.rs")]
+    );
+
+    let mut tree = walk_directory(root, &WalkConfig::default(), None).unwrap();
+    let changed = changed_paths_since(root, "HEAD").unwrap();
+    assert!(retain_paths(&mut tree.root, &changed));
+
+    let files: Vec<String> = get_all_files(&tree).into_iter().map(|f| f.name).collect();
+    assert_eq!(files, vec!["changed.rs".to_string()]);
+    // 没有变化的 docs/ 被整体剪除。
+    let top: Vec<&str> = tree
+        .root
+        .children
+        .as_ref()
+        .unwrap()
+        .iter()
+        .map(|c| c.name.as_str())
+        .collect();
+    assert_eq!(top, vec!["src"]);
+}
+
+#[test]
+fn test_since_commit_includes_untracked_and_scopes_to_subdir() {
+    let temp = TempDir::new().unwrap();
+    let root = temp.path();
+    let repo = Repository::init(root).unwrap();
+
+    write(root, "a/old.txt", "old");
+    commit_all(&repo, "first");
+    write(root, "a/new.txt", "new");
+    write(root, "b/other.txt", "other");
+
+    // 从子目录扫描时，路径相对子目录，且子目录外的变化被忽略。
+    let changed = changed_paths_since(&root.join("a"), "HEAD").unwrap();
+    assert_eq!(
+        changed.into_iter().collect::<Vec<_>>(),
+        vec![PathBuf::from("new.txt")]
+    );
+}
+
+#[test]
+fn test_since_commit_outside_repo_errors() {
+    let temp = TempDir::new().unwrap();
+    assert!(changed_paths_since(temp.path(), "HEAD").is_err());
+}
+
+#[test]
+fn test_git_ratio_counts_tracked_and_untracked() {
+    let temp = TempDir::new().unwrap();
+    let root = temp.path();
+    let repo = Repository::init(root).unwrap();
+    write(root, "tracked.txt", "1234");
+    commit_all(&repo, "initial");
+    write(root, "untracked.txt", "12");
+
+    let ratio = git_ratio(root).unwrap().unwrap();
+    assert_eq!(ratio.tracked, GitFileCount { files: 1, size: 4 });
+    assert_eq!(ratio.untracked, GitFileCount { files: 1, size: 2 });
+    assert_eq!(ratio.ignored, GitFileCount::default());
+    assert_eq!(ratio.total_files(), 2);
+}
+
+#[test]
+fn test_git_ratio_outside_repo_is_none() {
+    let temp = TempDir::new().unwrap();
+    assert_eq!(git_ratio(temp.path()).unwrap(), None);
+}
+
+#[test]
+fn test_last_authors_tracks_latest_commit_per_file() {
+    let dir = TempDir::new().unwrap();
+    let repo = Repository::init(dir.path()).unwrap();
+    write(dir.path(), "a.txt", "a");
+    write(dir.path(), "src/b.rs", "b");
+    commit_all_as(&repo, "init", "alice");
+    write(dir.path(), "src/b.rs", "b2");
+    commit_all_as(&repo, "edit b", "bob");
+    let base = repo.head().unwrap().peel_to_commit().unwrap();
+
+    // 侧分支上 carol 添加 side.txt；主线上 dave 添加 main.txt；再由 merger 合并。
+    write(dir.path(), "side.txt", "s");
+    commit_all_as(&repo, "side", "carol");
+    let side = repo.head().unwrap().peel_to_commit().unwrap();
+    repo.reset(base.as_object(), git2::ResetType::Hard, None)
+        .unwrap();
+    write(dir.path(), "main.txt", "m");
+    commit_all_as(&repo, "main", "dave");
+    let main = repo.head().unwrap().peel_to_commit().unwrap();
+    write(dir.path(), "side.txt", "s");
+    let mut index = repo.index().unwrap();
+    index.add_path(Path::new("side.txt")).unwrap();
+    index.write().unwrap();
+    let merged = repo.find_tree(index.write_tree().unwrap()).unwrap();
+    let sig = Signature::now("merger", "merger@example.com").unwrap();
+    repo.commit(Some("HEAD"), &sig, &sig, "merge", &merged, &[&main, &side])
+        .unwrap();
+
+    // 已暂存但从未提交的文件没有作者。
+    write(dir.path(), "staged.txt", "st");
+    let mut index = repo.index().unwrap();
+    index.add_path(Path::new("staged.txt")).unwrap();
+    index.write().unwrap();
+    write(dir.path(), "untracked.txt", "u");
+
+    let authors = last_authors(dir.path()).unwrap();
+    assert_eq!(
+        authors.get(Path::new("a.txt")).map(String::as_str),
+        Some("alice")
+    );
+    assert_eq!(
+        authors.get(Path::new("src/b.rs")).map(String::as_str),
+        Some("bob")
+    );
+    assert_eq!(
+        authors.get(Path::new("side.txt")).map(String::as_str),
+        Some("carol")
+    );
+    assert_eq!(
+        authors.get(Path::new("main.txt")).map(String::as_str),
+        Some("dave")
+    );
+    assert!(!authors.values().any(|author| author == "merger"));
+    assert!(!authors.contains_key(Path::new("staged.txt")));
+    assert!(!authors.contains_key(Path::new("untracked.txt")));
+
+    let mut tree = walk_directory(dir.path(), &WalkConfig::default(), None).unwrap();
+    apply_git_authors(&mut tree.root, &authors);
+    let children = tree.root.children.as_ref().unwrap();
+    let src = children.iter().find(|n| n.name == "src").unwrap();
+    let b = &src.children.as_ref().unwrap()[0];
+    assert_eq!(b.git_author.as_deref(), Some("bob"));
+    let untracked = children.iter().find(|n| n.name == "untracked.txt").unwrap();
+    assert!(untracked.git_author.is_none());
+}
+
+#[test]
+fn test_last_authors_outside_repo_is_empty() {
+    let dir = TempDir::new().unwrap();
+    write(dir.path(), "a.txt", "a");
+    assert!(last_authors(dir.path()).unwrap().is_empty());
+}
+
+#[test]
+fn test_auto_git_exclude_prunes_dot_git_in_repo() {
+    let temp = TempDir::new().unwrap();
+    std::fs::create_dir(temp.path().join(".git")).unwrap();
+    std::fs::write(temp.path().join(".git/HEAD"), b"ref: refs/heads/main\n").unwrap();
+    std::fs::write(temp.path().join("main.rs"), b"").unwrap();
+    assert!(rust_tree::find_git_dir(&temp.path().join("main.rs")).is_some());
+
+    let names = |extra: &[&str]| -> Vec<String> {
+        let path = temp.path().to_str().unwrap();
+        let mut args = vec!["rust-tree", "-a", path];
+        args.extend_from_slice(extra);
+        let config = Config::try_parse_from(args).unwrap();
+        let tree = walk_directory(temp.path(), &config.to_walk_config(), None).unwrap();
+        tree.root
+            .children
+            .unwrap()
+            .into_iter()
+            .map(|c| c.name)
+            .collect()
+    };
+
+    // 默认开启：即使显示隐藏文件，.git 也被剪除
+    assert_eq!(names(&[]), vec!["main.rs"]);
+    assert_eq!(names(&["--no-auto-git-exclude"]), vec![".git", "main.rs"]);
+    // 后出现的开关生效
+    assert_eq!(
+        names(&["--no-auto-git-exclude", "--auto-git-exclude"]),
+        vec!["main.rs"]
+    );
+}
@@ -0,0 +1,51 @@
+//! `core::lines`（`--count-lines` 行数统计）的测试。
+
+use rust_tree::core::lines::{count_lines, count_lines_in};
+use rust_tree::core::walker::{walk_directory, MetadataFields, WalkConfig};
+use std::fs;
+
+#[test]
+fn test_count_lines_counts_newline_bytes() {
+    assert_eq!(count_lines_in(&b""[..]).unwrap(), Some(0));
+    assert_eq!(count_lines_in(&b"no newline"[..]).unwrap(), Some(0));
+    assert_eq!(count_lines_in(&b"a\nb\nc\n"[..]).unwrap(), Some(3));
+    assert_eq!(count_lines_in(&b"a\r\nb"[..]).unwrap(), Some(1));
+}
+
+#[test]
+fn test_count_lines_skips_binary_files() {
+    assert_eq!(count_lines_in(&b"a\n\0b\n"[..]).unwrap(), None);
+
+    // NUL 只在开头 8 KiB 内才算二进制
+    let mut data = vec![b'x'; 8 * 1024];
+    data.extend_from_slice(b"\n\0\n");
+    assert_eq!(count_lines_in(&data[..]).unwrap(), Some(2));
+}
+
+#[test]
+fn test_walk_fills_line_count_only_when_requested() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("a.rs"), "fn main() {\n}\n").unwrap();
+    fs::write(dir.path().join("logo.png"), b"\x89PNG\0\0").unwrap();
+    assert_eq!(count_lines(&dir.path().join("a.rs")), Some(2));
+
+    let config = WalkConfig {
+        metadata: MetadataFields::LINES,
+        ..Default::default()
+    };
+    let tree = walk_directory(dir.path(), &config, None).unwrap();
+    let children = tree.root.children.as_ref().unwrap();
+    let counts: Vec<(&str, Option<usize>)> = children
+        .iter()
+        .map(|c| (c.name.as_str(), c.line_count))
+        .collect();
+    assert_eq!(counts, vec![("a.rs", Some(2)), ("logo.png", None)]);
+
+    let tree = walk_directory(dir.path(), &WalkConfig::default(), None).unwrap();
+    assert!(tree
+        .root
+        .children
+        .unwrap()
+        .iter()
+        .all(|c| c.line_count.is_none()));
+}
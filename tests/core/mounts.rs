@@ -0,0 +1,60 @@
+//! `core::mounts`（伪文件系统检测）的测试。
+
+use rust_tree::core::mounts::{parse_mounts, pseudo_filesystem_warning_in, Mount};
+use std::path::{Path, PathBuf};
+
+const MOUNTS: &str = "\
+/dev/sda1 / ext4 rw,relatime 0 0
+proc /proc proc rw,nosuid,nodev,noexec,relatime 0 0
+sysfs /sys sysfs rw,nosuid,nodev,noexec,relatime 0 0
+cgroup2 /sys/fs/cgroup cgroup2 rw,nosuid 0 0
+/dev/sdb1 /mnt/my\\040disk ext4 rw 0 0
+";
+
+#[test]
+fn test_parse_mounts() {
+    let mounts = parse_mounts(MOUNTS);
+    assert_eq!(mounts.len(), 5);
+    assert_eq!(
+        mounts[1],
+        Mount {
+            mount_point: PathBuf::from("/proc"),
+            fs_type: "proc".to_string(),
+        }
+    );
+    assert!(mounts[1].is_pseudo());
+    assert!(!mounts[0].is_pseudo());
+    // 挂载点中的 `\040` 还原为空格
+    assert_eq!(mounts[4].mount_point, Path::new("/mnt/my disk"));
+}
+
+#[test]
+fn test_warning_when_root_is_on_pseudo_filesystem() {
+    let mounts = parse_mounts(MOUNTS);
+    let warning = pseudo_filesystem_warning_in(Path::new("/proc/1"), &mounts, false).unwrap();
+    assert!(warning.contains("/proc/1 is on a proc pseudo-filesystem"));
+
+    // 即使开启了 --one-filesystem，扫描根本身仍是伪文件系统
+    assert!(pseudo_filesystem_warning_in(Path::new("/sys"), &mounts, true).is_some());
+}
+
+#[test]
+fn test_warning_for_nested_pseudo_mounts() {
+    let mounts = parse_mounts(MOUNTS);
+    let warning = pseudo_filesystem_warning_in(Path::new("/"), &mounts, false).unwrap();
+    assert!(
+        warning.contains("(/proc, /sys, /sys/fs/cgroup)"),
+        "{}",
+        warning
+    );
+    assert!(warning.contains("--one-filesystem"));
+
+    assert_eq!(
+        pseudo_filesystem_warning_in(Path::new("/"), &mounts, true),
+        None
+    );
+    assert_eq!(
+        pseudo_filesystem_warning_in(Path::new("/mnt/my disk"), &mounts, false),
+        None
+    );
+}
@@ -1,6 +1,7 @@
-//! `core::progress`（进度条配置）的测试。
+//! `core::progress`（进度条配置与线程安全的进度报告器）的测试。
 
-use rust_tree::core::progress::{create_progress_bar, ProgressConfig};
+use rust_tree::core::progress::{create_progress_bar, ProgressConfig, ProgressReporter};
+use std::thread;
 
 #[test]
 fn test_progress_config_default() {
@@ -25,3 +26,33 @@ fn test_create_progress_bar_enabled() {
     let pb = create_progress_bar(&config);
     assert!(pb.is_some());
 }
+
+#[test]
+fn test_progress_reporter_counts_across_threads() {
+    let reporter = ProgressReporter::new(Some(indicatif::ProgressBar::hidden()));
+    let handles: Vec<_> = (0..8)
+        .map(|_| {
+            let reporter = reporter.clone();
+            thread::spawn(move || {
+                for _ in 0..1000 {
+                    reporter.increment();
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    assert_eq!(reporter.count(), 8000);
+    assert_eq!(reporter.bar().unwrap().position(), 8000);
+}
+
+#[test]
+fn test_progress_reporter_without_bar_still_counts() {
+    let reporter = ProgressReporter::from_config(&ProgressConfig::default());
+    assert!(reporter.bar().is_none());
+    reporter.increment();
+    reporter.set_message("ignored");
+    assert_eq!(reporter.count(), 1);
+}
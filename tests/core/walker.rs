@@ -1,6 +1,9 @@
 //! `core::walker`（内存中的树构建器）的测试。
 
-use rust_tree::{walk_directory, WalkConfig};
+use rust_tree::{
+    natural_cmp, prune_to_matches, retain_dirs_containing, walk_directory, MetadataFields,
+    SortField, SubtreeSummary, TreeError, WalkConfig,
+};
 use tempfile::TempDir;
 
 #[test]
@@ -61,3 +64,616 @@ fn test_walk_directory_empty() {
     assert!(tree.root.children.is_none());
     assert_eq!(tree.max_depth, 0);
 }
+
+#[test]
+fn test_walk_directory_file_root() {
+    let temp = TempDir::new().unwrap();
+    let file = temp.path().join("single.txt");
+    std::fs::write(&file, b"hello").unwrap();
+
+    let tree = walk_directory(&file, &WalkConfig::default(), None).unwrap();
+    assert_eq!(tree.root.name, "single.txt");
+    assert!(tree.root.is_file());
+    assert_eq!(tree.root.size, 5);
+    assert!(tree.root.children.is_none());
+    assert_eq!(tree.max_depth, 0);
+}
+
+#[test]
+fn test_walk_directory_file_root_rejected_when_disallowed() {
+    let temp = TempDir::new().unwrap();
+    let file = temp.path().join("single.txt");
+    std::fs::write(&file, b"hello").unwrap();
+
+    let config = WalkConfig {
+        allow_file_root: false,
+        ..Default::default()
+    };
+    let err = walk_directory(&file, &config, None).unwrap_err();
+    assert!(matches!(err, TreeError::NotADirectory(_)));
+}
+
+#[test]
+fn test_retain_dirs_containing_prunes_unmatched() {
+    let temp = TempDir::new().unwrap();
+    let root = temp.path();
+    std::fs::create_dir_all(root.join("pkg_a/src")).unwrap();
+    std::fs::create_dir_all(root.join("pkg_a/tests")).unwrap();
+    std::fs::create_dir_all(root.join("pkg_b/src")).unwrap();
+    std::fs::create_dir_all(root.join("docs")).unwrap();
+    std::fs::write(root.join("pkg_a/tests/walk_test.rs"), b"").unwrap();
+    std::fs::write(root.join("pkg_a/src/lib.rs"), b"").unwrap();
+    std::fs::write(root.join("pkg_b/src/lib.rs"), b"").unwrap();
+    std::fs::write(root.join("docs/guide.md"), b"").unwrap();
+
+    let mut tree = walk_directory(root, &WalkConfig::default(), None).unwrap();
+    let pattern = glob::Pattern::new("*_test.rs").unwrap();
+    assert!(retain_dirs_containing(&mut tree.root, &pattern));
+
+    // 只剩 pkg_a/tests 这条链，且不含任何文件。
+    let top = tree.root.children.as_ref().unwrap();
+    assert_eq!(top.len(), 1);
+    assert_eq!(top[0].name, "pkg_a");
+    let inner = top[0].children.as_ref().unwrap();
+    assert_eq!(inner.len(), 1);
+    assert_eq!(inner[0].name, "tests");
+    assert!(inner[0].children.is_none());
+}
+
+#[test]
+fn test_retain_dirs_containing_no_match() {
+    let temp = TempDir::new().unwrap();
+    std::fs::create_dir(temp.path().join("src")).unwrap();
+    std::fs::write(temp.path().join("src/lib.rs"), b"").unwrap();
+
+    let mut tree = walk_directory(temp.path(), &WalkConfig::default(), None).unwrap();
+    let pattern = glob::Pattern::new("*_test.rs").unwrap();
+    assert!(!retain_dirs_containing(&mut tree.root, &pattern));
+    assert!(tree.root.children.is_none());
+}
+
+#[test]
+fn test_prune_to_matches_keeps_matching_files_and_ancestors() {
+    let temp = TempDir::new().unwrap();
+    let root = temp.path();
+    std::fs::create_dir_all(root.join("src/core")).unwrap();
+    std::fs::create_dir_all(root.join("docs")).unwrap();
+    std::fs::write(root.join("src/core/walker.rs"), b"").unwrap();
+    std::fs::write(root.join("src/core/notes.txt"), b"").unwrap();
+    std::fs::write(root.join("docs/guide.md"), b"").unwrap();
+    std::fs::write(root.join("README.md"), b"").unwrap();
+
+    let mut tree = walk_directory(root, &WalkConfig::default(), None).unwrap();
+    let pattern = glob::Pattern::new("*.rs").unwrap();
+    assert!(prune_to_matches(&mut tree, &pattern));
+
+    // 只剩 src/core/walker.rs：docs 与无关的叶子文件都被移除。
+    let top = tree.root.children.as_ref().unwrap();
+    assert_eq!(top.len(), 1);
+    assert_eq!(top[0].name, "src");
+    let core = top[0].children.as_ref().unwrap();
+    assert_eq!(core.len(), 1);
+    assert_eq!(core[0].name, "core");
+    let files: Vec<&str> = core[0]
+        .children
+        .iter()
+        .flatten()
+        .map(|c| c.name.as_str())
+        .collect();
+    assert_eq!(files, vec!["walker.rs"]);
+
+    let pattern = glob::Pattern::new("*.py").unwrap();
+    assert!(!prune_to_matches(&mut tree, &pattern));
+    assert!(tree.root.children.is_none());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_walk_directory_marks_followed_symlink_dir() {
+    let temp = TempDir::new().unwrap();
+    std::fs::create_dir(temp.path().join("real")).unwrap();
+    std::fs::write(temp.path().join("real/inner.txt"), b"hi").unwrap();
+    std::os::unix::fs::symlink("real", temp.path().join("link")).unwrap();
+
+    let config = WalkConfig {
+        follow_symlinks: true,
+        mark_followed_symlinks: true,
+        ..Default::default()
+    };
+    let tree = walk_directory(temp.path(), &config, None).unwrap();
+    let children = tree.root.children.as_ref().unwrap();
+
+    let link = children.iter().find(|c| c.name == "link").unwrap();
+    assert!(link.is_directory());
+    assert_eq!(
+        link.link_target.as_deref(),
+        Some(std::path::Path::new("real"))
+    );
+    // 被跟随的链接目录仍然展开其子节点。
+    assert_eq!(link.children.as_ref().unwrap()[0].name, "inner.txt");
+
+    let real = children.iter().find(|c| c.name == "real").unwrap();
+    assert!(real.link_target.is_none());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_walk_directory_cuts_symlink_loop() {
+    let temp = TempDir::new().unwrap();
+    std::fs::create_dir(temp.path().join("a")).unwrap();
+    std::os::unix::fs::symlink("..", temp.path().join("a/up")).unwrap();
+
+    let config = WalkConfig {
+        follow_symlinks: true,
+        ..Default::default()
+    };
+    let tree = walk_directory(temp.path(), &config, None).unwrap();
+    let a = &tree.root.children.as_ref().unwrap()[0];
+    let up = &a.children.as_ref().unwrap()[0];
+    assert_eq!(up.name, "up");
+    assert!(up.is_loop);
+    assert!(up.children.is_none());
+    assert_eq!(tree.max_depth, 2);
+}
+
+#[test]
+fn test_walk_directory_name_only_metadata() {
+    let temp = TempDir::new().unwrap();
+    std::fs::create_dir(temp.path().join("sub")).unwrap();
+    std::fs::write(temp.path().join("sub/inner.txt"), b"hi").unwrap();
+    std::fs::write(temp.path().join("top.txt"), b"hello").unwrap();
+
+    let config = WalkConfig {
+        metadata: MetadataFields::empty(),
+        ..Default::default()
+    };
+    let tree = walk_directory(temp.path(), &config, None).unwrap();
+
+    // 结构与默认遍历一致，但不获取任何元数据。
+    let children = tree.root.children.as_ref().unwrap();
+    assert_eq!(children[0].name, "sub");
+    assert_eq!(children[0].children.as_ref().unwrap()[0].name, "inner.txt");
+    assert_eq!(children[1].name, "top.txt");
+    assert_eq!(tree.max_depth, 2);
+    assert_eq!(tree.root.aggregate_size(), 0);
+    assert!(children[1].modified.is_none());
+}
+
+#[test]
+fn test_walk_directory_modified_metadata() {
+    let temp = TempDir::new().unwrap();
+    std::fs::write(temp.path().join("top.txt"), b"hello").unwrap();
+
+    let config = WalkConfig {
+        metadata: MetadataFields::MODIFIED,
+        ..Default::default()
+    };
+    let tree = walk_directory(temp.path(), &config, None).unwrap();
+    let file = &tree.root.children.as_ref().unwrap()[0];
+    assert!(file.modified.is_some());
+    assert_eq!(file.size, 0);
+}
+
+#[test]
+fn test_parallel_walk_matches_sequential() {
+    let temp = TempDir::new().unwrap();
+    for d in 0..4 {
+        for s in 0..3 {
+            let dir = temp.path().join(format!("d{}/s{}", d, s));
+            std::fs::create_dir_all(&dir).unwrap();
+            for f in 0..5 {
+                std::fs::write(dir.join(format!("f{}.txt", f)), vec![b'x'; d * 10 + f]).unwrap();
+            }
+        }
+        std::fs::create_dir_all(temp.path().join(format!("d{}/empty", d))).unwrap();
+    }
+    std::fs::write(temp.path().join("top.rs"), b"fn main() {}").unwrap();
+
+    for max_depth in [0, 2] {
+        let sequential = WalkConfig {
+            max_depth,
+            ..Default::default()
+        };
+        let expected = walk_directory(temp.path(), &sequential, None).unwrap();
+        let expected_json = serde_json::to_string(&expected.root).unwrap();
+
+        for threads in [0, 3] {
+            let parallel = WalkConfig {
+                threads,
+                ..sequential.clone()
+            };
+            let tree = walk_directory(temp.path(), &parallel, None).unwrap();
+            assert_eq!(serde_json::to_string(&tree.root).unwrap(), expected_json);
+            assert_eq!(tree.max_depth, expected.max_depth);
+        }
+    }
+}
+
+#[test]
+fn test_walk_directory_sort_by_mtime() {
+    use std::time::{Duration, SystemTime};
+
+    let temp = TempDir::new().unwrap();
+    std::fs::create_dir(temp.path().join("zdir")).unwrap();
+    let now = SystemTime::now();
+    for (name, age_secs) in [("old.txt", 300), ("new.txt", 10), ("mid.txt", 100)] {
+        let file = std::fs::File::create(temp.path().join(name)).unwrap();
+        file.set_modified(now - Duration::from_secs(age_secs))
+            .unwrap();
+    }
+
+    let names = |reverse: bool| -> Vec<String> {
+        let config = WalkConfig {
+            sort_by: SortField::MTime,
+            reverse,
+            ..Default::default()
+        };
+        let tree = walk_directory(temp.path(), &config, None).unwrap();
+        tree.root
+            .children
+            .unwrap()
+            .into_iter()
+            .map(|c| c.name)
+            .collect()
+    };
+
+    // 目录仍排在最前，文件按修改时间从新到旧
+    assert_eq!(names(false), vec!["zdir", "new.txt", "mid.txt", "old.txt"]);
+    // --reverse：整体反转，文件从旧到新
+    assert_eq!(names(true), vec!["old.txt", "mid.txt", "new.txt", "zdir"]);
+}
+
+#[test]
+fn test_walk_directory_counts_filtered_entries() {
+    let temp = TempDir::new().unwrap();
+    std::fs::create_dir_all(temp.path().join("target/debug")).unwrap();
+    std::fs::create_dir_all(temp.path().join("src/.cache")).unwrap();
+    std::fs::write(temp.path().join("target/debug/app"), b"").unwrap();
+    std::fs::write(temp.path().join("src/main.rs"), b"").unwrap();
+    std::fs::write(temp.path().join("src/a.log"), b"").unwrap();
+    std::fs::write(temp.path().join("b.log"), b"").unwrap();
+    std::fs::write(temp.path().join(".env"), b"").unwrap();
+
+    for threads in [1, 2] {
+        let mut config = WalkConfig {
+            threads,
+            ..Default::default()
+        };
+        config.filter.exclude_hidden = true;
+        config.filter.add_exclude("target").unwrap();
+        config.filter.add_exclude("*.log").unwrap();
+        let tree = walk_directory(temp.path(), &config, None).unwrap();
+
+        // 文件：b.log、src/a.log、.env（target 内部不被遍历，不计入）
+        // 目录：target、src/.cache
+        assert_eq!(tree.filtered.files, 3, "threads = {}", threads);
+        assert_eq!(tree.filtered.directories, 2, "threads = {}", threads);
+    }
+}
+
+#[test]
+fn test_walk_directory_without_filters_counts_nothing() {
+    let temp = TempDir::new().unwrap();
+    std::fs::write(temp.path().join("a.txt"), b"").unwrap();
+
+    let tree = walk_directory(temp.path(), &WalkConfig::default(), None).unwrap();
+    assert!(tree.filtered.is_empty());
+}
+
+#[test]
+fn test_subtree_summaries_aggregate_bottom_up() {
+    let temp = TempDir::new().unwrap();
+    std::fs::create_dir_all(temp.path().join("a/b")).unwrap();
+    std::fs::write(temp.path().join("a/b/x"), vec![0u8; 100]).unwrap();
+    std::fs::write(temp.path().join("a/y"), vec![0u8; 20]).unwrap();
+    std::fs::write(temp.path().join("z"), vec![0u8; 3]).unwrap();
+
+    let config = WalkConfig {
+        metadata: MetadataFields::SIZE,
+        ..Default::default()
+    };
+    let tree = walk_directory(temp.path(), &config, None).unwrap();
+    assert_eq!(tree.root.aggregate_size(), 123);
+
+    // 目录按先序排列：根、a、a/b；目录自身的 size 保持为 0。
+    let mut dirs = Vec::new();
+    let total = tree.root.subtree_summaries(&mut dirs);
+    assert_eq!((total.files, total.size), (3, 123));
+    let sizes: Vec<u64> = dirs.iter().map(|d| d.size).collect();
+    assert_eq!(sizes, [123, 120, 100]);
+    assert_eq!(tree.root.size, 0);
+}
+
+#[test]
+fn test_aggregate_size_counts_truncated_subtrees() {
+    let temp = TempDir::new().unwrap();
+    std::fs::create_dir_all(temp.path().join("a/b")).unwrap();
+    std::fs::write(temp.path().join("a/b/x"), vec![0u8; 100]).unwrap();
+    std::fs::write(temp.path().join("z"), vec![0u8; 3]).unwrap();
+
+    let config = WalkConfig {
+        max_depth: 1,
+        metadata: MetadataFields::SIZE,
+        summarize_truncated: true,
+        ..Default::default()
+    };
+    let tree = walk_directory(temp.path(), &config, None).unwrap();
+
+    // a/ 被深度限制截断，但 du 总计仍包含其内部的文件
+    assert_eq!(tree.root.aggregate_size(), 103);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_walk_directory_terminates_on_symlink_loops() {
+    use std::os::unix::fs::symlink;
+
+    // a/up -> ..（指向祖先）、self -> self（自引用）、
+    // x/to_y -> ../y 与 y/to_x -> ../x（互相指向）
+    let temp = TempDir::new().unwrap();
+    let root = temp.path();
+    std::fs::create_dir_all(root.join("a")).unwrap();
+    std::fs::create_dir_all(root.join("x")).unwrap();
+    std::fs::create_dir_all(root.join("y")).unwrap();
+    symlink("..", root.join("a/up")).unwrap();
+    symlink("self", root.join("self")).unwrap();
+    symlink("../y", root.join("x/to_y")).unwrap();
+    symlink("../x", root.join("y/to_x")).unwrap();
+
+    for threads in [1, 4] {
+        let config = WalkConfig {
+            follow_symlinks: true,
+            threads,
+            ..Default::default()
+        };
+        let tree = walk_directory(root, &config, None).unwrap();
+        let children = tree.root.children.as_ref().unwrap();
+
+        // x/to_y 进入 y，y/to_x 回到路径上的 x，被标记为循环而不再深入。
+        let x = children.iter().find(|c| c.name == "x").unwrap();
+        let to_y = &x.children.as_ref().unwrap()[0];
+        let to_x = &to_y.children.as_ref().unwrap()[0];
+        assert_eq!(to_x.name, "to_x");
+        assert!(to_x.is_loop);
+        assert!(to_x.children.is_none());
+        assert!(tree.max_depth <= 3, "threads={}", threads);
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn test_symlinked_root_matches_real_root_at_depth() {
+    use rust_tree::formatters::tree::{format_tree_with, TreeOptions};
+
+    let temp = TempDir::new().unwrap();
+    let real = temp.path().join("real");
+    std::fs::create_dir_all(real.join("a/b/c")).unwrap();
+    std::fs::write(real.join("a/b/c/deep.txt"), b"x").unwrap();
+    std::fs::write(real.join("a/x.txt"), b"x").unwrap();
+    std::fs::write(real.join("top.txt"), b"x").unwrap();
+    let link = temp.path().join("link");
+    std::os::unix::fs::symlink("real", &link).unwrap();
+
+    let options = TreeOptions {
+        color_mode: rust_tree::ColorMode::Never,
+        ..Default::default()
+    };
+    for (follow_symlinks, threads) in [(false, 1), (true, 1), (true, 4)] {
+        let config = WalkConfig {
+            max_depth: 2,
+            follow_symlinks,
+            threads,
+            ..Default::default()
+        };
+        let render = |root: &std::path::Path| {
+            let tree = walk_directory(root, &config, None).unwrap();
+            assert_eq!(tree.max_depth, 2);
+            // 去掉根行：只有根名称不同
+            let output = format_tree_with(&tree.root, &options);
+            output.split_once('\n').unwrap().1.to_string()
+        };
+        let expected = "├── a/\n│   ├── b/\n│   │   └── ...\n│   └── x.txt\n└── top.txt\n";
+        assert_eq!(render(&real), expected);
+        assert_eq!(render(&link), expected, "follow={}", follow_symlinks);
+    }
+}
+
+#[test]
+fn test_walk_directory_applies_treeignore() {
+    let temp = TempDir::new().unwrap();
+    std::fs::create_dir_all(temp.path().join("target/debug")).unwrap();
+    std::fs::create_dir(temp.path().join("src")).unwrap();
+    std::fs::write(temp.path().join("src/main.rs"), b"").unwrap();
+    std::fs::write(temp.path().join("src/build.log"), b"").unwrap();
+    std::fs::write(temp.path().join(".treeignore"), "target\n*.log\n").unwrap();
+
+    for threads in [1, 4] {
+        let config = WalkConfig {
+            threads,
+            ..Default::default()
+        };
+        let tree = walk_directory(temp.path(), &config, None).unwrap();
+        let names: Vec<_> = tree
+            .root
+            .children
+            .iter()
+            .flatten()
+            .map(|c| c.name.as_str())
+            .collect();
+        // target/ 与 build.log 被剪除；WalkConfig 默认不排除隐藏文件
+        assert_eq!(names, ["src", ".treeignore"]);
+        let src = &tree.root.children.as_ref().unwrap()[0];
+        assert_eq!(src.children.as_ref().unwrap().len(), 1);
+        assert_eq!(tree.filtered.directories, 1);
+        assert_eq!(tree.filtered.files, 1);
+    }
+}
+
+#[test]
+fn test_walk_directory_max_entries_per_dir() {
+    let temp = TempDir::new().unwrap();
+    std::fs::create_dir(temp.path().join("big")).unwrap();
+    for i in 0..5 {
+        std::fs::write(temp.path().join(format!("big/f{}.txt", i)), b"").unwrap();
+    }
+    std::fs::write(temp.path().join("a.txt"), b"").unwrap();
+    std::fs::write(temp.path().join("b.txt"), b"").unwrap();
+
+    for threads in [1, 4] {
+        let config = WalkConfig {
+            max_entries_per_dir: 2,
+            threads,
+            ..Default::default()
+        };
+        let tree = walk_directory(temp.path(), &config, None).unwrap();
+        let names = |node: &rust_tree::FsNode| -> Vec<String> {
+            node.children
+                .iter()
+                .flatten()
+                .map(|c| c.name.clone())
+                .collect()
+        };
+
+        // 排序之后截断：目录在前，因此保留 big/ 与 a.txt
+        assert_eq!(names(&tree.root), ["big", "a.txt"]);
+        assert_eq!(tree.root.omitted, 1);
+        let big = &tree.root.children.as_ref().unwrap()[0];
+        assert_eq!(names(big), ["f0.txt", "f1.txt"]);
+        assert_eq!(big.omitted, 3);
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn test_walk_directory_same_device_skips_other_filesystems() {
+    use std::os::unix::fs::MetadataExt;
+
+    let temp = TempDir::new().unwrap();
+    std::fs::create_dir(temp.path().join("sub")).unwrap();
+    std::fs::write(temp.path().join("a.txt"), b"").unwrap();
+    let dev = std::fs::metadata(temp.path()).unwrap().dev();
+
+    let same = WalkConfig {
+        same_device: Some(dev),
+        ..Default::default()
+    };
+    let tree = walk_directory(temp.path(), &same, None).unwrap();
+    assert_eq!(tree.root.children.as_ref().unwrap().len(), 2);
+
+    // 设备号不同的目录按挂载点处理：跳过并计入被过滤的目录
+    let other = WalkConfig {
+        same_device: Some(dev.wrapping_add(1)),
+        ..Default::default()
+    };
+    let tree = walk_directory(temp.path(), &other, None).unwrap();
+    let names: Vec<&str> = tree
+        .root
+        .children
+        .iter()
+        .flatten()
+        .map(|c| c.name.as_str())
+        .collect();
+    assert_eq!(names, ["a.txt"]);
+    assert_eq!(tree.filtered.directories, 1);
+}
+
+#[test]
+fn test_walk_directory_min_depth_hoists_deeper_entries() {
+    let temp = TempDir::new().unwrap();
+    std::fs::create_dir_all(temp.path().join("a/b/c")).unwrap();
+    std::fs::create_dir_all(temp.path().join("x/b")).unwrap();
+    std::fs::write(temp.path().join("top.txt"), b"").unwrap();
+    std::fs::write(temp.path().join("a/f.txt"), b"").unwrap();
+    std::fs::write(temp.path().join("a/b/c/g.txt"), b"").unwrap();
+    std::fs::write(temp.path().join("x/b/h.txt"), b"").unwrap();
+
+    // 顺序与并行两条路径结果相同
+    for threads in [1, 2] {
+        let config = WalkConfig {
+            min_depth: 2,
+            threads,
+            ..Default::default()
+        };
+        let tree = walk_directory(temp.path(), &config, None).unwrap();
+        let children = tree.root.children.as_ref().unwrap();
+        let names: Vec<(&str, usize)> = children
+            .iter()
+            .map(|c| (c.name.as_str(), c.depth))
+            .collect();
+        // 深度 1 的 top.txt 与目录本身不再出现；同名的 b 以相对路径区分
+        assert_eq!(names, vec![("a/b", 1), ("a/f.txt", 1), ("x/b", 1)]);
+
+        let c = &children[0].children.as_ref().unwrap()[0];
+        assert_eq!((c.name.as_str(), c.depth), ("c", 2));
+        assert_eq!(tree.max_depth, 3);
+    }
+}
+
+#[test]
+fn test_natural_cmp_orders_embedded_numbers() {
+    let mut names = vec!["img2", "img10", "img1"];
+    names.sort_by(|a, b| natural_cmp(a, b));
+    assert_eq!(names, vec!["img1", "img2", "img10"]);
+
+    let mut names = vec!["v1.10", "v1.9", "v1", "a01", "a1", "b"];
+    names.sort_by(|a, b| natural_cmp(a, b));
+    assert_eq!(names, vec!["a01", "a1", "b", "v1", "v1.9", "v1.10"]);
+}
+
+#[test]
+fn test_natural_sort_keeps_directories_first() {
+    let temp = TempDir::new().unwrap();
+    for name in ["img2.png", "img10.png", "img1.png"] {
+        std::fs::write(temp.path().join(name), b"").unwrap();
+    }
+    std::fs::create_dir(temp.path().join("z9")).unwrap();
+    let config = WalkConfig {
+        sort_by: SortField::Natural,
+        ..Default::default()
+    };
+    let tree = walk_directory(temp.path(), &config, None).unwrap();
+    let names: Vec<&str> = tree
+        .root
+        .children
+        .iter()
+        .flatten()
+        .map(|c| c.name.as_str())
+        .collect();
+    assert_eq!(names, vec!["z9", "img1.png", "img2.png", "img10.png"]);
+}
+
+#[test]
+fn test_collapsed_dirs_keep_subtree_totals() {
+    let temp = TempDir::new().unwrap();
+    let root = temp.path();
+    std::fs::create_dir_all(root.join("a/b/c")).unwrap();
+    std::fs::create_dir(root.join("empty")).unwrap();
+    std::fs::write(root.join("a/top.txt"), b"1").unwrap();
+    std::fs::write(root.join("a/b/x.bin"), vec![0u8; 1000]).unwrap();
+    std::fs::write(root.join("a/b/c/y.bin"), vec![0u8; 24]).unwrap();
+
+    let config = WalkConfig {
+        max_depth: 2,
+        summarize_truncated: true,
+        ..Default::default()
+    };
+    let tree = walk_directory(root, &config, None).unwrap();
+    let a = &tree.root.children.as_ref().unwrap()[0];
+    assert_eq!(a.name, "a");
+    assert!(a.collapsed_summary.is_none());
+    let b = &a.children.as_ref().unwrap()[0];
+    assert_eq!(b.name, "b");
+    assert!(b.truncated && b.children.is_none());
+    assert_eq!(
+        b.collapsed_summary,
+        Some(SubtreeSummary {
+            files: 2,
+            size: 1024
+        })
+    );
+
+    // 空目录没有可折叠的内容。
+    let empty = &tree.root.children.as_ref().unwrap()[1];
+    assert_eq!(empty.name, "empty");
+    assert!(empty.collapsed_summary.is_none());
+}
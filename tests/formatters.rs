@@ -1,13 +1,37 @@
-//! `formatters`（tree、json、table、streaming_tree 输出）的测试。
+//! `formatters`（tree、json、table、prometheus、outline、names、manifest、markdown、html、csv、ndjson、diff、streaming_tree 输出及共享的 size 格式化、行宽裁剪）的测试。
 //!
 //! `tests/formatters.rs` 是 `formatters` 集成测试目标的 crate root，因此每个
 //! 子模块都用 `#[path]` 锚定到 `tests/formatters/` 下对应的镜像位置。
 
+#[path = "formatters/csv.rs"]
+mod csv;
+#[path = "formatters/diff.rs"]
+mod diff;
+#[path = "formatters/html.rs"]
+mod html;
 #[path = "formatters/json.rs"]
 mod json;
+#[path = "formatters/manifest.rs"]
+mod manifest;
+#[path = "formatters/markdown.rs"]
+mod markdown;
+#[path = "formatters/names.rs"]
+mod names;
+#[path = "formatters/ndjson.rs"]
+mod ndjson;
+#[path = "formatters/number.rs"]
+mod number;
+#[path = "formatters/outline.rs"]
+mod outline;
+#[path = "formatters/prometheus.rs"]
+mod prometheus;
+#[path = "formatters/size.rs"]
+mod size;
 #[path = "formatters/streaming_tree.rs"]
 mod streaming_tree;
 #[path = "formatters/table.rs"]
 mod table;
 #[path = "formatters/tree.rs"]
 mod tree;
+#[path = "formatters/width.rs"]
+mod width;
@@ -0,0 +1,75 @@
+//! `formatters::csv`（按 `--fields` 选择列的 CSV 输出）的测试。
+
+use clap::Parser;
+use rust_tree::formatters::csv::required_metadata;
+use rust_tree::{format_csv, Config, CsvField, FsNode, FsNodeType, MetadataFields};
+
+fn sample_tree() -> FsNode {
+    let file = FsNode::new(
+        "main.rs".into(),
+        "src/main.rs".into(),
+        FsNodeType::File,
+        42,
+        1,
+    );
+    FsNode::new_directory("src".into(), "src".into(), 0, vec![file])
+}
+
+#[test]
+fn test_fields_select_and_order_columns() {
+    let cfg = Config::try_parse_from(["rust-tree", "-f", "csv", "--fields", "path,size"]).unwrap();
+    assert_eq!(cfg.fields, vec![CsvField::Path, CsvField::Size]);
+
+    let output = format_csv(&sample_tree(), &cfg.fields);
+    assert_eq!(output, "path,size\nsrc,0\nsrc/main.rs,42\n");
+
+    let output = format_csv(&sample_tree(), &[CsvField::Size, CsvField::Type]);
+    assert_eq!(output, "size,type\n0,directory\n42,file\n");
+}
+
+#[test]
+fn test_fields_default_is_full_set_and_unknown_rejected() {
+    let cfg = Config::try_parse_from(["rust-tree"]).unwrap();
+    assert_eq!(cfg.fields, CsvField::ALL.to_vec());
+
+    assert!(Config::try_parse_from(["rust-tree", "--fields", "path,owner"]).is_err());
+}
+
+#[test]
+fn test_csv_escapes_special_characters() {
+    let root = FsNode::new(
+        "a,\"b\".txt".into(),
+        "a,\"b\".txt".into(),
+        FsNodeType::File,
+        1,
+        0,
+    );
+    assert_eq!(
+        format_csv(&root, &[CsvField::Name]),
+        "name\n\"a,\"\"b\"\".txt\"\n"
+    );
+}
+
+#[test]
+fn test_csv_required_metadata_follows_fields() {
+    assert_eq!(
+        required_metadata(&[CsvField::Path]),
+        MetadataFields::empty()
+    );
+    assert_eq!(
+        required_metadata(&[CsvField::Size, CsvField::Mtime]),
+        MetadataFields::SIZE | MetadataFields::MODIFIED
+    );
+    assert_eq!(required_metadata(&[CsvField::Mode]), MetadataFields::MODE);
+}
+
+#[test]
+fn test_csv_mode_column_is_octal() {
+    let mut root = sample_tree();
+    root.mode = Some(0o40755);
+    // 未读取 mode 的节点留空
+    assert_eq!(
+        format_csv(&root, &[CsvField::Name, CsvField::Mode]),
+        "name,mode\nsrc,0755\nmain.rs,\n"
+    );
+}
@@ -0,0 +1,96 @@
+//! `formatters::diff`（差异行输出）的测试。
+
+use rust_tree::formatters::size::{SizeOptions, SizeUnit};
+use rust_tree::{format_diff, DiffEntry, DiffKind};
+use std::path::PathBuf;
+
+#[test]
+fn test_format_diff_lines_and_summary() {
+    let entries = vec![
+        DiffEntry {
+            path: PathBuf::from("new.rs"),
+            kind: DiffKind::Added,
+            old_size: None,
+            new_size: Some(10),
+        },
+        DiffEntry {
+            path: PathBuf::from("old.rs"),
+            kind: DiffKind::Removed,
+            old_size: Some(3),
+            new_size: None,
+        },
+        DiffEntry {
+            path: PathBuf::from("lib.rs"),
+            kind: DiffKind::Changed,
+            old_size: Some(1),
+            new_size: Some(2),
+        },
+    ];
+
+    let output = format_diff(&entries, &SizeOptions::default());
+    assert!(output.contains("+ new.rs (10 B)"));
+    assert!(output.contains("- old.rs (3 B)"));
+    assert!(output.contains("~ lib.rs (1 B -> 2 B)"));
+    assert!(output.ends_with("1 added, 1 removed, 1 changed\n"));
+}
+
+#[test]
+fn test_format_diff_empty() {
+    assert_eq!(
+        format_diff(&[], &SizeOptions::default()),
+        "No differences\n"
+    );
+}
+
+#[test]
+fn test_format_diff_honours_size_options() {
+    let entries = vec![DiffEntry {
+        path: PathBuf::from("big.bin"),
+        kind: DiffKind::Changed,
+        old_size: Some(1_000_000),
+        new_size: Some(2_500_000),
+    }];
+    let size = SizeOptions {
+        fixed_unit: Some(SizeUnit::MB),
+        ..Default::default()
+    };
+
+    let output = format_diff(&entries, &size);
+    assert!(
+        output.contains("~ big.bin (1.0 MB -> 2.5 MB)"),
+        "{}",
+        output
+    );
+}
+
+#[test]
+fn test_format_stats_delta_signs_counts_and_sizes() {
+    use rust_tree::core::models::ExtensionDelta;
+    use rust_tree::formatters::diff::format_stats_delta;
+    use rust_tree::formatters::size::SizeOptions;
+    use rust_tree::StatsDelta;
+
+    let delta = StatsDelta {
+        total_files: 12,
+        total_directories: 0,
+        total_symlinks: -1,
+        total_size: 4_200_000,
+        extensions: vec![
+            ExtensionDelta {
+                extension: ".rs".to_string(),
+                count: 1,
+                size: 2_000,
+            },
+            ExtensionDelta {
+                extension: ".txt".to_string(),
+                count: -3,
+                size: -500,
+            },
+        ],
+    };
+    let output = format_stats_delta(&delta, &SizeOptions::default());
+    assert_eq!(
+        output,
+        "changes since baseline:\n  total_files +12\n  total_directories +0\n  total_symlinks -1\n  total_size +4.20 MB\n  .rs +1 file, +2 kB\n  .txt -3 files, -500 B"
+    );
+}
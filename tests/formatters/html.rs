@@ -0,0 +1,88 @@
+//! `formatters::html`（自包含、可折叠的 HTML 输出）的测试。
+
+use rust_tree::formatters::html::format_html_with;
+use rust_tree::formatters::size::SizeOptions;
+use rust_tree::{collect_stats, format_html, FsNode, FsNodeType, FsTree};
+use std::time::Instant;
+
+fn sample_tree() -> FsTree {
+    let main = FsNode::new(
+        "main.rs".into(),
+        "/r/src/main.rs".into(),
+        FsNodeType::File,
+        42,
+        2,
+    );
+    let src = FsNode::new_directory("src".into(), "/r/src".into(), 1, vec![main]);
+    let odd = FsNode::new(
+        "<b>&.txt".into(),
+        "/r/<b>&.txt".into(),
+        FsNodeType::File,
+        3,
+        1,
+    );
+    let root = FsNode::new_directory("r".into(), "/r".into(), 0, vec![src, odd]);
+    FsTree::new(root, 2)
+}
+
+#[test]
+fn test_format_html_is_self_contained_with_collapsible_dirs() {
+    let tree = sample_tree();
+    let stats = collect_stats(&tree, Instant::now(), 10);
+    let html = format_html(&tree, &stats);
+
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.trim_end().ends_with("</html>"));
+    // 样式内联，没有外部资源
+    assert!(html.contains("<style>"));
+    assert!(!html.contains("<link") && !html.contains("<script") && !html.contains("src=\""));
+
+    // 统计表在树之前
+    let stats_at = html.find("<table class=\"stats\">").unwrap();
+    let tree_at = html.find("<div class=\"tree\">").unwrap();
+    assert!(stats_at < tree_at);
+    assert!(html.contains("<td>Total Files</td><td class=\"num\">2</td>"));
+
+    // 根目录展开、子目录可折叠；每个目录一个 <details>
+    assert!(html.contains("<details open><summary class=\"dir\">r/</summary>"));
+    assert!(html.contains("<details><summary class=\"dir\">src/</summary>"));
+    assert_eq!(html.matches("<details").count(), 2);
+    assert_eq!(html.matches("</details>").count(), 2);
+}
+
+#[test]
+fn test_format_html_colors_and_escapes_names() {
+    let tree = sample_tree();
+    let stats = collect_stats(&tree, Instant::now(), 10);
+    let html = format_html(&tree, &stats);
+
+    // 使用扩展配色方案的类别
+    assert!(html.contains(".cat-rust {"));
+    assert!(html.contains("<span class=\"cat-rust\">main.rs</span>"));
+    // 文件名中的 HTML 特殊字符被转义
+    assert!(html.contains("&lt;b&gt;&amp;.txt"));
+    assert!(!html.contains("<b>&"));
+}
+
+#[test]
+fn test_format_html_with_honours_size_options() {
+    let tree = sample_tree();
+    let stats = collect_stats(&tree, Instant::now(), 10);
+    let raw = SizeOptions {
+        raw_bytes: true,
+        ..Default::default()
+    };
+    let html = format_html_with(&tree, &stats, &raw);
+
+    assert!(
+        html.contains("<td>Total Size</td><td class=\"num\">45</td>"),
+        "{}",
+        html
+    );
+    assert!(
+        html.contains("<span class=\"size\">(42)</span>"),
+        "{}",
+        html
+    );
+    assert!(!html.contains("42 B"));
+}
@@ -1,8 +1,9 @@
 //! `formatters::json`（JSON 输出）的测试。
 
-use rust_tree::formatters::json::format_tree_only;
-use rust_tree::{format_json, FsNode, FsNodeType, FsTree, TreeStats};
-use std::time::Duration;
+use rust_tree::formatters::json::{format_tree_only, json_schema};
+use rust_tree::{collect_stats, format_json, FsNode, FsNodeType, FsTree, TreeStats};
+use serde_json::Value;
+use std::time::{Duration, Instant, SystemTime};
 
 #[test]
 fn test_format_json() {
@@ -16,6 +17,7 @@ fn test_format_json() {
         files_by_extension: Default::default(),
         largest_files: vec![],
         scan_duration: Duration::from_millis(100),
+        ..Default::default()
     };
 
     let json = format_json(&tree, &stats, true).unwrap();
@@ -32,3 +34,77 @@ fn test_format_tree_only() {
     let json = format_tree_only(&tree, true).unwrap();
     assert!(json.contains("\"name\": \"test\""));
 }
+
+#[test]
+fn test_format_json_directory_aggregate_size() {
+    let a = FsNode::new("a.txt".into(), "/r/a.txt".into(), FsNodeType::File, 100, 1);
+    let b = FsNode::new(
+        "b.txt".into(),
+        "/r/sub/b.txt".into(),
+        FsNodeType::File,
+        250,
+        2,
+    );
+    let sub = FsNode::new_directory("sub".into(), "/r/sub".into(), 1, vec![b]);
+    let root = FsNode::new_directory("r".into(), "/r".into(), 0, vec![sub, a]);
+    let tree = FsTree::new(root, 2);
+
+    let json = format_json(&tree, &TreeStats::new(), false).unwrap();
+    let value: Value = serde_json::from_str(&json).unwrap();
+    let root = &value["tree"]["root"];
+
+    // 目录自身 size 仍为 0，aggregate_size 为后代文件之和。
+    assert_eq!(root["size"], 0);
+    assert_eq!(root["aggregate_size"], 350);
+    assert_eq!(root["children"][0]["aggregate_size"], 250);
+    // 文件节点不带 aggregate_size。
+    assert!(root["children"][1].get("aggregate_size").is_none());
+    assert_eq!(tree.root.aggregate_size(), 350);
+}
+
+#[test]
+fn test_format_json_validates_against_schema() {
+    let mut main = FsNode::new(
+        "main.rs".into(),
+        "/r/src/main.rs".into(),
+        FsNodeType::File,
+        42,
+        2,
+    );
+    main.modified = Some(SystemTime::now());
+    main.uid = Some(1000);
+    let mut link = FsNode::new("link".into(), "/r/link".into(), FsNodeType::Symlink, 0, 1);
+    link.link_target = Some("src".into());
+    let mut deep = FsNode::new_directory("deep".into(), "/r/deep".into(), 1, vec![]);
+    deep.truncated = true;
+    let src = FsNode::new_directory("src".into(), "/r/src".into(), 1, vec![main]);
+    let readme = FsNode::new(
+        "README.md".into(),
+        "/r/README.md".into(),
+        FsNodeType::File,
+        0,
+        1,
+    );
+    let root = FsNode::new_directory("r".into(), "/r".into(), 0, vec![deep, src, link, readme]);
+    let tree = FsTree::new(root, 2);
+    let stats = collect_stats(&tree, Instant::now(), 10);
+
+    let schema = json_schema();
+    let validator = jsonschema::JSONSchema::compile(&schema).expect("schema should compile");
+    let output: Value = serde_json::from_str(&format_json(&tree, &stats, false).unwrap()).unwrap();
+    if let Err(errors) = validator.validate(&output) {
+        let messages: Vec<String> = errors.map(|e| e.to_string()).collect();
+        panic!(
+            "format_json output does not match its schema: {:?}",
+            messages
+        );
+    }
+
+    // 与输出不符的文档应被拒绝，说明 schema 确实约束了结构。
+    let mut broken = output.clone();
+    broken["stats"]["total_files"] = Value::from("ten");
+    assert!(!validator.is_valid(&broken));
+    assert!(schema["definitions"]["FsNode"]["properties"]
+        .get("aggregate_size")
+        .is_some());
+}
@@ -0,0 +1,51 @@
+//! `formatters::manifest`（带累计偏移的归档清单）的测试。
+
+use rust_tree::formatters::manifest::manifest_entries;
+use rust_tree::{format_manifest, FsNode, FsNodeType};
+
+fn file(name: &str, size: u64, depth: usize) -> FsNode {
+    FsNode::new(name.into(), name.into(), FsNodeType::File, size, depth)
+}
+
+fn sample_tree() -> FsNode {
+    let src = FsNode::new_directory(
+        "src".into(),
+        "/r/src".into(),
+        1,
+        vec![file("lib.rs", 120, 2), file("main.rs", 48, 2)],
+    );
+    let empty = FsNode::new_directory("empty".into(), "/r/empty".into(), 1, vec![]);
+    let link = FsNode::new("link".into(), "/r/link".into(), FsNodeType::Symlink, 0, 1);
+    FsNode::new_directory(
+        "r".into(),
+        "/r".into(),
+        0,
+        vec![src, empty, file("README.md", 900, 1), link],
+    )
+}
+
+#[test]
+fn test_manifest_offsets_are_running_sums() {
+    let entries = manifest_entries(&sample_tree());
+    let paths: Vec<&str> = entries.iter().map(|e| e.path.as_str()).collect();
+    // 只列文件，按遍历顺序，路径相对扫描根
+    assert_eq!(paths, ["src/lib.rs", "src/main.rs", "README.md"]);
+
+    let mut expected = 0;
+    for entry in &entries {
+        assert_eq!(entry.offset, expected, "{}", entry.path);
+        expected += entry.size;
+    }
+    assert_eq!(entries[2].offset, 168);
+}
+
+#[test]
+fn test_format_manifest_aligns_columns() {
+    assert_eq!(
+        format_manifest(&sample_tree()),
+        "  0  120  src/lib.rs\n120   48  src/main.rs\n168  900  README.md\n"
+    );
+
+    // 单文件根：只有一行，偏移为 0
+    assert_eq!(format_manifest(&file("a.bin", 7, 0)), "0  7  a.bin\n");
+}
@@ -0,0 +1,56 @@
+//! `formatters::markdown`（GitHub 风格 Markdown 嵌套列表）的测试。
+
+use rust_tree::formatters::markdown::format_markdown_with;
+use rust_tree::formatters::size::SizeOptions;
+use rust_tree::{format_markdown, FsNode, FsNodeType};
+
+fn sample_tree() -> FsNode {
+    let main = FsNode::new(
+        "main.rs".into(),
+        "/r/src/main.rs".into(),
+        FsNodeType::File,
+        42,
+        2,
+    );
+    let src = FsNode::new_directory("src".into(), "/r/src".into(), 1, vec![main]);
+    let mut link = FsNode::new(
+        "latest".into(),
+        "/r/latest".into(),
+        FsNodeType::Symlink,
+        0,
+        1,
+    );
+    link.link_target = Some("src/main.rs".into());
+    let notes = FsNode::new(
+        "my_notes.md".into(),
+        "/r/my_notes.md".into(),
+        FsNodeType::File,
+        0,
+        1,
+    );
+    FsNode::new_directory("r".into(), "/r".into(), 0, vec![src, link, notes])
+}
+
+#[test]
+fn test_format_markdown_nested_list() {
+    assert_eq!(
+        format_markdown(&sample_tree()),
+        "- **r/**\n  - **src/**\n    - main.rs\n  - latest → src/main.rs\n  - my\\_notes.md\n"
+    );
+}
+
+#[test]
+fn test_format_markdown_with_sizes() {
+    let output = format_markdown_with(&sample_tree(), true, &SizeOptions::default());
+    // 只有非空文件附带大小；目录与符号链接不变。
+    assert!(output.contains("    - main.rs `42 B`\n"), "{}", output);
+    assert!(output.contains("  - my\\_notes.md\n"));
+    assert!(output.starts_with("- **r/**\n  - **src/**\n"));
+
+    let raw = SizeOptions {
+        raw_bytes: true,
+        ..Default::default()
+    };
+    let output = format_markdown_with(&sample_tree(), true, &raw);
+    assert!(output.contains("    - main.rs `42`\n"), "{}", output);
+}
@@ -0,0 +1,34 @@
+//! `formatters::names`（按深度缩进的名称列表）的测试。
+
+use rust_tree::{format_names, FsNode, FsNodeType};
+
+#[test]
+fn test_format_names_indents_by_depth() {
+    let main = FsNode::new(
+        "main.rs".into(),
+        "/root/src/main.rs".into(),
+        FsNodeType::File,
+        0,
+        2,
+    );
+    let src = FsNode::new_directory("src".into(), "/root/src".into(), 1, vec![main]);
+    let readme = FsNode::new(
+        "README.md".into(),
+        "/root/README.md".into(),
+        FsNodeType::File,
+        0,
+        1,
+    );
+    let root = FsNode::new_directory("root".into(), "/root".into(), 0, vec![src, readme]);
+
+    let output = format_names(&root);
+    assert_eq!(output, "root\n  src\n    main.rs\n  README.md\n");
+
+    // 缩进恰为节点深度的两倍，且不含任何制表符或路径分隔符。
+    let depths = [0, 1, 2, 1];
+    for (line, depth) in output.lines().zip(depths) {
+        let name = line.trim_start_matches(' ');
+        assert_eq!(line.len() - name.len(), depth * 2, "{:?}", line);
+        assert!(!line.contains(['│', '├', '└', '─', '/']), "{:?}", line);
+    }
+}
@@ -0,0 +1,98 @@
+//! `formatters::ndjson`（每个节点一行扁平 JSON 的流式输出）的测试。
+
+use clap::Parser;
+use rust_tree::formatters::ndjson::write_ndjson;
+use rust_tree::Config;
+use serde_json::Value;
+use tempfile::TempDir;
+
+fn ndjson_lines(args: &[&str], root: &std::path::Path) -> Vec<Value> {
+    let mut argv = vec!["rust-tree", "-f", "ndjson"];
+    argv.extend_from_slice(args);
+    let config = Config::try_parse_from(argv).unwrap();
+
+    let mut output = Vec::new();
+    write_ndjson(root, &config.to_walk_config(), &config.fields, &mut output).unwrap();
+    String::from_utf8(output)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+#[test]
+fn test_ndjson_one_object_per_node_with_metadata() {
+    let temp = TempDir::new().unwrap();
+    std::fs::create_dir(temp.path().join("src")).unwrap();
+    std::fs::write(temp.path().join("src/main.rs"), b"fn main() {}").unwrap();
+    std::fs::write(temp.path().join("README"), b"hi").unwrap();
+
+    let lines = ndjson_lines(&[], temp.path());
+    assert_eq!(lines.len(), 4);
+
+    // 根在最前，深度 0
+    assert_eq!(lines[0]["type"], "directory");
+    assert_eq!(lines[0]["depth"], 0);
+
+    for line in &lines {
+        for key in ["path", "name", "type", "size", "mtime", "depth", "mode"] {
+            assert!(line.get(key).is_some(), "missing {} in {}", key, line);
+        }
+        assert!(line["mtime"].is_u64());
+    }
+
+    let main = lines.iter().find(|l| l["name"] == "main.rs").unwrap();
+    assert_eq!(main["size"], 12);
+    assert_eq!(main["depth"], 2);
+    assert_eq!(main["type"], "file");
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(temp.path().join("src/main.rs"))
+            .unwrap()
+            .permissions()
+            .mode();
+        assert_eq!(main["mode"], format!("{:04o}", mode & 0o7777));
+        assert!(lines[0]["mode"].is_string());
+    }
+}
+
+#[test]
+fn test_ndjson_keys_follow_fields() {
+    let temp = TempDir::new().unwrap();
+    std::fs::write(temp.path().join("a.txt"), b"abc").unwrap();
+
+    let config =
+        Config::try_parse_from(["rust-tree", "-f", "ndjson", "--fields", "size,path"]).unwrap();
+    // 未选 mtime 时不读取修改时间
+    assert!(!config
+        .required_metadata()
+        .contains(rust_tree::MetadataFields::MODIFIED));
+
+    let lines = ndjson_lines(&["--fields", "size,path"], temp.path());
+    let file = lines[1].as_object().unwrap();
+    assert_eq!(file.len(), 2);
+    assert_eq!(file["size"], 3);
+
+    // 键顺序与 --fields 一致
+    let mut output = Vec::new();
+    write_ndjson(
+        temp.path(),
+        &config.to_walk_config(),
+        &config.fields,
+        &mut output,
+    )
+    .unwrap();
+    let text = String::from_utf8(output).unwrap();
+    assert!(text
+        .lines()
+        .nth(1)
+        .unwrap()
+        .starts_with("{\"size\":3,\"path\":"));
+}
+
+#[test]
+fn test_jsonl_is_an_alias_for_ndjson() {
+    let config = Config::try_parse_from(["rust-tree", "-f", "jsonl"]).unwrap();
+    assert_eq!(config.format, rust_tree::OutputFormat::Ndjson);
+}
@@ -0,0 +1,30 @@
+//! `formatters::number`（计数的千位分组与缩写）的测试。
+
+use rust_tree::formatters::number::{format_count, format_count_compact};
+
+#[test]
+fn test_format_count_ungrouped() {
+    assert_eq!(format_count(0, None), "0");
+    assert_eq!(format_count(1234567, None), "1234567");
+}
+
+#[test]
+fn test_format_count_grouped() {
+    assert_eq!(format_count(0, Some(',')), "0");
+    assert_eq!(format_count(999, Some(',')), "999");
+    assert_eq!(format_count(1000, Some(',')), "1,000");
+    assert_eq!(format_count(1234567, Some(',')), "1,234,567");
+    assert_eq!(format_count(123456, Some('.')), "123.456");
+}
+
+#[test]
+fn test_format_count_compact_boundaries() {
+    assert_eq!(format_count_compact(0), "0");
+    assert_eq!(format_count_compact(999), "999");
+    assert_eq!(format_count_compact(1000), "1.0k");
+    assert_eq!(format_count_compact(12_345), "12.3k");
+    assert_eq!(format_count_compact(1_500_000), "1.5M");
+    // 四舍五入到 1000 时进位到下一个单位。
+    assert_eq!(format_count_compact(999_950), "1.0M");
+    assert_eq!(format_count_compact(4_500_000_000), "4.5G");
+}
@@ -0,0 +1,45 @@
+//! `formatters::outline`（带深度标记的纯文本大纲）的测试。
+
+use rust_tree::formatters::outline::format_outline_to;
+use rust_tree::{format_outline, FsNode, FsNodeType};
+
+#[test]
+fn test_format_outline_depth_markers() {
+    let main = FsNode::new(
+        "main.rs".into(),
+        "/root/src/main.rs".into(),
+        FsNodeType::File,
+        0,
+        2,
+    );
+    let src = FsNode::new_directory("src".into(), "/root/src".into(), 1, vec![main]);
+    let readme = FsNode::new(
+        "README.md".into(),
+        "/root/README.md".into(),
+        FsNodeType::File,
+        0,
+        1,
+    );
+    let root = FsNode::new_directory("root".into(), "/root".into(), 0, vec![src, readme]);
+
+    let output = format_outline(&root);
+    assert_eq!(output, "0 root\n1 . src\n2 . . main.rs\n1 . README.md\n");
+
+    // 每行首个字段即深度，且点号数量与深度一致。
+    for line in output.lines() {
+        let (depth, rest) = line.split_once(' ').unwrap();
+        let depth: usize = depth.parse().unwrap();
+        assert_eq!(rest.matches(". ").count(), depth);
+    }
+}
+
+#[test]
+fn test_format_outline_to_writes_incrementally() {
+    let leaf = FsNode::new("x".into(), "/r/x".into(), FsNodeType::File, 0, 1);
+    let root = FsNode::new_directory("r".into(), "/r".into(), 0, vec![leaf]);
+
+    let mut written = Vec::new();
+    format_outline_to(&root, &mut written).unwrap();
+    assert_eq!(written, b"0 r\n1 . x\n");
+    assert_eq!(String::from_utf8(written).unwrap(), format_outline(&root));
+}
@@ -0,0 +1,79 @@
+//! `formatters::prometheus`（Prometheus 文本暴露格式）的测试。
+
+use rust_tree::formatters::prometheus::escape_label_value;
+use rust_tree::{format_prometheus, FileTypeInfo, TreeStats};
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::Duration;
+
+fn sample_stats() -> TreeStats {
+    let mut files_by_extension = HashMap::new();
+    files_by_extension.insert(
+        ".rs".to_string(),
+        FileTypeInfo {
+            extension: ".rs".into(),
+            count: 40,
+            total_size: 4000,
+            percentage: 80.0,
+        },
+    );
+    files_by_extension.insert(
+        ".md".to_string(),
+        FileTypeInfo {
+            extension: ".md".into(),
+            count: 2,
+            total_size: 1000,
+            percentage: 20.0,
+        },
+    );
+    TreeStats {
+        total_files: 42,
+        total_directories: 5,
+        total_size: 5000,
+        files_by_extension,
+        scan_duration: Duration::from_millis(250),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_format_prometheus_is_valid_exposition() {
+    let output = format_prometheus(&sample_stats(), Path::new("/srv/data"));
+
+    let mut typed: Vec<String> = Vec::new();
+    for line in output.lines() {
+        if let Some(rest) = line.strip_prefix("# TYPE ") {
+            let mut parts = rest.split(' ');
+            let name = parts.next().unwrap();
+            assert_eq!(parts.next(), Some("gauge"));
+            typed.push(name.to_string());
+            continue;
+        }
+        if line.starts_with("# HELP ") {
+            continue;
+        }
+
+        // 样本行：name{labels} value —— 指标名必须已声明 TYPE，值可解析为数字。
+        let (series, value) = line.rsplit_once(' ').unwrap();
+        let name = &series[..series.find('{').unwrap()];
+        assert!(series.ends_with('}'), "bad series: {}", series);
+        assert!(typed.iter().any(|t| t == name), "undeclared: {}", name);
+        assert!(value.parse::<f64>().is_ok(), "bad value: {}", value);
+    }
+
+    assert!(output.contains("rust_tree_total_files{path=\"/srv/data\"} 42\n"));
+    assert!(output.contains("rust_tree_total_bytes{path=\"/srv/data\"} 5000\n"));
+    assert!(output.contains("rust_tree_scan_duration_seconds{path=\"/srv/data\"} 0.25\n"));
+    assert!(output.contains("rust_tree_extension_files{path=\"/srv/data\",extension=\".rs\"} 40\n"));
+    assert!(
+        output.contains("rust_tree_extension_bytes{path=\"/srv/data\",extension=\".md\"} 1000\n")
+    );
+}
+
+#[test]
+fn test_escape_label_value() {
+    assert_eq!(escape_label_value("plain"), "plain");
+    assert_eq!(escape_label_value(r"C:\dir"), r"C:\\dir");
+    assert_eq!(escape_label_value("a\"b"), "a\\\"b");
+    assert_eq!(escape_label_value("a\nb"), "a\\nb");
+}
@@ -0,0 +1,103 @@
+//! `formatters::size`（共享的字节大小格式化）的测试。
+
+use rust_tree::formatters::size::{format_size, SizeOptions, SizeUnit, SizeUnits};
+
+#[test]
+fn test_format_size_default_is_decimal() {
+    let opts = SizeOptions::default();
+    assert_eq!(format_size(1000, &opts), "1 kB");
+    assert_eq!(format_size(1500, &opts), "1.50 kB");
+}
+
+#[test]
+fn test_format_size_binary_units() {
+    let opts = SizeOptions {
+        units: SizeUnits::Binary,
+        ..Default::default()
+    };
+    assert_eq!(format_size(1024, &opts), "1 KiB");
+    assert_eq!(format_size(1536, &opts), "1.50 KiB");
+}
+
+#[test]
+fn test_format_size_precision() {
+    let decimal = SizeOptions {
+        precision: Some(1),
+        ..Default::default()
+    };
+    assert_eq!(format_size(1234, &decimal), "1.2 kB");
+
+    let binary = SizeOptions {
+        units: SizeUnits::Binary,
+        precision: Some(3),
+        ..Default::default()
+    };
+    assert_eq!(format_size(1536, &binary), "1.500 KiB");
+}
+
+#[test]
+fn test_format_size_raw_bytes_ignores_units_and_precision() {
+    let opts = SizeOptions {
+        units: SizeUnits::Binary,
+        precision: Some(2),
+        raw_bytes: true,
+        ..Default::default()
+    };
+    assert_eq!(format_size(1536, &opts), "1536");
+    assert_eq!(format_size(0, &opts), "0");
+}
+
+#[test]
+fn test_format_size_zero_handling() {
+    // 默认：0 照常格式化。
+    assert_eq!(format_size(0, &SizeOptions::default()), "0 B");
+
+    // 设置了 zero_label 时，0 使用替代文本，无论单位或原始字节模式。
+    let opts = SizeOptions {
+        zero_label: Some("-"),
+        raw_bytes: true,
+        ..Default::default()
+    };
+    assert_eq!(format_size(0, &opts), "-");
+    assert_eq!(format_size(7, &opts), "7");
+
+    let opts = SizeOptions {
+        units: SizeUnits::Binary,
+        zero_label: Some("empty"),
+        ..Default::default()
+    };
+    assert_eq!(format_size(0, &opts), "empty");
+    assert_eq!(format_size(2048, &opts), "2 KiB");
+}
+
+#[test]
+fn test_format_size_fixed_unit_ignores_magnitude() {
+    let mb = SizeOptions {
+        fixed_unit: Some(SizeUnit::MB),
+        ..Default::default()
+    };
+    assert_eq!(format_size(1_500_000_000, &mb), "1500.0 MB");
+    assert_eq!(format_size(2_500, &mb), "0.0 MB");
+
+    // 精度与单位制可以组合；B 不带小数
+    let gib = SizeOptions {
+        fixed_unit: Some(SizeUnit::GiB),
+        precision: Some(2),
+        ..Default::default()
+    };
+    assert_eq!(format_size(3 << 29, &gib), "1.50 GiB");
+    let bytes = SizeOptions {
+        fixed_unit: Some(SizeUnit::B),
+        ..Default::default()
+    };
+    assert_eq!(format_size(1_500_000_000, &bytes), "1500000000 B");
+}
+
+#[test]
+fn test_parse_size_unit() {
+    assert_eq!("MB".parse::<SizeUnit>(), Ok(SizeUnit::MB));
+    assert_eq!("mib".parse::<SizeUnit>(), Ok(SizeUnit::MiB));
+    assert_eq!("KB".parse::<SizeUnit>(), Ok(SizeUnit::KB));
+    let err = "megabytes".parse::<SizeUnit>().unwrap_err();
+    assert!(err.contains("expected one of B, kB, MB"), "{}", err);
+}
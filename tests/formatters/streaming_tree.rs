@@ -1,6 +1,11 @@
-//! `formatters::streaming_tree`（制表符前缀构建器）的测试。
+//! `formatters::streaming_tree`（制表符前缀构建器、带前缀的流式遍历、累计值回调）的测试。
 
-use rust_tree::formatters::streaming_tree::build_prefix;
+use rust_tree::formatters::streaming_tree::{
+    build_prefix, format_tree_streaming_with, walk_streaming_with_prefix, RunningTotals,
+    StreamOptions,
+};
+use rust_tree::WalkConfig;
+use tempfile::TempDir;
 
 // prefix_stack[d] = 路径上深度为 d 的节点的 is_last 标志。
 // 索引 0 未使用（根节点单独绘制）；子节点从深度 1 开始。
@@ -36,3 +41,138 @@ fn test_build_prefix_nested_ancestor_last() {
     let prefix = build_prefix(&prefix_stack, 2);
     assert_eq!(prefix, "    └── ");
 }
+
+#[test]
+fn test_running_totals_fire_and_never_decrease() {
+    let temp = TempDir::new().unwrap();
+    std::fs::create_dir(temp.path().join("sub")).unwrap();
+    std::fs::write(temp.path().join("sub/a.txt"), b"aaaa").unwrap();
+    std::fs::write(temp.path().join("sub/b.txt"), b"bb").unwrap();
+    std::fs::write(temp.path().join("c.txt"), b"c").unwrap();
+
+    let options = StreamOptions {
+        running_totals_every: 2,
+        ..Default::default()
+    };
+    let mut reports: Vec<RunningTotals> = Vec::new();
+    let mut out = Vec::new();
+    format_tree_streaming_with(
+        temp.path(),
+        &mut out,
+        &options,
+        WalkConfig::default(),
+        None,
+        |t| reports.push(*t),
+    )
+    .unwrap();
+
+    // 4 个节点、每 2 个报告一次 => 2 次，最后一次为最终累计值。
+    assert_eq!(reports.len(), 2);
+    for pair in reports.windows(2) {
+        assert!(pair[1].nodes >= pair[0].nodes);
+        assert!(pair[1].files >= pair[0].files);
+        assert!(pair[1].bytes >= pair[0].bytes);
+    }
+    let last = reports.last().unwrap();
+    assert_eq!(last.nodes, 4);
+    assert_eq!(last.files, 3);
+    assert_eq!(last.bytes, 7);
+}
+
+#[test]
+fn test_running_totals_disabled_never_fire() {
+    let temp = TempDir::new().unwrap();
+    std::fs::write(temp.path().join("a.txt"), b"a").unwrap();
+
+    let mut fired = false;
+    let mut out = Vec::new();
+    format_tree_streaming_with(
+        temp.path(),
+        &mut out,
+        &StreamOptions::default(),
+        WalkConfig::default(),
+        None,
+        |_| fired = true,
+    )
+    .unwrap();
+    assert!(!fired);
+}
+
+#[test]
+fn test_streaming_depth_truncated_dir_shows_ellipsis() {
+    let temp = TempDir::new().unwrap();
+    std::fs::create_dir_all(temp.path().join("a/b")).unwrap();
+    std::fs::write(temp.path().join("z.txt"), b"z").unwrap();
+
+    let config = WalkConfig {
+        max_depth: 1,
+        ..Default::default()
+    };
+    let options = StreamOptions {
+        color_mode: rust_tree::ColorMode::Never,
+        ..Default::default()
+    };
+    let mut out = Vec::new();
+    format_tree_streaming_with(temp.path(), &mut out, &options, config, None, |_| {}).unwrap();
+
+    let output = String::from_utf8(out).unwrap();
+    assert!(
+        output.contains("├── a/\n│   └── ...\n└── z.txt\n"),
+        "{}",
+        output
+    );
+}
+
+#[test]
+fn test_walk_streaming_with_prefix_last_child() {
+    let temp = TempDir::new().unwrap();
+    std::fs::create_dir_all(temp.path().join("a")).unwrap();
+    std::fs::write(temp.path().join("a/inner.txt"), b"x").unwrap();
+    std::fs::write(temp.path().join("b.txt"), b"b").unwrap();
+
+    let mut lines = Vec::new();
+    walk_streaming_with_prefix(temp.path(), &WalkConfig::default(), |prefix, node| {
+        lines.push((prefix.to_string(), node.name.clone(), node.is_last));
+    })
+    .unwrap();
+
+    assert_eq!(
+        lines,
+        vec![
+            ("├── ".to_string(), "a".to_string(), false),
+            ("│   └── ".to_string(), "inner.txt".to_string(), true),
+            ("└── ".to_string(), "b.txt".to_string(), true),
+        ]
+    );
+    for (prefix, _, is_last) in &lines {
+        assert_eq!(prefix.ends_with("└── "), *is_last);
+    }
+}
+
+#[test]
+fn test_streaming_ascii_charset() {
+    let temp = TempDir::new().unwrap();
+    std::fs::create_dir_all(temp.path().join("a/b")).unwrap();
+    std::fs::write(temp.path().join("a/x.txt"), b"x").unwrap();
+    std::fs::write(temp.path().join("z.txt"), b"z").unwrap();
+
+    let config = WalkConfig {
+        max_depth: 2,
+        ..Default::default()
+    };
+    let options = StreamOptions {
+        color_mode: rust_tree::ColorMode::Never,
+        charset: rust_tree::TreeCharset::Ascii,
+        ..Default::default()
+    };
+    let mut out = Vec::new();
+    format_tree_streaming_with(temp.path(), &mut out, &options, config, None, |_| {}).unwrap();
+
+    let output = String::from_utf8(out).unwrap();
+    assert!(
+        output.ends_with("/\n|-- a/\n|   |-- b/\n|   `-- x.txt\n`-- z.txt\n"),
+        "{}",
+        output
+    );
+    assert!(output.is_ascii(), "{}", output);
+}
@@ -1,7 +1,11 @@
 //! `formatters::table`（表格统计输出）的测试。
 
-use rust_tree::formatters::table::{format_compact, format_duration};
-use rust_tree::{format_table, TreeStats};
+use rust_tree::formatters::ext_labels::ExtensionLabels;
+use rust_tree::formatters::table::{
+    collapse_below_percentage, format_compact, format_compact_with, format_duration,
+    format_table_with, size_heat_color, TableOptions, OTHERS_LABEL,
+};
+use rust_tree::{format_table, FileTypeInfo, TreeStats};
 use std::collections::HashMap;
 use std::time::Duration;
 
@@ -15,6 +19,7 @@ fn test_format_table() {
         files_by_extension: HashMap::new(),
         largest_files: vec![],
         scan_duration: Duration::from_millis(150),
+        ..Default::default()
     };
 
     let table = format_table(&stats);
@@ -34,6 +39,7 @@ fn test_format_compact() {
         files_by_extension: HashMap::new(),
         largest_files: vec![],
         scan_duration: Duration::from_millis(50),
+        ..Default::default()
     };
 
     let compact = format_compact(&stats);
@@ -46,3 +52,328 @@ fn test_format_duration() {
     assert_eq!(format_duration(Duration::from_millis(500)), "500ms");
     assert_eq!(format_duration(Duration::from_millis(1500)), "1.5s");
 }
+
+#[test]
+fn test_format_duration_sub_millisecond() {
+    assert_eq!(format_duration(Duration::from_micros(850)), "850µs");
+    assert_eq!(format_duration(Duration::ZERO), "0µs");
+    assert_eq!(format_duration(Duration::from_micros(1000)), "1ms");
+}
+
+#[test]
+fn test_format_duration_minutes_and_hours() {
+    assert_eq!(format_duration(Duration::from_millis(59_940)), "59.9s");
+    // 取整后到达 60s 的时长改用分钟，而不是显示为 60.0s
+    assert_eq!(format_duration(Duration::from_millis(59_950)), "1m 0s");
+    assert_eq!(format_duration(Duration::from_millis(59_999)), "1m 0s");
+    assert_eq!(format_duration(Duration::from_secs(60)), "1m 0s");
+    assert_eq!(format_duration(Duration::from_secs(133)), "2m 13s");
+    assert_eq!(format_duration(Duration::from_secs(3900)), "1h 5m");
+}
+
+fn ext(extension: &str, count: usize, total_size: u64, percentage: f64) -> FileTypeInfo {
+    FileTypeInfo {
+        extension: extension.into(),
+        count,
+        total_size,
+        percentage,
+    }
+}
+
+#[test]
+fn test_collapse_below_percentage_boundary() {
+    // 恰好等于阈值的条目保留，严格低于阈值的才合并。
+    let extensions = vec![
+        ext(".rs", 3, 900, 90.0),
+        ext(".md", 1, 50, 5.0),
+        ext(".txt", 2, 40, 4.0),
+        ext(".toml", 1, 10, 1.0),
+    ];
+    let collapsed = collapse_below_percentage(extensions, 5.0, 1000);
+
+    assert_eq!(collapsed.len(), 3);
+    assert_eq!(collapsed[0].extension, ".rs");
+    assert_eq!(collapsed[1].extension, ".md");
+    let others = &collapsed[2];
+    assert_eq!(others.extension, OTHERS_LABEL);
+    assert_eq!(others.count, 3);
+    assert_eq!(others.total_size, 50);
+    assert!((others.percentage - 5.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_collapse_below_percentage_nothing_below() {
+    let extensions = vec![ext(".rs", 1, 600, 60.0), ext(".md", 1, 400, 40.0)];
+    let collapsed = collapse_below_percentage(extensions, 40.0, 1000);
+    assert_eq!(collapsed.len(), 2);
+    assert!(collapsed.iter().all(|e| e.extension != OTHERS_LABEL));
+}
+
+#[test]
+fn test_format_table_with_min_percentage() {
+    let mut files_by_extension = HashMap::new();
+    files_by_extension.insert(".rs".to_string(), ext(".rs", 5, 990, 99.0));
+    files_by_extension.insert(".lock".to_string(), ext(".lock", 1, 10, 1.0));
+    let stats = TreeStats {
+        total_files: 6,
+        total_size: 1000,
+        files_by_extension,
+        ..Default::default()
+    };
+
+    let options = TableOptions {
+        min_percentage: Some(1.5),
+        ..Default::default()
+    };
+    let table = format_table_with(&stats, &options);
+    assert!(table.contains(OTHERS_LABEL));
+    assert!(!table.contains(".lock"));
+
+    let table = format_table(&stats);
+    assert!(!table.contains(OTHERS_LABEL));
+    assert!(table.contains(".lock"));
+}
+
+#[test]
+fn test_thousands_separator_in_table_and_compact() {
+    let stats = TreeStats {
+        total_files: 1234567,
+        total_directories: 4321,
+        ..Default::default()
+    };
+
+    // 默认不分组，保持脚本可解析。
+    assert!(format_table(&stats).contains("1234567"));
+    assert_eq!(
+        format_compact(&stats),
+        "1234567 files, 4321 directories, 0 B total"
+    );
+
+    let options = TableOptions {
+        thousands_sep: Some(','),
+        ..Default::default()
+    };
+    let table = format_table_with(&stats, &options);
+    assert!(table.contains("1,234,567"));
+    assert!(table.contains("4,321"));
+    assert_eq!(
+        format_compact_with(&stats, &options),
+        "1,234,567 files, 4,321 directories, 0 B total"
+    );
+}
+
+#[test]
+fn test_format_table_shows_leaf_depth_balance() {
+    let stats = TreeStats {
+        leaf_depth_mean: 2.5,
+        leaf_depth_stddev: 1.5,
+        ..Default::default()
+    };
+
+    let table = format_table(&stats);
+    assert!(table.contains("Leaf Depth"));
+    assert!(table.contains("2.50 ± 1.50"));
+}
+
+#[test]
+fn test_compact_counts_in_table_and_compact() {
+    let stats = TreeStats {
+        total_files: 1_500_000,
+        total_directories: 12_345,
+        ..Default::default()
+    };
+    let options = TableOptions {
+        compact_counts: true,
+        ..Default::default()
+    };
+
+    let table = format_table_with(&stats, &options);
+    assert!(table.contains("1.5M"));
+    assert!(table.contains("12.3k"));
+    assert_eq!(
+        format_compact_with(&stats, &options),
+        "1.5M files, 12.3k directories, 0 B total"
+    );
+}
+
+#[test]
+fn test_repeat_header_every_n_rows() {
+    let files_by_extension: HashMap<String, FileTypeInfo> = (0..25)
+        .map(|i| {
+            let name = format!(".e{:02}", i);
+            (name.clone(), ext(&name, 25 - i, 100, 4.0))
+        })
+        .collect();
+    let stats = TreeStats {
+        total_files: 325,
+        total_size: 2500,
+        files_by_extension,
+        ..Default::default()
+    };
+
+    let repeated = format_table_with(
+        &stats,
+        &TableOptions {
+            repeat_header: Some(10),
+            ..Default::default()
+        },
+    );
+    // 表头本身 + 第 10、20 行之前各重复一次
+    assert_eq!(repeated.matches("Percentage").count(), 3);
+
+    let plain = format_table_with(&stats, &TableOptions::default());
+    assert_eq!(plain.matches("Percentage").count(), 1);
+}
+
+#[test]
+fn test_largest_files_full_paths_fit_width() {
+    use rust_tree::FileEntry;
+
+    let stats = TreeStats {
+        total_files: 2,
+        largest_files: vec![
+            FileEntry::new(
+                "file.bin".into(),
+                "/very/long/directory/structure/deep/file.bin".into(),
+                4096,
+            ),
+            FileEntry::new("a.txt".into(), "/a.txt".into(), 10),
+        ],
+        ..Default::default()
+    };
+
+    let options = TableOptions {
+        path_width: Some(40),
+        ..Default::default()
+    };
+    let table = format_table_with(&stats, &options);
+    let largest = &table[table.find("Largest Files").unwrap()..];
+    assert!(largest.contains("…/structure/deep/file.bin"), "{}", largest);
+    assert!(largest.contains("/a.txt"));
+    for line in largest.lines().skip(1) {
+        assert!(line.chars().count() <= 40, "{}", line);
+    }
+
+    // 默认仍只显示文件名
+    let table = format_table_with(&stats, &TableOptions::default());
+    assert!(table.contains("file.bin") && !table.contains("/very"));
+}
+
+#[test]
+fn test_extension_table_shows_friendly_names() {
+    let mut files_by_extension = HashMap::new();
+    files_by_extension.insert(".rs".to_string(), ext(".rs", 3, 600, 60.0));
+    files_by_extension.insert(".ipynb".to_string(), ext(".ipynb", 1, 300, 30.0));
+    files_by_extension.insert(".xyz".to_string(), ext(".xyz", 1, 100, 10.0));
+    let stats = TreeStats {
+        total_files: 5,
+        total_size: 1000,
+        files_by_extension,
+        ..Default::default()
+    };
+
+    // 内置映射；原始扩展名保留在括号中，未知扩展名原样显示
+    let table = format_table(&stats);
+    assert!(table.contains("Rust (.rs)"), "{}", table);
+    assert!(table.contains("Jupyter Notebook (.ipynb)"));
+    assert!(table.contains(".xyz") && !table.contains("(.xyz)"));
+
+    let mut labels = ExtensionLabels::new(false);
+    labels.insert("xyz", "XYZ Model");
+    let options = TableOptions {
+        extension_labels: labels,
+        ..Default::default()
+    };
+    let table = format_table_with(&stats, &options);
+    assert!(table.contains("XYZ Model (.xyz)"));
+    assert!(!table.contains("Rust"));
+}
+
+#[test]
+fn test_empty_directories_in_compact_and_overview() {
+    let stats = TreeStats {
+        total_files: 3,
+        total_directories: 4,
+        empty_directories: vec!["a".into(), "a/b".into()],
+        ..Default::default()
+    };
+    assert_eq!(
+        format_compact(&stats),
+        "3 files, 4 directories, 0 B total, 2 empty directories"
+    );
+    let table = format_table(&stats);
+    assert!(table.contains("Empty Directories"));
+}
+
+#[test]
+fn test_ext_limit_caps_extension_rows() {
+    let files_by_extension: HashMap<String, FileTypeInfo> = (0..30)
+        .map(|i| {
+            let name = format!(".e{:02}", i);
+            (name.clone(), ext(&name, 30 - i, 10, 1.0))
+        })
+        .collect();
+    let stats = TreeStats {
+        total_files: 465,
+        total_size: 300,
+        files_by_extension,
+        ..Default::default()
+    };
+    let options = TableOptions {
+        ext_limit: Some(5),
+        ..Default::default()
+    };
+
+    let table = format_table_with(&stats, &options);
+    let section = &table[table.find("Files by Extension").unwrap()..];
+    let rows: Vec<&str> = section.lines().filter(|l| l.contains(" .e")).collect();
+    assert_eq!(rows.len(), 5, "{}", section);
+    // 按文件数取前 5 个
+    assert!(rows[0].contains(".e00") && rows[4].contains(".e04"));
+    // 其余 25 个扩展名共 (25 + 24 + … + 1) = 325 个文件
+    assert!(section.contains("(25 more extensions, 325 files, 250 B)"));
+
+    let table = format_table(&stats);
+    assert!(!table.contains("more extensions"));
+}
+
+#[test]
+fn test_duplicates_table_section() {
+    use rust_tree::FileEntry;
+
+    let group = vec![
+        FileEntry::new("a.bin".into(), "/r/a.bin".into(), 100),
+        FileEntry::new("b.bin".into(), "/r/x/b.bin".into(), 100),
+        FileEntry::new("c.bin".into(), "/r/y/c.bin".into(), 100),
+    ];
+    let stats = TreeStats {
+        duplicates: Some(vec![group]),
+        ..Default::default()
+    };
+    let table = format_table(&stats);
+    assert!(
+        table.contains("Duplicate Files (1 group, 200 B wasted)"),
+        "{}",
+        table
+    );
+    assert!(table.contains("/r/x/b.bin"));
+
+    let none = TreeStats {
+        duplicates: Some(Vec::new()),
+        ..Default::default()
+    };
+    assert!(format_table(&none).contains("Duplicate Files: none found"));
+    assert!(!format_table(&TreeStats::default()).contains("Duplicate Files"));
+}
+
+#[test]
+fn test_size_heat_color_shades_by_ratio_to_largest() {
+    use comfy_table::Color;
+
+    assert_eq!(size_heat_color(900, 900), Color::Red);
+    assert_eq!(size_heat_color(600, 900), Color::Red);
+    assert_eq!(size_heat_color(599, 900), Color::Yellow);
+    assert_eq!(size_heat_color(300, 900), Color::Yellow);
+    assert_eq!(size_heat_color(299, 900), Color::Green);
+    assert_eq!(size_heat_color(0, 0), Color::Green);
+}
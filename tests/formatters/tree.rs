@@ -1,7 +1,14 @@
 //! `formatters::tree`（Unicode 树状输出）的测试。
 
-use rust_tree::formatters::tree::format_size_impl;
-use rust_tree::{format_tree, ColorMode, ColorScheme, FsNode, FsNodeType};
+use rust_tree::formatters::tree::{
+    format_size_impl, format_tree_to, format_tree_with, path_from_cwd, relative_path, TreeCharset,
+    TreeOptions,
+};
+use rust_tree::{
+    format_tree, walk_directory, ColorMode, ColorScheme, FsNode, FsNodeType, WalkConfig,
+};
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
 
 #[test]
 fn test_format_tree_simple() {
@@ -39,3 +46,654 @@ fn test_format_size() {
     let s2 = format_size_impl(1048576);
     assert!(s2.contains("M") || s2.contains("m"));
 }
+
+#[test]
+fn test_format_tree_file_root() {
+    let root = FsNode::new(
+        "notes.txt".into(),
+        "/notes.txt".into(),
+        FsNodeType::File,
+        2048,
+        0,
+    );
+
+    let output = format_tree(&root, true, ColorMode::Never, ColorScheme::None);
+    assert_eq!(output, format!("notes.txt ({})\n", format_size_impl(2048)));
+}
+
+#[test]
+fn test_path_from_cwd_inside_cwd() {
+    let cwd = Path::new("/work/project");
+    assert_eq!(
+        path_from_cwd(Path::new("/work/project/src/main.rs"), cwd),
+        PathBuf::from("src/main.rs")
+    );
+    // 相对路径按 cwd 补全后再计算。
+    assert_eq!(
+        path_from_cwd(Path::new("src/lib.rs"), cwd),
+        PathBuf::from("src/lib.rs")
+    );
+    assert_eq!(path_from_cwd(cwd, cwd), PathBuf::from("."));
+}
+
+#[test]
+fn test_path_from_cwd_outside_cwd_falls_back_to_absolute() {
+    let cwd = Path::new("/work/project");
+    assert_eq!(
+        path_from_cwd(Path::new("/etc/hosts"), cwd),
+        PathBuf::from("/etc/hosts")
+    );
+    assert_eq!(
+        path_from_cwd(Path::new("../sibling/a.txt"), cwd),
+        PathBuf::from("/work/sibling/a.txt")
+    );
+    assert_eq!(
+        path_from_cwd(Path::new("/work/project/./src/../../x"), cwd),
+        PathBuf::from("/work/x")
+    );
+    assert_eq!(
+        path_from_cwd(Path::new("src/../lib.rs"), cwd),
+        PathBuf::from("lib.rs")
+    );
+}
+
+#[test]
+fn test_relative_path_to_ancestor_base() {
+    let cwd = Path::new("/work/project");
+    assert_eq!(
+        relative_path(Path::new("src/main.rs"), Path::new("/work"), cwd),
+        PathBuf::from("project/src/main.rs")
+    );
+    // 相对的 base 同样按 cwd 补全
+    assert_eq!(
+        relative_path(Path::new("/work/project/src"), Path::new("src"), cwd),
+        PathBuf::from(".")
+    );
+}
+
+#[test]
+fn test_relative_path_to_sibling_base_uses_parent_components() {
+    let cwd = Path::new("/work/project");
+    assert_eq!(
+        relative_path(Path::new("src/main.rs"), Path::new("/work/other"), cwd),
+        PathBuf::from("../project/src/main.rs")
+    );
+    assert_eq!(
+        relative_path(
+            Path::new("/work/project/src/lib.rs"),
+            Path::new("tests"),
+            cwd
+        ),
+        PathBuf::from("../src/lib.rs")
+    );
+}
+
+#[test]
+fn test_relative_path_to_unrelated_base_falls_back_to_absolute() {
+    let cwd = Path::new("/work/project");
+    assert_eq!(
+        relative_path(Path::new("src/main.rs"), Path::new("/usr/share"), cwd),
+        PathBuf::from("/work/project/src/main.rs")
+    );
+    // 根目录是任何路径的祖先
+    assert_eq!(
+        relative_path(Path::new("src/main.rs"), Path::new("/"), cwd),
+        PathBuf::from("work/project/src/main.rs")
+    );
+}
+
+#[test]
+fn test_format_tree_with_path_from_cwd() {
+    let file = FsNode::new(
+        "main.rs".into(),
+        "/work/project/src/main.rs".into(),
+        FsNodeType::File,
+        0,
+        1,
+    );
+    let root = FsNode::new_directory("src".into(), "/work/project/src".into(), 0, vec![file]);
+
+    let options = TreeOptions {
+        color_mode: ColorMode::Never,
+        path_from_cwd: Some(PathBuf::from("/work/project")),
+        ..Default::default()
+    };
+    assert_eq!(format_tree_with(&root, &options), "src/\n└── src/main.rs\n");
+}
+
+/// 写满指定字节数后返回错误的 writer，用于验证错误会被向上传递。
+struct FailingWriter {
+    remaining: usize,
+}
+
+impl std::io::Write for FailingWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if self.remaining == 0 {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::BrokenPipe,
+                "closed",
+            ));
+        }
+        let n = buf.len().min(self.remaining);
+        self.remaining -= n;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_format_tree_to_matches_string_output() {
+    let inner = FsNode::new("a.rs".into(), "/r/d/a.rs".into(), FsNodeType::File, 10, 2);
+    let dir = FsNode::new_directory("d".into(), "/r/d".into(), 1, vec![inner]);
+    let file = FsNode::new("b.txt".into(), "/r/b.txt".into(), FsNodeType::File, 20, 1);
+    let root = FsNode::new_directory("r".into(), "/r".into(), 0, vec![dir, file]);
+
+    let options = TreeOptions {
+        file_size: true,
+        dir_count: true,
+        color_mode: ColorMode::Never,
+        ..Default::default()
+    };
+    let expected = format_tree_with(&root, &options);
+    let mut written = Vec::new();
+    format_tree_to(&root, &options, &mut written).unwrap();
+    assert_eq!(String::from_utf8(written).unwrap(), expected);
+
+    // 根行写完后即失败：错误必须从递归深处传回，而不是被吞掉。
+    let root_line = expected.find('\n').unwrap() + 1;
+    let mut writer = FailingWriter {
+        remaining: root_line,
+    };
+    let err = format_tree_to(&root, &options, &mut writer).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::BrokenPipe);
+}
+
+#[test]
+fn test_depth_truncated_dir_shows_ellipsis() {
+    let temp = TempDir::new().unwrap();
+    std::fs::create_dir(temp.path().join("full")).unwrap();
+    std::fs::write(temp.path().join("full/inner.txt"), b"x").unwrap();
+    std::fs::create_dir(temp.path().join("empty")).unwrap();
+
+    let config = WalkConfig {
+        max_depth: 1,
+        ..Default::default()
+    };
+    let tree = walk_directory(temp.path(), &config, None).unwrap();
+    let output = format_tree(&tree.root, false, ColorMode::Never, ColorScheme::None);
+
+    // 非空子目录下出现占位符；本来就空的目录不出现。
+    assert!(
+        output.ends_with("├── empty/\n└── full/\n    └── ...\n"),
+        "{}",
+        output
+    );
+    assert_eq!(output.matches("...").count(), 1);
+}
+
+#[test]
+fn test_compact_counts_in_directory_annotation() {
+    let files: Vec<FsNode> = (0..1500)
+        .map(|i| {
+            let name = format!("f{}", i);
+            let path = format!("/r/d/{}", name);
+            FsNode::new(name, path.into(), FsNodeType::File, 0, 2)
+        })
+        .collect();
+    let dir = FsNode::new_directory("d".into(), "/r/d".into(), 1, files);
+    let root = FsNode::new_directory("r".into(), "/r".into(), 0, vec![dir]);
+
+    let options = TreeOptions {
+        dir_count: true,
+        compact_counts: true,
+        color_mode: ColorMode::Never,
+        ..Default::default()
+    };
+    let output = format_tree_with(&root, &options);
+    assert!(
+        output.starts_with("r/ (1.5k files)\n└── d/ (1.5k files)\n"),
+        "{}",
+        &output[..40]
+    );
+
+    // 默认仍输出完整数字。
+    let output = format_tree(&root, true, ColorMode::Never, ColorScheme::None);
+    assert!(output.starts_with("r/ (1500 files)\n"));
+}
+
+fn sized_tree() -> FsNode {
+    let file = FsNode::new(
+        "a.bin".into(),
+        "/r/d/a.bin".into(),
+        FsNodeType::File,
+        2048,
+        2,
+    );
+    let dir = FsNode::new_directory("d".into(), "/r/d".into(), 1, vec![file]);
+    FsNode::new_directory("r".into(), "/r".into(), 0, vec![dir])
+}
+
+fn render(file_size: bool, dir_count: bool, dir_size: bool) -> String {
+    let options = TreeOptions {
+        file_size,
+        dir_count,
+        dir_size,
+        color_mode: ColorMode::Never,
+        ..Default::default()
+    };
+    format_tree_with(&sized_tree(), &options)
+}
+
+#[test]
+fn test_size_annotation_combinations() {
+    let size = format_size_impl(2048);
+
+    assert_eq!(render(false, false, false), "r/\n└── d/\n    └── a.bin\n");
+    assert_eq!(
+        render(true, false, false),
+        format!("r/\n└── d/\n    └── a.bin ({})\n", size)
+    );
+    assert_eq!(
+        render(false, true, false),
+        "r/ (1 files)\n└── d/ (1 files)\n    └── a.bin\n"
+    );
+    assert_eq!(
+        render(false, false, true),
+        format!("r/ ({0})\n└── d/ ({0})\n    └── a.bin\n", size)
+    );
+    assert_eq!(
+        render(true, true, true),
+        format!(
+            "r/ (1 files, {0})\n└── d/ (1 files, {0})\n    └── a.bin ({0})\n",
+            size
+        )
+    );
+}
+
+#[test]
+fn test_size_shortcut_is_file_size_plus_dir_count() {
+    let output = format_tree(&sized_tree(), true, ColorMode::Never, ColorScheme::None);
+    assert_eq!(output, render(true, true, false));
+}
+
+#[test]
+fn test_format_filtered_footer() {
+    use rust_tree::formatters::tree::format_filtered;
+    use rust_tree::FilteredCounts;
+
+    let footer = format_filtered(&FilteredCounts {
+        files: 3,
+        directories: 1,
+        ..Default::default()
+    });
+    assert_eq!(footer, "3 files and 1 directory filtered out");
+}
+
+#[test]
+fn test_format_excluded_extensions_footer() {
+    use rust_tree::formatters::tree::format_excluded_extensions;
+    use rust_tree::FilteredCounts;
+
+    let mut filtered = FilteredCounts::default();
+    assert_eq!(
+        format_excluded_extensions(&filtered),
+        "hidden by --ext: none"
+    );
+
+    filtered.add_excluded_extension(Some("txt"));
+    filtered.add_excluded_extension(Some("md"));
+    filtered.add_excluded_extension(Some("md"));
+    filtered.add_excluded_extension(None);
+    assert_eq!(filtered.files, 4);
+    assert_eq!(
+        format_excluded_extensions(&filtered),
+        "hidden by --ext: 2 .md, 1 (no extension), 1 .txt"
+    );
+}
+
+#[test]
+fn test_du_shows_aggregated_directory_sizes() {
+    let root = sized_tree();
+
+    let options = TreeOptions {
+        file_size: true,
+        du: true,
+        color_mode: ColorMode::Never,
+        ..Default::default()
+    };
+    // 根行显示总计，目录显示汇总大小而非文件数
+    assert_eq!(
+        format_tree_with(&root, &options),
+        format!(
+            "r/ ({0})\n└── d/ ({0})\n    └── a.bin ({0})\n",
+            format_size_impl(2048)
+        )
+    );
+}
+
+#[test]
+fn test_size_column_right_aligns_sizes() {
+    let small = FsNode::new("s.txt".into(), "/r/s.txt".into(), FsNodeType::File, 5, 1);
+    let mut root = sized_tree();
+    root.children.as_mut().unwrap().push(small);
+
+    let options = TreeOptions {
+        file_size: true,
+        size_column: true,
+        color_mode: ColorMode::Never,
+        ..Default::default()
+    };
+    let output = format_tree_with(&root, &options);
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(lines.len(), 4);
+
+    // 每行的树部分都从同一列开始，大小在其左侧右对齐，且不再重复为注解。
+    let width = format_size_impl(2053).len();
+    for (line, tree_part) in lines
+        .iter()
+        .zip(["r/", "├── d/", "│   └── a.bin", "└── s.txt"])
+    {
+        let (size, rest) = line.split_at(width);
+        assert_eq!(rest, format!("  {}", tree_part));
+        assert!(!size.trim().is_empty());
+        assert!(!size.ends_with(' '), "{:?}", line);
+    }
+    assert!(lines[3].starts_with(&format!("{:>w$}", format_size_impl(5), w = width)));
+}
+
+#[test]
+fn test_dir_totals_follow_preorder_across_siblings() {
+    // 多个兄弟目录与文件交错：每个目录的注解必须对应它自己的子树。
+    let file = |name: &str, size: u64, depth: usize| {
+        FsNode::new(
+            name.into(),
+            format!("/r/{}", name).into(),
+            FsNodeType::File,
+            size,
+            depth,
+        )
+    };
+    let inner = FsNode::new_directory(
+        "inner".into(),
+        "/r/a/inner".into(),
+        2,
+        vec![file("x", 1000, 3)],
+    );
+    let a = FsNode::new_directory("a".into(), "/r/a".into(), 1, vec![inner, file("y", 24, 2)]);
+    let empty = FsNode::new_directory("empty".into(), "/r/empty".into(), 1, vec![]);
+    let b = FsNode::new_directory("b".into(), "/r/b".into(), 1, vec![file("z", 2048, 2)]);
+    let root = FsNode::new_directory(
+        "r".into(),
+        "/r".into(),
+        0,
+        vec![a, file("top", 4, 1), empty, b],
+    );
+
+    let options = TreeOptions {
+        dir_count: true,
+        dir_size: true,
+        color_mode: ColorMode::Never,
+        ..Default::default()
+    };
+    let output = format_tree_with(&root, &options);
+    let s = format_size_impl;
+    let expected = format!(
+        "r/ (4 files, {})\n├── a/ (2 files, {})\n│   ├── inner/ (1 files, {})\n│   │   └── x\n│   └── y\n├── top\n├── empty/ ({})\n└── b/ (1 files, {})\n    └── z\n",
+        s(3076),
+        s(1024),
+        s(1000),
+        s(0),
+        s(2048)
+    );
+    assert_eq!(output, expected);
+}
+
+fn wide_dir(entries: usize) -> FsNode {
+    let files = (0..entries)
+        .map(|i| {
+            let name = format!("f{}", i);
+            let path = format!("/r/{}", name);
+            FsNode::new(name, path.into(), FsNodeType::File, 0, 1)
+        })
+        .collect();
+    FsNode::new_directory("r".into(), "/r".into(), 0, files)
+}
+
+#[test]
+fn test_compact_levels_elides_middle_entries() {
+    let options = TreeOptions {
+        compact_levels: Some((2, 1)),
+        color_mode: ColorMode::Never,
+        ..Default::default()
+    };
+    assert_eq!(
+        format_tree_with(&wide_dir(6), &options),
+        "r/\n├── f0\n├── f1\n├── … (3 more)\n└── f5\n"
+    );
+
+    // tail 为 0 时省略标记是最后一项
+    let head_only = TreeOptions {
+        compact_levels: Some((1, 0)),
+        ..options.clone()
+    };
+    assert_eq!(
+        format_tree_with(&wide_dir(3), &head_only),
+        "r/\n├── f0\n└── … (2 more)\n"
+    );
+}
+
+#[test]
+fn test_compact_levels_keeps_small_directories_intact() {
+    let options = TreeOptions {
+        compact_levels: Some((2, 1)),
+        color_mode: ColorMode::Never,
+        ..Default::default()
+    };
+    // 恰好 head + tail 个以及更少时不省略
+    for entries in [3, 2] {
+        let full = TreeOptions {
+            compact_levels: None,
+            ..options.clone()
+        };
+        assert_eq!(
+            format_tree_with(&wide_dir(entries), &options),
+            format_tree_with(&wide_dir(entries), &full)
+        );
+    }
+}
+
+#[test]
+fn test_compact_levels_dir_counts_skip_hidden_subtrees() {
+    let dir = |name: &str, files: usize| {
+        let children = (0..files)
+            .map(|i| {
+                FsNode::new(
+                    format!("{}{}", name, i),
+                    format!("/r/{}/{}", name, i).into(),
+                    FsNodeType::File,
+                    0,
+                    2,
+                )
+            })
+            .collect();
+        FsNode::new_directory(name.into(), format!("/r/{}", name).into(), 1, children)
+    };
+    let root = FsNode::new_directory(
+        "r".into(),
+        "/r".into(),
+        0,
+        vec![dir("a", 1), dir("b", 2), dir("c", 3)],
+    );
+
+    let options = TreeOptions {
+        dir_count: true,
+        compact_levels: Some((1, 1)),
+        color_mode: ColorMode::Never,
+        ..Default::default()
+    };
+    let output = format_tree_with(&root, &options);
+    // 被省略的 b/ 不影响其后 c/ 的文件数
+    assert!(
+        output.contains("├── … (1 more)\n└── c/ (3 files)\n"),
+        "{}",
+        output
+    );
+    assert!(output.starts_with("r/ (6 files)\n├── a/ (1 files)\n"));
+}
+
+#[test]
+fn test_omitted_entries_render_more_line() {
+    let mut big = FsNode::new_directory(
+        "big".into(),
+        "/r/big".into(),
+        1,
+        vec![FsNode::new(
+            "f0.txt".into(),
+            "/r/big/f0.txt".into(),
+            FsNodeType::File,
+            0,
+            2,
+        )],
+    );
+    big.omitted = 4;
+    let mut root = FsNode::new_directory("r".into(), "/r".into(), 0, vec![big]);
+    root.omitted = 1;
+
+    let output = format_tree(&root, false, ColorMode::Never, ColorScheme::None);
+    assert_eq!(
+        output,
+        "r/\n├── big/\n│   ├── f0.txt\n│   └── ... (4 more)\n└── ... (1 more)\n"
+    );
+}
+
+#[test]
+fn test_format_duplicates_labels_empty_group() {
+    use rust_tree::formatters::size::SizeOptions;
+    use rust_tree::formatters::tree::format_duplicates;
+    use rust_tree::FileEntry;
+
+    let groups = vec![
+        vec![
+            FileEntry::new("a".into(), "r/a".into(), 10),
+            FileEntry::new("b".into(), "r/b".into(), 10),
+        ],
+        vec![
+            FileEntry::new("e1".into(), "r/e1".into(), 0),
+            FileEntry::new("e2".into(), "r/e2".into(), 0),
+            FileEntry::new("e3".into(), "r/e3".into(), 0),
+        ],
+    ];
+    assert_eq!(
+        format_duplicates(&groups, &SizeOptions::default()),
+        "duplicate files: 2 groups, 10 B wasted\n  10 B × 2: r/a, r/b\n  empty files × 3: r/e1, r/e2, r/e3"
+    );
+    assert_eq!(
+        format_duplicates(&[], &SizeOptions::default()),
+        "duplicate files: none"
+    );
+}
+
+#[test]
+fn test_format_mode_renders_permission_bits() {
+    use rust_tree::formatters::tree::format_mode;
+
+    assert_eq!(format_mode(0o755), "rwxr-xr-x");
+    assert_eq!(format_mode(0o644), "rw-r--r--");
+    // 文件类型位被忽略；特殊位显示在执行位上
+    assert_eq!(format_mode(0o100644), "rw-r--r--");
+    assert_eq!(format_mode(0o4755), "rwsr-xr-x");
+    assert_eq!(format_mode(0o1777), "rwxrwxrwt");
+    assert_eq!(format_mode(0o2644), "rw-r-Sr--");
+}
+
+#[test]
+fn test_show_perms_prefixes_entries_with_mode() {
+    let mut file = FsNode::new("run.sh".into(), "/r/run.sh".into(), FsNodeType::File, 0, 1);
+    file.mode = Some(0o755);
+    // 没有 mode 的节点（非 Unix）不加前缀
+    let other = FsNode::new("x".into(), "/r/x".into(), FsNodeType::File, 0, 1);
+    let mut root = FsNode::new_directory("r".into(), "/r".into(), 0, vec![file, other]);
+    root.mode = Some(0o700);
+
+    let options = TreeOptions {
+        color_mode: ColorMode::Never,
+        show_perms: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        format_tree_with(&root, &options),
+        "[drwx------]  r/\n├── [-rwxr-xr-x]  run.sh\n└── x\n"
+    );
+}
+
+#[test]
+fn test_format_tree_ascii_charset() {
+    let temp = TempDir::new().unwrap();
+    std::fs::create_dir_all(temp.path().join("a/b/c")).unwrap();
+    std::fs::write(temp.path().join("a/x.txt"), b"x").unwrap();
+    std::fs::write(temp.path().join("z.txt"), b"z").unwrap();
+    let config = WalkConfig {
+        max_depth: 2,
+        ..Default::default()
+    };
+    let tree = walk_directory(temp.path(), &config, None).unwrap();
+
+    let options = TreeOptions {
+        charset: TreeCharset::Ascii,
+        ..Default::default()
+    };
+    let output = format_tree_with(&tree.root, &options);
+    let body: Vec<&str> = output.lines().skip(1).collect();
+    assert_eq!(
+        body,
+        vec![
+            "|-- a/",
+            "|   |-- b/",
+            "|   |   `-- ...",
+            "|   `-- x.txt",
+            "`-- z.txt"
+        ]
+    );
+
+    // 默认仍是 Unicode 制表符。
+    let output = format_tree_with(&tree.root, &TreeOptions::default());
+    assert!(output.contains("├── a/\n│   ├── b/\n"), "{}", output);
+}
+
+#[test]
+fn test_format_tree_ascii_markers_are_plain_ascii() {
+    let files: Vec<FsNode> = ["a", "b", "c", "d"]
+        .iter()
+        .map(|name| {
+            FsNode::new(
+                name.to_string(),
+                format!("/r/{}", name).into(),
+                FsNodeType::File,
+                0,
+                1,
+            )
+        })
+        .collect();
+    let root = FsNode::new_directory("r".into(), "/r".into(), 0, files);
+
+    let options = TreeOptions {
+        charset: TreeCharset::Ascii,
+        compact_levels: Some((1, 1)),
+        ..Default::default()
+    };
+    let output = format_tree_with(&root, &options);
+    assert!(output.contains("|-- ... (2 more)\n"), "{}", output);
+    assert!(output.is_ascii(), "{}", output);
+
+    let options = TreeOptions {
+        charset: TreeCharset::Ascii,
+        shape_only: true,
+        ..Default::default()
+    };
+    let output = format_tree_with(&root, &options);
+    assert!(output.starts_with("#/\n|-- *\n"), "{}", output);
+    assert!(output.is_ascii(), "{}", output);
+}
@@ -0,0 +1,80 @@
+//! `formatters::width`（--width / --scroll-indicator 行宽裁剪）的测试。
+
+use rust_tree::formatters::width::{
+    clip_line, display_width, truncate_path_left, ClipWriter, SCROLL_INDICATOR,
+};
+use std::io::Write;
+
+#[test]
+fn test_short_line_unchanged() {
+    assert_eq!(clip_line("├── a.rs", 20, true), "├── a.rs");
+    assert_eq!(clip_line("exactly10!", 10, true), "exactly10!");
+}
+
+#[test]
+fn test_over_width_line_gets_indicator() {
+    let clipped = clip_line("└── a_very_long_file_name.rs", 10, true);
+    assert!(clipped.ends_with(SCROLL_INDICATOR));
+    assert_eq!(display_width(&clipped), 10);
+    assert_eq!(clipped, "└── a_ver→");
+}
+
+#[test]
+fn test_over_width_line_without_indicator_is_cut() {
+    assert_eq!(
+        clip_line("└── a_very_long_file_name.rs", 10, false),
+        "└── a_very"
+    );
+}
+
+#[test]
+fn test_ansi_escapes_do_not_count() {
+    let line = "\x1b[34msrc_directory\x1b[0m";
+    assert_eq!(display_width(line), 13);
+    // 被裁掉部分中的重置序列仍保留，颜色不会泄漏到下一行
+    assert_eq!(clip_line(line, 5, true), "\x1b[34msrc_→\x1b[0m");
+}
+
+#[test]
+fn test_wide_chars_count_two_columns() {
+    assert_eq!(display_width("目录"), 4);
+    assert_eq!(clip_line("目录名称", 5, true), "目录→");
+}
+
+#[test]
+fn test_clip_writer_clips_each_line() {
+    let mut out = Vec::new();
+    let mut writer = ClipWriter::new(&mut out, Some(6), true);
+    write!(writer, "root/\n└── ").unwrap();
+    writeln!(writer, "long_name").unwrap();
+    write!(writer, "tail without newline").unwrap();
+    writer.finish().unwrap();
+    assert_eq!(String::from_utf8(out).unwrap(), "root/\n└── l→\ntail →");
+}
+
+#[test]
+fn test_clip_writer_without_width_passes_through() {
+    let mut out = Vec::new();
+    let mut writer = ClipWriter::new(&mut out, None, true);
+    writeln!(writer, "{}", "x".repeat(200)).unwrap();
+    writer.finish().unwrap();
+    assert_eq!(out.len(), 201);
+}
+
+#[test]
+fn test_truncate_path_left() {
+    let path = "/home/user/deep/path/file.bin";
+
+    // 比宽度短或恰好相等：原样返回
+    assert_eq!(truncate_path_left(path, 40), path);
+    assert_eq!(truncate_path_left(path, path.len()), path);
+
+    // 超宽：保留尾部，并从完整的目录名开始
+    assert_eq!(truncate_path_left(path, 20), "…/deep/path/file.bin");
+    assert_eq!(truncate_path_left(path, 17), "…/path/file.bin");
+    assert!(display_width(&truncate_path_left(path, 17)) <= 17);
+
+    // 文件名本身放不下时保留其尾部
+    assert_eq!(truncate_path_left(path, 6), "…e.bin");
+    assert_eq!(truncate_path_left(path, 0), "");
+}
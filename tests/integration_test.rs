@@ -3,6 +3,14 @@
 use std::fs::{self, File};
 use std::io::Write;
 
+/// 运行 rust-tree 二进制的命令；带 `--no-config`，结果不受开发者本地的
+/// `.rust-tree.toml` 影响。
+fn rust_tree_command() -> std::process::Command {
+    let mut command = std::process::Command::new(env!("CARGO_BIN_EXE_rust-tree"));
+    command.arg("--no-config");
+    command
+}
+
 /// 创建一个临时的测试目录结构
 fn create_test_dir() -> tempfile::TempDir {
     let dir = tempfile::tempdir().unwrap();
@@ -63,3 +71,777 @@ fn test_format_output() {
     let result = rust_tree::run(config);
     assert!(result.is_ok());
 }
+
+#[test]
+fn test_exclude_vcs_hides_git_contents() {
+    let test_dir = create_test_dir();
+    let path = test_dir.path();
+    fs::create_dir_all(path.join(".git/objects")).unwrap();
+    File::create(path.join(".git/HEAD")).unwrap();
+    fs::create_dir_all(path.join("src/.hg")).unwrap();
+
+    let config = rust_tree::Config {
+        path: path.to_path_buf(),
+        show_hidden: true,
+        exclude_vcs: true,
+        ..Default::default()
+    };
+    let tree =
+        rust_tree::walk_directory(path, &config.to_walk_config(), None).expect("walk failed");
+    let all = rust_tree::get_all_directories(&tree)
+        .into_iter()
+        .chain(rust_tree::get_all_files(&tree));
+
+    for node in all {
+        let p = node.path.unwrap();
+        assert!(
+            !p.components()
+                .any(|c| c.as_os_str() == ".git" || c.as_os_str() == ".hg"),
+            "VCS path leaked: {}",
+            p.display()
+        );
+    }
+}
+
+#[test]
+fn test_count_only_prints_bare_number() {
+    let test_dir = create_test_dir();
+
+    let files = rust_tree_command()
+        .arg("--count-only")
+        .arg(test_dir.path())
+        .output()
+        .expect("failed to run rust-tree");
+    assert!(files.status.success());
+    // Cargo.toml、README.md、src/main.rs、src/lib.rs、src/core/models.rs
+    assert_eq!(String::from_utf8(files.stdout).unwrap(), "5\n");
+
+    let dirs = rust_tree_command()
+        .args(["--count-only", "--dirs"])
+        .arg(test_dir.path())
+        .output()
+        .expect("failed to run rust-tree");
+    assert!(dirs.status.success());
+    // 根目录、src、src/core、tests
+    assert_eq!(String::from_utf8(dirs.stdout).unwrap(), "4\n");
+}
+
+#[test]
+fn test_largest_path_prints_only_the_path() {
+    let test_dir = create_test_dir();
+
+    // src/main.rs 是唯一非空的文件
+    let output = rust_tree_command()
+        .arg("--largest-path")
+        .arg(test_dir.path())
+        .output()
+        .expect("failed to run rust-tree");
+    assert!(output.status.success());
+    let expected = format!(
+        "{}\n",
+        std::path::Path::new("src").join("main.rs").display()
+    );
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), expected);
+
+    // 空树：不输出任何内容，以非零退出码失败
+    let empty = tempfile::tempdir().unwrap();
+    let output = rust_tree_command()
+        .arg("--largest-path")
+        .arg(empty.path())
+        .output()
+        .expect("failed to run rust-tree");
+    assert_eq!(output.status.code(), Some(3));
+    assert!(output.stdout.is_empty());
+}
+
+#[test]
+fn test_scroll_indicator_marks_over_width_lines() {
+    let test_dir = create_test_dir();
+
+    let output = rust_tree_command()
+        .args(["--width", "12", "--scroll-indicator", "--color", "never"])
+        .arg(test_dir.path())
+        .output()
+        .expect("failed to run rust-tree");
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    // "├── Cargo.toml" 有 14 列，超出 12 列
+    assert!(stdout.lines().any(|l| l == "├── Cargo.t→"), "{}", stdout);
+    assert!(stdout.lines().all(|l| l.chars().count() <= 12));
+}
+
+#[test]
+fn test_fail_if_empty_exits_with_distinct_code_when_nothing_matches() {
+    let test_dir = create_test_dir();
+
+    let output = rust_tree_command()
+        .args([
+            "--fail-if-empty",
+            "--include-only",
+            "*.py",
+            "--color",
+            "never",
+        ])
+        .arg(test_dir.path())
+        .output()
+        .expect("failed to run rust-tree");
+    assert_eq!(
+        output.status.code(),
+        Some(rust_tree::TreeError::EMPTY_TREE_EXIT_CODE)
+    );
+    // 树照常输出，错误信息写到 stderr
+    assert!(!output.stdout.is_empty());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("No files found"));
+}
+
+#[test]
+fn test_fail_if_empty_succeeds_when_files_match() {
+    let test_dir = create_test_dir();
+
+    let output = rust_tree_command()
+        .args([
+            "--fail-if-empty",
+            "--include-only",
+            "*.rs",
+            "--color",
+            "never",
+        ])
+        .arg(test_dir.path())
+        .output()
+        .expect("failed to run rust-tree");
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stdout)
+        .unwrap()
+        .contains("main.rs"));
+}
+
+#[test]
+fn test_ext_reports_excluded_extensions() {
+    let test_dir = create_test_dir();
+
+    let output = rust_tree_command()
+        .args(["--ext", "rs", "--report-excluded-ext", "--color", "never"])
+        .arg(test_dir.path())
+        .output()
+        .expect("failed to run rust-tree");
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("main.rs"));
+    assert!(!stdout.contains("README.md"));
+    assert!(stdout.contains("hidden by --ext: 1 .md, 1 .toml"));
+}
+
+#[test]
+fn test_ext_report_skips_entries_hidden_by_other_filters() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("main.rs"), "fn main() {}").unwrap();
+    std::fs::write(dir.path().join("notes.md"), "# notes").unwrap();
+    std::fs::write(dir.path().join("debug.log"), "log").unwrap();
+    std::fs::write(dir.path().join(".hidden"), "h").unwrap();
+
+    let output = rust_tree_command()
+        .args(["--ext", "rs", "--report-excluded-ext", "--color", "never"])
+        .args(["--exclude", "*.log"])
+        .arg(dir.path())
+        .output()
+        .expect("failed to run rust-tree");
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    // .log 被 --exclude 隐藏、.hidden 被隐藏文件规则隐藏，都不应记到 --ext 名下。
+    assert!(stdout.contains("hidden by --ext: 1 .md"), "{}", stdout);
+    assert!(!stdout.contains(".log"), "{}", stdout);
+    assert!(!stdout.contains("no extension"), "{}", stdout);
+}
+
+#[test]
+fn test_ls_colors_scheme_uses_environment() {
+    let test_dir = create_test_dir();
+
+    let output = rust_tree_command()
+        .args(["--color", "always", "--color-scheme", "ls-colors"])
+        .arg(test_dir.path())
+        .env("LS_COLORS", "*.rs=01;32:di=04;35")
+        // colored 自己也会检测终端；stdout 被捕获时需强制输出颜色。
+        .env("CLICOLOR_FORCE", "1")
+        .output()
+        .expect("failed to run rust-tree");
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("\u{1b}[1;32mmain.rs"), "{:?}", stdout);
+    assert!(stdout.contains("\u{1b}[4;35msrc"), "{:?}", stdout);
+    // 没有条目的扩展名不着色
+    assert!(stdout.contains(" README.md"), "{:?}", stdout);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_scanning_proc_warns_about_pseudo_filesystem() {
+    if !std::path::Path::new("/proc/self").exists() {
+        return;
+    }
+
+    let output = rust_tree_command()
+        .args(["-d", "1", "--color", "never", "/proc"])
+        .output()
+        .expect("failed to run rust-tree");
+    assert!(output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("warning: /proc is on a proc pseudo-filesystem"),
+        "{}",
+        stderr
+    );
+
+    // 普通目录不警告
+    let test_dir = create_test_dir();
+    let output = rust_tree_command()
+        .arg(test_dir.path())
+        .output()
+        .expect("failed to run rust-tree");
+    assert!(!String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("pseudo-filesystem"));
+}
+
+#[test]
+fn test_tar_from_stdin_renders_entries() {
+    let mut builder = tar::Builder::new(Vec::new());
+    for (path, data) in [
+        ("pkg/src/main.rs", &b"fn main() {}\n"[..]),
+        ("pkg/README.md", b"hi\n"),
+    ] {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(data.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder.append_data(&mut header, path, data).unwrap();
+    }
+    let archive = builder.into_inner().unwrap();
+
+    let mut child = rust_tree_command()
+        .args(["--tar", "--du", "--color", "never"])
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .spawn()
+        .expect("failed to run rust-tree");
+    child.stdin.take().unwrap().write_all(&archive).unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "<stdin>/ (16 B)
+└── pkg/ (16 B)
+    ├── src/ (13 B)
+    │   └── main.rs (13 B)
+    └── README.md (3 B)
+"
+    );
+}
+
+#[test]
+fn test_relative_to_sibling_in_tree_and_csv() {
+    let test_dir = create_test_dir();
+
+    let output = rust_tree_command()
+        .args(["--relative-to", "tests", "--color", "never", "src/core"])
+        .current_dir(test_dir.path())
+        .output()
+        .expect("failed to run rust-tree");
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "../src/core/\n└── ../src/core/models.rs\n"
+    );
+
+    let output = rust_tree_command()
+        .args([
+            "-f",
+            "csv",
+            "--fields",
+            "path",
+            "--relative-to",
+            "src",
+            "src/core",
+        ])
+        .current_dir(test_dir.path())
+        .output()
+        .expect("failed to run rust-tree");
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "path\ncore\ncore/models.rs\n"
+    );
+}
+
+#[test]
+fn test_relative_to_rewrites_table_and_largest_path() {
+    let test_dir = create_test_dir();
+    let run = |args: &[&str]| {
+        rust_tree_command()
+            .args(args)
+            .current_dir(test_dir.path())
+            .output()
+            .expect("failed to run rust-tree")
+    };
+
+    let output = run(&["-f", "table", "--relative-to", "tests", "src"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("../src/core/models.rs"), "{}", stdout);
+
+    let output = run(&["--largest-path", "--relative-to", "tests", "src"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.starts_with("../src/"), "{}", stdout);
+
+    // 只输出名称的格式中没有可改写的路径。
+    for format in ["names", "outline", "manifest", "markdown", "prometheus"] {
+        let output = run(&["-f", format, "--relative-to", "tests", "src"]);
+        assert!(
+            !output.status.success(),
+            "{} accepted --relative-to",
+            format
+        );
+    }
+}
+
+#[test]
+fn test_stats_file_moves_stats_out_of_json_output() {
+    let test_dir = create_test_dir();
+    let stats_path = test_dir.path().join("stats.json");
+
+    let output = rust_tree_command()
+        .args(["-f", "json", "--stats-file"])
+        .arg(&stats_path)
+        .arg(test_dir.path().join("src"))
+        .output()
+        .expect("failed to run rust-tree");
+    assert!(output.status.success());
+    // stdout 只剩树本身
+    let tree: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert!(tree.get("stats").is_none());
+    assert_eq!(tree["name"], "src");
+
+    let stats: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&stats_path).unwrap()).unwrap();
+    assert_eq!(stats["total_files"], 3);
+
+    // 无法写入时报 IO 错误而不是 panic
+    let output = rust_tree_command()
+        .args(["--stats-file"])
+        .arg(test_dir.path().join("missing/stats.json"))
+        .arg(test_dir.path())
+        .output()
+        .expect("failed to run rust-tree");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("cannot write stats file"), "{}", stderr);
+}
+
+#[test]
+fn test_output_writes_to_file_without_colors() {
+    let test_dir = create_test_dir();
+    let out_path = test_dir.path().join("tree.txt");
+
+    let output = rust_tree_command()
+        .env("CLICOLOR_FORCE", "1")
+        .arg("-O")
+        .arg(&out_path)
+        .arg(test_dir.path().join("src"))
+        .output()
+        .expect("failed to run rust-tree");
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+    let written = fs::read_to_string(&out_path).unwrap();
+    assert!(written.starts_with("src/\n"), "{}", written);
+    assert!(!written.contains('\x1b'));
+
+    // 显式 --color always 时仍着色；流式模式同样写入文件
+    let output = rust_tree_command()
+        .env("CLICOLOR_FORCE", "1")
+        .args(["--streaming", "--color", "always", "-O"])
+        .arg(&out_path)
+        .arg(test_dir.path().join("src"))
+        .output()
+        .expect("failed to run rust-tree");
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+    assert!(fs::read_to_string(&out_path).unwrap().contains('\x1b'));
+
+    let output = rust_tree_command()
+        .arg("-O")
+        .arg(test_dir.path().join("missing/tree.txt"))
+        .arg(test_dir.path())
+        .output()
+        .expect("failed to run rust-tree");
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("cannot create output file"));
+}
+
+#[test]
+fn test_duplicates_in_tree_footer_and_json() {
+    let test_dir = create_test_dir();
+    fs::write(
+        test_dir.path().join("tests/main_copy.rs"),
+        b"fn main() { println!(\"Hello\"); }",
+    )
+    .unwrap();
+
+    let output = rust_tree_command()
+        .args(["--duplicates", "--color", "never"])
+        .arg(test_dir.path())
+        .output()
+        .expect("failed to run rust-tree");
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(
+        stdout.contains("duplicate files: 1 group, 32 B wasted"),
+        "{}",
+        stdout
+    );
+    assert!(stdout.contains("main_copy.rs"));
+
+    let output = rust_tree_command()
+        .args(["--duplicates", "--size-unit", "MB", "--color", "never"])
+        .arg(test_dir.path())
+        .output()
+        .expect("failed to run rust-tree");
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(
+        stdout.contains("duplicate files: 1 group, 0.0 MB wasted"),
+        "{}",
+        stdout
+    );
+
+    let output = rust_tree_command()
+        .args(["--duplicates", "-f", "json"])
+        .arg(test_dir.path())
+        .output()
+        .expect("failed to run rust-tree");
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let groups = json["stats"]["duplicates"].as_array().unwrap();
+    assert_eq!(groups.len(), 1);
+    assert_eq!(groups[0].as_array().unwrap().len(), 2);
+}
+
+#[test]
+fn test_list_empty_dirs_marks_dirs_emptied_by_filters() {
+    let test_dir = create_test_dir();
+    fs::create_dir_all(test_dir.path().join("cache/objects")).unwrap();
+    fs::create_dir(test_dir.path().join("logs")).unwrap();
+    File::create(test_dir.path().join("logs/build.log")).unwrap();
+
+    let output = rust_tree_command()
+        .args(["--list-empty-dirs", "-e", "*.log", "--color", "never"])
+        .arg(test_dir.path())
+        .output()
+        .expect("failed to run rust-tree");
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let root = test_dir.path();
+    let expected = format!(
+        "empty directories: 4\n  {}\n  {}\n  {} (empty after filtering)\n  {}\n",
+        root.join("cache").display(),
+        root.join("cache/objects").display(),
+        root.join("logs").display(),
+        root.join("tests").display()
+    );
+    assert!(stdout.ends_with(&expected), "{}", stdout);
+}
+
+#[test]
+fn test_count_lines_annotates_files_and_totals() {
+    let test_dir = create_test_dir();
+    fs::write(
+        test_dir.path().join("README.md"),
+        "# rust-tree\n\nA tree.\n",
+    )
+    .unwrap();
+
+    let output = rust_tree_command()
+        .args(["--count-lines", "--color", "never"])
+        .arg(test_dir.path())
+        .output()
+        .expect("failed to run rust-tree");
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("README.md [3 lines]"), "{}", stdout);
+    // 没有换行的单行文件计 0 行
+    assert!(stdout.contains("main.rs [0 lines]"), "{}", stdout);
+
+    let output = rust_tree_command()
+        .args(["stats", "--count-lines", "-f", "json"])
+        .arg(test_dir.path())
+        .output()
+        .expect("failed to run rust-tree");
+    assert!(output.status.success());
+    let stats: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(stats["total_lines"], 3);
+}
+
+#[test]
+fn test_shape_only_hides_every_name() {
+    let test_dir = create_test_dir();
+
+    let output = rust_tree_command()
+        .args(["--shape-only", "-s", "--color", "always"])
+        .arg(test_dir.path())
+        .output()
+        .expect("failed to run rust-tree");
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let root_name = test_dir.path().file_name().unwrap().to_str().unwrap();
+    for name in [
+        root_name, "Cargo", "toml", "README", "src", "main", "lib", "core", "models", "tests",
+        ".rs", " B",
+    ] {
+        assert!(!stdout.contains(name), "{:?} leaked:\n{}", name, stdout);
+    }
+    // 嵌套与条目数保持不变：根下 4 项，src 下 3 项
+    assert_eq!(stdout.lines().count(), 9, "{}", stdout);
+    assert!(stdout.starts_with("▣/\n├── ▣/\n"), "{}", stdout);
+
+    // 其余会输出路径、文件名或扩展名的模式都被拒绝。
+    for args in [
+        &["-f", "json"][..],
+        &["--largest-path"],
+        &["--duplicates"],
+        &["--list-empty-dirs"],
+        &["--report-excluded-ext", "--ext", "rs"],
+        &["--compare-stats", "baseline.json"],
+    ] {
+        let output = rust_tree_command()
+            .arg("--shape-only")
+            .args(args)
+            .arg(test_dir.path())
+            .output()
+            .expect("failed to run rust-tree");
+        assert!(!output.status.success(), "{:?} was accepted", args);
+        assert!(output.stdout.is_empty(), "{:?} printed output", args);
+    }
+}
+
+/// `tree proj`（GNU tree 2.x）在 UTF-8 locale（如 `LANG=en_US.UTF-8`）下对下面这个
+/// 目录的输出：竖线后是两个不换行空格（U+00A0）。`LC_ALL=C` 下 `tree` 改用 ASCII
+/// 连接线（`|--`），不是这里比较的格式。指向目录的链接 `docs-link` 计为目录。
+#[cfg(unix)]
+const GNU_TREE_GOLDEN: &str = "proj
+├── Cargo.toml
+├── README.md
+├── docs
+│\u{a0}\u{a0} ├── guide.md
+│\u{a0}\u{a0} └── img
+├── docs-link -> docs
+├── link -> src/main.rs
+└── src
+    ├── lib.rs
+    └── main.rs
+
+4 directories, 6 files
+";
+
+#[cfg(unix)]
+#[test]
+fn test_compat_tree_matches_gnu_tree_output() {
+    let temp = tempfile::tempdir().unwrap();
+    let root = temp.path().join("proj");
+    fs::create_dir_all(root.join("src")).unwrap();
+    fs::create_dir_all(root.join("docs/img")).unwrap();
+    for file in [
+        "Cargo.toml",
+        "README.md",
+        ".hidden",
+        "src/main.rs",
+        "src/lib.rs",
+        "docs/guide.md",
+    ] {
+        fs::write(root.join(file), b"x").unwrap();
+    }
+    std::os::unix::fs::symlink("src/main.rs", root.join("link")).unwrap();
+    std::os::unix::fs::symlink("docs", root.join("docs-link")).unwrap();
+
+    let output = rust_tree_command()
+        .args(["--compat-tree", "--color", "never", "proj"])
+        .current_dir(temp.path())
+        .output()
+        .expect("failed to run rust-tree");
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), GNU_TREE_GOLDEN);
+
+    // -d 1 对应 tree -L 1：未展开的目录不显示占位行，汇总只计显示的条目。
+    let output = rust_tree_command()
+        .args(["--compat-tree", "--color", "never", "-d", "1", "proj"])
+        .current_dir(temp.path())
+        .output()
+        .expect("failed to run rust-tree");
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "proj\n├── Cargo.toml\n├── README.md\n├── docs\n├── docs-link -> docs\n├── link -> src/main.rs\n└── src\n\n3 directories, 3 files\n"
+    );
+}
+
+#[test]
+fn test_streaming_stats_prints_summary_line() {
+    let temp = tempfile::tempdir().unwrap();
+    fs::create_dir_all(temp.path().join("src/empty")).unwrap();
+    fs::write(temp.path().join("src/main.rs"), b"fn main() {}").unwrap();
+    fs::write(temp.path().join("README.md"), b"hello").unwrap();
+
+    let output = rust_tree_command()
+        .args(["--streaming", "--stats", "--color", "never"])
+        .arg(temp.path())
+        .output()
+        .expect("failed to run rust-tree");
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(
+        stdout.ends_with("\n\n2 files, 2 directories, 17 B total\n"),
+        "{}",
+        stdout
+    );
+
+    // 其余统计仍需要完整的树。
+    let output = rust_tree_command()
+        .args(["--streaming", "-f", "json"])
+        .arg(temp.path())
+        .output()
+        .expect("failed to run rust-tree");
+    assert!(!output.status.success());
+
+    for flag in ["--dir-count", "--dir-size"] {
+        let output = rust_tree_command()
+            .args(["--streaming", flag])
+            .arg(temp.path())
+            .output()
+            .expect("failed to run rust-tree");
+        assert!(!output.status.success(), "{flag}");
+    }
+}
+
+#[test]
+fn test_render_returns_output_instead_of_printing() {
+    let test_dir = create_test_dir();
+    let config = rust_tree::Config {
+        path: test_dir.path().to_path_buf(),
+        format: rust_tree::OutputFormat::Names,
+        ..Default::default()
+    };
+    let output = rust_tree::render(config).unwrap();
+    assert!(
+        output.lines().any(|line| line.trim() == "main.rs"),
+        "{}",
+        output
+    );
+
+    // 流式输出不缓冲。
+    let config = rust_tree::Config {
+        path: test_dir.path().to_path_buf(),
+        streaming: true,
+        ..Default::default()
+    };
+    assert!(rust_tree::render(config).is_err());
+}
+
+#[test]
+fn test_run_to_writes_into_the_given_writer() {
+    let test_dir = create_test_dir();
+    let config = rust_tree::Config {
+        path: test_dir.path().to_path_buf(),
+        format: rust_tree::OutputFormat::Names,
+        ..Default::default()
+    };
+    let mut out = Vec::new();
+    rust_tree::run_to(config, &mut out).unwrap();
+    let output = String::from_utf8(out).unwrap();
+    assert!(
+        output.lines().any(|line| line.trim() == "lib.rs"),
+        "{}",
+        output
+    );
+
+    // 失败的调用不影响之后写往同一线程的输出。
+    let config = rust_tree::Config {
+        path: test_dir.path().join("missing"),
+        ..Default::default()
+    };
+    assert!(rust_tree::run_to(config, &mut Vec::new()).is_err());
+    let config = rust_tree::Config {
+        path: test_dir.path().to_path_buf(),
+        format: rust_tree::OutputFormat::Names,
+        ..Default::default()
+    };
+    assert_eq!(rust_tree::render(config).unwrap(), output);
+}
+
+#[test]
+fn test_collapse_depth_renders_collapsed_summary() {
+    let test_dir = create_test_dir();
+
+    let output = rust_tree_command()
+        .args(["--collapse-depth", "1", "--color", "never"])
+        .arg(test_dir.path())
+        .output()
+        .expect("failed to run rust-tree");
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    // src 下有 main.rs、lib.rs 与 core/models.rs
+    assert!(
+        stdout.contains("├── src/ [collapsed: 3 files, "),
+        "{}",
+        stdout
+    );
+    assert!(!stdout.contains("main.rs"), "{}", stdout);
+    assert!(!stdout.contains("..."), "{}", stdout);
+}
+
+#[test]
+fn test_du_json_keeps_directory_size_zero() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir_all(dir.path().join("a/b")).unwrap();
+    std::fs::write(dir.path().join("a/b/x"), vec![0u8; 100]).unwrap();
+    std::fs::write(dir.path().join("z"), vec![0u8; 3]).unwrap();
+
+    let output = rust_tree_command()
+        .args(["--du", "-f", "json"])
+        .arg(dir.path())
+        .output()
+        .expect("failed to run rust-tree");
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let root = &json["tree"]["root"];
+    // --du 不改变目录自身的 size；子树总大小只在 aggregate_size 中。
+    assert_eq!(root["size"], 0);
+    assert_eq!(root["aggregate_size"], 103);
+    let a = &root["children"][0];
+    assert_eq!(
+        (a["size"].as_u64(), a["aggregate_size"].as_u64()),
+        (Some(0), Some(100))
+    );
+}
+
+#[test]
+fn test_json_aggregate_size_includes_truncated_subtrees() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir_all(dir.path().join("a/b")).unwrap();
+    std::fs::write(dir.path().join("a/b/x"), vec![0u8; 100]).unwrap();
+    std::fs::write(dir.path().join("z"), vec![0u8; 3]).unwrap();
+
+    let output = rust_tree_command()
+        .args(["--du", "-d", "1", "-f", "json"])
+        .arg(dir.path())
+        .output()
+        .expect("failed to run rust-tree");
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let root = &json["tree"]["root"];
+    // a/ 被深度限制截断，其子树仍计入 aggregate_size。
+    assert_eq!(root["aggregate_size"], 103);
+    assert_eq!(root["children"][0]["aggregate_size"], 100);
+}